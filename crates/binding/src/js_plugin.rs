@@ -9,7 +9,7 @@ pub struct JsPlugin {
 use anyhow::{anyhow, Result};
 use mako::ast::file::{Content, JsContent};
 use mako::compiler::Context;
-use mako::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam};
+use mako::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam, PluginTransformOutputParam};
 
 impl Plugin for JsPlugin {
     fn name(&self) -> &str {
@@ -29,7 +29,7 @@ impl Plugin for JsPlugin {
         Ok(None)
     }
 
-    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if let Some(hook) = &self.hooks.load {
             let (tx, rx) = mpsc::channel::<napi::Result<Option<LoadResult>>>();
             hook.call(
@@ -43,6 +43,9 @@ impl Plugin for JsPlugin {
                 .recv()
                 .unwrap_or_else(|e| panic!("recv error: {:?}", e.to_string()))?;
             if let Some(x) = x {
+                for file in &x.watch_files {
+                    param.add_file_dependency(file, context);
+                }
                 match x.content_type.as_str() {
                     "js" | "ts" => {
                         return Ok(Some(Content::Js(JsContent {
@@ -84,6 +87,35 @@ impl Plugin for JsPlugin {
         Ok(None)
     }
 
+    fn has_transform_output(&self) -> bool {
+        self.hooks.transform_output.is_some()
+    }
+
+    fn transform_output(
+        &self,
+        param: &PluginTransformOutputParam,
+        content: &mut Vec<u8>,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        if let Some(hook) = &self.hooks.transform_output {
+            let (tx, rx) = mpsc::channel::<napi::Result<Option<Vec<u8>>>>();
+            hook.call(
+                ReadMessage {
+                    message: (param.filename.to_string(), content.clone()),
+                    tx,
+                },
+                threadsafe_function::ThreadsafeFunctionCallMode::Blocking,
+            );
+            let transformed = rx
+                .recv()
+                .unwrap_or_else(|e| panic!("recv error: {:?}", e.to_string()))?;
+            if let Some(transformed) = transformed {
+                *content = transformed;
+            }
+        }
+        Ok(())
+    }
+
     fn before_write_fs(&self, path: &std::path::Path, content: &[u8]) -> Result<()> {
         if let Some(hook) = &self.hooks._on_generate_file {
             let (tx, rx) = mpsc::channel::<napi::Result<()>>();
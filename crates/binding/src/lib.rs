@@ -9,6 +9,7 @@ use js_plugin::JsPlugin;
 use mako::compiler::{Args, Compiler};
 use mako::config::Config;
 use mako::dev::DevServer;
+use mako::features::library::Library;
 use mako::plugin::Plugin;
 use mako::utils::logger::init_logger;
 use napi::bindgen_prelude::*;
@@ -41,11 +42,13 @@ pub struct BuildParams {
     #[napi(ts_type = r#"
 {
     entry?: Record<string, string>;
+    entryConditions?: Record<string, { mode?: "development" | "production"; env?: string }>;
     output?: {
         path: string;
         mode: "bundle" | "bundless" ;
         esVersion?: string;
         meta?: boolean;
+        chunkLoading?: "jsonp" | "import";
         preserveModules?: boolean;
         preserveModulesRoot?: string;
         skipWrite?: boolean;
@@ -53,13 +56,16 @@ pub struct BuildParams {
     resolve?: {
        alias?: Array<[string, string]>;
        extensions?: string[];
+       platforms?: string[];
     };
     manifest?: false | {
         fileName: string;
         basePath: string;
+        signature?: { keyEnv?: string; fileName?: string };
     };
     mode?: "development" | "production";
     define?: Record<string, string>;
+    evaluateDefine?: boolean;
     devtool?: false | "source-map" | "inline-source-map";
     externals?: Record<
         string,
@@ -101,16 +107,75 @@ pub struct BuildParams {
               minSize?: number;
               maxSize?: number;
               priority?: number;
+              licenses?: string[];
             }[];
           }
         };
     providers?: Record<string, string[]>;
+    inject?: Record<string, { from: string; named?: string; namespace?: boolean; bare?: boolean; preferRequire?: boolean; exclude?: string | string[]; include?: string | string[]; order?: number } |
+        { code: string; exclude?: string | string[]; include?: string | string[]; order?: number }>;
+    sideEffects?: Record<string, boolean>;
     publicPath?: string;
+    publicAssets?: false | { dir?: string; helper?: string };
     inlineLimit?: number;
     targets?: Record<string, number>;
     platform?: "node" | "browser";
+    polyfillReport?: boolean;
     hmr?: false | {};
     devServer?: false | { host?: string; port?: number };
+    inspect?: false | { maxModules?: number };
+    cache?: false | { dir?: string; remote?: string };
+    html?: false | {
+        template?: string;
+        filename?: string;
+        preconnect?: string[];
+        templates?: Record<string, string>;
+    };
+    mpa?: false | { pagesDir?: string; manifest?: string };
+    buildEvents?: false | { url?: string; socket?: string };
+    bundlePolicy?:
+      | false
+      | {
+          forbid?: string[];
+          allow?: string[];
+          maxSize?: Record<string, number>;
+        };
+    banner?: false | {
+        js?: string;
+        css?: string;
+        entryOnly?: boolean;
+        modules?: { include: string[]; text: string }[];
+    };
+    footer?: false | { js?: string; css?: string; entryOnly?: boolean };
+    chunkIntegrity?: false | { onCorruptChunk?: string };
+    importMap?: false | {
+        path?: string;
+        imports?: Record<string, string>;
+        emit?: boolean;
+    };
+    chunkLoadTelemetry?: false | { latency?: number };
+    envBanner?: false | {
+        text: string;
+        modes?: ("development" | "production")[];
+        console?: boolean;
+        ribbon?: boolean;
+    };
+    envFile?: false | { prefix?: string };
+    envTypes?: false | { path?: string };
+    runtimeEnv?: string[];
+    strictEnv?: boolean;
+    chunkInvalidation?: false | { records?: string };
+    circularDependency?:
+      | false
+      | {
+          runtime?: "off" | "warn" | "error";
+          dynamic?: "off" | "warn" | "error";
+          overrides?: {
+            dir: string;
+            runtime?: "off" | "warn" | "error";
+            dynamic?: "off" | "warn" | "error";
+          }[];
+        };
     px2rem?: false | {
         root?: number;
         propBlackList?: string[];
@@ -119,7 +184,17 @@ pub struct BuildParams {
         selectorWhiteList?: string[];
     };
     stats?: boolean;
+    metafile?: boolean;
+    chunkManifest?: boolean;
+    runtimeChunk?: false | "single";
+    chunkedEmit?:
+      | false
+      | {
+          threshold?: number;
+          pieceSize?: number;
+        };
     hash?: boolean;
+    hashSalt?: string;
     autoCSSModules?: boolean;
     ignoreCSSParserErrors?: boolean;
     dynamicImportToRequire?: boolean;
@@ -127,24 +202,27 @@ pub struct BuildParams {
     cjs?: boolean;
     writeToDisk?: boolean;
     transformImport?: { libraryName: string; libraryDirectory?: string; style?: boolean | string }[];
+    transformStages?: Record<string, { after?: string; before?: string }>;
+    iconImports?: { libraryName: string; template: string }[];
     clean?: boolean;
     nodePolyfill?: boolean;
     ignores?: string[];
-    moduleIdStrategy?: "hashed" | "named";
+    moduleIdStrategy?: "hashed" | "named" | "deterministic";
     minify?: boolean;
     _minifish?: false | {
         mapping: Record<string, string>;
         metaPath?: string;
-        inject?: Record<string, { from:string;exclude?:string; include?:string; preferRequire?:
-        boolean } |
-            { from:string; named:string; exclude?:string; include?:string;preferRequire?: boolean
-             } |
-            { from:string; namespace: true; exclude?:string; include?:string; preferRequire?:
-            boolean }
+        inject?: Record<string, { from:string; exclude?:string | string[]; include?:string | string[]; preferRequire?: boolean } |
+            { from:string; named:string; exclude?:string | string[]; include?:string | string[]; preferRequire?: boolean } |
+            { from:string; namespace: true; exclude?:string | string[]; include?:string | string[]; preferRequire?: boolean } |
+            { from:string; bare: true; exclude?:string | string[]; include?:string | string[]; preferRequire?: boolean }
             >;
     };
     optimization?: false | {
         skipModules?: boolean;
+        concatenateModules?: boolean;
+        polyfillChunk?: boolean;
+        isolateLicenses?: string[];
     };
     react?: {
         runtime?: "automatic" | "classic";
@@ -152,8 +230,15 @@ pub struct BuildParams {
         importSource?: string;
         pragmaFrag?: string;
     };
+    preact?: boolean;
     emitAssets?: boolean;
     cssModulesExportOnlyLocales?: boolean;
+    cssModules?:
+      | false
+      | {
+          namedExports?: boolean;
+          localsConvention?: "asIs" | "camelCase" | "camelCaseOnly";
+        };
     inlineCSS?: false | {};
     rscServer?: false | {
         "emitCSS": boolean;
@@ -164,10 +249,55 @@ pub struct BuildParams {
     };
     experimental?: {
         webpackSyntaxValidate?: string[];
+        reactNative?: false | { platform: "ios" | "android"; inlineRequires?: boolean };
+        prebundle?: boolean;
     };
     watch?: {
         ignoredPaths?: string[];
     };
+    swcHelpers?: {
+        mode?: "inline" | "runtime";
+        source?: string;
+    };
+    splitByKey?: string[];
+    splitChunks?:
+      | false
+      | {
+          chunks?: 'all' | 'initial' | 'async';
+          minSize?: number;
+          maxSize?: number;
+          minChunks?: number;
+          cacheGroups?: Record<
+            string,
+            | false
+            | {
+                name?: string;
+                test?: string;
+                priority?: number;
+                chunks?: 'all' | 'initial' | 'async';
+                minSize?: number;
+                maxSize?: number;
+                minChunks?: number;
+              }
+          >;
+        };
+    dedupePackages?: string[];
+    mocks?: Record<string, string>;
+    codegen?: {
+        dir: string;
+        command: string;
+        output: string;
+        name?: string;
+    }[];
+    lockfileCheck?: boolean;
+    errorMapping?: boolean;
+    library?:
+      | false
+      | {
+          formats: ("esm" | "cjs")[];
+          dts?: { command: string; output: string };
+          validatePackageJson?: boolean;
+        };
 }"#)]
     pub config: serde_json::Value,
     pub plugins: Vec<JsHooks>,
@@ -193,13 +323,41 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
         napi::Error::new(Status::GenericFailure, format!("Load config failed: {}", e))
     })?;
 
+    // library mode builds every format with its own compiler
+    if config.library.is_some() {
+        if build_params.watch {
+            return Err(napi::Error::new(
+                Status::GenericFailure,
+                "library mode does not support watch yet".to_string(),
+            ));
+        }
+        let (deferred, promise) = env.create_deferred()?;
+        rayon::spawn(move || {
+            let ret = Library::compile(&root, &config, Some(&default_config), Some(plugins))
+                .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+            if let Err(e) = ret {
+                deferred.reject(e);
+                return;
+            }
+            deferred.resolve(move |env| env.get_undefined());
+        });
+        return Ok(promise);
+    }
+
     if build_params.watch {
         let (deferred, promise) = env.create_deferred()?;
         env.execute_tokio_future(
             async move {
-                let compiler =
-                    Compiler::new(config, root.clone(), Args { watch: true }, Some(plugins))
-                        .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+                let compiler = Compiler::new(
+                    config,
+                    root.clone(),
+                    Args {
+                        watch: true,
+                        verbose: false,
+                    },
+                    Some(plugins),
+                )
+                .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
                 if let Err(e) = compiler {
                     deferred.reject(e);
                     return Ok(());
@@ -224,9 +382,16 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
     } else {
         let (deferred, promise) = env.create_deferred()?;
         rayon::spawn(move || {
-            let compiler =
-                Compiler::new(config, root.clone(), Args { watch: false }, Some(plugins))
-                    .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+            let compiler = Compiler::new(
+                config,
+                root.clone(),
+                Args {
+                    watch: false,
+                    verbose: false,
+                },
+                Some(plugins),
+            )
+            .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
             let compiler = match compiler {
                 Ok(c) => c,
                 Err(e) => {
@@ -4,7 +4,7 @@ use std::sync::mpsc::Sender;
 
 use mako::plugin::PluginGenerateEndParams;
 use napi::bindgen_prelude::*;
-use napi::{JsObject, JsString, JsUnknown, NapiRaw};
+use napi::{JsBuffer, JsObject, JsString, JsUnknown, NapiRaw};
 
 use crate::threadsafe_function;
 
@@ -12,7 +12,7 @@ use crate::threadsafe_function;
 pub struct JsHooks {
     pub name: Option<String>,
     #[napi(
-        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js' } | void> | void;"
+        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js', watchFiles?: string[] } | void> | void;"
     )]
     pub load: Option<JsFunction>,
     #[napi(ts_type = "(data: {isFirstCompile: boolean; time: number; stats: {
@@ -24,6 +24,10 @@ pub struct JsHooks {
     pub _on_generate_file: Option<JsFunction>,
     #[napi(ts_type = "() => Promise<void>;")]
     pub build_start: Option<JsFunction>,
+    #[napi(
+        ts_type = "(filename: string, content: Buffer) => Promise<Buffer | void> | Buffer | void;"
+    )]
+    pub transform_output: Option<JsFunction>,
 }
 
 pub struct TsFnHooks {
@@ -34,6 +38,9 @@ pub struct TsFnHooks {
     pub load:
         Option<threadsafe_function::ThreadsafeFunction<ReadMessage<String, Option<LoadResult>>>>,
     pub _on_generate_file: Option<threadsafe_function::ThreadsafeFunction<WriteRequest>>,
+    pub transform_output: Option<
+        threadsafe_function::ThreadsafeFunction<ReadMessage<(String, Vec<u8>), Option<Vec<u8>>>>,
+    >,
 }
 
 impl TsFnHooks {
@@ -129,10 +136,65 @@ impl TsFnHooks {
                 )
                 .unwrap()
             }),
+            transform_output: hooks.transform_output.as_ref().map(|hook| {
+                threadsafe_function::ThreadsafeFunction::create(
+                    env.raw(),
+                    unsafe { hook.raw() },
+                    0,
+                    |ctx: threadsafe_function::ThreadSafeCallContext<
+                        ReadMessage<(String, Vec<u8>), Option<Vec<u8>>>,
+                    >| {
+                        let (filename, content) = ctx.value.message;
+                        let filename = ctx.env.create_string(&filename)?.into_unknown();
+                        let content = ctx.env.create_buffer_with_data(content)?.into_unknown();
+                        let result = ctx.callback.unwrap().call(None, &[filename, content])?;
+                        await_promise_buffer(ctx.env, result, ctx.value.tx).unwrap();
+                        Ok(())
+                    },
+                )
+                .unwrap()
+            }),
         }
     }
 }
 
+fn js_buffer_to_vec(value: JsUnknown) -> napi::Result<Option<Vec<u8>>> {
+    if matches!(value.get_type()?, ValueType::Undefined) {
+        return Ok(None);
+    }
+    let buffer: JsBuffer = value.try_into()?;
+    Ok(Some(buffer.into_value()?.to_vec()))
+}
+
+fn await_promise_buffer(
+    env: Env,
+    result: JsUnknown,
+    tx: Sender<napi::Result<Option<Vec<u8>>>>,
+) -> napi::Result<()> {
+    // If the result is a promise, wait for it to resolve, and send the result to the channel.
+    // Otherwise, send the result immediately.
+    if result.is_promise()? {
+        let result: JsObject = result.try_into()?;
+        let then: JsFunction = result.get_named_property("then")?;
+        let tx2 = tx.clone();
+        let cb = env.create_function_from_closure("callback", move |ctx| {
+            let res = ctx.get::<JsUnknown>(0)?;
+            tx.send(js_buffer_to_vec(res)).unwrap();
+            ctx.env.get_undefined()
+        })?;
+        let eb = env.create_function_from_closure("error_callback", move |ctx| {
+            let res = ctx.get::<JsUnknown>(0)?;
+            tx2.send(Err(napi::Error::from(res))).unwrap();
+            ctx.env.get_undefined()
+        })?;
+        then.call(Some(&result), &[cb, eb])?;
+    } else {
+        tx.send(js_buffer_to_vec(result)).unwrap();
+    }
+
+    Ok(())
+}
+
 #[allow(dead_code)]
 fn await_promise(
     env: Env,
@@ -185,16 +247,7 @@ fn await_promise_js_object(
                 return ctx.env.get_undefined();
             }
             let res: JsObject = res.try_into()?;
-            // let s = res.into_owned()?;
-            // get res.content as string
-            // println!("res: {:}", res.get_element(0)?.into_utf8()?.into_owned()?);
-            let content: JsString = res.get_named_property("content")?;
-            let content_type: JsString = res.get_named_property("type")?;
-            tx.send(Ok(Some(LoadResult {
-                content: content.into_utf8()?.into_owned()?,
-                content_type: content_type.into_utf8()?.into_owned()?,
-            })))
-            .unwrap();
+            tx.send(Ok(Some(load_result(res)?))).unwrap();
             ctx.env.get_undefined()
         })?;
         let eb = env.create_function_from_closure("error_callback", move |ctx| {
@@ -209,18 +262,34 @@ fn await_promise_js_object(
             return Ok(());
         }
         let res: JsObject = result.try_into()?;
-        let content: JsString = res.get_named_property("content")?;
-        let content_type: JsString = res.get_named_property("type")?;
-        tx.send(Ok(Some(LoadResult {
-            content: content.into_utf8()?.into_owned()?,
-            content_type: content_type.into_utf8()?.into_owned()?,
-        })))
-        .unwrap();
+        tx.send(Ok(Some(load_result(res)?))).unwrap();
     }
 
     Ok(())
 }
 
+fn load_result(res: JsObject) -> napi::Result<LoadResult> {
+    let content: JsString = res.get_named_property("content")?;
+    let content_type: JsString = res.get_named_property("type")?;
+    // files read by the loader besides the loaded file, e.g. imports of less files
+    let mut watch_files = vec![];
+    if res.has_named_property("watchFiles")? {
+        let files: JsUnknown = res.get_named_property("watchFiles")?;
+        if files.is_array()? {
+            let files: JsObject = files.try_into()?;
+            for i in 0..files.get_array_length()? {
+                let file: JsString = files.get_element(i)?;
+                watch_files.push(file.into_utf8()?.into_owned()?);
+            }
+        }
+    }
+    Ok(LoadResult {
+        content: content.into_utf8()?.into_owned()?,
+        content_type: content_type.into_utf8()?.into_owned()?,
+        watch_files,
+    })
+}
+
 fn await_promise_with_void(
     env: Env,
     result: JsUnknown,
@@ -263,4 +332,5 @@ pub struct WriteRequest {
 pub struct LoadResult {
     pub content: String,
     pub content_type: String,
+    pub watch_files: Vec<String>,
 }
@@ -37,18 +37,41 @@ impl Comments {
      */
     #[allow(dead_code)]
     pub fn has_pure(&self, span: Span) -> bool {
-        self.has_flag(span, "PURE")
+        self.0.has_pure(span)
     }
 
     /**
      * Check for `/*#__NO_SIDE_EFFECTS__*/`
      */
     #[allow(dead_code)]
-    fn has_no_side_effects(&self, span: Span) -> bool {
-        self.has_flag(span, "NO_SIDE_EFFECTS")
+    pub fn has_no_side_effects(&self, span: Span) -> bool {
+        self.0.has_no_side_effects(span)
     }
 
     #[allow(dead_code)]
+    fn has_flag(&self, span: Span, text: &'static str) -> bool {
+        self.0.has_flag(span, text)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MakoComments(SwcComments);
+
+impl MakoComments {
+    /**
+     * Check for `/*#__NO_SIDE_EFFECTS__*/`
+     */
+    pub fn has_no_side_effects(&self, span: Span) -> bool {
+        self.has_flag(span, "NO_SIDE_EFFECTS")
+    }
+
+    /**
+     * Check for `/*#__PURE__*/`
+     */
+    pub fn has_pure(&self, span: Span) -> bool {
+        self.has_flag(span, "PURE")
+    }
+
     fn has_flag(&self, span: Span, text: &'static str) -> bool {
         self.find_comment(span, |c| {
             if c.kind == common::comments::CommentKind::Block {
@@ -66,13 +89,12 @@ impl Comments {
         })
     }
 
-    #[allow(dead_code)]
     fn find_comment<F>(&self, span: Span, mut op: F) -> bool
     where
         F: FnMut(&common::comments::Comment) -> bool,
     {
         let mut found = false;
-        let cs: Option<_> = common::comments::Comments::get_leading(&self.0, span.lo);
+        let cs: Option<_> = common::comments::Comments::get_leading(self, span.lo);
         if let Some(cs) = cs {
             for c in &cs {
                 found |= op(c);
@@ -86,9 +108,6 @@ impl Comments {
     }
 }
 
-#[derive(Clone, Default)]
-pub struct MakoComments(SwcComments);
-
 impl CommentsTrait for MakoComments {
     fn add_pure_comment(&self, pos: BytePos) {
         //ref: https://github.com/swc-project/swc/pull/8172
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
 
@@ -19,8 +20,9 @@ use crate::ast::file::{Content, File};
 use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::ast::{error, utils};
 use crate::compiler::Context;
-use crate::config::{DevtoolConfig, Mode};
+use crate::config::{CssModulesConfig, CssModulesLocalsConvention, DevtoolConfig, Mode};
 use crate::module::Dependency;
+use crate::utils::salted_md5;
 use crate::visitors::css_dep_analyzer::CSSDepAnalyzer;
 
 #[derive(Clone)]
@@ -145,11 +147,12 @@ impl CssAst {
         Ok(CSSAstGenerated { code, sourcemap })
     }
 
-    pub fn compile_css_modules(path: &str, ast: &mut Stylesheet) -> TransformResult {
+    pub fn compile_css_modules(path: &str, ast: &mut Stylesheet, salt: &str) -> TransformResult {
         compile(
             ast,
             CssModuleRename {
                 path: path.to_string(),
+                salt: salt.to_string(),
             },
         )
     }
@@ -158,8 +161,10 @@ impl CssAst {
         path: &str,
         ast: &mut Stylesheet,
         export_only: bool,
+        salt: &str,
+        css_modules_config: &CssModulesConfig,
     ) -> String {
-        let result = Self::compile_css_modules(path, ast);
+        let result = Self::compile_css_modules(path, ast, salt);
         let mut export_names = Vec::new();
         for (name, classes) in result.renamed.iter() {
             let mut after_transform_classes = Vec::new();
@@ -178,31 +183,133 @@ impl CssAst {
                     }
                 }
             }
-            export_names.push((name, after_transform_classes));
+            export_names.push((
+                name.to_string(),
+                after_transform_classes.join(" ").trim().to_string(),
+            ));
         }
-        let export_names = export_names
-            .iter()
-            .map(|(name, classes)| format!("\"{}\": `{}`", name, classes.join(" ").trim()))
-            .collect::<Vec<String>>()
-            .join(",");
-
-        if export_only {
-            format!(
-                r#"
-export default {{{}}}
-"#,
-                export_names
-            )
+        css_modules_exports_code(path, export_names, export_only, css_modules_config)
+    }
+}
+
+// the exports are sorted by name, so that the outputs are stable across builds
+fn css_modules_exports_code(
+    path: &str,
+    export_names: Vec<(String, String)>,
+    export_only: bool,
+    css_modules_config: &CssModulesConfig,
+) -> String {
+    let mut exports = BTreeMap::new();
+    for (name, classes) in export_names {
+        let camel_case_name = camel_case(&name);
+        match css_modules_config.locals_convention {
+            CssModulesLocalsConvention::AsIs => {
+                exports.insert(name, classes);
+            }
+            CssModulesLocalsConvention::CamelCase => {
+                exports.insert(camel_case_name, classes.clone());
+                exports.insert(name, classes);
+            }
+            CssModulesLocalsConvention::CamelCaseOnly => {
+                exports.insert(camel_case_name, classes);
+            }
+        }
+    }
+
+    let mut code = String::new();
+    if !export_only {
+        code.push_str(&format!("\nimport \"{}?modules\";", path));
+    }
+    let mut default_props = vec![];
+    for (name, classes) in &exports {
+        if css_modules_config.named_exports && is_valid_export_name(name) {
+            code.push_str(&format!("\nexport const {} = `{}`;", name, classes));
+            default_props.push(format!("\"{}\": {}", name, name));
+        } else {
+            default_props.push(format!("\"{}\": `{}`", name, classes));
+        }
+    }
+    code.push_str(&format!(
+        "\nexport default {{{}}}\n",
+        default_props.join(",")
+    ));
+    code
+}
+
+// foo-bar_baz -> fooBarBaz
+fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper_next = false;
+    for c in name.chars() {
+        if c == '-' || c == '_' {
+            upper_next = !result.is_empty();
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
         } else {
-            format!(
-                r#"
-import "{}?modules";
-export default {{{}}}
-"#,
-                path, export_names
-            )
+            result.push(c);
         }
     }
+    if result.is_empty() {
+        name.to_string()
+    } else {
+        result
+    }
+}
+
+const RESERVED_WORDS: [&str; 42] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+fn is_valid_export_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !RESERVED_WORDS.contains(&name)
 }
 
 pub struct CSSAstGenerated {
@@ -212,20 +319,75 @@ pub struct CSSAstGenerated {
 
 struct CssModuleRename {
     pub path: String,
+    pub salt: String,
 }
 
 impl TransformConfig for CssModuleRename {
     fn new_name_for(&self, local: &atoms::JsWord) -> atoms::JsWord {
         let name = local.to_string();
-        let new_name = ident_name(&self.path, &name);
+        let new_name = ident_name(&self.path, &name, &self.salt);
         new_name.into()
     }
 }
 
-fn ident_name(path: &str, name: &str) -> String {
-    let source = format!("{}__{}", path, name);
-    let digest = md5::compute(source);
+fn ident_name(path: &str, name: &str, salt: &str) -> String {
+    let digest = salted_md5(format!("{}__{}", path, name), salt);
     let hash = general_purpose::URL_SAFE.encode(digest.0);
     let hash_slice = hash[..8].to_string();
     format!("{}-{}", name, hash_slice)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{camel_case, css_modules_exports_code, ident_name};
+    use crate::config::{CssModulesConfig, CssModulesLocalsConvention};
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(camel_case("foo-bar_baz"), "fooBarBaz");
+        assert_eq!(camel_case("-foo"), "foo");
+        assert_eq!(camel_case("foo"), "foo");
+    }
+
+    #[test]
+    fn test_ident_name_with_salt() {
+        let name = ident_name("src/a.module.css", "title", "");
+        assert!(name.starts_with("title-"));
+        assert_eq!(ident_name("src/a.module.css", "title", ""), name);
+        assert_ne!(ident_name("src/a.module.css", "title", "v2"), name);
+    }
+
+    #[test]
+    fn test_css_modules_exports_code() {
+        let export_names = || {
+            vec![
+                ("title".to_string(), "title-a".to_string()),
+                ("foo-bar".to_string(), "foo-bar-b".to_string()),
+                ("default".to_string(), "default-c".to_string()),
+            ]
+        };
+        assert_eq!(
+            css_modules_exports_code("a.css", export_names(), false, &Default::default()),
+            r#"
+import "a.css?modules";
+export default {"default": `default-c`,"foo-bar": `foo-bar-b`,"title": `title-a`}
+"#
+        );
+        assert_eq!(
+            css_modules_exports_code(
+                "a.css",
+                export_names(),
+                true,
+                &CssModulesConfig {
+                    named_exports: true,
+                    locals_convention: CssModulesLocalsConvention::CamelCase,
+                }
+            ),
+            r#"
+export const fooBar = `foo-bar-b`;
+export const title = `title-a`;
+export default {"default": `default-c`,"foo-bar": `foo-bar-b`,"fooBar": fooBar,"title": title}
+"#
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt;
 use std::sync::{Arc, Mutex};
 
+use serde::Serialize;
 use swc_core::common::errors::Handler;
-use swc_core::common::Span;
+use swc_core::common::{SourceMap, Span};
 use swc_error_reporters::{GraphicalReportHandler, PrettyEmitter, PrettyEmitterConfig};
 use thiserror::Error;
 
@@ -24,6 +25,25 @@ pub enum GenerateError {
     CSSGenerateError { message: String },
 }
 
+// syntax errors which are tolerated in lenient parsing, line is 1-based and column is 0-based
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseDiagnostic {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ParseDiagnostic {
+    pub fn new(span: Span, message: &str, cm: &SourceMap) -> Self {
+        let loc = cm.lookup_char_pos(span.lo);
+        Self {
+            message: message.to_string(),
+            line: loc.line,
+            column: loc.col.0,
+        }
+    }
+}
+
 pub enum ErrorSpan {
     Js(Span),
     Css(Span),
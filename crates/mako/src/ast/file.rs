@@ -15,7 +15,7 @@ use url::Url;
 use {md5, mime_guess};
 
 use crate::compiler::Context;
-use crate::utils::base64_decode;
+use crate::utils::{base64_decode, consume_salt};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Asset {
@@ -244,7 +244,7 @@ impl File {
         }
     }
 
-    pub fn get_content_hash(&self) -> Result<String> {
+    pub fn get_content_hash(&self, salt: &str) -> Result<String> {
         let file = std::fs::File::open(&self.pathname)?;
         let len = file.metadata()?.len();
         // Decide on a reasonable buffer size (1MB in this case, fastest will depend on hardware)
@@ -252,6 +252,7 @@ impl File {
         let mut buf = BufReader::with_capacity(buf_len, file);
         // webpack use md4
         let mut context = md5::Context::new();
+        consume_salt(&mut context, salt);
         loop {
             // Get a chunk of the file
             let part = buf.fill_buf()?;
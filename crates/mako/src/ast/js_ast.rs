@@ -5,27 +5,35 @@ use anyhow::{anyhow, Result};
 use swc_core::base::try_with_handler;
 use swc_core::common::errors::HANDLER;
 use swc_core::common::util::take::Take;
-use swc_core::common::{FileName, Mark, Spanned, GLOBALS};
+use swc_core::common::{FileName, Mark, SourceFile, Spanned, GLOBALS};
 use swc_core::ecma::ast::{EsVersion, Module};
 use swc_core::ecma::codegen::text_writer::JsWriter;
 use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
-use swc_core::ecma::parser::error::SyntaxError;
+use swc_core::ecma::parser::error::{Error as ParseErrorDetail, SyntaxError};
 use swc_core::ecma::parser::lexer::Lexer;
-use swc_core::ecma::parser::{EsConfig, Parser, StringInput, Syntax, TsConfig};
+use swc_core::ecma::parser::{EsConfig, PResult, Parser, StringInput, Syntax, TsConfig};
 use swc_core::ecma::transforms::base::helpers::{inject_helpers, Helpers, HELPERS};
 use swc_core::ecma::utils::contains_top_level_await;
 use swc_core::ecma::visit;
 use swc_core::ecma::visit::{VisitMutWith, VisitWith};
 
+use crate::ast::error::ParseDiagnostic;
 use crate::ast::file::{Content, File, JsContent};
+use crate::ast::pipeline::{TransformPass, TransformPipeline};
 use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::ast::{error, utils};
 use crate::compiler::Context;
 use crate::config::{DevtoolConfig, Mode, OutputMode};
+use crate::dev::inspect::TransformRecorder;
 use crate::module::Dependency;
 use crate::plugin::PluginTransformJsParam;
+use crate::stats::ModuleStageTimer;
 use crate::utils::base64_encode;
-use crate::visitors::dep_analyzer::DepAnalyzer;
+use crate::visitors::dep_analyzer::{collect_type_only_deps, DepAnalyzer};
+use crate::visitors::swc_helpers_source::{SwcHelpersSource, SWC_HELPERS_SOURCE};
+
+// how many times the code is cut to get a partial ast in lenient mode
+const MAX_LENIENT_ATTEMPTS: usize = 8;
 
 #[derive(Clone)]
 pub struct JsAst {
@@ -34,6 +42,8 @@ pub struct JsAst {
     pub top_level_mark: Mark,
     pub path: String,
     pub contains_top_level_await: bool,
+    // sources of type-only imports and exports, which are elided from module graph
+    pub type_only_deps: Vec<String>,
 }
 
 impl fmt::Debug for JsAst {
@@ -48,6 +58,89 @@ impl JsAst {
             FileName::Real(file.relative_path.to_path_buf()),
             file.get_content_raw(),
         );
+        let (ast, mut ast_errors) = Self::parse(file, &fm, &context);
+
+        // handle ast errors
+        if ast.is_err() {
+            ast_errors.push(ast.clone().unwrap_err());
+        }
+        if !ast_errors.is_empty() {
+            let errors = ast_errors
+                .iter()
+                .map(|err| {
+                    error::code_frame(
+                        error::ErrorSpan::Js(err.span()),
+                        err.kind().msg().to_string().as_str(),
+                        context.clone(),
+                    )
+                })
+                .collect::<Vec<String>>();
+            return Err(anyhow!(error::ParseError::JsParseError {
+                messages: errors.join("\n")
+            }));
+        }
+        let ast = ast./*safe*/unwrap();
+        Self::from_module(ast, file, &context)
+    }
+
+    /**
+     * error-tolerant parsing for editor integrations which work on in-progress code, syntax
+     * errors are returned as diagnostics instead of failures, the ast is partial if the error is
+     * unrecoverable, i.e. only the statements before the error are kept
+     */
+    pub fn new_lenient(file: &File, context: Arc<Context>) -> Result<(Self, Vec<ParseDiagnostic>)> {
+        let content = file.get_content_raw();
+        let cm = context.meta.script.cm.clone();
+        let fm = cm.new_source_file(
+            FileName::Real(file.relative_path.to_path_buf()),
+            content.clone(),
+        );
+        let (ast, ast_errors) = Self::parse(file, &fm, &context);
+        let mut diagnostics = ast_errors
+            .iter()
+            .map(|err| ParseDiagnostic::new(err.span(), err.kind().msg().as_ref(), &cm))
+            .collect::<Vec<_>>();
+        let ast = match ast {
+            Ok(ast) => ast,
+            Err(err) => {
+                diagnostics.push(ParseDiagnostic::new(
+                    err.span(),
+                    err.kind().msg().as_ref(),
+                    &cm,
+                ));
+                let mut end = (err.span().lo - fm.start_pos).0 as usize;
+                let mut ast = None;
+                // cut the code at the line of the error until the rest can be parsed, errors of
+                // the prefixes are not reported since they are caused by the cutting
+                for _ in 0..MAX_LENIENT_ATTEMPTS {
+                    let prefix = content
+                        .get(..end)
+                        .and_then(|c| c.rfind('\n'))
+                        .map_or("", |i| &content[..i]);
+                    let fm = cm.new_source_file(
+                        FileName::Real(file.relative_path.to_path_buf()),
+                        prefix.to_string(),
+                    );
+                    match Self::parse(file, &fm, &context).0 {
+                        Ok(module) => {
+                            ast = Some(module);
+                            break;
+                        }
+                        Err(_) if prefix.is_empty() => break,
+                        Err(err) => end = (err.span().lo - fm.start_pos).0 as usize,
+                    }
+                }
+                ast.unwrap_or_else(Module::dummy)
+            }
+        };
+        Ok((Self::from_module(ast, file, &context)?, diagnostics))
+    }
+
+    fn parse(
+        file: &File,
+        fm: &SourceFile,
+        context: &Arc<Context>,
+    ) -> (PResult<Module>, Vec<ParseErrorDetail>) {
         let comments = context.meta.script.origin_comments.read().unwrap();
         let extname = &file.extname;
         let syntax = if extname == "ts" || extname == "tsx" {
@@ -70,47 +163,32 @@ impl JsAst {
         let lexer = Lexer::new(
             syntax,
             EsVersion::Es2015,
-            StringInput::from(&*fm),
+            StringInput::from(fm),
             Some(comments.get_swc_comments()),
         );
         let mut parser = Parser::new_from(lexer);
         let ast = parser.parse_module();
 
-        // handle ast errors
         let mut ast_errors = parser.take_errors();
         // ignore with syntax error in strict mode
         ast_errors.retain_mut(|error| !matches!(error.kind(), SyntaxError::WithInStrict));
-        if ast.is_err() {
-            ast_errors.push(ast.clone().unwrap_err());
-        }
-        if !ast_errors.is_empty() {
-            let errors = ast_errors
-                .iter()
-                .map(|err| {
-                    error::code_frame(
-                        error::ErrorSpan::Js(err.span()),
-                        err.kind().msg().to_string().as_str(),
-                        context.clone(),
-                    )
-                })
-                .collect::<Vec<String>>();
-            return Err(anyhow!(error::ParseError::JsParseError {
-                messages: errors.join("\n")
-            }));
-        }
-        let ast = ast./*safe*/unwrap();
+        (ast, ast_errors)
+    }
 
+    fn from_module(ast: Module, file: &File, context: &Arc<Context>) -> Result<Self> {
         // top level mark and unresolved mark need to be persisted for transform usage
         GLOBALS.set(&context.meta.script.globals, || {
             let top_level_mark = Mark::new();
             let unresolved_mark = Mark::new();
             let contains_top_level_await = contains_top_level_await(&ast);
+            let type_only_deps = collect_type_only_deps(&ast);
             Ok(JsAst {
                 ast,
                 unresolved_mark,
                 top_level_mark,
                 path: file.relative_path.to_string_lossy().to_string(),
                 contains_top_level_await,
+                type_only_deps,
             })
         })
     }
@@ -130,49 +208,106 @@ impl JsAst {
         )
     }
 
+    // same as build, but with new_lenient, e.g. for single module transforms of editors
+    pub fn build_lenient(
+        path: &str,
+        content: &str,
+        context: Arc<Context>,
+    ) -> Result<(Self, Vec<ParseDiagnostic>)> {
+        let is_jsx = path.ends_with(".jsx") || path.ends_with(".tsx");
+        JsAst::new_lenient(
+            &File::with_content(
+                path.to_string(),
+                Content::Js(JsContent {
+                    content: content.to_string(),
+                    is_jsx,
+                }),
+                context.clone(),
+            ),
+            context.clone(),
+        )
+    }
+
+    // visitors, then folders, then the transform_js hooks of plugins
+    // stages are named by callers, e.g. in stats and the module inspector
     pub fn transform(
         &mut self,
-        mut_visitors: &mut Vec<Box<dyn visit::VisitMut>>,
-        folders: &mut Vec<Box<dyn visit::Fold>>,
+        mut_visitors: &mut Vec<(&str, Box<dyn visit::VisitMut>)>,
+        folders: &mut Vec<(&str, Box<dyn visit::Fold>)>,
+        file: &File,
+        should_inject_helpers: bool,
+        context: Arc<Context>,
+    ) -> Result<()> {
+        let mut pipeline = TransformPipeline::new();
+        for (name, visitor) in mut_visitors.drain(..) {
+            pipeline.visitor(name, visitor);
+        }
+        for (name, folder) in folders.drain(..) {
+            pipeline.folder(name, folder);
+        }
+        pipeline.plugins();
+        self.transform_pipeline(&mut pipeline, file, should_inject_helpers, context)
+    }
+
+    pub fn transform_pipeline(
+        &mut self,
+        pipeline: &mut TransformPipeline,
         file: &File,
         should_inject_helpers: bool,
         context: Arc<Context>,
     ) -> Result<()> {
         let cm = context.meta.script.cm.clone();
+        let swc_helpers = &context.config.swc_helpers;
+        let external_helpers = swc_helpers.external();
         GLOBALS.set(&context.meta.script.globals, || {
             try_with_handler(cm, Default::default(), |handler| {
-                HELPERS.set(&Helpers::new(true), || {
+                HELPERS.set(&Helpers::new(external_helpers), || {
                     HANDLER.set(handler, || {
                         let ast = &mut self.ast;
+                        let path = file.path.to_str().unwrap();
+                        let mut recorder = TransformRecorder::new(&context);
+                        recorder.record("source", ast);
+                        // stages are timed in the buckets of their kinds, custom stages of
+                        // plugins and config included
+                        let mut timer = ModuleStageTimer::new(&context, path);
 
-                        // visitors
-                        for visitor in mut_visitors {
-                            ast.visit_mut_with(visitor.as_mut());
-                        }
-
-                        // folders
-                        let body = ast.body.take();
-                        let mut module = Module {
-                            span: ast.span,
-                            shebang: ast.shebang.clone(),
-                            body,
-                        };
-                        for folder in folders {
-                            module = folder.as_mut().fold_module(module);
+                        for stage in pipeline.stages_mut() {
+                            let bucket = match &stage.pass {
+                                TransformPass::Visitor(_) => "transform:visitors",
+                                TransformPass::Folder(_) => "transform:folders",
+                                TransformPass::Plugins => "transform:plugins",
+                            };
+                            timer.time(bucket, || {
+                                match &mut stage.pass {
+                                    TransformPass::Visitor(visitor) => {
+                                        ast.visit_mut_with(visitor.as_mut());
+                                    }
+                                    TransformPass::Folder(folder) => {
+                                        let module = Module {
+                                            span: ast.span,
+                                            shebang: ast.shebang.clone(),
+                                            body: ast.body.take(),
+                                        };
+                                        ast.body = folder.fold_module(module).body;
+                                    }
+                                    TransformPass::Plugins => {
+                                        context.plugin_driver.transform_js(
+                                            &PluginTransformJsParam {
+                                                handler,
+                                                path,
+                                                top_level_mark: self.top_level_mark,
+                                                unresolved_mark: self.unresolved_mark,
+                                            },
+                                            ast,
+                                            &context,
+                                        )?;
+                                    }
+                                }
+                                Ok::<(), anyhow::Error>(())
+                            })?;
+                            recorder.record(&stage.name, ast);
                         }
-                        ast.body = module.body;
-
-                        // transform with plugin
-                        context.plugin_driver.transform_js(
-                            &PluginTransformJsParam {
-                                handler,
-                                path: file.path.to_str().unwrap(),
-                                top_level_mark: self.top_level_mark,
-                                unresolved_mark: self.unresolved_mark,
-                            },
-                            ast,
-                            &context,
-                        )?;
+                        timer.finish();
 
                         // FIXME: remove this, it's special logic
                         // inject helpers
@@ -195,8 +330,16 @@ impl JsAst {
                                     .visit_mut_with(&mut inject_helpers(self.unresolved_mark));
                                 ast.body = script_ast.body.into_iter().map(|i| i.into()).collect();
                             }
+                            // externalize helpers to the configured module
+                            if external_helpers && swc_helpers.source != SWC_HELPERS_SOURCE {
+                                ast.visit_mut_with(&mut SwcHelpersSource::new(
+                                    swc_helpers.source.clone(),
+                                ));
+                            }
+                            recorder.record("helpers", ast);
                         }
 
+                        recorder.finish(path);
                         Ok(())
                     })
                 })
@@ -205,7 +348,8 @@ impl JsAst {
     }
 
     pub fn analyze_deps(&self, context: Arc<Context>) -> Vec<Dependency> {
-        let mut visitor = DepAnalyzer::new(self.unresolved_mark);
+        let mut visitor = DepAnalyzer::new(self.unresolved_mark)
+            .with_require_resolve(context.config.require_resolve());
         GLOBALS.set(&context.meta.script.globals, || {
             self.ast.visit_with(&mut visitor);
             visitor.dependencies
@@ -285,7 +429,11 @@ pub struct JSAstGenerated {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use super::JsAst;
     use crate::ast::tests::TestUtils;
+    use crate::compiler::Context;
 
     #[test]
     #[ignore]
@@ -302,6 +450,29 @@ class Bar {}
         "#);
     }
 
+    #[test]
+    fn test_lenient() {
+        let context = Arc::new(Context::default());
+        let (ast, diagnostics) = JsAst::build_lenient(
+            "test.js",
+            "const a = 1;\nconst b = a +;\nconst c = 2;",
+            context.clone(),
+        )
+        .unwrap();
+        assert_eq!(ast.ast.body.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+
+        let (ast, diagnostics) =
+            JsAst::build_lenient("test.js", "const a = 1;", context.clone()).unwrap();
+        assert_eq!(ast.ast.body.len(), 1);
+        assert!(diagnostics.is_empty());
+
+        let (ast, diagnostics) = JsAst::build_lenient("test.js", "const = ", context).unwrap();
+        assert!(ast.ast.body.is_empty());
+        assert_eq!(diagnostics.len(), 1);
+    }
+
     fn run(js_code: &str) -> String {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let code = test_utils.js_ast_to_code();
@@ -3,7 +3,8 @@ pub(crate) mod css_ast;
 pub(crate) mod error;
 pub mod file;
 pub(crate) mod js_ast;
+pub mod pipeline;
 pub(crate) mod sourcemap;
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod tests;
 pub(crate) mod utils;
@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Result};
+use swc_core::ecma::visit::{Fold, VisitMut};
+
+use crate::config::TransformStageOrder;
+
+// the stage of the transform_js hooks of plugins
+pub const PLUGINS_STAGE: &str = "plugins";
+
+pub enum TransformPass {
+    Visitor(Box<dyn VisitMut>),
+    Folder(Box<dyn Fold>),
+    // runs the transform_js hooks of all plugins
+    Plugins,
+}
+
+pub struct PipelineStage {
+    pub name: String,
+    pub pass: TransformPass,
+    // None for the built-in stages
+    order: Option<TransformStageOrder>,
+}
+
+// a visitor provided by plugins, which is inserted before or after the named stages
+pub struct CustomTransformStage {
+    pub name: String,
+    pub order: TransformStageOrder,
+    pub visitor: Box<dyn VisitMut>,
+}
+
+/**
+ * the named stages of the js transform of a module, which run in order
+ *
+ * the built-in stages are always added (optional ones are wrapped with `Optional`), so that
+ * custom stages can be placed relative to them regardless of the file type, e.g.
+ * { after: "env_replacer", before: "plugins" }
+ */
+#[derive(Default)]
+pub struct TransformPipeline {
+    stages: Vec<PipelineStage>,
+}
+
+impl TransformPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn visitor<V: VisitMut + 'static>(&mut self, name: &str, visitor: V) {
+        self.push(name, TransformPass::Visitor(Box::new(visitor)));
+    }
+
+    pub fn folder<F: Fold + 'static>(&mut self, name: &str, folder: F) {
+        self.push(name, TransformPass::Folder(Box::new(folder)));
+    }
+
+    pub fn plugins(&mut self) {
+        self.push(PLUGINS_STAGE, TransformPass::Plugins);
+    }
+
+    fn push(&mut self, name: &str, pass: TransformPass) {
+        self.stages.push(PipelineStage {
+            name: name.to_string(),
+            pass,
+            order: None,
+        });
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.stages
+            .iter()
+            .map(|stage| stage.name.as_str())
+            .collect()
+    }
+
+    pub fn stages_mut(&mut self) -> &mut [PipelineStage] {
+        &mut self.stages
+    }
+
+    // stages without order are placed before the plugins stage, stages may be placed relative
+    // to each other, unknown stages, cycles and conflicting orders are errors
+    pub fn insert(&mut self, stages: Vec<CustomTransformStage>) -> Result<()> {
+        let mut pending = vec![];
+        for mut stage in stages {
+            if self.position(&stage.name).is_some()
+                || pending
+                    .iter()
+                    .any(|s: &CustomTransformStage| s.name == stage.name)
+            {
+                return Err(anyhow!("duplicated transform stage {}", stage.name));
+            }
+            if stage.order.before.is_none() && stage.order.after.is_none() {
+                stage.order.before = Some(PLUGINS_STAGE.to_string());
+            }
+            pending.push(stage);
+        }
+
+        while !pending.is_empty() {
+            let mut rest = vec![];
+            let count = pending.len();
+            for stage in pending {
+                match self.insert_position(&stage.order) {
+                    Some(index) => self.stages.insert(
+                        index,
+                        PipelineStage {
+                            name: stage.name,
+                            pass: TransformPass::Visitor(stage.visitor),
+                            order: Some(stage.order),
+                        },
+                    ),
+                    None => rest.push(stage),
+                }
+            }
+            if rest.len() == count {
+                let names = rest
+                    .iter()
+                    .map(|stage| stage.name.as_str())
+                    .collect::<Vec<_>>();
+                return Err(anyhow!(
+                    "transform stages {} are placed relative to unknown stages or each other in a cycle",
+                    names.join(", ")
+                ));
+            }
+            pending = rest;
+        }
+
+        self.validate()
+    }
+
+    // None if the stages of the order are not placed yet
+    fn insert_position(&self, order: &TransformStageOrder) -> Option<usize> {
+        let before = match &order.before {
+            Some(before) => Some(self.position(before)?),
+            None => None,
+        };
+        match &order.after {
+            Some(after) => {
+                // after the stages which are placed after the same stage, to keep the order
+                // of declarations
+                let mut index = self.position(after)? + 1;
+                while index < self.stages.len()
+                    && self.stages[index]
+                        .order
+                        .as_ref()
+                        .is_some_and(|o| o.after.as_ref() == Some(after))
+                {
+                    index += 1;
+                }
+                Some(index)
+            }
+            None => before,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (index, stage) in self.stages.iter().enumerate() {
+            let Some(order) = &stage.order else {
+                continue;
+            };
+            let after_ok = order
+                .after
+                .as_ref()
+                .map_or(true, |after| self.position(after) < Some(index));
+            let before_ok = order
+                .before
+                .as_ref()
+                .map_or(true, |before| self.position(before) > Some(index));
+            if !after_ok || !before_ok {
+                return Err(anyhow!(
+                    "conflicting order of transform stage {}, which is after {} and before {}",
+                    stage.name,
+                    order.after.as_deref().unwrap_or("-"),
+                    order.before.as_deref().unwrap_or("-"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| stage.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::visit::VisitMut;
+
+    use super::{CustomTransformStage, TransformPipeline};
+    use crate::config::TransformStageOrder;
+
+    struct Noop;
+    impl VisitMut for Noop {}
+
+    fn pipeline() -> TransformPipeline {
+        let mut pipeline = TransformPipeline::new();
+        pipeline.visitor("resolver", Noop);
+        pipeline.visitor("env_replacer", Noop);
+        pipeline.visitor("provide", Noop);
+        pipeline.plugins();
+        pipeline
+    }
+
+    fn stage(name: &str, after: Option<&str>, before: Option<&str>) -> CustomTransformStage {
+        CustomTransformStage {
+            name: name.to_string(),
+            order: TransformStageOrder {
+                after: after.map(|s| s.to_string()),
+                before: before.map(|s| s.to_string()),
+            },
+            visitor: Box::new(Noop),
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut pipeline = pipeline();
+        pipeline
+            .insert(vec![
+                stage("c", Some("b"), None),
+                stage("a", Some("env_replacer"), Some("provide")),
+                stage("b", Some("env_replacer"), None),
+                stage("d", None, None),
+                stage("e", None, Some("resolver")),
+            ])
+            .unwrap();
+        assert_eq!(
+            pipeline.names(),
+            vec![
+                "e",
+                "resolver",
+                "env_replacer",
+                "a",
+                "b",
+                "c",
+                "provide",
+                "d",
+                "plugins"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_errors() {
+        let error = |stages| pipeline().insert(stages).unwrap_err().to_string();
+        assert_eq!(
+            error(vec![stage("provide", None, None)]),
+            "duplicated transform stage provide"
+        );
+        assert_eq!(
+            error(vec![stage("a", Some("foo"), None)]),
+            "transform stages a are placed relative to unknown stages or each other in a cycle"
+        );
+        assert_eq!(
+            error(vec![
+                stage("a", Some("b"), None),
+                stage("b", Some("a"), None)
+            ]),
+            "transform stages a, b are placed relative to unknown stages or each other in a cycle"
+        );
+        assert_eq!(
+            error(vec![stage("a", Some("provide"), Some("env_replacer"))]),
+            "conflicting order of transform stage a, which is after provide and before env_replacer"
+        );
+    }
+}
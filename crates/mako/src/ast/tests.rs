@@ -3,7 +3,7 @@ use std::sync::Arc;
 use swc_core::common::GLOBALS;
 use swc_core::ecma::transforms::base::hygiene::{self, hygiene_with_config};
 use swc_core::ecma::transforms::base::resolver;
-use swc_core::ecma::visit::VisitMutWith;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use super::css_ast::{CSSAstGenerated, CssAst};
 use super::file::{Content, File, JsContent};
@@ -124,6 +124,15 @@ impl TestUtils {
         test_utils
     }
 
+    // apply the visitor to the js ast, with the swc globals of the context
+    pub fn transform_js<V: VisitMut>(&mut self, visitor: &mut V) -> &mut Self {
+        let ast = self.ast.js_mut();
+        GLOBALS.set(&self.context.meta.script.globals, || {
+            ast.ast.visit_mut_with(visitor);
+        });
+        self
+    }
+
     pub fn js_ast_to_code(&mut self) -> String {
         let ast = self.ast.js_mut();
         let top_level_mark = ast.top_level_mark;
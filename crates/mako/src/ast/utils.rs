@@ -63,6 +63,20 @@ pub fn is_commonjs_require(call_expr: &CallExpr, unresolved_mark: &Mark) -> bool
     }
 }
 
+// e.g. require.resolve('a')
+pub fn is_commonjs_require_resolve(call_expr: &CallExpr, unresolved_mark: &Mark) -> bool {
+    if let Callee::Expr(box Expr::Member(MemberExpr {
+        obj: box Expr::Ident(ident),
+        prop: MemberProp::Ident(prop),
+        ..
+    })) = &call_expr.callee
+    {
+        is_ident_undefined(ident, "require", unresolved_mark) && prop.sym == *"resolve"
+    } else {
+        false
+    }
+}
+
 pub fn get_call_expr_ident(call_expr: &CallExpr) -> Option<&Ident> {
     if let Callee::Expr(box Expr::Ident(ident)) = &call_expr.callee {
         Some(ident)
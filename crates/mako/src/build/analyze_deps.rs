@@ -2,13 +2,18 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use swc_core::ecma::visit::VisitWith;
 use thiserror::Error;
+use tracing::debug;
 
 use crate::ast::error;
 use crate::ast::file::File;
+use crate::ast::js_ast::JsAst;
 use crate::compiler::Context;
-use crate::module::{Dependency, ModuleAst};
+use crate::module::{Dependency, ModuleAst, ResolveType};
+use crate::plugin::PluginChunkNameParam;
 use crate::resolve::{resolve, ResolverResource};
+use crate::visitors::chunk_name::{sanitize_chunk_name, ChunkNameCollector};
 
 #[derive(Debug, Error)]
 pub enum AnalyzeDepsError {
@@ -22,6 +27,10 @@ pub struct AnalyzeDepsResult {
     // why use hash map?
     // since we need source as key to replace in generate step
     pub missing_deps: HashMap<String, Dependency>,
+    // type-only deps which are not added to module graph, for debugging
+    pub elided_deps: Vec<String>,
+    // source -> chunk name of dynamic imports, from magic comments and plugins
+    pub chunk_names: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +55,27 @@ impl AnalyzeDeps {
         };
         context.plugin_driver.before_resolve(&mut deps, &context)?;
         Self::check_deps(&deps, file)?;
+        let elided_deps = match ast {
+            ModuleAst::Script(ast) => ast
+                .type_only_deps
+                .iter()
+                .filter(|source| !deps.iter().any(|dep| &dep.source == *source))
+                .cloned()
+                .collect::<Vec<_>>(),
+            _ => vec![],
+        };
+        if !elided_deps.is_empty() {
+            debug!(
+                "elided type-only deps of {}: {:?}",
+                file.path.to_string_lossy(),
+                elided_deps
+            );
+        }
+
+        let chunk_names = match ast {
+            ModuleAst::Script(ast) => Self::get_chunk_names(ast, &deps, file, &context)?,
+            _ => HashMap::new(),
+        };
 
         let mut resolved_deps = vec![];
         let mut missing_deps = HashMap::new();
@@ -89,9 +119,43 @@ impl AnalyzeDeps {
         Ok(AnalyzeDepsResult {
             resolved_deps,
             missing_deps,
+            elided_deps,
+            chunk_names,
         })
     }
 
+    fn get_chunk_names(
+        ast: &JsAst,
+        deps: &[Dependency],
+        file: &File,
+        context: &Arc<Context>,
+    ) -> Result<HashMap<String, String>> {
+        let mut comment_names = {
+            let comments = context.meta.script.origin_comments.read().unwrap();
+            let mut collector = ChunkNameCollector::new(comments.get_swc_comments());
+            ast.ast.visit_with(&mut collector);
+            collector.chunk_names
+        };
+        let importer = file.path.to_string_lossy().to_string();
+        let mut chunk_names = HashMap::new();
+        for dep in deps
+            .iter()
+            .filter(|dep| dep.resolve_type == ResolveType::DynamicImport)
+        {
+            let param = PluginChunkNameParam {
+                importer: &importer,
+                source: &dep.source,
+            };
+            let name = comment_names.remove(&dep.source);
+            if let Some(name) = context.plugin_driver.chunk_name(&param, name, context)?
+                && let Some(name) = sanitize_chunk_name(&name)
+            {
+                chunk_names.insert(dep.source.clone(), name);
+            }
+        }
+        Ok(chunk_names)
+    }
+
     fn check_deps(deps: &Vec<Dependency>, file: &File) -> Result<()> {
         for dep in deps {
             // webpack loader syntax is not supported
@@ -0,0 +1,111 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+use twox_hash::XxHash64;
+
+/**
+ * files which are not modules but read by loaders and transforms of modules, e.g. a graphql
+ * schema read by a codegen transform, so that edits to them rebuild the modules in watch mode
+ *
+ * dependencies of a module are cleared before it's rebuilt, and registered again by the hooks
+ */
+#[derive(Default, Debug)]
+pub struct FileDependencies {
+    // module path -> files
+    modules: HashMap<String, HashSet<PathBuf>>,
+}
+
+impl FileDependencies {
+    pub fn add(&mut self, module: &str, file: PathBuf) {
+        self.modules
+            .entry(module.to_string())
+            .or_default()
+            .insert(file);
+    }
+
+    pub fn clear(&mut self, module: &str) {
+        self.modules.remove(module);
+    }
+
+    // modules which depend on the file
+    pub fn dependents(&self, file: &Path) -> Vec<String> {
+        let mut modules = self
+            .modules
+            .iter()
+            .filter(|(_, files)| files.contains(file))
+            .map(|(module, _)| module.clone())
+            .collect::<Vec<_>>();
+        modules.sort();
+        modules
+    }
+
+    pub fn files(&self) -> HashSet<PathBuf> {
+        self.modules.values().flatten().cloned().collect()
+    }
+
+    // hash of the contents of the files which the module depends on, it's added to the raw
+    // hash of the module, so that caches of the module are invalidated when the files change
+    pub fn content_hash(&self, module: &str) -> u64 {
+        let Some(files) = self.modules.get(module) else {
+            return 0;
+        };
+        let mut files = files.iter().collect::<Vec<_>>();
+        files.sort();
+        let mut hasher: XxHash64 = Default::default();
+        for file in files {
+            hasher.write(file.to_string_lossy().as_bytes());
+            hasher.write(&fs::read(file).unwrap_or_default());
+        }
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::FileDependencies;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_file_dependencies() {
+        let mut file_dependencies = FileDependencies::default();
+        let schema = PathBuf::from("/project/schema.graphql");
+        file_dependencies.add("/project/src/b.ts", schema.clone());
+        file_dependencies.add("/project/src/a.ts", schema.clone());
+        file_dependencies.add("/project/src/a.ts", PathBuf::from("/project/i18n.json"));
+
+        assert_eq!(
+            file_dependencies.dependents(&schema),
+            vec!["/project/src/a.ts", "/project/src/b.ts"]
+        );
+        assert_eq!(file_dependencies.files().len(), 2);
+
+        file_dependencies.clear("/project/src/b.ts");
+        assert_eq!(
+            file_dependencies.dependents(&schema),
+            vec!["/project/src/a.ts"]
+        );
+        assert!(file_dependencies
+            .dependents(Path::new("/project/src/a.ts"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let dir = create_temp_dir("file_deps");
+        let schema = dir.path().join("schema.graphql");
+        fs::write(&schema, "type Query { a: Int }").unwrap();
+
+        let mut file_dependencies = FileDependencies::default();
+        assert_eq!(file_dependencies.content_hash("a.ts"), 0);
+        file_dependencies.add("a.ts", schema.clone());
+        let hash = file_dependencies.content_hash("a.ts");
+        assert_eq!(file_dependencies.content_hash("a.ts"), hash);
+        fs::write(&schema, "type Query { b: Int }").unwrap();
+        assert_ne!(file_dependencies.content_hash("a.ts"), hash);
+    }
+}
@@ -13,7 +13,7 @@ use tracing::debug;
 use crate::ast::file::{Content, File, JsContent};
 use crate::compiler::Context;
 use crate::config::Mode;
-use crate::plugin::PluginLoadParam;
+use crate::plugin::{PluginEmitAssetKind, PluginLoadParam};
 
 #[derive(Debug, Error)]
 enum LoadError {
@@ -45,6 +45,20 @@ const SVGR_NAMED_EXPORT: &str = r#"ReactComponent"#;
 pub struct Load {}
 
 impl Load {
+    // react-refresh is skipped for preact since it's not installed
+    pub fn hmr_entry_runtime(context: &Context) -> String {
+        let hmr_entry = include_str!("../runtime/runtime_hmr_entry.js");
+        if context.config.preact {
+            hmr_entry.to_string()
+        } else {
+            format!(
+                "{}\n{}",
+                include_str!("../runtime/runtime_react_refresh_entry.js"),
+                hmr_entry
+            )
+        }
+    }
+
     pub fn load(file: &File, context: Arc<Context>) -> Result<Content> {
         crate::mako_profile_function!(file.path.to_string_lossy());
         debug!("load: {:?}", file);
@@ -106,7 +120,7 @@ export function moduleToDom(css) {
             if file.is_entry && file.has_param("hmr") {
                 let content = format!(
                     "{}\nmodule.exports = require(\"{}\");\n",
-                    include_str!("../runtime/runtime_hmr_entry.js"),
+                    Self::hmr_entry_runtime(&context),
                     file.pathname.to_string_lossy(),
                 );
                 return Ok(Content::Js(JsContent { content, is_jsx }));
@@ -183,7 +197,7 @@ export function moduleToDom(css) {
             let final_file_name = format!(
                 "{}.{}.{}",
                 file.get_file_stem(),
-                file.get_content_hash()?,
+                file.get_content_hash(&context.config.hash_salt)?,
                 file.extname
             );
             context.emit_assets(
@@ -250,7 +264,7 @@ export function moduleToDom(css) {
                 path: file.path.to_string_lossy().to_string(),
             })?;
         let emit_assets = || -> Result<String> {
-            let final_file_name = Self::emit_asset(file, context.clone());
+            let final_file_name = Self::emit_asset(file, context.clone())?;
             if inject_public_path {
                 Ok(format!("`${{require.publicPath}}{}`", final_file_name))
             } else {
@@ -274,16 +288,27 @@ export function moduleToDom(css) {
         }
     }
 
-    pub fn emit_asset(file: &File, context: Arc<Context>) -> String {
+    pub fn emit_asset(file: &File, context: Arc<Context>) -> Result<String> {
         let path = file.pathname.to_string_lossy().to_string();
         let final_file_name = format!(
             "{}.{}.{}",
             file.get_file_stem(),
-            file.get_content_hash().unwrap(),
+            file.get_content_hash(&context.config.hash_salt).unwrap(),
             file.extname
         );
-        context.emit_assets(path, final_file_name.clone());
-        final_file_name
+        // plugins may rename the asset, e.g. move fonts to static/fonts/
+        // or skip it, e.g. it's uploaded to cdn by themselves
+        match context.plugin_driver.emit_asset(
+            PluginEmitAssetKind::Asset,
+            final_file_name.clone(),
+            &context,
+        )? {
+            Some(final_file_name) => {
+                context.emit_assets(path, final_file_name.clone());
+                Ok(final_file_name)
+            }
+            None => Ok(final_file_name),
+        }
     }
 }
 
@@ -297,3 +322,72 @@ impl FileSystem {
         Ok(String::from_utf8_lossy(&buf).to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use super::Load;
+    use crate::ast::file::File;
+    use crate::compiler::Context;
+    use crate::plugin::{
+        Plugin, PluginDriver, PluginEmitAsset, PluginEmitAssetKind, PluginEmitAssetParam,
+    };
+    use crate::utils::test_helper::create_temp_dir;
+
+    struct EmitAssetPlugin {}
+
+    impl Plugin for EmitAssetPlugin {
+        fn name(&self) -> &str {
+            "emit_asset"
+        }
+
+        fn emit_asset(
+            &self,
+            param: &PluginEmitAssetParam,
+            _context: &Arc<Context>,
+        ) -> Result<Option<PluginEmitAsset>> {
+            assert_eq!(param.kind, PluginEmitAssetKind::Asset);
+            if param.filename.ends_with(".woff2") {
+                return Ok(Some(PluginEmitAsset::Rename(format!(
+                    "static/fonts/{}",
+                    param.filename
+                ))));
+            }
+            if param.filename.ends_with(".mp4") {
+                return Ok(Some(PluginEmitAsset::Skip));
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_emit_asset() {
+        let dir = create_temp_dir("emit_asset");
+        let root = dir.path();
+        fs::write(root.join("a.woff2"), "font").unwrap();
+        fs::write(root.join("b.mp4"), "video").unwrap();
+        let context = Arc::new(Context {
+            root: root.to_path_buf(),
+            plugin_driver: PluginDriver::new(vec![Arc::new(EmitAssetPlugin {})]),
+            ..Default::default()
+        });
+
+        let font = root.join("a.woff2").to_string_lossy().to_string();
+        let file = File::new(font.clone(), context.clone());
+        let filename = Load::emit_asset(&file, context.clone()).unwrap();
+        assert!(filename.starts_with("static/fonts/a."));
+        assert!(filename.ends_with(".woff2"));
+
+        let video = root.join("b.mp4").to_string_lossy().to_string();
+        let file = File::new(video.clone(), context.clone());
+        Load::emit_asset(&file, context.clone()).unwrap();
+
+        let assets_info = context.assets_info.lock().unwrap();
+        assert_eq!(assets_info.get(&font), Some(&filename));
+        assert!(!assets_info.contains_key(&video));
+    }
+}
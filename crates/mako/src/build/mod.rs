@@ -1,8 +1,10 @@
 pub(crate) mod analyze_deps;
+pub(crate) mod file_dependencies;
 pub(crate) mod load;
 pub(crate) mod parse;
 pub(crate) mod targets;
 pub(crate) mod transform;
+pub(crate) mod transform_cache;
 
 use std::collections::HashSet;
 use std::sync::mpsc::channel;
@@ -17,7 +19,9 @@ use crate::compiler::{Compiler, Context};
 use crate::generate::chunk_pot::util::hash_hashmap;
 use crate::module::{Module, ModuleAst, ModuleId, ModuleInfo};
 use crate::plugin::NextBuildParam;
+use crate::plugins::bundle_policy::check_package_allowed;
 use crate::resolve::ResolverResource;
+use crate::stats::time_module_stage;
 use crate::utils::thread_pool;
 
 #[derive(Debug, Error)]
@@ -108,7 +112,14 @@ impl Compiler {
                         ResolverResource::Virtual(_) | ResolverResource::Resolved(_) => {
                             let file = File::new(path.clone(), self.context.clone());
 
-                            if self.context.plugin_driver.next_build(&NextBuildParam {
+                            if let Err(err) = check_package_allowed(
+                                &path,
+                                &module_id,
+                                &module_graph,
+                                &self.context,
+                            ) {
+                                errors.push(err);
+                            } else if self.context.plugin_driver.next_build(&NextBuildParam {
                                 current_module: &module_id,
                                 next_file: &file,
                                 resource: &dep.resolver_resource,
@@ -157,7 +168,7 @@ impl Compiler {
             // safe
             .unwrap();
         let external_script = resolved_resource.get_script();
-        let is_async = external_script.is_some();
+        let is_async = external_script.is_some() || resolved_resource.get_import_map().is_some();
         let origin_path = resolved_resource.get_resolved_path();
         let path = format!("virtual:external_{}", origin_path);
         let mut file = File::new(path.clone(), context.clone());
@@ -269,22 +280,47 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         parent_resource: Option<ResolverResource>,
         context: Arc<Context>,
     ) -> Result<Module> {
+        let path = file.path.to_string_lossy().to_string();
+        // registered again by the hooks below
+        context.file_dependencies.write().unwrap().clear(&path);
+
         // 1. load
         let mut file = file.clone();
-        let content = load::Load::load(&file, context.clone())?;
+        let content = time_module_stage(&context, "load", &path, || {
+            load::Load::load(&file, context.clone())
+        })?;
         file.set_content(content);
 
-        // 2. parse
-        let mut ast = parse::Parse::parse(&file, context.clone())?;
-
-        // 3. transform
-        transform::Transform::transform(&mut ast, &file, context.clone())?;
+        // 2. parse + 3. transform, transform results of dependencies are restored from the
+        // persistent cache if possible
+        let cache_key = transform_cache::cache_key(&file, &context);
+        let cached_ast = cache_key.as_ref().and_then(|key| {
+            time_module_stage(&context, "parse", &path, || {
+                transform_cache::restore(key, &file, &context)
+            })
+        });
+        let ast = match cached_ast {
+            Some(ast) => ast,
+            None => {
+                let mut ast = time_module_stage(&context, "parse", &path, || {
+                    parse::Parse::parse(&file, context.clone())
+                })?;
+                time_module_stage(&context, "transform", &path, || {
+                    transform::Transform::transform(&mut ast, &file, context.clone())
+                })?;
+                if let Some(key) = &cache_key {
+                    transform_cache::store(key, &ast, &context)?;
+                }
+                ast
+            }
+        };
 
         // 4. analyze deps + resolve
-        let deps = analyze_deps::AnalyzeDeps::analyze_deps(&ast, &file, context.clone())?;
+        let deps = time_module_stage(&context, "analyzeDeps", &path, || {
+            analyze_deps::AnalyzeDeps::analyze_deps(&ast, &file, context.clone())
+        })?;
 
         // 5. create module
-        let path = file.path.to_string_lossy().to_string();
         let module_id = ModuleId::new(path.clone());
         let raw = file.get_content_raw();
         let is_entry = file.is_entry;
@@ -301,6 +337,13 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         let raw_hash = if context.args.watch {
             file.get_raw_hash()
                 .wrapping_add(hash_hashmap(&deps.missing_deps))
+                .wrapping_add(
+                    context
+                        .file_dependencies
+                        .read()
+                        .unwrap()
+                        .content_hash(&path),
+                )
         } else {
             0
         };
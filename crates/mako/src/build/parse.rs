@@ -69,6 +69,8 @@ impl Parse {
                     &file.pathname.to_string_lossy(),
                     &mut ast.ast,
                     context.config.css_modules_export_only_locales,
+                    &context.config.hash_salt,
+                    &context.config.css_modules.clone().unwrap_or_default(),
                 );
                 file.set_content(Content::Js(JsContent {
                     content,
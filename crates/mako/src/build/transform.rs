@@ -13,17 +13,21 @@ use swc_core::ecma::transforms::base::{resolver, Assumptions};
 use swc_core::ecma::transforms::optimization::simplifier;
 use swc_core::ecma::transforms::optimization::simplify::{dce, Config as SimpilifyConfig};
 use swc_core::ecma::transforms::proposal::decorators;
-use swc_core::ecma::visit::{Fold, VisitMut};
+use swc_core::ecma::visit::Optional;
 
 use crate::ast::css_ast::CssAst;
 use crate::ast::file::File;
+use crate::ast::pipeline::TransformPipeline;
 use crate::build::targets;
 use crate::build::targets::swc_preset_env_targets_from_map;
 use crate::compiler::Context;
 use crate::config::Mode;
 use crate::features;
 use crate::module::ModuleAst;
+use crate::plugin::PluginTransformStagesParam;
 use crate::plugins::context_module::ContextModuleVisitor;
+use crate::plugins::polyfill_report;
+use crate::visitors::constant_condition::ConstantCondition;
 use crate::visitors::css_assets::CSSAssets;
 use crate::visitors::css_flexbugs::CSSFlexbugs;
 use crate::visitors::css_px2rem::Px2Rem;
@@ -32,8 +36,11 @@ use crate::visitors::dynamic_import_to_require::DynamicImportToRequire;
 use crate::visitors::env_replacer::{build_env_map, EnvReplacer};
 use crate::visitors::fix_helper_inject_position::FixHelperInjectPosition;
 use crate::visitors::fix_symbol_conflict::FixSymbolConflict;
+use crate::visitors::import_meta_resolve::ImportMetaResolve;
 use crate::visitors::new_url_assets::NewUrlAssets;
+use crate::visitors::no_side_effects::NoSideEffects;
 use crate::visitors::provide::Provide;
+use crate::visitors::public_assets::PublicAssets;
 use crate::visitors::react::react;
 use crate::visitors::try_resolve::TryResolve;
 use crate::visitors::ts_strip::ts_strip;
@@ -61,56 +68,86 @@ impl Transform {
                         || file.extname == "ts"
                         || file.extname == "tsx";
 
-                    // visitors
-                    let mut visitors: Vec<Box<dyn VisitMut>> = vec![
-                        Box::new(resolver(unresolved_mark, top_level_mark, is_ts || is_tsx)),
-                        // fix helper inject position
-                        // should be removed after upgrade to latest swc
-                        // ref: https://github.com/umijs/mako/issues/1193
-                        Box::new(FixHelperInjectPosition::new()),
-                        Box::new(FixSymbolConflict::new(top_level_mark)),
-                        Box::new(NewUrlAssets {
+                    // built-in stages, custom stages of plugins are placed relative to the names
+                    let mut pipeline = TransformPipeline::new();
+                    pipeline.visitor(
+                        "resolver",
+                        resolver(unresolved_mark, top_level_mark, is_ts || is_tsx),
+                    );
+                    // fix helper inject position
+                    // should be removed after upgrade to latest swc
+                    // ref: https://github.com/umijs/mako/issues/1193
+                    pipeline.visitor("fix_helper_inject_position", FixHelperInjectPosition::new());
+                    pipeline.visitor(
+                        "fix_symbol_conflict",
+                        FixSymbolConflict::new(top_level_mark),
+                    );
+                    pipeline.visitor(
+                        "new_url_assets",
+                        NewUrlAssets {
                             context: context.clone(),
                             path: file.path.clone(),
                             unresolved_mark,
-                        }),
-                        Box::new(WorkerModule::new(unresolved_mark)),
-                    ];
-                    if is_tsx {
-                        visitors.push(Box::new(tsx_strip(
-                            cm.clone(),
-                            context.clone(),
-                            top_level_mark,
-                        )))
-                    }
+                        },
+                    );
+                    pipeline.visitor("worker_module", WorkerModule::new(unresolved_mark));
+                    pipeline.visitor(
+                        "no_side_effects",
+                        NoSideEffects::new(origin_comments.get_swc_comments().clone()),
+                    );
+                    pipeline.visitor(
+                        "public_assets",
+                        Optional::new(
+                            PublicAssets {
+                                context: context.clone(),
+                                unresolved_mark,
+                            },
+                            context.config.public_assets.is_some(),
+                        ),
+                    );
+                    pipeline.visitor(
+                        "tsx_strip",
+                        Optional::new(
+                            tsx_strip(cm.clone(), context.clone(), top_level_mark),
+                            is_tsx,
+                        ),
+                    );
                     // strip should be ts only
                     // since when use this in js, it will remove all unused imports
                     // which is not expected as what webpack does
-                    if is_ts {
-                        visitors.push(Box::new(ts_strip(top_level_mark)))
-                    }
+                    pipeline.visitor("ts_strip", Optional::new(ts_strip(top_level_mark), is_ts));
                     // named default export
-                    if context.args.watch && !file.is_under_node_modules && is_jsx {
-                        visitors.push(Box::new(DefaultExportNamer::new()));
-                    }
+                    pipeline.visitor(
+                        "default_export_namer",
+                        Optional::new(
+                            DefaultExportNamer::new(),
+                            context.args.watch && !file.is_under_node_modules && is_jsx,
+                        ),
+                    );
                     // react & react-refresh
                     let is_dev = matches!(context.config.mode, Mode::Development);
                     let is_browser =
                         matches!(context.config.platform, crate::config::Platform::Browser);
+                    // react-refresh doesn't work with preact
                     let use_refresh = is_dev
                         && context.args.watch
+                        && !context.config.preact
                         && context.config.hmr.is_some()
                         && !file.is_under_node_modules
                         && is_browser;
-                    if is_jsx {
-                        visitors.push(react(
-                            cm,
-                            context.clone(),
-                            use_refresh,
-                            &top_level_mark,
-                            &unresolved_mark,
-                        ));
-                    }
+                    pipeline.visitor(
+                        "react",
+                        Optional::new(
+                            react(
+                                cm,
+                                context.clone(),
+                                use_refresh,
+                                &top_level_mark,
+                                &unresolved_mark,
+                            ),
+                            is_jsx,
+                        ),
+                    );
                     // TODO: refact env replacer
                     {
                         let mut define = context.config.define.clone();
@@ -119,78 +156,137 @@ impl Transform {
                             .entry("NODE_ENV".to_string())
                             .or_insert_with(|| format!("\"{}\"", mode).into());
                         let env_map = build_env_map(define, &context)?;
-                        visitors.push(Box::new(EnvReplacer::new(
-                            Lrc::new(env_map),
-                            unresolved_mark,
-                        )));
+                        let typeof_process =
+                            matches!(context.config.platform, crate::config::Platform::Node)
+                                || context.config.providers.contains_key("process");
+                        let mut env_replacer = EnvReplacer::new(Lrc::new(env_map), unresolved_mark)
+                            .with_typeof_process(typeof_process)
+                            .with_runtime_env(&context.config.runtime_env);
+                        // libraries commonly probe optional env keys, only check user code
+                        if !file.is_under_node_modules {
+                            env_replacer = env_replacer.with_diagnostics(
+                                context.clone(),
+                                file.relative_path.to_string_lossy().to_string(),
+                            );
+                        }
+                        pipeline.visitor("env_replacer", env_replacer);
                     }
-                    visitors.push(Box::new(TryResolve {
-                        path: file.path.to_string_lossy().to_string(),
-                        context: context.clone(),
-                        unresolved_mark,
-                    }));
+                    // after EnvReplacer, so that the conditions of env checks are literals
+                    pipeline.visitor(
+                        "constant_condition",
+                        ConstantCondition::new(unresolved_mark),
+                    );
+                    pipeline.visitor(
+                        "try_resolve",
+                        TryResolve {
+                            path: file.path.to_string_lossy().to_string(),
+                            context: context.clone(),
+                            unresolved_mark,
+                        },
+                    );
+                    pipeline.visitor(
+                        "import_meta_resolve",
+                        ImportMetaResolve {
+                            path: file.path.to_string_lossy().to_string(),
+                            context: context.clone(),
+                        },
+                    );
                     // TODO: refact provide
-                    visitors.push(Box::new(Provide::new(
-                        context.config.providers.clone(),
-                        unresolved_mark,
-                        top_level_mark,
-                    )));
-                    visitors.push(Box::new(VirtualCSSModules {
-                        auto_css_modules: context.config.auto_css_modules,
-                    }));
+                    pipeline.visitor(
+                        "provide",
+                        Provide::new(
+                            context.config.providers.clone(),
+                            unresolved_mark,
+                            top_level_mark,
+                        ),
+                    );
+                    pipeline.visitor(
+                        "virtual_css_modules",
+                        VirtualCSSModules {
+                            auto_css_modules: context.config.auto_css_modules,
+                        },
+                    );
                     // TODO: move ContextModuleVisitor out of plugin
-                    visitors.push(Box::new(ContextModuleVisitor { unresolved_mark }));
+                    pipeline.visitor("context_module", ContextModuleVisitor { unresolved_mark });
                     // DynamicImportToRequire must be after ContextModuleVisitor
                     // since ContextModuleVisitor will add extra dynamic imports
-                    if context.config.dynamic_import_to_require {
-                        visitors.push(Box::new(DynamicImportToRequire { unresolved_mark }));
-                    }
-                    if matches!(context.config.platform, crate::config::Platform::Node) {
-                        visitors.push(Box::new(features::node::MockFilenameAndDirname {
-                            unresolved_mark,
-                            current_path: file.path.clone(),
-                            context: context.clone(),
-                        }));
-                    }
+                    pipeline.visitor(
+                        "dynamic_import_to_require",
+                        Optional::new(
+                            DynamicImportToRequire { unresolved_mark },
+                            context.config.dynamic_import_to_require,
+                        ),
+                    );
+                    pipeline.visitor(
+                        "mock_filename_and_dirname",
+                        Optional::new(
+                            features::node::MockFilenameAndDirname {
+                                unresolved_mark,
+                                current_path: file.path.clone(),
+                                context: context.clone(),
+                            },
+                            matches!(context.config.platform, crate::config::Platform::Node),
+                        ),
+                    );
 
                     // folders
-                    let mut folders: Vec<Box<dyn Fold>> = vec![];
                     // decorators should go before preset_env, when compile down to es5, classes become functions, then the decorators on the functions will be removed silently.
-                    folders.push(Box::new(decorators(decorators::Config {
-                        legacy: true,
-                        emit_metadata: false,
-                        ..Default::default()
-                    })));
+                    pipeline.folder(
+                        "decorators",
+                        decorators(decorators::Config {
+                            legacy: true,
+                            emit_metadata: false,
+                            ..Default::default()
+                        }),
+                    );
                     let comments = origin_comments.get_swc_comments().clone();
                     let assumptions = context.assumptions_for(file);
 
-                    folders.push(Box::new(swc_preset_env::preset_env(
-                        unresolved_mark,
-                        Some(comments),
-                        swc_preset_env::Config {
-                            mode: Some(swc_preset_env::Mode::Entry),
-                            targets: Some(swc_preset_env_targets_from_map(
-                                context.config.targets.clone(),
-                            )),
-                            ..Default::default()
-                        },
-                        assumptions,
-                        &mut FeatureFlag::default(),
-                    )));
-                    folders.push(Box::new(paren_remover(Default::default())));
+                    pipeline.folder(
+                        "polyfill_report",
+                        Optional::new(
+                            polyfill_report::PolyfillReport {
+                                unresolved_mark,
+                                comments: comments.clone(),
+                                assumptions,
+                                context: context.clone(),
+                            },
+                            polyfill_report::enabled(&context),
+                        ),
+                    );
+                    pipeline.folder(
+                        "preset_env",
+                        swc_preset_env::preset_env(
+                            unresolved_mark,
+                            Some(comments),
+                            swc_preset_env::Config {
+                                mode: Some(swc_preset_env::Mode::Entry),
+                                targets: Some(swc_preset_env_targets_from_map(
+                                    context.config.targets.clone(),
+                                )),
+                                ..Default::default()
+                            },
+                            assumptions,
+                            &mut FeatureFlag::default(),
+                        ),
+                    );
+                    pipeline.folder("paren_remover", paren_remover(Default::default()));
                     // simplify, but keep top level dead code
                     // e.g. import x from 'foo'; but x is not used
                     // this must be kept for tree shaking to work
-                    folders.push(Box::new(simplifier(
-                        unresolved_mark,
-                        SimpilifyConfig {
-                            dce: dce::Config {
-                                top_level: false,
+                    pipeline.folder(
+                        "simplifier",
+                        simplifier(
+                            unresolved_mark,
+                            SimpilifyConfig {
+                                dce: dce::Config {
+                                    top_level: false,
+                                    ..Default::default()
+                                },
                                 ..Default::default()
                             },
-                            ..Default::default()
-                        },
-                    )));
+                        ),
+                    );
                     // NOTICE: remove optimize_package_imports temporarily
                     // folders.push(Box::new(Optional {
                     //     enabled: should_optimize(file.path.to_str().unwrap(), context.clone()),
@@ -199,8 +295,19 @@ impl Transform {
                     //         context.clone(),
                     //     ),
                     // }));
+                    pipeline.plugins();
+
+                    let custom_stages = context.plugin_driver.transform_stages(
+                        &PluginTransformStagesParam {
+                            file,
+                            top_level_mark,
+                            unresolved_mark,
+                        },
+                        &context,
+                    )?;
+                    pipeline.insert(custom_stages)?;
 
-                    ast.transform(&mut visitors, &mut folders, file, true, context.clone())?;
+                    ast.transform_pipeline(&mut pipeline, file, true, context.clone())?;
 
                     Ok(())
                 })
@@ -238,7 +345,11 @@ impl Transform {
                 // css modules
                 let is_modules = file.has_param("modules");
                 if is_modules {
-                    CssAst::compile_css_modules(file.pathname.to_str().unwrap(), &mut ast.ast);
+                    CssAst::compile_css_modules(
+                        file.pathname.to_str().unwrap(),
+                        &mut ast.ast,
+                        &context.config.hash_salt,
+                    );
                 }
 
                 Ok(())
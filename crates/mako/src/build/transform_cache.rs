@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use swc_core::common::{Mark, GLOBALS};
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
+use swc_core::ecma::transforms::base::fixer::fixer;
+use swc_core::ecma::transforms::base::hygiene::{self, hygiene_with_config};
+use swc_core::ecma::transforms::base::resolver;
+use swc_core::ecma::visit::VisitMutWith;
+
+use crate::ast::file::{Content, File, JsContent};
+use crate::ast::js_ast::JsAst;
+use crate::compiler::Context;
+use crate::module::ModuleAst;
+use crate::plugin::PluginTransformStagesParam;
+use crate::plugins::polyfill_report;
+
+/**
+ * transform results of dependencies in the persistent cache, the transformed code is parsed
+ * instead of parsing and transforming the source again
+ *
+ * keys are hashes of the root-relative paths and the contents of modules, so that they are the
+ * same across machines and checkouts, the mako version, the config and the lockfile are
+ * included by the namespace of the cache
+ */
+pub(crate) fn cache_key(file: &File, context: &Arc<Context>) -> Option<String> {
+    let cache = context.persistent_cache.as_ref()?;
+    // user code is excluded since its transforms report diagnostics, and the transforms of
+    // dependencies only depend on the installed packages, which are keyed by the lockfile
+    if context.args.watch || !file.is_under_node_modules || polyfill_report::enabled(context) {
+        return None;
+    }
+    // client components are emitted when they are parsed
+    if context.config.rsc_server.is_some() || context.config.rsc_client.is_some() {
+        return None;
+    }
+    let Some(Content::Js(JsContent { content, .. })) = &file.content else {
+        return None;
+    };
+    // assets referenced by `new URL()` are emitted by the transform
+    if content.contains("new URL(") {
+        return None;
+    }
+    // custom stages of plugins aren't keyed
+    let custom_stages = context
+        .plugin_driver
+        .transform_stages(
+            &PluginTransformStagesParam {
+                file,
+                top_level_mark: Mark::root(),
+                unresolved_mark: Mark::root(),
+            },
+            context,
+        )
+        .ok()?;
+    if !custom_stages.is_empty() {
+        return None;
+    }
+
+    let relative_path = file.relative_path.to_string_lossy().replace('\\', "/");
+    let mut md5_context = md5::Context::new();
+    md5_context.consume(cache.namespace());
+    md5_context.consume(relative_path);
+    md5_context.consume(&file.search);
+    md5_context.consume(file.get_raw_hash().to_le_bytes());
+    Some(format!("{:x}", md5_context.compute()))
+}
+
+pub(crate) fn restore(key: &str, file: &File, context: &Arc<Context>) -> Option<ModuleAst> {
+    let code = String::from_utf8(context.persistent_cache.as_ref()?.get(key)?).ok()?;
+    let mut file = file.clone();
+    // the transformed code is plain js
+    file.extname = "js".to_string();
+    file.set_content(Content::Js(JsContent {
+        content: code,
+        ..Default::default()
+    }));
+    let mut ast = JsAst::new(&file, context.clone()).ok()?;
+    GLOBALS.set(&context.meta.script.globals, || {
+        ast.ast.visit_mut_with(&mut resolver(
+            ast.unresolved_mark,
+            ast.top_level_mark,
+            false,
+        ));
+    });
+    Some(ModuleAst::Script(ast))
+}
+
+pub(crate) fn store(key: &str, ast: &ModuleAst, context: &Arc<Context>) -> Result<()> {
+    let (Some(cache), ModuleAst::Script(ast)) = (&context.persistent_cache, ast) else {
+        return Ok(());
+    };
+    let comments = context.meta.script.origin_comments.read().unwrap();
+    let swc_comments = comments.get_swc_comments();
+    // identifiers of the transforms are told apart by marks, which are lost in the code
+    let mut module = ast.ast.clone();
+    GLOBALS.set(&context.meta.script.globals, || {
+        module.visit_mut_with(&mut hygiene_with_config(hygiene::Config {
+            top_level_mark: ast.top_level_mark,
+            ..Default::default()
+        }));
+        module.visit_mut_with(&mut fixer(Some(swc_comments)));
+    });
+
+    let mut buf = vec![];
+    {
+        let cm = context.meta.script.cm.clone();
+        let mut emitter = Emitter {
+            cfg: JsCodegenConfig::default().with_target(context.config.output.es_version),
+            cm: cm.clone(),
+            // annotations like #__PURE__ are kept for tree shaking and minify
+            comments: Some(swc_comments),
+            wr: Box::new(JsWriter::new(cm, "\n", &mut buf, None)),
+        };
+        emitter.emit_module(&module)?;
+    }
+    cache.put(key, &buf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::{cache_key, restore, store};
+    use crate::ast::file::{Content, File, JsContent};
+    use crate::ast::js_ast::JsAst;
+    use crate::build::transform::Transform;
+    use crate::cache::PersistentCache;
+    use crate::compiler::{Args, Context};
+    use crate::config::{CacheConfig, Config};
+    use crate::module::ModuleAst;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_cache_key() {
+        let (dir_a, dir_b) = (
+            create_temp_dir("transform_cache"),
+            create_temp_dir("transform_cache"),
+        );
+        let context_a = context(dir_a.path());
+        let context_b = context(dir_b.path());
+        let key = |path: &str, content: &str, context: &Arc<Context>| {
+            let file = file(&context.root.join(path).to_string_lossy(), content, context);
+            cache_key(&file, context)
+        };
+
+        let dep = "node_modules/foo/index.js";
+        let dep_key = key(dep, "const a = 1;", &context_a);
+        assert!(dep_key.is_some());
+        // the same across checkouts
+        assert_eq!(dep_key, key(dep, "const a = 1;", &context_b));
+        assert_ne!(dep_key, key(dep, "const a = 2;", &context_a));
+        assert_ne!(
+            dep_key,
+            key("node_modules/bar/index.js", "const a = 1;", &context_a)
+        );
+        assert_eq!(key("src/index.js", "const a = 1;", &context_a), None);
+        assert_eq!(
+            key(dep, "new URL('./a.png', import.meta.url);", &context_a),
+            None
+        );
+    }
+
+    #[test]
+    fn test_restore() {
+        let dir = create_temp_dir("transform_cache");
+        let context = context(dir.path());
+        let file = file(
+            &context
+                .root
+                .join("node_modules/foo/index.ts")
+                .to_string_lossy(),
+            "export const a: number = 1; export function b() { return a; }",
+            &context,
+        );
+        let key = cache_key(&file, &context).unwrap();
+        assert!(restore(&key, &file, &context).is_none());
+
+        let mut ast = ModuleAst::Script(JsAst::new(&file, context.clone()).unwrap());
+        Transform::transform(&mut ast, &file, context.clone()).unwrap();
+        store(&key, &ast, &context).unwrap();
+        let restored = restore(&key, &file, &context).unwrap();
+        let code = |ast: &ModuleAst| {
+            ast.as_script()
+                .unwrap()
+                .generate(context.clone())
+                .unwrap()
+                .code
+        };
+        assert_eq!(code(&restored), code(&ast));
+    }
+
+    fn context(dir: &Path) -> Arc<Context> {
+        let root = dir.join("project");
+        let mut config = Config::default();
+        config.devtool = None;
+        config.cache = Some(CacheConfig {
+            dir: "cache".into(),
+            remote: None,
+        });
+        let persistent_cache = PersistentCache::new(&config, &root, None).unwrap();
+        Arc::new(Context {
+            config,
+            root,
+            args: Args {
+                watch: false,
+                verbose: false,
+            },
+            persistent_cache,
+            ..Default::default()
+        })
+    }
+
+    fn file(path: &str, content: &str, context: &Arc<Context>) -> File {
+        let mut file = File::new(path.to_string(), context.clone());
+        file.set_content(Content::Js(JsContent {
+            content: content.to_string(),
+            ..Default::default()
+        }));
+        file
+    }
+}
@@ -0,0 +1,257 @@
+//! A builder-facing API to drive mako from other tools, e.g. a Storybook builder, enabled by the
+//! `builder-api` feature.
+//!
+//! ```rust,ignore
+//! use mako::builder::{Builder, RebuildEvent};
+//!
+//! let compiler = Builder::new(root)
+//!     .config(serde_json::json!({ "mode": "development", "writeToDisk": false }))
+//!     .virtual_entry("main", "import './.storybook/preview'; import './src/Button.stories';")
+//!     .on_rebuild(|event| {
+//!         if let RebuildEvent::Failure { error } = event {
+//!             eprintln!("{}", error);
+//!         }
+//!     })
+//!     .watch(true)
+//!     .build()?;
+//! compiler.compile()?;
+//! let main_js = compiler.read_asset("main.js");
+//! // rebuilds are driven by the dev server, e.g.
+//! // DevServer::new(root, compiler.clone()).serve(|_| {}).await;
+//! ```
+//!
+//! Items here follow semver of the crate, changes of them are breaking changes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::ast::file::{Content, JsContent};
+use crate::build::load::Load;
+use crate::compiler::{Args, Compiler, Context};
+use crate::config::Config;
+use crate::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam};
+
+const VIRTUAL_ENTRY_PREFIX: &str = "virtual:builder:entry:";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebuildEvent {
+    // both the first compile and the rebuilds of watch
+    Success { is_first_compile: bool, time: u64 },
+    Failure { error: String },
+}
+
+type RebuildCallback = Box<dyn Fn(&RebuildEvent) + Send + Sync>;
+
+pub struct Builder {
+    root: PathBuf,
+    config: Value,
+    virtual_entries: BTreeMap<String, String>,
+    plugins: Vec<Arc<dyn Plugin>>,
+    on_rebuild: Vec<RebuildCallback>,
+    watch: bool,
+}
+
+impl Builder {
+    // root must be absolute, the mako.config.json of root is still loaded if it exists
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            config: Value::Object(Default::default()),
+            virtual_entries: BTreeMap::new(),
+            plugins: vec![],
+            on_rebuild: vec![],
+            watch: false,
+        }
+    }
+
+    // partial config in the shape of mako.config.json, which overrides the config file
+    pub fn config(mut self, config: Value) -> Self {
+        self.config = config;
+        self
+    }
+
+    // an entry whose code is provided instead of read from disk, imports of it are resolved
+    // from root
+    pub fn virtual_entry(mut self, name: &str, code: &str) -> Self {
+        self.virtual_entries
+            .insert(name.to_string(), code.to_string());
+        self
+    }
+
+    pub fn plugin(mut self, plugin: Arc<dyn Plugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    pub fn on_rebuild<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RebuildEvent) + Send + Sync + 'static,
+    {
+        self.on_rebuild.push(Box::new(callback));
+        self
+    }
+
+    // build for the dev server, chunks are kept in memory and only written to disk when
+    // writeToDisk is enabled
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    pub fn build(self) -> Result<Arc<Compiler>> {
+        let mut config = self.config;
+        let Value::Object(config_map) = &mut config else {
+            return Err(anyhow!("builder config must be an object"));
+        };
+        if !self.virtual_entries.is_empty() {
+            let entry = config_map
+                .entry("entry")
+                .or_insert_with(|| Value::Object(Default::default()));
+            let Value::Object(entry) = entry else {
+                return Err(anyhow!("builder config entry must be an object"));
+            };
+            for name in self.virtual_entries.keys() {
+                entry.insert(name.clone(), Value::String(virtual_entry_path(name)));
+            }
+        }
+        let config = Config::new(&self.root, None, Some(&config.to_string()))?;
+
+        let mut plugins = self.plugins;
+        plugins.insert(
+            0,
+            Arc::new(BuilderPlugin {
+                virtual_entries: self.virtual_entries,
+                on_rebuild: self.on_rebuild,
+            }),
+        );
+        let args = Args {
+            watch: self.watch,
+            ..Default::default()
+        };
+        Ok(Arc::new(Compiler::new(
+            config,
+            self.root,
+            args,
+            Some(plugins),
+        )?))
+    }
+}
+
+impl Compiler {
+    // emitted file relative to the output path, from memory in watch mode or from disk
+    pub fn read_asset(&self, path: &str) -> Option<Vec<u8>> {
+        let path = path.trim_start_matches('/');
+        self.context
+            .get_static_content(path)
+            .or_else(|| fs::read(self.context.config.output.path.join(path)).ok())
+    }
+}
+
+fn virtual_entry_path(name: &str) -> String {
+    format!("{}{}.js", VIRTUAL_ENTRY_PREFIX, name)
+}
+
+struct BuilderPlugin {
+    virtual_entries: BTreeMap<String, String>,
+    on_rebuild: Vec<RebuildCallback>,
+}
+
+impl BuilderPlugin {
+    fn emit(&self, event: RebuildEvent) {
+        for callback in &self.on_rebuild {
+            callback(&event);
+        }
+    }
+}
+
+impl Plugin for BuilderPlugin {
+    fn name(&self) -> &str {
+        "builder"
+    }
+
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+        let pathname = param.file.pathname.to_string_lossy();
+        let Some(code) = pathname
+            .strip_prefix(VIRTUAL_ENTRY_PREFIX)
+            .and_then(|name| name.strip_suffix(".js"))
+            .and_then(|name| self.virtual_entries.get(name))
+        else {
+            return Ok(None);
+        };
+        // same as the entries on disk, the entry with ?hmr requires itself after the runtime
+        let content = if param.file.is_entry && param.file.has_param("hmr") {
+            format!(
+                "{}\nmodule.exports = require(\"{}\");\n",
+                Load::hmr_entry_runtime(context),
+                pathname,
+            )
+        } else {
+            code.clone()
+        };
+        Ok(Some(Content::Js(JsContent {
+            content,
+            is_jsx: false,
+        })))
+    }
+
+    fn generate_end(
+        &self,
+        params: &PluginGenerateEndParams,
+        _context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        self.emit(RebuildEvent::Success {
+            is_first_compile: params.is_first_compile,
+            time: params.time,
+        });
+        Ok(None)
+    }
+
+    fn build_error(&self, error: &anyhow::Error, _context: &Arc<Context>) -> Result<()> {
+        self.emit(RebuildEvent::Failure {
+            error: error.to_string(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    use super::{Builder, RebuildEvent};
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_virtual_entry() {
+        let dir = create_temp_dir("builder");
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.js"), "export default 'from_a';").unwrap();
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_clone = events.clone();
+        let compiler = Builder::new(root.to_path_buf())
+            .config(serde_json::json!({ "mode": "production", "minify": false }))
+            .virtual_entry("main", "import a from './src/a'; console.log(a);")
+            .on_rebuild(move |event| events_clone.lock().unwrap().push(event.clone()))
+            .build()
+            .unwrap();
+        compiler.compile().unwrap();
+
+        let main_js = String::from_utf8(compiler.read_asset("main.js").unwrap()).unwrap();
+        assert!(main_js.contains("from_a"));
+        assert!(matches!(
+            events.lock().unwrap()[..],
+            [RebuildEvent::Success {
+                is_first_compile: true,
+                ..
+            }]
+        ));
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode};
+
+use crate::config::{CacheConfig, Config};
+use crate::plugins::lockfile::lockfile_hash;
+use crate::utils::tokio_runtime;
+
+pub(crate) fn default_cache_dir() -> PathBuf {
+    PathBuf::from("node_modules/.cache_mako/persistent")
+}
+
+/**
+ * storage of the persistent cache, values are addressed by content keys
+ * so they can be shared across machines, e.g. by CI farms
+ *
+ * remote backends (s3, redis, etc.) can be plugged with Plugin::cache_backend
+ */
+pub trait CacheBackend: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn put(&self, key: &str, value: &[u8]) -> Result<()>;
+
+    // batches of the remote backend before and after builds, which may run concurrently
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
+    fn put_many(&self, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        entries
+            .iter()
+            .try_for_each(|(key, value)| self.put(key, value))
+    }
+}
+
+pub struct DiskCacheBackend {
+    dir: PathBuf,
+}
+
+impl DiskCacheBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl CacheBackend for DiskCacheBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.dir.join(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read(path)?))
+    }
+
+    // write to a temp file first, so readers never see a partial value
+    fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(key);
+        let tmp_path = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        fs::write(&tmp_path, value)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+// GET / PUT {url}/{key}, 404 means a miss
+pub struct HttpCacheBackend {
+    url: String,
+    client: Client<HttpConnector>,
+}
+
+impl HttpCacheBackend {
+    pub fn new(url: &str) -> Result<Self> {
+        if !url.starts_with("http://") {
+            return Err(anyhow!(
+                "cache.remote only supports http urls, use Plugin::cache_backend for others: {}",
+                url
+            ));
+        }
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        })
+    }
+}
+
+// number of concurrent requests of batches
+const HTTP_CONCURRENCY: usize = 16;
+
+impl HttpCacheBackend {
+    async fn fetch(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let uri = format!("{}/{}", self.url, key);
+        let res = self.client.get(uri.parse()?).await?;
+        match res.status() {
+            StatusCode::OK => Ok(Some(hyper::body::to_bytes(res.into_body()).await?.to_vec())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(anyhow!("GET {} failed with {}", uri, status)),
+        }
+    }
+
+    async fn upload(&self, key: &str, value: &[u8]) -> Result<()> {
+        let uri = format!("{}/{}", self.url, key);
+        let req = Request::builder()
+            .method(Method::PUT)
+            .uri(&uri)
+            .body(Body::from(value.to_vec()))?;
+        let res = self.client.request(req).await?;
+        if !res.status().is_success() {
+            return Err(anyhow!("PUT {} failed with {}", uri, res.status()));
+        }
+        Ok(())
+    }
+}
+
+impl CacheBackend for HttpCacheBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        tokio_runtime::block_in_place(self.fetch(key))
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        tokio_runtime::block_in_place(self.upload(key, value))
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        tokio_runtime::block_in_place(
+            stream::iter(keys)
+                .map(|key| self.fetch(key))
+                .buffered(HTTP_CONCURRENCY)
+                .try_collect(),
+        )
+    }
+
+    fn put_many(&self, entries: &[(String, Vec<u8>)]) -> Result<()> {
+        tokio_runtime::block_in_place(
+            stream::iter(entries)
+                .map(|(key, value)| self.upload(key, value))
+                .buffer_unordered(HTTP_CONCURRENCY)
+                .try_collect(),
+        )
+    }
+}
+
+/**
+ * values are read from and written to the local disk during builds, the remote backend if any
+ * is only accessed before and after builds, so that the threads of builds never wait for the
+ * network
+ *
+ * the keys used by a build are recorded in an index in the remote backend, values of the index
+ * are downloaded before the next build, and the new values are uploaded after it
+ *
+ * failures of backends never fail the build, they are reported as warnings
+ * and treated as misses
+ */
+pub struct PersistentCache {
+    // hash of the mako version, the config and the lockfile, which are shared by all keys
+    namespace: String,
+    local: DiskCacheBackend,
+    remote: Option<Arc<dyn CacheBackend>>,
+    // keys of the remote index which are downloaded to the local disk
+    remote_keys: Mutex<HashSet<String>>,
+    used_keys: Mutex<HashSet<String>>,
+}
+
+impl PersistentCache {
+    pub fn new(
+        config: &Config,
+        root: &Path,
+        remote: Option<Arc<dyn CacheBackend>>,
+    ) -> Result<Option<Self>> {
+        let Some(CacheConfig {
+            dir,
+            remote: remote_url,
+        }) = &config.cache
+        else {
+            return Ok(None);
+        };
+        let remote = match (remote, remote_url) {
+            (Some(remote), _) => Some(remote),
+            (None, Some(url)) => {
+                Some(Arc::new(HttpCacheBackend::new(url)?) as Arc<dyn CacheBackend>)
+            }
+            (None, None) => None,
+        };
+        Ok(Some(Self {
+            namespace: namespace(config, root)?,
+            local: DiskCacheBackend::new(root.join(dir)),
+            remote,
+            remote_keys: Default::default(),
+            used_keys: Default::default(),
+        }))
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let value = warn_on_error(self.local.get(key)).flatten()?;
+        self.used_keys.lock().unwrap().insert(key.to_string());
+        Some(value)
+    }
+
+    pub fn put(&self, key: &str, value: &[u8]) {
+        if warn_on_error(self.local.put(key, value)).is_some() {
+            self.used_keys.lock().unwrap().insert(key.to_string());
+        }
+    }
+
+    // download values of the remote index which are missing on the local disk
+    pub fn pull(&self) {
+        let Some(remote) = &self.remote else {
+            return;
+        };
+        let Some(index) = warn_on_error(remote.get(&self.index_key())).flatten() else {
+            return;
+        };
+        let (local_keys, missing_keys): (Vec<String>, Vec<String>) =
+            String::from_utf8_lossy(&index)
+                .lines()
+                .map(|key| key.to_string())
+                .partition(|key| self.local.dir.join(key).exists());
+        let mut remote_keys = self.remote_keys.lock().unwrap();
+        remote_keys.extend(local_keys);
+        let Some(values) = warn_on_error(remote.get_many(&missing_keys)) else {
+            return;
+        };
+        for (key, value) in missing_keys.into_iter().zip(values) {
+            if let Some(value) = value
+                && warn_on_error(self.local.put(&key, &value)).is_some()
+            {
+                remote_keys.insert(key);
+            }
+        }
+    }
+
+    // upload values which are not in the remote backend, and the index of the keys of this build
+    pub fn push(&self) {
+        let Some(remote) = &self.remote else {
+            return;
+        };
+        let used_keys = self.used_keys.lock().unwrap();
+        let remote_keys = self.remote_keys.lock().unwrap();
+        if *used_keys == *remote_keys {
+            return;
+        }
+        let entries = used_keys
+            .iter()
+            .filter(|key| !remote_keys.contains(*key))
+            .filter_map(|key| Some((key.clone(), warn_on_error(self.local.get(key)).flatten()?)))
+            .collect::<Vec<_>>();
+        if warn_on_error(remote.put_many(&entries)).is_none() {
+            return;
+        }
+        let mut keys = used_keys.iter().map(|key| key.as_str()).collect::<Vec<_>>();
+        keys.sort();
+        warn_on_error(remote.put(&self.index_key(), keys.join("\n").as_bytes()));
+    }
+
+    fn index_key(&self) -> String {
+        format!("index-{}", self.namespace)
+    }
+}
+
+// paths in the config are relative to root, so that keys are the same across checkouts
+fn namespace(config: &Config, root: &Path) -> Result<String> {
+    let root_json = serde_json::to_string(&root.to_string_lossy())?;
+    let config = serde_json::to_string(config)?.replace(root_json.trim_matches('"'), "");
+    let mut md5_context = md5::Context::new();
+    md5_context.consume(env!("CARGO_PKG_VERSION"));
+    md5_context.consume(config);
+    md5_context.consume(lockfile_hash(root).to_le_bytes());
+    Ok(format!("{:x}", md5_context.compute()))
+}
+
+fn warn_on_error<T>(result: Result<T>) -> Option<T> {
+    result
+        .map_err(|e| {
+            println!(
+                "{}: persistent cache is skipped, {}",
+                "warning".to_string().yellow(),
+                e
+            );
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use super::{CacheBackend, DiskCacheBackend, PersistentCache};
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[derive(Default)]
+    struct MemoryCacheBackend {
+        values: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    impl CacheBackend for MemoryCacheBackend {
+        fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+            Ok(self.values.lock().unwrap().get(key).cloned())
+        }
+
+        fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+            self.values
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_disk_cache_backend() {
+        let dir = create_temp_dir("cache");
+        let backend = DiskCacheBackend::new(dir.path().to_path_buf());
+        assert_eq!(backend.get("a").unwrap(), None);
+        backend.put("a", b"value").unwrap();
+        assert_eq!(backend.get("a").unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[test]
+    fn test_pull_and_push() {
+        let remote = Arc::new(MemoryCacheBackend::default());
+        let dir = create_temp_dir("cache_remote");
+        let machine_a = persistent_cache(dir.path().join("a"), remote.clone());
+        let machine_b = persistent_cache(dir.path().join("b"), remote.clone());
+
+        // values are only uploaded after the build
+        machine_a.pull();
+        machine_a.put("k1", b"v1");
+        assert_eq!(remote.get("k1").unwrap(), None);
+        machine_a.push();
+        assert_eq!(remote.get("k1").unwrap(), Some(b"v1".to_vec()));
+        assert_eq!(remote.get("index-ns").unwrap(), Some(b"k1".to_vec()));
+
+        // and downloaded before the next build of other machines
+        assert_eq!(machine_b.get("k1"), None);
+        machine_b.pull();
+        assert_eq!(machine_b.get("k1"), Some(b"v1".to_vec()));
+        machine_b.put("k2", b"v2");
+        machine_b.push();
+        assert_eq!(remote.get("k2").unwrap(), Some(b"v2".to_vec()));
+        assert_eq!(remote.get("index-ns").unwrap(), Some(b"k1\nk2".to_vec()));
+    }
+
+    fn persistent_cache(dir: PathBuf, remote: Arc<MemoryCacheBackend>) -> PersistentCache {
+        PersistentCache {
+            namespace: "ns".to_string(),
+            local: DiskCacheBackend::new(dir),
+            remote: Some(remote),
+            remote_keys: Default::default(),
+            used_keys: Default::default(),
+        }
+    }
+}
@@ -1,15 +1,62 @@
 use std::path::PathBuf;
 
 use clap;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use crate::config::Mode;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     #[arg(short, long)]
     pub watch: bool,
-    pub root: PathBuf,
+    /// List every occurrence of aggregated warnings
+    #[arg(long)]
+    pub verbose: bool,
+    /// Report circular dependencies, same as `circularDependency` with both levels "warn"
+    /// if it's not configured
+    #[arg(long)]
+    pub detect_circular: bool,
+    /// Write the module graph after build, in json if the extension is .json, otherwise in dot
+    #[arg(long)]
+    pub graph: Option<PathBuf>,
+    #[arg(required = true)]
+    pub root: Option<PathBuf>,
     #[arg(long, default_value_t = Mode::Development, value_enum)]
     pub mode: Mode,
 }
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Resolve stack traces of production chunks with sourcemaps and module boundaries
+    Symbolicate {
+        /// The output directory of the build
+        #[arg(long, default_value = "dist")]
+        dist: PathBuf,
+        /// The file of the stack trace, read from stdin if not specified
+        stack: Option<PathBuf>,
+    },
+    /// Verify the files of the output directory with the signed manifest, see `manifest.signature`
+    VerifyManifest {
+        /// The output directory of the build
+        #[arg(long, default_value = "dist")]
+        dist: PathBuf,
+        /// The signed manifest, relative to dist
+        #[arg(long, default_value = "asset-manifest.sig.json")]
+        manifest: PathBuf,
+        /// The environment variable of the signing key
+        #[arg(long, default_value = "MAKO_MANIFEST_KEY")]
+        key_env: String,
+    },
+    /// Explain why a module is in the bundle with the shortest import path from an entry
+    Why {
+        /// The file of the module relative to root, or the name of a package
+        module: String,
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        #[arg(long, default_value_t = Mode::Development, value_enum)]
+        mode: Mode,
+    },
+}
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Instant, UNIX_EPOCH};
 
@@ -13,7 +14,10 @@ use swc_core::ecma::ast::Ident;
 use tracing::debug;
 
 use crate::ast::comments::Comments;
+use crate::build::file_dependencies::FileDependencies;
+use crate::cache::PersistentCache;
 use crate::config::{Config, OutputMode};
+use crate::dev::inspect::TransformInspector;
 use crate::generate::chunk_graph::ChunkGraph;
 use crate::generate::optimize_chunk::OptimizeChunksInfo;
 use crate::module_graph::ModuleGraph;
@@ -21,7 +25,7 @@ use crate::plugin::{Plugin, PluginDriver, PluginGenerateEndParams, PluginGenerat
 use crate::plugins;
 use crate::resolve::{get_resolvers, Resolvers};
 use crate::stats::StatsInfo;
-use crate::utils::{thread_pool, ParseRegex};
+use crate::utils::thread_pool;
 
 pub struct Context {
     pub module_graph: RwLock<ModuleGraph>,
@@ -34,9 +38,19 @@ pub struct Context {
     pub meta: Meta,
     pub plugin_driver: PluginDriver,
     pub stats_info: StatsInfo,
+    pub transform_inspector: TransformInspector,
+    pub persistent_cache: Option<PersistentCache>,
     pub resolvers: Resolvers,
     pub static_cache: RwLock<MemoryChunkFileCache>,
     pub optimize_infos: Mutex<Option<Vec<OptimizeChunksInfo>>>,
+    pub owned_writes: RwLock<HashSet<PathBuf>>,
+    // ids of the deterministic moduleIdStrategy, origin module id -> assigned id
+    pub module_ids: RwLock<HashMap<String, String>>,
+    // non-module files read by loaders and transforms of modules, see add_file_dependency
+    pub file_dependencies: RwLock<FileDependencies>,
+    // set when the changes of a rebuild can't be applied with hmr, e.g. the scripts injected
+    // into html are changed, so that the dev server asks the clients to reload
+    pub full_reload: AtomicBool,
 }
 
 #[derive(Default)]
@@ -86,6 +100,8 @@ impl MemoryChunkFileCache {
 #[derive(Default)]
 pub struct Args {
     pub watch: bool,
+    // list every occurrence of aggregated warnings
+    pub verbose: bool,
 }
 
 impl Context {
@@ -111,7 +127,10 @@ impl Default for Context {
         let resolvers = get_resolvers(&config);
         Self {
             config,
-            args: Args { watch: false },
+            args: Args {
+                watch: false,
+                verbose: false,
+            },
             root: PathBuf::from(""),
             module_graph: RwLock::new(ModuleGraph::new()),
             chunk_graph: RwLock::new(ChunkGraph::new()),
@@ -120,9 +139,15 @@ impl Default for Context {
             meta: Meta::new(),
             plugin_driver: Default::default(),
             stats_info: StatsInfo::new(),
+            transform_inspector: Default::default(),
+            persistent_cache: None,
             resolvers,
             optimize_infos: Mutex::new(None),
             static_cache: Default::default(),
+            owned_writes: Default::default(),
+            module_ids: Default::default(),
+            file_dependencies: Default::default(),
+            full_reload: AtomicBool::new(false),
         }
     }
 }
@@ -199,6 +224,44 @@ impl Context {
         let mut assets_info = self.assets_info.lock().unwrap();
         assets_info.insert(origin_path, output_path);
     }
+
+    // plugins which write files into watched directories should register them here,
+    // so that the watcher won't trigger a rebuild for their own writes
+    pub fn register_owned_write<T: AsRef<Path>>(&self, path: T) {
+        let path = path.as_ref();
+        let path = if path.is_relative() {
+            self.root.join(path)
+        } else {
+            path.to_path_buf()
+        };
+        self.owned_writes.write().unwrap().insert(path);
+    }
+
+    // loaders and transforms which read files other than the module should register them here,
+    // e.g. a graphql schema read by a codegen transform, so that edits to them rebuild the
+    // module in watch mode
+    pub fn add_file_dependency<T: AsRef<Path>>(&self, module_path: &str, file: T) {
+        let file = file.as_ref();
+        let file = if file.is_relative() {
+            self.root.join(file)
+        } else {
+            file.to_path_buf()
+        };
+        self.file_dependencies
+            .write()
+            .unwrap()
+            .add(module_path, file);
+    }
+
+    pub fn is_owned_write(&self, path: &Path) -> bool {
+        path.starts_with(&self.config.output.path)
+            || self
+                .owned_writes
+                .read()
+                .unwrap()
+                .iter()
+                .any(|owned| path.starts_with(owned))
+    }
 }
 
 pub struct Compiler {
@@ -226,8 +289,25 @@ impl Compiler {
         let builtin_plugins: Vec<Arc<dyn Plugin>> = vec![
             // features
             Arc::new(plugins::manifest::ManifestPlugin {}),
+            Arc::new(plugins::html::HtmlPlugin::default()),
+            Arc::new(plugins::mpa::MpaPlugin {}),
             Arc::new(plugins::copy::CopyPlugin {}),
             Arc::new(plugins::import::ImportPlugin {}),
+            Arc::new(plugins::icon_imports::IconImportsPlugin {}),
+            Arc::new(plugins::duplicate_packages::DuplicatePackagesPlugin {}),
+            Arc::new(plugins::circular_dependency::CircularDependencyPlugin {}),
+            Arc::new(plugins::lockfile::LockfilePlugin {}),
+            Arc::new(plugins::bundle_policy::BundlePolicyPlugin {}),
+            Arc::new(plugins::chunk_invalidation::ChunkInvalidationPlugin {}),
+            Arc::new(plugins::error_mapping::ErrorMappingPlugin {}),
+            Arc::new(plugins::chunk_integrity::ChunkIntegrityPlugin {}),
+            Arc::new(plugins::import_map::ImportMapPlugin {}),
+            Arc::new(plugins::chunk_load_telemetry::ChunkLoadTelemetryPlugin {}),
+            Arc::new(plugins::env_banner::EnvBannerPlugin {}),
+            Arc::new(plugins::env_types::EnvTypesPlugin {}),
+            Arc::new(plugins::banner::BannerPlugin {}),
+            Arc::new(plugins::build_events::BuildEventsPlugin {}),
+            Arc::new(plugins::codegen::CodegenPlugin {}),
             // file types
             Arc::new(plugins::context_module::ContextModulePlugin {}),
             Arc::new(plugins::runtime::MakoRuntime {}),
@@ -236,6 +316,7 @@ impl Compiler {
             Arc::new(plugins::wasm_runtime::WasmRuntimePlugin {}),
             Arc::new(plugins::async_runtime::AsyncRuntimePlugin {}),
             Arc::new(plugins::emotion::EmotionPlugin {}),
+            Arc::new(plugins::preact::PreactPlugin {}),
             Arc::new(plugins::tree_shaking::FarmTreeShake {}),
         ];
         plugins.extend(builtin_plugins);
@@ -250,32 +331,19 @@ impl Compiler {
             plugins.push(Arc::new(plugins::graphviz::Graphviz {}));
         }
 
-        if args.watch && std::env::var("SSU").is_ok_and(|v| v == "true") {
+        // prebundle dependencies to speed up the cold start in watch mode
+        if args.watch
+            && (config.experimental.prebundle || std::env::var("SSU").is_ok_and(|v| v == "true"))
+        {
             plugins.push(Arc::new(plugins::ssu::SUPlus::new()));
         }
 
         if let Some(minifish_config) = &config._minifish {
-            let inject = if let Some(inject) = &minifish_config.inject {
-                let mut map = HashMap::new();
-
-                for (k, ii) in inject.iter() {
-                    map.insert(
-                        k.clone(),
-                        plugins::minifish::Inject {
-                            from: ii.from.clone(),
-                            name: k.clone(),
-                            named: ii.named.clone(),
-                            namespace: ii.namespace,
-                            exclude: ii.exclude.parse_into_regex()?,
-                            include: ii.include.parse_into_regex()?,
-                            prefer_require: ii.prefer_require.map_or(false, |v| v),
-                        },
-                    );
-                }
-                Some(map)
-            } else {
-                None
-            };
+            let inject = minifish_config
+                .inject
+                .as_ref()
+                .map(plugins::inject::create_injects)
+                .transpose()?;
 
             plugins.insert(
                 0,
@@ -287,6 +355,12 @@ impl Compiler {
             );
         }
 
+        if !config.inject.is_empty() {
+            plugins.push(Arc::new(plugins::inject::InjectPlugin {
+                injects: plugins::inject::create_injects(&config.inject)?,
+            }));
+        }
+
         if !config.ignores.is_empty() {
             let ignores = config
                 .ignores
@@ -296,11 +370,30 @@ impl Compiler {
             plugins.push(Arc::new(plugins::ignore::IgnorePlugin { ignores }))
         }
 
+        if config.polyfill_report {
+            plugins.push(Arc::new(plugins::polyfill_report::PolyfillReportPlugin {}));
+        }
+
+        if !config.split_by_key.is_empty() {
+            let patterns = config
+                .split_by_key
+                .iter()
+                .map(|pattern| Regex::new(pattern).map_err(Error::new))
+                .collect::<Result<Vec<Regex>>>()?;
+            // should go before other load plugins
+            plugins.insert(
+                0,
+                Arc::new(plugins::split_by_key::SplitByKeyPlugin { patterns }),
+            );
+        }
+
         let plugin_driver = PluginDriver::new(plugins);
 
         plugin_driver.modify_config(&mut config, &root, &args)?;
 
         let resolvers = get_resolvers(&config);
+        let persistent_cache =
+            PersistentCache::new(&config, &root, plugin_driver.cache_backend(&config, &root))?;
         Ok(Self {
             context: Arc::new(Context {
                 static_cache: if config.write_to_disk {
@@ -318,13 +411,27 @@ impl Compiler {
                 meta: Meta::new(),
                 plugin_driver,
                 stats_info: StatsInfo::new(),
+                transform_inspector: Default::default(),
+                persistent_cache,
                 resolvers,
                 optimize_infos: Mutex::new(None),
+                owned_writes: Default::default(),
+                module_ids: Default::default(),
+                file_dependencies: Default::default(),
+                full_reload: AtomicBool::new(false),
             }),
         })
     }
 
     pub fn compile(&self) -> Result<()> {
+        let result = self.compile_stages();
+        if let Err(e) = &result {
+            self.context.plugin_driver.build_error(e, &self.context)?;
+        }
+        result
+    }
+
+    fn compile_stages(&self) -> Result<()> {
         // 先清空 dist 目录
         if self.context.config.clean {
             self.clean_dist()?;
@@ -362,7 +469,23 @@ impl Compiler {
                 .collect();
             self.context.plugin_driver.build_start(&self.context)?;
 
+            // the remote cache is accessed outside of the build, which runs on the threads of
+            // rayon, so that they never wait for the network
+            let persistent_cache = self
+                .context
+                .persistent_cache
+                .as_ref()
+                .filter(|_| !self.context.args.watch);
+            if let Some(cache) = persistent_cache {
+                cache.pull();
+            }
             self.build(files)?;
+            if let Some(cache) = persistent_cache {
+                cache.push();
+            }
+            self.context
+                .stats_info
+                .flush_grouped_warnings(self.context.args.verbose);
 
             debug!("start after build");
 
@@ -411,7 +534,7 @@ impl Compiler {
         crate::mako_profile_function!();
         let cg = self.context.chunk_graph.read().unwrap();
         let mg = self.context.module_graph.read().unwrap();
-        cg.full_hash(&mg)
+        cg.full_hash(&mg, &self.context.config.hash_salt)
     }
 
     fn clean_dist(&self) -> Result<()> {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::{Path, PathBuf};
 
@@ -13,8 +13,9 @@ use swc_core::ecma::ast::EsVersion;
 use thiserror::Error;
 use {clap, config, thiserror};
 
+use crate::config::{evaluate_define, load_env_files, EnvFileConfig};
 use crate::features::node::Node;
-use crate::{plugins, visitors};
+use crate::{features, plugins, visitors};
 
 #[derive(Debug, Diagnostic)]
 #[diagnostic(code("mako.config.json parsed failed"))]
@@ -89,8 +90,27 @@ macro_rules! create_deserialize_fn {
 }
 create_deserialize_fn!(deserialize_hmr, HmrConfig);
 create_deserialize_fn!(deserialize_dev_server, DevServerConfig);
+create_deserialize_fn!(deserialize_inspect, InspectConfig);
+create_deserialize_fn!(deserialize_html, HtmlConfig);
+create_deserialize_fn!(deserialize_mpa, MpaConfig);
+create_deserialize_fn!(deserialize_build_events, BuildEventsConfig);
+create_deserialize_fn!(deserialize_css_modules, CssModulesConfig);
+create_deserialize_fn!(deserialize_public_assets, PublicAssetsConfig);
+create_deserialize_fn!(deserialize_cache, CacheConfig);
+create_deserialize_fn!(deserialize_bundle_policy, BundlePolicyConfig);
+create_deserialize_fn!(deserialize_chunk_integrity, ChunkIntegrityConfig);
+create_deserialize_fn!(deserialize_banner, ChunkBannerConfig);
+create_deserialize_fn!(deserialize_chunk_load_telemetry, ChunkLoadTelemetryConfig);
+create_deserialize_fn!(deserialize_env_banner, EnvBannerConfig);
+create_deserialize_fn!(deserialize_env_types, EnvTypesConfig);
+create_deserialize_fn!(deserialize_import_map, ImportMapConfig);
+create_deserialize_fn!(deserialize_env_file, EnvFileConfig);
+create_deserialize_fn!(deserialize_react_native, ReactNativeConfig);
+create_deserialize_fn!(deserialize_chunk_invalidation, ChunkInvalidationConfig);
+create_deserialize_fn!(deserialize_circular_dependency, CircularDependencyConfig);
 create_deserialize_fn!(deserialize_manifest, ManifestConfig);
 create_deserialize_fn!(deserialize_code_splitting, CodeSplitting);
+create_deserialize_fn!(deserialize_split_chunks, SplitChunksConfig);
 create_deserialize_fn!(deserialize_px2rem, Px2RemConfig);
 create_deserialize_fn!(deserialize_umd, String);
 create_deserialize_fn!(deserialize_devtool, DevtoolConfig);
@@ -101,6 +121,9 @@ create_deserialize_fn!(deserialize_inline_css, InlineCssConfig);
 create_deserialize_fn!(deserialize_rsc_client, RscClientConfig);
 create_deserialize_fn!(deserialize_rsc_server, RscServerConfig);
 create_deserialize_fn!(deserialize_stats, StatsConfig);
+create_deserialize_fn!(deserialize_library, LibraryConfig);
+create_deserialize_fn!(deserialize_chunked_emit, ChunkedEmitConfig);
+create_deserialize_fn!(deserialize_runtime_chunk, RuntimeChunk);
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -110,6 +133,11 @@ pub struct OutputConfig {
     pub es_version: EsVersion,
     pub meta: bool,
     pub chunk_loading_global: String,
+    pub chunk_loading: ChunkLoading,
+    // attach all runtime state to globalThis[global_namespace] instead of globalThis,
+    // so that multiple bundles can coexist in micro-frontend sandboxes
+    #[serde(default)]
+    pub global_namespace: Option<String>,
     pub preserve_modules: bool,
     pub preserve_modules_root: PathBuf,
     pub skip_write: bool,
@@ -124,12 +152,32 @@ pub struct ManifestConfig {
     pub file_name: String,
     #[serde(rename(deserialize = "basePath"), default)]
     pub base_path: String,
+    #[serde(default)]
+    pub signature: Option<ManifestSignatureConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ManifestSignatureConfig {
+    /// The environment variable of the signing key, so that the key is not in the config
+    #[serde(
+        rename(deserialize = "keyEnv"),
+        default = "plugins::manifest::default_signature_key_env"
+    )]
+    pub key_env: String,
+    #[serde(
+        rename(deserialize = "fileName"),
+        default = "plugins::manifest::default_signature_file_name"
+    )]
+    pub file_name: String,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ResolveConfig {
     pub alias: Vec<(String, String)>,
     pub extensions: Vec<String>,
+    // suffixes of platform-specific files in order of precedence, e.g. ["web"] for `x.web.ts`
+    #[serde(default)]
+    pub platforms: Vec<String>,
 }
 
 // format: HashMap<identifier, (import_source, specifier)>
@@ -154,7 +202,26 @@ pub enum OutputMode {
     Bundless,
 }
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+// where the module registry and chunk loading runtime goes
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RuntimeChunk {
+    // one runtime chunk shared by all entries
+    #[serde(rename = "single")]
+    Single,
+}
+
+// how async chunks are loaded in browsers
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ChunkLoading {
+    // script tags
+    #[serde(rename = "jsonp")]
+    Jsonp,
+    // dynamic import(), e.g. for module workers without document
+    #[serde(rename = "import")]
+    Import,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
 pub enum Platform {
     #[serde(rename = "browser")]
     Browser,
@@ -184,6 +251,8 @@ pub enum ModuleIdStrategy {
     Hashed,
     #[serde(rename = "named")]
     Named,
+    #[serde(rename = "deterministic")]
+    Deterministic,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -197,10 +266,23 @@ pub struct CodeSplittingGranularOptions {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct StatsConfig {
     pub modules: bool,
+    // record per-stage timings of every module and report the slowest n of each stage
+    #[serde(rename = "slowestModules", default)]
+    pub slowest_modules: usize,
+    // write what's changed to stats.delta.json on rebuilds in watch mode instead of full stats
+    #[serde(default)]
+    pub incremental: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct AnalyzeConfig {}
+pub struct AnalyzeConfig {
+    // attribute output bytes to exported symbols of original modules via source maps
+    #[serde(default)]
+    pub symbols: bool,
+    // list unused exports which are retained because of possible side effects
+    #[serde(rename = "sideEffects", default)]
+    pub side_effects: bool,
+}
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum CodeSplittingStrategy {
@@ -225,6 +307,29 @@ pub struct CodeSplitting {
     pub options: Option<CodeSplittingStrategyOptions>,
 }
 
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CssModulesLocalsConvention {
+    #[default]
+    #[serde(rename = "asIs")]
+    AsIs,
+    // export both the original and the camelCased class names
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    #[serde(rename = "camelCaseOnly")]
+    CamelCaseOnly,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CssModulesConfig {
+    // export every class name which is a valid identifier as a named export too, so that the
+    // unused ones can be tree shaken
+    #[serde(default)]
+    pub named_exports: bool,
+    #[serde(default)]
+    pub locals_convention: CssModulesLocalsConvention,
+}
+
 #[derive(Deserialize, Serialize, Clone, Copy, Debug)]
 pub enum TreeShakingStrategy {
     #[serde(rename = "basic")]
@@ -277,6 +382,13 @@ pub struct TransformImportConfig {
     pub style: Option<TransformImportStyle>,
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IconImportConfig {
+    pub library_name: String,
+    pub template: String,
+}
+
 #[derive(Deserialize, Serialize, Debug, Hash)]
 pub enum ExternalAdvancedSubpathConverter {
     PascalCase,
@@ -354,14 +466,30 @@ pub enum ExternalConfig {
 }
 
 #[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct InjectItem {
+    #[serde(default)]
     pub from: String,
+    // code spliced at the top of modules instead of an import, e.g. `const __DEV__ = true;`
+    pub code: Option<String>,
     pub named: Option<String>,
     pub namespace: Option<bool>,
-    pub exclude: Option<String>,
-    pub include: Option<String>,
+    pub exclude: Option<InjectFilterConfig>,
+    pub include: Option<InjectFilterConfig>,
     pub prefer_require: Option<bool>,
+    pub bare: Option<bool>,
+    // injects are emitted in ascending order, and then by name
+    #[serde(default)]
+    pub order: i32,
+}
+
+// a regex string matched against the absolute path, or globs matched against the path
+// relative to root
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+pub enum InjectFilterConfig {
+    Regex(String),
+    Globs(Vec<String>),
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -395,6 +523,9 @@ pub struct MinifishConfig {
 pub struct OptimizationConfig {
     pub skip_modules: Option<bool>,
     pub concatenate_modules: Option<bool>,
+    pub polyfill_chunk: Option<bool>,
+    // modules of packages with these licenses are isolated into `license-*` chunks
+    pub isolate_licenses: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -426,6 +557,37 @@ pub struct RscClientConfig {
 #[serde(rename_all = "camelCase")]
 pub struct ExperimentalConfig {
     pub webpack_syntax_validate: Vec<String>,
+    #[serde(deserialize_with = "deserialize_react_native", default)]
+    pub react_native: Option<ReactNativeConfig>,
+    // bundle dependencies into cached chunks in dev, see plugins::ssu
+    #[serde(default)]
+    pub prebundle: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum ReactNativePlatform {
+    #[serde(rename = "ios")]
+    Ios,
+    #[serde(rename = "android")]
+    Android,
+}
+
+impl std::fmt::Display for ReactNativePlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReactNativePlatform::Ios => write!(f, "ios"),
+            ReactNativePlatform::Android => write!(f, "android"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactNativeConfig {
+    pub platform: ReactNativePlatform,
+    // evaluate dependencies lazily where they are used, like inlineRequires of metro
+    #[serde(default)]
+    pub inline_requires: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -434,10 +596,96 @@ pub struct WatchConfig {
     pub ignore_paths: Vec<String>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodegenConfig {
+    /// The sources of the codegen step, e.g. a rust crate, relative to root
+    pub dir: String,
+    /// The command to run in dir, e.g. `wasm-pack build --target web`
+    pub command: String,
+    /// The output dir of the command, relative to root
+    pub output: String,
+    /// Resolve the output dir as a package of the name
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum LibraryFormat {
+    #[serde(rename = "esm")]
+    Esm,
+    #[serde(rename = "cjs")]
+    Cjs,
+}
+
+impl std::fmt::Display for LibraryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibraryFormat::Esm => write!(f, "esm"),
+            LibraryFormat::Cjs => write!(f, "cjs"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryDtsConfig {
+    /// The command to roll up declaration files, e.g. `api-extractor run`
+    pub command: String,
+    /// The rolled up declaration file, relative to root
+    pub output: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformStageOrder {
+    /// The stage to run after, e.g. `env_replacer`
+    #[serde(default)]
+    pub after: Option<String>,
+    /// The stage to run before, e.g. `plugins`
+    #[serde(default)]
+    pub before: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryConfig {
+    pub formats: Vec<LibraryFormat>,
+    #[serde(default)]
+    pub dts: Option<LibraryDtsConfig>,
+    #[serde(default = "features::library::default_validate_package_json")]
+    pub validate_package_json: bool,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct HmrConfig {}
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum SwcHelpersMode {
+    /// Inline helpers into every module which uses them
+    #[serde(rename = "inline")]
+    Inline,
+    /// Import helpers from a shared module, so that they are emitted once
+    #[serde(rename = "runtime")]
+    Runtime,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SwcHelpersConfig {
+    pub mode: SwcHelpersMode,
+    /// The module to import helpers from when mode is runtime
+    pub source: String,
+}
+
+impl SwcHelpersConfig {
+    // helpers are imported instead of inlined, which is what `Helpers::new` of swc takes
+    pub fn external(&self) -> bool {
+        self.mode == SwcHelpersMode::Runtime
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DevServerConfig {
@@ -445,10 +693,268 @@ pub struct DevServerConfig {
     pub port: u16,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheConfig {
+    // relative to root
+    #[serde(default = "crate::cache::default_cache_dir")]
+    pub dir: PathBuf,
+    // http url of the remote cache server shared by machines
+    pub remote: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePolicyConfig {
+    // package names which can't be bundled
+    #[serde(default)]
+    pub forbid: Vec<String>,
+    // if specified, only these packages can be bundled
+    pub allow: Option<Vec<String>>,
+    // package name -> max bytes of its modules' source
+    #[serde(default)]
+    pub max_size: HashMap<String, usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvBannerConfig {
+    // supports [mode], [version] and [builtAt] placeholders
+    pub text: String,
+    // enabled in all modes if empty
+    #[serde(default)]
+    pub modes: Vec<Mode>,
+    #[serde(default = "plugins::env_banner::default_console")]
+    pub console: bool,
+    #[serde(default)]
+    pub ribbon: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvTypesConfig {
+    // relative to root
+    #[serde(default = "plugins::env_types::default_env_types_path")]
+    pub path: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkBannerConfig {
+    // added to chunk files as is, e.g. `#!/usr/bin/env node` or `/*! license */`
+    pub js: Option<String>,
+    pub css: Option<String>,
+    // only chunk files of entries and workers
+    #[serde(default)]
+    pub entry_only: bool,
+    // comments of modules in js chunks, only for banner
+    #[serde(default)]
+    pub modules: Vec<ModuleBannerConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleBannerConfig {
+    // globs of paths relative to root
+    pub include: Vec<String>,
+    pub text: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkLoadTelemetryConfig {
+    // artificial latency in ms added to every chunk load
+    #[serde(default)]
+    pub latency: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ImportMapConfig {
+    // relative to root, an import map json like { "imports": { "react": "https://..." } }
+    pub path: Option<PathBuf>,
+    // merged over the imports of path
+    #[serde(default)]
+    pub imports: BTreeMap<String, String>,
+    // emit importmap.json of the used imports, and inject it into html
+    #[serde(default)]
+    pub emit: bool,
+}
+
+impl ImportMapConfig {
+    // the url of a bare specifier, keys ending with / match as prefixes like browsers do
+    pub fn resolve(&self, source: &str) -> Option<String> {
+        if let Some(url) = self.imports.get(source) {
+            return Some(url.clone());
+        }
+        self.imports
+            .iter()
+            .filter(|(key, _)| key.ends_with('/') && source.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(key, url)| format!("{}{}", url, &source[key.len()..]))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkIntegrityConfig {
+    // js expression of the handler called with { chunkId, url, expected, actual }
+    pub on_corrupt_chunk: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkInvalidationConfig {
+    // relative to root, modules of chunks are recorded here to diff with the next build
+    #[serde(default = "plugins::chunk_invalidation::default_records_path")]
+    pub records: PathBuf,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircularDependencyLevel {
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "warn")]
+    Warn,
+    #[serde(rename = "error")]
+    Error,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CircularDependencyConfig {
+    // cycles of synchronous imports and requires
+    #[serde(default = "plugins::circular_dependency::default_runtime_level")]
+    pub runtime: CircularDependencyLevel,
+    // cycles broken by dynamic imports or workers
+    #[serde(default = "plugins::circular_dependency::default_dynamic_level")]
+    pub dynamic: CircularDependencyLevel,
+    #[serde(default)]
+    pub overrides: Vec<CircularDependencyOverride>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CircularDependencyOverride {
+    // relative to root, applies to cycles of which all modules are in the dir
+    pub dir: PathBuf,
+    pub runtime: Option<CircularDependencyLevel>,
+    pub dynamic: Option<CircularDependencyLevel>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HtmlConfig {
+    // relative to root, a minimal document is used if not specified
+    pub template: Option<PathBuf>,
+    // [name] is replaced with the entry name
+    #[serde(default = "plugins::html::default_html_file_name")]
+    pub filename: String,
+    // extra origins to preconnect besides the cross-origin publicPath, e.g. api hosts
+    #[serde(default)]
+    pub preconnect: Vec<String>,
+    // entry name -> template relative to root, overrides template for the entry
+    #[serde(default)]
+    pub templates: HashMap<String, PathBuf>,
+}
+
+impl Default for HtmlConfig {
+    fn default() -> Self {
+        Self {
+            template: None,
+            filename: plugins::html::default_html_file_name(),
+            preconnect: vec![],
+            templates: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicAssetsConfig {
+    // relative to root
+    #[serde(default = "visitors::public_assets::default_public_dir")]
+    pub dir: PathBuf,
+    // name of the global helper function, e.g. publicAsset("robots.txt")
+    #[serde(default = "visitors::public_assets::default_public_asset_helper")]
+    pub helper: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildEventsConfig {
+    // http url which events are posted to as json
+    pub url: Option<String>,
+    // relative to root, unix socket which events are written to as json lines
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MpaConfig {
+    // relative to root, every sub directory with an index file is a page
+    #[serde(default = "plugins::mpa::default_pages_dir")]
+    pub pages_dir: PathBuf,
+    // files of each page, written to output
+    #[serde(default = "plugins::mpa::default_pages_manifest_file_name")]
+    pub manifest: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkedEmitConfig {
+    /// Chunks whose rendered content is at least the threshold are written in pieces, in bytes
+    #[serde(default = "GenericUsizeDefault::<10485760>::value")]
+    pub threshold: usize,
+    /// Size of the pieces written to disk, in bytes
+    #[serde(default = "GenericUsizeDefault::<1048576>::value")]
+    pub piece_size: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectConfig {
+    // how many modules' transform stages are kept
+    #[serde(default = "GenericUsizeDefault::<100>::value")]
+    pub max_modules: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EntryCondition {
+    // the entry only exists in this mode
+    pub mode: Option<Mode>,
+    // the entry only exists when the env var is set, and not empty, "0" or "false"
+    pub env: Option<String>,
+}
+
+impl EntryCondition {
+    // why the entry is skipped, None if the condition is met
+    pub fn skip_reason(&self, mode: &Mode) -> Option<String> {
+        if let Some(expected) = &self.mode
+            && expected != mode
+        {
+            return Some(format!("mode is not {}", expected));
+        }
+        if let Some(env) = &self.env {
+            let enabled = std::env::var(env)
+                .is_ok_and(|value| !value.is_empty() && value != "0" && value != "false");
+            if !enabled {
+                return Some(format!("env {} is not enabled", env));
+            }
+        }
+        None
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub entry: HashMap<String, PathBuf>,
+    pub entry_conditions: HashMap<String, EntryCondition>,
+    // entry name -> reason, entries removed by entryConditions
+    #[serde(skip)]
+    pub skipped_entries: BTreeMap<String, String>,
     pub output: OutputConfig,
     pub resolve: ResolveConfig,
     #[serde(deserialize_with = "deserialize_manifest", default)]
@@ -459,6 +965,11 @@ pub struct Config {
     pub devtool: Option<DevtoolConfig>,
     pub externals: HashMap<String, ExternalConfig>,
     pub providers: Providers,
+    pub inject: HashMap<String, InjectItem>,
+    // globs of paths relative to root to whether the modules have side effects, which take
+    // precedence over sideEffects of package.json, the longest matched glob wins
+    #[serde(rename = "sideEffects")]
+    pub side_effects: HashMap<String, bool>,
     pub copy: Vec<String>,
     pub public_path: String,
     pub inline_limit: usize,
@@ -466,18 +977,71 @@ pub struct Config {
     pub platform: Platform,
     pub module_id_strategy: ModuleIdStrategy,
     pub define: HashMap<String, Value>,
+    // evaluate constant expressions of define values at config load
+    pub evaluate_define: bool,
+    #[serde(deserialize_with = "deserialize_env_file")]
+    pub env_file: Option<EnvFileConfig>,
+    // variables loaded by envFile, for debugging in stats
+    #[serde(skip)]
+    pub env_file_vars: BTreeMap<String, String>,
+    // env keys looked up from `window.__RUNTIME_ENV__` at runtime instead of inlined
+    pub runtime_env: Vec<String>,
+    // fail the build on unknown `process.env.XX` instead of warning
+    pub strict_env: bool,
     pub analyze: Option<AnalyzeConfig>,
     pub stats: Option<StatsConfig>,
+    // write esbuild compatible metafile.json to output
+    pub metafile: bool,
+    // write chunk-manifest.json of the files to load entries and async chunks to output
+    pub chunk_manifest: bool,
+    // write large chunks to disk in pieces, to reduce the peak memory of builds
+    #[serde(deserialize_with = "deserialize_chunked_emit")]
+    pub chunked_emit: Option<ChunkedEmitConfig>,
+    #[serde(deserialize_with = "deserialize_runtime_chunk")]
+    pub runtime_chunk: Option<RuntimeChunk>,
     pub mdx: bool,
     #[serde(deserialize_with = "deserialize_hmr")]
     pub hmr: Option<HmrConfig>,
     #[serde(deserialize_with = "deserialize_dev_server")]
     pub dev_server: Option<DevServerConfig>,
+    #[serde(deserialize_with = "deserialize_inspect")]
+    pub inspect: Option<InspectConfig>,
+    #[serde(deserialize_with = "deserialize_html")]
+    pub html: Option<HtmlConfig>,
+    #[serde(deserialize_with = "deserialize_mpa")]
+    pub mpa: Option<MpaConfig>,
+    #[serde(deserialize_with = "deserialize_build_events")]
+    pub build_events: Option<BuildEventsConfig>,
+    #[serde(deserialize_with = "deserialize_cache")]
+    pub cache: Option<CacheConfig>,
+    #[serde(deserialize_with = "deserialize_bundle_policy")]
+    pub bundle_policy: Option<BundlePolicyConfig>,
+    #[serde(deserialize_with = "deserialize_banner")]
+    pub banner: Option<ChunkBannerConfig>,
+    #[serde(deserialize_with = "deserialize_banner")]
+    pub footer: Option<ChunkBannerConfig>,
+    #[serde(deserialize_with = "deserialize_env_banner")]
+    pub env_banner: Option<EnvBannerConfig>,
+    #[serde(deserialize_with = "deserialize_env_types")]
+    pub env_types: Option<EnvTypesConfig>,
+    #[serde(deserialize_with = "deserialize_chunk_integrity")]
+    pub chunk_integrity: Option<ChunkIntegrityConfig>,
+    #[serde(deserialize_with = "deserialize_import_map")]
+    pub import_map: Option<ImportMapConfig>,
+    #[serde(deserialize_with = "deserialize_chunk_load_telemetry")]
+    pub chunk_load_telemetry: Option<ChunkLoadTelemetryConfig>,
+    #[serde(deserialize_with = "deserialize_chunk_invalidation")]
+    pub chunk_invalidation: Option<ChunkInvalidationConfig>,
+    #[serde(deserialize_with = "deserialize_circular_dependency")]
+    pub circular_dependency: Option<CircularDependencyConfig>,
     #[serde(deserialize_with = "deserialize_code_splitting", default)]
     pub code_splitting: Option<CodeSplitting>,
+    #[serde(deserialize_with = "deserialize_split_chunks", default)]
+    pub split_chunks: Option<SplitChunksConfig>,
     #[serde(deserialize_with = "deserialize_px2rem", default)]
     pub px2rem: Option<Px2RemConfig>,
     pub hash: bool,
+    pub hash_salt: String,
     #[serde(rename = "_treeShaking", deserialize_with = "deserialize_tree_shaking")]
     pub _tree_shaking: Option<TreeShakingStrategy>,
     #[serde(rename = "autoCSSModules")]
@@ -490,6 +1054,7 @@ pub struct Config {
     pub cjs: bool,
     pub write_to_disk: bool,
     pub transform_import: Vec<TransformImportConfig>,
+    pub icon_imports: Vec<IconImportConfig>,
     pub chunk_parallel: bool,
     pub clean: bool,
     pub node_polyfill: bool,
@@ -503,6 +1068,7 @@ pub struct Config {
     #[serde(rename = "optimizePackageImports")]
     pub optimize_package_imports: bool,
     pub emotion: bool,
+    pub preact: bool,
     pub flex_bugs: bool,
     #[serde(deserialize_with = "deserialize_optimization")]
     pub optimization: Option<OptimizationConfig>,
@@ -510,6 +1076,10 @@ pub struct Config {
     pub emit_assets: bool,
     #[serde(rename = "cssModulesExportOnlyLocales")]
     pub css_modules_export_only_locales: bool,
+    #[serde(deserialize_with = "deserialize_css_modules")]
+    pub css_modules: Option<CssModulesConfig>,
+    #[serde(deserialize_with = "deserialize_public_assets")]
+    pub public_assets: Option<PublicAssetsConfig>,
     #[serde(
         rename = "inlineCSS",
         deserialize_with = "deserialize_inline_css",
@@ -531,13 +1101,28 @@ pub struct Config {
     pub experimental: ExperimentalConfig,
     pub watch: WatchConfig,
     pub use_define_for_class_fields: bool,
+    pub swc_helpers: SwcHelpersConfig,
+    pub split_by_key: Vec<String>,
+    #[serde(deserialize_with = "deserialize_library", default)]
+    pub library: Option<LibraryConfig>,
+    // see Config::require_resolve
+    #[serde(default)]
+    pub require_resolve: Option<bool>,
+    pub dedupe_packages: Vec<String>,
+    pub mocks: HashMap<String, String>,
+    pub codegen: Vec<CodegenConfig>,
+    pub polyfill_report: bool,
+    pub transform_stages: HashMap<String, TransformStageOrder>,
+    pub lockfile_check: bool,
+    pub error_mapping: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub enum OptimizeAllowChunks {
     #[serde(rename = "all")]
     All,
-    #[serde(rename = "entry")]
+    // `initial` of webpack's splitChunks.chunks
+    #[serde(rename = "entry", alias = "initial")]
     Entry,
     #[serde(rename = "async")]
     #[default]
@@ -589,6 +1174,9 @@ pub struct OptimizeChunkGroup {
     pub priority: i8,
     #[serde(default, with = "optimize_test_format")]
     pub test: Option<Regex>,
+    // licenses of packages (from package.json), e.g. `LGPL` matches `LGPL-3.0`
+    #[serde(default)]
+    pub licenses: Vec<String>,
 }
 
 impl Default for OptimizeChunkGroup {
@@ -603,6 +1191,122 @@ impl Default for OptimizeChunkGroup {
             min_module_size: None,
             test: None,
             priority: i8::default(),
+            licenses: vec![],
+        }
+    }
+}
+
+/**
+ * webpack style splitChunks, which is a shorthand of the advanced codeSplitting strategy, every
+ * cache group becomes an optimize chunk group, options of cache groups default to the top level
+ * ones, the default cache groups of webpack are included and can be disabled with false, e.g.
+ * { cacheGroups: { default: false, vendors: { test: "[\\/]node_modules[\\/]", priority: 10 } } }
+ */
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitChunksConfig {
+    #[serde(default)]
+    pub chunks: OptimizeAllowChunks,
+    #[serde(default = "GenericUsizeDefault::<20000>::value")]
+    pub min_size: usize,
+    #[serde(default = "GenericUsizeDefault::<5000000>::value")]
+    pub max_size: usize,
+    #[serde(default = "GenericUsizeDefault::<1>::value")]
+    pub min_chunks: usize,
+    #[serde(default)]
+    pub cache_groups: BTreeMap<String, SplitChunksCacheGroupConfig>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum SplitChunksCacheGroupConfig {
+    // only false is meaningful, which disables the default cache groups
+    Enabled(bool),
+    Group(SplitChunksCacheGroup),
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitChunksCacheGroup {
+    /// The name of the chunk, default to the key of the cache group
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, with = "optimize_test_format")]
+    pub test: Option<Regex>,
+    #[serde(default)]
+    pub priority: i8,
+    #[serde(default)]
+    pub chunks: Option<OptimizeAllowChunks>,
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    #[serde(default)]
+    pub max_size: Option<usize>,
+    #[serde(default)]
+    pub min_chunks: Option<usize>,
+}
+
+impl SplitChunksConfig {
+    // same as the default cache groups of webpack
+    fn default_cache_groups() -> Vec<(String, SplitChunksCacheGroup)> {
+        vec![
+            (
+                "defaultVendors".to_string(),
+                SplitChunksCacheGroup {
+                    name: Some("vendors".to_string()),
+                    test: Regex::new(r"[\\/]node_modules[\\/]").ok(),
+                    priority: -10,
+                    chunks: None,
+                    min_size: None,
+                    max_size: None,
+                    min_chunks: None,
+                },
+            ),
+            (
+                "default".to_string(),
+                SplitChunksCacheGroup {
+                    name: Some("common".to_string()),
+                    test: None,
+                    priority: -20,
+                    chunks: None,
+                    min_size: None,
+                    max_size: None,
+                    min_chunks: Some(2),
+                },
+            ),
+        ]
+    }
+
+    pub fn to_code_splitting(&self) -> CodeSplitting {
+        let mut cache_groups = Self::default_cache_groups()
+            .into_iter()
+            .filter(|(key, _)| !self.cache_groups.contains_key(key))
+            .collect::<Vec<_>>();
+        for (key, group) in &self.cache_groups {
+            if let SplitChunksCacheGroupConfig::Group(group) = group {
+                cache_groups.push((key.clone(), group.clone()));
+            }
+        }
+        let groups = cache_groups
+            .into_iter()
+            .map(|(key, group)| OptimizeChunkGroup {
+                name: group.name.unwrap_or(key),
+                allow_chunks: group.chunks.unwrap_or_else(|| self.chunks.clone()),
+                min_chunks: group.min_chunks.unwrap_or(self.min_chunks),
+                min_size: group.min_size.unwrap_or(self.min_size),
+                max_size: group.max_size.unwrap_or(self.max_size),
+                priority: group.priority,
+                test: group.test,
+                ..Default::default()
+            })
+            .collect();
+        CodeSplitting {
+            strategy: CodeSplittingStrategy::Advanced,
+            options: Some(CodeSplittingStrategyOptions::Advanced(
+                CodeSplittingAdvancedOptions {
+                    min_size: self.min_size,
+                    groups,
+                },
+            )),
         }
     }
 }
@@ -644,45 +1348,60 @@ const CONFIG_FILE: &str = "mako.config.json";
 const DEFAULT_CONFIG: &str = r#"
 {
     "entry": {},
+    "entryConditions": {},
     "output": {
       "path": "dist",
       "mode": "bundle",
       "esVersion": "es2022",
       "meta": false,
       "chunkLoadingGlobal": "",
+      "chunkLoading": "jsonp",
       "preserveModules": false,
       "preserveModulesRoot": "",
       "skipWrite": false
     },
-    "resolve": { "alias": [], "extensions": ["js", "jsx", "ts", "tsx"] },
+    "resolve": { "alias": [], "extensions": ["js", "jsx", "ts", "tsx"], "platforms": [] },
     "mode": "development",
     "minify": true,
     "devtool": "source-map",
     "externals": {},
     "copy": ["public"],
     "providers": {},
+    "inject": {},
+    "sideEffects": {},
     "publicPath": "/",
     "inlineLimit": 10000,
     "targets": { "chrome": 80 },
     "less": { "theme": {}, "lesscPath": "", javascriptEnabled: true },
     "define": {},
+    "evaluateDefine": false,
+    "envFile": false,
+    "runtimeEnv": [],
+    "strictEnv": false,
     "mdx": false,
+    "metafile": false,
+    "chunkManifest": false,
+    "chunkedEmit": false,
+    "runtimeChunk": false,
     "platform": "browser",
     "hmr": {},
     "moduleIdStrategy": "named",
     "hash": false,
+    "hashSalt": "",
     "_treeShaking": "basic",
     "autoCSSModules": false,
     "ignoreCSSParserErrors": false,
     "dynamicImportToRequire": false,
     "writeToDisk": true,
     "transformImport": [],
+    "iconImports": [],
     "chunkParallel": true,
     "clean": true,
     "nodePolyfill": true,
     "ignores": [],
     "optimizePackageImports": false,
     "emotion": false,
+    "preact": false,
     "flexBugs": false,
     "cjs": false,
     "optimization": { "skipModules": true, "concatenateModules": true },
@@ -694,13 +1413,40 @@ const DEFAULT_CONFIG: &str = r#"
     },
     "emitAssets": true,
     "cssModulesExportOnlyLocales": false,
+    "cssModules": false,
+    "publicAssets": false,
     "inlineCSS": false,
     "rscServer": false,
     "rscClient": false,
-    "experimental": { "webpackSyntaxValidate": [] },
+    "experimental": { "webpackSyntaxValidate": [], "reactNative": false, "prebundle": false },
     "useDefineForClassFields": true,
+    "swcHelpers": { "mode": "runtime", "source": "@swc/helpers" },
+    "splitByKey": [],
+    "library": false,
+    "dedupePackages": [],
+    "mocks": {},
+    "codegen": [],
+    "polyfillReport": false,
+    "transformStages": {},
+    "lockfileCheck": false,
+    "errorMapping": false,
     "watch": { "ignorePaths": [] },
-    "devServer": { "host": "127.0.0.1", "port": 3000 }
+    "devServer": { "host": "127.0.0.1", "port": 3000 },
+    "inspect": false,
+    "html": false,
+    "mpa": false,
+    "buildEvents": false,
+    "cache": false,
+    "circularDependency": false,
+    "bundlePolicy": false,
+    "banner": false,
+    "footer": false,
+    "chunkIntegrity": false,
+    "importMap": false,
+    "chunkLoadTelemetry": false,
+    "envBanner": false,
+    "envTypes": false,
+    "chunkInvalidation": false
 }
 "#;
 
@@ -774,19 +1520,130 @@ impl Config {
                 return Err(anyhow!("cjs and umd cannot be used at the same time",));
             }
 
+            if config.runtime_chunk.is_some() {
+                if config.cjs || config.umd.is_some() {
+                    return Err(anyhow!("runtimeChunk cannot be used with cjs or umd"));
+                }
+                if config.entry.contains_key("runtime") {
+                    return Err(anyhow!(
+                        "runtimeChunk cannot be used with an entry named runtime"
+                    ));
+                }
+            }
+
             if config.hmr.is_some() && config.dev_server.is_none() {
                 return Err(anyhow!("hmr can only be used with devServer",));
             }
 
+            if config
+                .library
+                .as_ref()
+                .is_some_and(|library| library.formats.is_empty())
+            {
+                return Err(anyhow!("library.formats can not be empty"));
+            }
+
+            if config
+                .output
+                .global_namespace
+                .as_ref()
+                .is_some_and(|namespace| {
+                    namespace.is_empty()
+                        || !namespace
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+                })
+            {
+                return Err(anyhow!(
+                    "output.globalNamespace must be a non-empty identifier"
+                ));
+            }
+
+            if config
+                .resolve
+                .platforms
+                .iter()
+                .any(|platform| platform.is_empty() || platform.contains('.'))
+            {
+                return Err(anyhow!(
+                    "resolve.platforms must be non-empty suffixes without dots"
+                ));
+            }
+
+            if let Some(build_events) = &config.build_events {
+                if build_events.url.is_none() && build_events.socket.is_none() {
+                    return Err(anyhow!("buildEvents requires url or socket"));
+                }
+                if build_events
+                    .url
+                    .as_ref()
+                    .is_some_and(|url| !url.starts_with("http://"))
+                {
+                    return Err(anyhow!("buildEvents.url only supports http urls"));
+                }
+            }
+
             if config.inline_css.is_some() && config.umd.is_none() {
                 return Err(anyhow!("inlineCSS can only be used with umd",));
             }
 
+            if config
+                .chunked_emit
+                .as_ref()
+                .is_some_and(|chunked_emit| chunked_emit.piece_size == 0)
+            {
+                return Err(anyhow!("chunkedEmit.pieceSize must be greater than 0"));
+            }
+
+            if let Some(split_chunks) = &config.split_chunks {
+                if config.code_splitting.is_some() {
+                    return Err(anyhow!(
+                        "splitChunks and codeSplitting cannot be used at the same time",
+                    ));
+                }
+                config.code_splitting = Some(split_chunks.to_code_splitting());
+            }
+
             let mode = format!("\"{}\"", config.mode);
             config
                 .define
                 .insert("NODE_ENV".to_string(), serde_json::Value::String(mode));
 
+            // variables of env files are strings, explicit define takes precedence
+            if let Some(env_file) = &config.env_file {
+                config.env_file_vars = load_env_files(root, &config.mode, env_file)?;
+                for (key, value) in &config.env_file_vars {
+                    config
+                        .define
+                        .entry(key.clone())
+                        .or_insert_with(|| Value::String(serde_json::to_string(value).unwrap()));
+                }
+            }
+
+            // react native loads a single bundle without dom
+            if let Some(react_native) = &config.experimental.react_native {
+                if config.resolve.platforms.is_empty() {
+                    config.resolve.platforms =
+                        vec![react_native.platform.to_string(), "native".to_string()];
+                }
+                config.code_splitting = None;
+                config.dynamic_import_to_require = true;
+                config.hmr = None;
+                let is_dev = config.mode == Mode::Development;
+                config
+                    .define
+                    .entry("__DEV__".to_string())
+                    .or_insert(Value::Bool(is_dev));
+            }
+
+            if config.evaluate_define {
+                config.define.values_mut().for_each(evaluate_define);
+            }
+
+            if config.swc_helpers.source.is_empty() {
+                return Err(anyhow!("swcHelpers.source can not be empty"));
+            }
+
             if config.public_path != "runtime" && !config.public_path.ends_with('/') {
                 return Err(anyhow!("public_path must end with '/' or be 'runtime'"));
             }
@@ -810,6 +1667,46 @@ impl Config {
                 }
             }
 
+            // imports of the import map file are loaded once, config imports take precedence
+            if let Some(import_map) = &mut config.import_map
+                && let Some(path) = &import_map.path
+            {
+                let path = root.join(path);
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow!("failed to read import map {}: {}", path.display(), e))?;
+                let file: serde_json::Value = serde_json::from_str(&content)?;
+                if let Some(imports) = file.get("imports").and_then(|v| v.as_object()) {
+                    for (key, url) in imports {
+                        let url = url.as_str().ok_or_else(|| {
+                            anyhow!(
+                                "importMap: url of {} in {} must be a string",
+                                key,
+                                path.display()
+                            )
+                        })?;
+                        import_map
+                            .imports
+                            .entry(key.clone())
+                            .or_insert_with(|| url.to_string());
+                    }
+                }
+            }
+
+            // every page is an entry with its own html, configured entries take precedence
+            if let Some(mpa) = &config.mpa {
+                let pages = plugins::mpa::collect_pages(root, &mpa.pages_dir)?;
+                let html = config.html.get_or_insert_with(HtmlConfig::default);
+                for page in pages {
+                    if let Some(template) = page.template {
+                        html.templates.entry(page.name.clone()).or_insert(template);
+                    }
+                    config.entry.entry(page.name).or_insert(page.entry);
+                }
+                if config.code_splitting.is_none() {
+                    config.code_splitting = Some(plugins::mpa::mpa_code_splitting());
+                }
+            }
+
             // support default entries
             if config.entry.is_empty() {
                 let file_paths = vec!["src/index.tsx", "src/index.ts", "index.tsx", "index.ts"];
@@ -825,12 +1722,36 @@ impl Config {
                 }
             }
 
+            // entries are skipped before the module graph is built
+            for (name, condition) in &config.entry_conditions {
+                if !config.entry.contains_key(name) {
+                    return Err(anyhow!(
+                        "entryConditions.{}: entry {} not found",
+                        name,
+                        name
+                    ));
+                }
+                if let Some(reason) = condition.skip_reason(&config.mode) {
+                    config.entry.remove(name);
+                    config.skipped_entries.insert(name.clone(), reason);
+                }
+            }
+            if config.entry.is_empty() {
+                return Err(anyhow!(
+                    "Entry is empty, all entries are skipped by entryConditions"
+                ));
+            }
+
             // normalize entry
             let entry_tuples = config
                 .entry
                 .clone()
                 .into_iter()
                 .map(|(k, v)| {
+                    // virtual entries are loaded by plugins, e.g. entries of the builder api
+                    if v.to_string_lossy().starts_with("virtual:") {
+                        return Ok((k, v));
+                    }
                     if let Ok(entry_path) = root.join(v).canonicalize() {
                         Ok((k, entry_path))
                     } else {
@@ -840,6 +1761,16 @@ impl Config {
                 .collect::<Result<Vec<_>>>()?;
             config.entry = entry_tuples.into_iter().collect();
 
+            // output dirs of codegen steps are resolved as packages
+            for codegen in &config.codegen {
+                if let Some(name) = &codegen.name {
+                    config.resolve.alias.push((
+                        name.clone(),
+                        root.join(&codegen.output).to_string_lossy().to_string(),
+                    ));
+                }
+            }
+
             // support relative alias
             config.resolve.alias = config
                 .resolve
@@ -866,6 +1797,12 @@ impl Config {
         }
         ret.map_err(|e| anyhow!("{}: {}", "config error".red(), e.to_string().red()))
     }
+
+    // require.resolve is resolved at build time by default, except for node builds which
+    // need the native behaviour
+    pub fn require_resolve(&self) -> bool {
+        self.require_resolve.unwrap_or(self.platform != Platform::Node)
+    }
 }
 
 impl Default for Config {
@@ -917,8 +1854,13 @@ impl<const U: usize> GenericUsizeDefault<U> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use crate::config::config::GenericUsizeDefault;
-    use crate::config::{Config, Mode, Platform};
+    use crate::config::{
+        CodeSplitting, CodeSplittingStrategy, CodeSplittingStrategyOptions, Config,
+        ImportMapConfig, Mode, OptimizeAllowChunks, Platform,
+    };
 
     #[test]
     fn test_config() {
@@ -981,6 +1923,29 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_global_namespace() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"globalNamespace":"$app_1"}}"#),
+        )
+        .unwrap();
+        assert_eq!(config.output.global_namespace, Some("$app_1".to_string()));
+        for namespace in ["", "my-app", "app.b"] {
+            let err = Config::new(
+                &current_dir.join("test/config/normal"),
+                None,
+                Some(&format!(r#"{{"output":{{"globalNamespace":"{}"}}}}"#, namespace)),
+            )
+            .unwrap_err();
+            assert!(err
+                .to_string()
+                .contains("output.globalNamespace must be a non-empty identifier"));
+        }
+    }
+
     #[test]
     fn test_node_platform() {
         let current_dir = std::env::current_dir().unwrap();
@@ -997,6 +1962,194 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_require_resolve() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(&current_dir.join("test/config/normal"), None, None).unwrap();
+        assert_eq!(config.platform, Platform::Node);
+        assert!(!config.require_resolve());
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"requireResolve":true}"#),
+        )
+        .unwrap();
+        assert!(config.require_resolve());
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"platform":"browser"}"#),
+        )
+        .unwrap();
+        assert!(config.require_resolve());
+    }
+
+    #[test]
+    fn test_browser_node_globals() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/define"),
+            None,
+            Some(r#"{"define":{"process.platform":"\"web\""}}"#),
+        )
+        .unwrap();
+        assert_eq!(
+            config.define.get("process.browser"),
+            Some(&serde_json::Value::Bool(true))
+        );
+        assert_eq!(
+            config.define.get("process.platform"),
+            Some(&serde_json::Value::String("\"web\"".to_string())),
+            "explicit define takes precedence",
+        );
+        assert_eq!(
+            config.define.get("global"),
+            Some(&serde_json::Value::String("globalThis".to_string()))
+        );
+        assert!(!config.providers.contains_key("global"));
+
+        // targets without globalThis keep the polyfill of global
+        let config = Config::new(
+            &current_dir.join("test/config/define"),
+            None,
+            Some(r#"{"targets":{"ie":11}}"#),
+        )
+        .unwrap();
+        assert!(!config.define.contains_key("global"));
+        assert!(config.providers.contains_key("global"));
+
+        let config =
+            Config::new(&current_dir.join("test/config/node-platform"), None, None).unwrap();
+        assert!(!config.define.contains_key("process.browser"));
+    }
+
+    #[test]
+    fn test_entry_conditions() {
+        let current_dir = std::env::current_dir().unwrap();
+        let root = current_dir.join("test/config/entry-conditions");
+        let config = Config::new(&root, None, Some(r#"{"mode":"development"}"#)).unwrap();
+        assert!(config.entry.contains_key("mock-bootstrap"));
+        assert!(config.skipped_entries.is_empty());
+
+        let config = Config::new(&root, None, Some(r#"{"mode":"production"}"#)).unwrap();
+        assert!(!config.entry.contains_key("mock-bootstrap"));
+        assert!(config.entry.contains_key("index"));
+        assert_eq!(
+            config.skipped_entries.get("mock-bootstrap"),
+            Some(&"mode is not development".to_string())
+        );
+    }
+
+    #[test]
+    fn test_env_file() {
+        let current_dir = std::env::current_dir().unwrap();
+        let root = current_dir.join("test/config/env-file");
+        let config = Config::new(&root, None, Some(r#"{"mode":"production"}"#)).unwrap();
+        assert_eq!(
+            config.env_file_vars.get("MAKO_APP_API"),
+            Some(&"https://api.example.com".to_string())
+        );
+        assert_eq!(
+            config.env_file_vars.get("MAKO_APP_NAME"),
+            Some(&"local".to_string())
+        );
+        assert!(!config.env_file_vars.contains_key("SECRET"));
+        assert_eq!(
+            config.define.get("MAKO_APP_NAME"),
+            Some(&serde_json::Value::String(r#""local""#.to_string()))
+        );
+        assert_eq!(
+            config.define.get("MAKO_APP_DEFINED"),
+            Some(&serde_json::Value::String(r#""define""#.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_import_map_resolve() {
+        let import_map = ImportMapConfig {
+            path: None,
+            imports: BTreeMap::from([
+                ("react".to_string(), "https://esm.sh/react".to_string()),
+                ("lodash/".to_string(), "https://esm.sh/lodash/".to_string()),
+            ]),
+            emit: false,
+        };
+        assert_eq!(
+            import_map.resolve("react"),
+            Some("https://esm.sh/react".to_string())
+        );
+        assert_eq!(
+            import_map.resolve("lodash/get"),
+            Some("https://esm.sh/lodash/get".to_string())
+        );
+        assert_eq!(import_map.resolve("react-dom"), None);
+    }
+
+    #[test]
+    fn test_mpa() {
+        let current_dir = std::env::current_dir().unwrap();
+        let root = current_dir.join("test/config/mpa");
+        let config = Config::new(&root, None, None).unwrap();
+        let mut entries = config.entry.keys().cloned().collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, vec!["about", "home"]);
+        let html = config.html.unwrap();
+        assert_eq!(
+            html.templates.get("about"),
+            Some(&root.join("src/pages/about/index.html"))
+        );
+        assert!(!html.templates.contains_key("home"));
+        assert!(config.code_splitting.is_some());
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(
+                r#"{"splitChunks":{"chunks":"initial","minSize":1000,"cacheGroups":{"default":false,"react":{"test":"react","priority":10,"chunks":"all"}}}}"#,
+            ),
+        )
+        .unwrap();
+        let Some(CodeSplitting {
+            strategy: CodeSplittingStrategy::Advanced,
+            options: Some(CodeSplittingStrategyOptions::Advanced(options)),
+        }) = config.code_splitting
+        else {
+            panic!("splitChunks should be the advanced strategy");
+        };
+        assert_eq!(options.min_size, 1000);
+        let groups = options
+            .groups
+            .iter()
+            .map(|group| {
+                (
+                    group.name.as_str(),
+                    group.priority,
+                    group.min_size,
+                    matches!(group.allow_chunks, OptimizeAllowChunks::All),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            vec![("vendors", -10, 1000, false), ("react", 10, 1000, true)]
+        );
+        assert!(matches!(
+            options.groups[0].allow_chunks,
+            OptimizeAllowChunks::Entry
+        ));
+
+        assert!(Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"splitChunks":{},"codeSplitting":{"strategy":"auto"}}"#),
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_generic_usize_default() {
         assert!(GenericUsizeDefault::<100>::value() == 100usize)
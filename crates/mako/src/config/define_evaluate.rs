@@ -0,0 +1,259 @@
+use serde_json::{Map, Number, Value};
+use swc_core::common::BytePos;
+use swc_core::ecma::ast::{
+    BinExpr, BinaryOp, CallExpr, Callee, EsVersion, Expr, Ident, Lit, MemberExpr, MemberProp,
+    ModuleItem, Prop, PropName, PropOrSpread, Stmt, UnaryExpr, UnaryOp,
+};
+use swc_core::ecma::parser::lexer::Lexer;
+use swc_core::ecma::parser::{EsConfig, Parser, StringInput, Syntax};
+
+// constant value of a define expression
+#[derive(Debug, Clone, PartialEq)]
+enum Const {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+    Array(Vec<Const>),
+    Object(Vec<(String, Const)>),
+}
+
+/**
+ * evaluate the constant expressions of define values, so that literals are injected instead
+ * of the expressions
+ *
+ * e.g.
+ * "\"v\" + (1 + 2)" -> "\"v3\""
+ * "60 * 60 * 1000" -> 3600000
+ * "JSON.stringify({ a: 1 })" -> "\"{\\\"a\\\":1}\""
+ *
+ * values which are not constant, e.g. `Date.now()`, are kept as is
+ */
+pub fn evaluate_define(define: &mut Value) {
+    match define {
+        Value::String(expr) => {
+            if let Some(value) = evaluate_expr_str(expr) {
+                *define = value;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(evaluate_define),
+        Value::Object(props) => props.values_mut().for_each(evaluate_define),
+        _ => {}
+    }
+}
+
+fn evaluate_expr_str(expr: &str) -> Option<Value> {
+    let lexer = Lexer::new(
+        Syntax::Es(EsConfig::default()),
+        EsVersion::EsNext,
+        StringInput::new(expr, BytePos(1), BytePos(1 + expr.len() as u32)),
+        None,
+    );
+    let module = Parser::new_from(lexer).parse_module().ok()?;
+    match module.body.as_slice() {
+        [ModuleItem::Stmt(Stmt::Expr(stmt))] => to_define_value(&evaluate(&stmt.expr)?),
+        _ => None,
+    }
+}
+
+fn evaluate(expr: &Expr) -> Option<Const> {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => Some(Const::Str(s.value.to_string())),
+        Expr::Lit(Lit::Num(n)) => Some(Const::Num(n.value)),
+        Expr::Lit(Lit::Bool(b)) => Some(Const::Bool(b.value)),
+        Expr::Lit(Lit::Null(_)) => Some(Const::Null),
+        Expr::Paren(paren) => evaluate(&paren.expr),
+        Expr::Tpl(tpl) => {
+            let mut s = String::new();
+            for (i, quasi) in tpl.quasis.iter().enumerate() {
+                s.push_str(quasi.cooked.as_ref()?);
+                if let Some(expr) = tpl.exprs.get(i) {
+                    s.push_str(&to_js_string(&evaluate(expr)?)?);
+                }
+            }
+            Some(Const::Str(s))
+        }
+        Expr::Unary(UnaryExpr { op, arg, .. }) => match (op, evaluate(arg)?) {
+            (UnaryOp::Minus, Const::Num(n)) => Some(Const::Num(-n)),
+            (UnaryOp::Plus, Const::Num(n)) => Some(Const::Num(n)),
+            (UnaryOp::Bang, Const::Bool(b)) => Some(Const::Bool(!b)),
+            _ => None,
+        },
+        Expr::Bin(BinExpr {
+            op, left, right, ..
+        }) => match (op, evaluate(left)?, evaluate(right)?) {
+            (BinaryOp::Add, Const::Num(l), Const::Num(r)) => Some(Const::Num(l + r)),
+            (BinaryOp::Add, l @ Const::Str(_), r) | (BinaryOp::Add, l, r @ Const::Str(_)) => {
+                Some(Const::Str(to_js_string(&l)? + &to_js_string(&r)?))
+            }
+            (BinaryOp::Sub, Const::Num(l), Const::Num(r)) => Some(Const::Num(l - r)),
+            (BinaryOp::Mul, Const::Num(l), Const::Num(r)) => Some(Const::Num(l * r)),
+            (BinaryOp::Div, Const::Num(l), Const::Num(r)) => Some(Const::Num(l / r)),
+            (BinaryOp::Mod, Const::Num(l), Const::Num(r)) => Some(Const::Num(l % r)),
+            (BinaryOp::Exp, Const::Num(l), Const::Num(r)) => Some(Const::Num(l.powf(r))),
+            _ => None,
+        },
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                Some(elem) if elem.spread.is_none() => evaluate(&elem.expr),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Const::Array),
+        Expr::Object(object) => object
+            .props
+            .iter()
+            .map(|prop| match prop {
+                PropOrSpread::Prop(box Prop::KeyValue(kv)) => {
+                    let key = match &kv.key {
+                        PropName::Ident(ident) => ident.sym.to_string(),
+                        PropName::Str(s) => s.value.to_string(),
+                        PropName::Num(n) => js_number_to_string(n.value),
+                        _ => return None,
+                    };
+                    Some((key, evaluate(&kv.value)?))
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Const::Object),
+        // JSON.stringify(value)
+        Expr::Call(CallExpr {
+            callee:
+                Callee::Expr(box Expr::Member(MemberExpr {
+                    obj: box Expr::Ident(Ident { sym: obj, .. }),
+                    prop: MemberProp::Ident(Ident { sym: prop, .. }),
+                    ..
+                })),
+            args,
+            ..
+        }) if obj == "JSON"
+            && prop == "stringify"
+            && args.len() == 1
+            && args[0].spread.is_none() =>
+        {
+            Some(Const::Str(stringify(&evaluate(&args[0].expr)?)))
+        }
+        _ => None,
+    }
+}
+
+// `String(value)` of primitives
+fn to_js_string(value: &Const) -> Option<String> {
+    match value {
+        Const::Str(s) => Some(s.clone()),
+        Const::Num(n) => Some(js_number_to_string(*n)),
+        Const::Bool(b) => Some(b.to_string()),
+        Const::Null => Some("null".to_string()),
+        Const::Array(_) | Const::Object(_) => None,
+    }
+}
+
+fn js_number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        let s = if n > 0.0 { "Infinity" } else { "-Infinity" };
+        s.to_string()
+    } else if n.fract() == 0.0 && n.abs() < 1e21 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+fn to_json_number(n: f64) -> Option<Value> {
+    if n.fract() == 0.0 && n.abs() < 9007199254740992.0 {
+        Some(Value::Number((n as i64).into()))
+    } else {
+        Number::from_f64(n).map(Value::Number)
+    }
+}
+
+// `JSON.stringify(value)`, keys are kept in the order of the source
+fn stringify(value: &Const) -> String {
+    match value {
+        Const::Str(s) => serde_json::to_string(s).unwrap(),
+        // NaN and Infinity are stringified as null
+        Const::Num(n) if !n.is_finite() => "null".to_string(),
+        Const::Num(n) => js_number_to_string(*n),
+        Const::Bool(b) => b.to_string(),
+        Const::Null => "null".to_string(),
+        Const::Array(items) => format!(
+            "[{}]",
+            items.iter().map(stringify).collect::<Vec<_>>().join(",")
+        ),
+        Const::Object(props) => format!(
+            "{{{}}}",
+            props
+                .iter()
+                .map(|(key, value)| format!(
+                    "{}:{}",
+                    serde_json::to_string(key).unwrap(),
+                    stringify(value)
+                ))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+// value of define config, strings are expressions so they are quoted
+fn to_define_value(value: &Const) -> Option<Value> {
+    match value {
+        Const::Str(s) => Some(Value::String(serde_json::to_string(s).unwrap())),
+        // NaN and Infinity can't be represented in json, keep the expressions
+        Const::Num(n) => to_json_number(*n),
+        Const::Bool(b) => Some(Value::Bool(*b)),
+        Const::Null => Some(Value::Null),
+        Const::Array(items) => items
+            .iter()
+            .map(to_define_value)
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        Const::Object(props) => props
+            .iter()
+            .map(|(key, value)| Some((key.clone(), to_define_value(value)?)))
+            .collect::<Option<Map<_, _>>>()
+            .map(Value::Object),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::evaluate_define;
+
+    #[test]
+    fn test_evaluate_define() {
+        assert_eq!(run(json!(r#""v" + (1 + 2)"#)), json!(r#""v3""#));
+        assert_eq!(run(json!("60 * 60 * 1000")), json!(3600000));
+        assert_eq!(run(json!("-0.5 + 2 ** 2")), json!(3.5));
+        assert_eq!(run(json!("!true")), json!(false));
+        assert_eq!(run(json!("`${1}-${'a'}`")), json!(r#""1-a""#));
+        assert_eq!(
+            run(json!(r#"JSON.stringify({ a: 1, "b": [true, null, "c"] })"#)),
+            json!(r#""{\"a\":1,\"b\":[true,null,\"c\"]}""#)
+        );
+        assert_eq!(
+            run(json!({ "a": "1 + 1", "b": ["'x' + 'y'"] })),
+            json!({ "a": 2, "b": [r#""xy""#] })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_define_non_constant() {
+        assert_eq!(run(json!("Date.now()")), json!("Date.now()"));
+        assert_eq!(run(json!("'v' + version")), json!("'v' + version"));
+        assert_eq!(run(json!("1 / 0")), json!("1 / 0"));
+        assert_eq!(run(json!("1; 2")), json!("1; 2"));
+    }
+
+    fn run(mut define: Value) -> Value {
+        evaluate_define(&mut define);
+        define
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Mode;
+
+pub(crate) fn default_env_file_prefix() -> String {
+    "MAKO_APP_".to_string()
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EnvFileConfig {
+    // only keys with the prefix are loaded, so that secrets in .env aren't bundled by accident
+    #[serde(default = "default_env_file_prefix")]
+    pub prefix: String,
+}
+
+// files loaded in order, later ones override earlier ones
+fn env_file_names(mode: &Mode) -> Vec<String> {
+    vec![
+        ".env".to_string(),
+        ".env.local".to_string(),
+        format!(".env.{}", mode),
+        format!(".env.{}.local", mode),
+    ]
+}
+
+/**
+ * load variables from .env, .env.local, .env.[mode] and .env.[mode].local under root,
+ * variables of the process environment take precedence over the files
+ */
+pub fn load_env_files(
+    root: &Path,
+    mode: &Mode,
+    config: &EnvFileConfig,
+) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for name in env_file_names(mode) {
+        let path = root.join(&name);
+        if !path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))?;
+        vars.extend(parse_env_file(&content).map_err(|e| anyhow!("{}: {}", name, e))?);
+    }
+    vars.extend(std::env::vars());
+    vars.retain(|key, _| key.starts_with(&config.prefix));
+    Ok(vars)
+}
+
+// KEY=value lines, with optional `export`, quotes and # comments
+fn parse_env_file(content: &str) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid line {}: {}", index + 1, line))?;
+        let key = key.trim();
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = value[1..]
+                    .find(quote)
+                    .ok_or_else(|| anyhow!("unterminated quote at line {}", index + 1))?;
+                let value = &value[1..end + 1];
+                if quote == '"' {
+                    value.replace("\\n", "\n")
+                } else {
+                    value.to_string()
+                }
+            }
+            // inline comments need a leading space, e.g. `KEY=value # comment`
+            _ => value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim_end()
+                .to_string(),
+        };
+        vars.insert(key.to_string(), value);
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_env_file;
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse_env_file(
+            r#"
+# comment
+MAKO_APP_A=1
+export MAKO_APP_B = "hello world" # comment
+MAKO_APP_C='single # quoted'
+MAKO_APP_D=plain # comment
+MAKO_APP_E="line\nbreak"
+MAKO_APP_F=
+"#,
+        )
+        .unwrap();
+        assert_eq!(vars["MAKO_APP_A"], "1");
+        assert_eq!(vars["MAKO_APP_B"], "hello world");
+        assert_eq!(vars["MAKO_APP_C"], "single # quoted");
+        assert_eq!(vars["MAKO_APP_D"], "plain");
+        assert_eq!(vars["MAKO_APP_E"], "line\nbreak");
+        assert_eq!(vars["MAKO_APP_F"], "");
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        assert!(parse_env_file("MAKO_APP_A").is_err());
+    }
+}
@@ -1,3 +1,7 @@
 #[allow(clippy::module_inception)]
 mod config;
+mod define_evaluate;
+mod env_file;
 pub use config::*;
+pub use define_evaluate::*;
+pub use env_file::*;
@@ -0,0 +1,284 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use swc_core::ecma::ast::Module;
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
+
+use crate::compiler::{Compiler, Context};
+use crate::module::{ModuleAst, ModuleId, ResolveType};
+
+/**
+ * the source, transformed code and dependencies of a module,
+ * it's served at /__/inspect/module?id=<module id> by the dev server
+ * for debugging plugin pipelines, e.g.
+ *
+ * curl http://localhost:3000/__/inspect/module?id=src/index.tsx
+ */
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectModule {
+    pub id: String,
+    // content on disk, or the loaded content for virtual modules
+    pub source: String,
+    // code generated from the transformed ast
+    pub transformed: Option<String>,
+    pub dependencies: Vec<InspectDependency>,
+    pub dependents: Vec<String>,
+    // when inspect is enabled, the code after each transform stage which changed it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transform_stages: Vec<TransformStage>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectDependency {
+    pub source: String,
+    pub id: String,
+    pub resolve_type: ResolveType,
+}
+
+impl Compiler {
+    pub fn inspect_module_ids(&self) -> Vec<String> {
+        module_ids(&self.context)
+    }
+
+    pub fn inspect_module(&self, id: &str) -> Option<InspectModule> {
+        inspect_module(&self.context, id)
+    }
+}
+
+pub fn module_ids(context: &Context) -> Vec<String> {
+    let module_graph = context.module_graph.read().unwrap();
+    let mut ids = module_graph
+        .get_module_ids()
+        .into_iter()
+        .map(|id| id.id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+// id can be either absolute or relative to root
+pub fn inspect_module(context: &Arc<Context>, id: &str) -> Option<InspectModule> {
+    let module_graph = context.module_graph.read().unwrap();
+    let module_id = [
+        ModuleId::new(id.to_string()),
+        ModuleId::from_path(context.root.join(id)),
+    ]
+    .into_iter()
+    .find(|module_id| module_graph.has_module(module_id))?;
+    let module = module_graph.get_module(&module_id)?;
+    let info = module.info.as_ref()?;
+
+    let source = fs::read(&info.file.pathname)
+        .map(|content| String::from_utf8_lossy(&content).to_string())
+        .unwrap_or_else(|_| info.raw.clone());
+    let transformed = match &info.ast {
+        ModuleAst::Script(ast) => ast.generate(context.clone()).ok().map(|r| r.code),
+        ModuleAst::Css(ast) => ast.generate(context.clone()).ok().map(|r| r.code),
+        ModuleAst::None => None,
+    };
+    let dependencies = module_graph
+        .get_dependencies(&module_id)
+        .into_iter()
+        .map(|(id, dep)| InspectDependency {
+            source: dep.source.clone(),
+            id: id.id.clone(),
+            resolve_type: dep.resolve_type,
+        })
+        .collect();
+    let dependents = module_graph
+        .get_dependents(&module_id)
+        .into_iter()
+        .map(|(id, _)| id.id.clone())
+        .collect();
+
+    Some(InspectModule {
+        transform_stages: context
+            .transform_inspector
+            .get(&info.file.path.to_string_lossy()),
+        id: module_id.id.clone(),
+        source,
+        transformed,
+        dependencies,
+        dependents,
+    })
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TransformStage {
+    pub name: String,
+    pub code: String,
+}
+
+// transform stages of the most recently transformed modules
+#[derive(Default)]
+pub struct TransformInspector {
+    records: Mutex<(VecDeque<String>, HashMap<String, Vec<TransformStage>>)>,
+}
+
+impl TransformInspector {
+    pub fn record(&self, path: &str, stages: Vec<TransformStage>, max_modules: usize) {
+        let mut records = self.records.lock().unwrap();
+        let (order, stages_map) = &mut *records;
+        // a rebuilt module is moved to the back
+        order.retain(|p| p != path);
+        order.push_back(path.to_string());
+        stages_map.insert(path.to_string(), stages);
+        while order.len() > max_modules {
+            if let Some(oldest) = order.pop_front() {
+                stages_map.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Vec<TransformStage> {
+        let records = self.records.lock().unwrap();
+        records.1.get(path).cloned().unwrap_or_default()
+    }
+}
+
+// records the code after each transform stage of a module when inspect is enabled
+pub struct TransformRecorder<'a> {
+    context: &'a Context,
+    stages: Option<Vec<TransformStage>>,
+}
+
+impl<'a> TransformRecorder<'a> {
+    pub fn new(context: &'a Context) -> Self {
+        Self {
+            context,
+            stages: context.config.inspect.is_some().then(Vec::new),
+        }
+    }
+
+    // stages which don't change the code are skipped
+    pub fn record(&mut self, name: &str, ast: &Module) {
+        if let Some(stages) = &mut self.stages {
+            let code = module_to_code(ast, self.context);
+            if stages.last().map_or(true, |stage| stage.code != code) {
+                stages.push(TransformStage {
+                    name: name.to_string(),
+                    code,
+                });
+            }
+        }
+    }
+
+    pub fn finish(self, path: &str) {
+        if let (Some(stages), Some(inspect)) = (self.stages, &self.context.config.inspect) {
+            self.context
+                .transform_inspector
+                .record(path, stages, inspect.max_modules);
+        }
+    }
+}
+
+fn module_to_code(ast: &Module, context: &Context) -> String {
+    let cm = context.meta.script.cm.clone();
+    let comments = context.meta.script.origin_comments.read().unwrap();
+    let mut buf = vec![];
+    let mut emitter = Emitter {
+        cfg: JsCodegenConfig::default().with_target(context.config.output.es_version),
+        cm: cm.clone(),
+        comments: Some(comments.get_swc_comments()),
+        wr: Box::new(JsWriter::new(cm, "\n", &mut buf, None)),
+    };
+    match emitter.emit_module(ast) {
+        Ok(_) => String::from_utf8_lossy(&buf).to_string(),
+        Err(err) => format!("// failed to generate code: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::{inspect_module, module_ids, TransformInspector, TransformStage};
+    use crate::ast::file::File;
+    use crate::compiler::Context;
+    use crate::module::{Dependency, Module, ModuleId, ModuleInfo, ResolveType};
+
+    fn module(path: &str, raw: &str, is_entry: bool) -> Module {
+        let info = ModuleInfo {
+            file: File {
+                path: PathBuf::from(path),
+                pathname: PathBuf::from(path),
+                ..Default::default()
+            },
+            raw: raw.to_string(),
+            ..Default::default()
+        };
+        Module::new(ModuleId::new(path.to_string()), is_entry, Some(info))
+    }
+
+    #[test]
+    fn test_inspect_module() {
+        let context = Arc::new(Context {
+            root: PathBuf::from("/mako_inspect"),
+            ..Default::default()
+        });
+        {
+            let mut module_graph = context.module_graph.write().unwrap();
+            module_graph.add_module(module(
+                "/mako_inspect/src/index.ts",
+                "require('./a');",
+                true,
+            ));
+            module_graph.add_module(module("/mako_inspect/src/a.ts", "export {};", false));
+            module_graph.add_dependency(
+                &ModuleId::new("/mako_inspect/src/index.ts".to_string()),
+                &ModuleId::new("/mako_inspect/src/a.ts".to_string()),
+                Dependency {
+                    source: "./a".to_string(),
+                    resolve_as: None,
+                    specifiers: vec![],
+                    resolve_type: ResolveType::Require,
+                    order: 0,
+                    span: None,
+                },
+            );
+        }
+
+        assert_eq!(
+            module_ids(&context),
+            vec!["/mako_inspect/src/a.ts", "/mako_inspect/src/index.ts"]
+        );
+        // relative to root
+        let index = inspect_module(&context, "src/index.ts").unwrap();
+        assert_eq!(index.id, "/mako_inspect/src/index.ts");
+        // files which don't exist on disk fall back to the loaded content
+        assert_eq!(index.source, "require('./a');");
+        assert_eq!(index.transformed, None);
+        assert_eq!(index.dependencies.len(), 1);
+        assert_eq!(index.dependencies[0].source, "./a");
+        assert_eq!(index.dependencies[0].id, "/mako_inspect/src/a.ts");
+        assert!(index.dependents.is_empty());
+
+        let a = inspect_module(&context, "/mako_inspect/src/a.ts").unwrap();
+        assert_eq!(a.dependents, vec!["/mako_inspect/src/index.ts"]);
+        assert!(inspect_module(&context, "src/b.ts").is_none());
+    }
+
+    #[test]
+    fn test_transform_inspector_ring_buffer() {
+        let inspector = TransformInspector::default();
+        let stages = |code: &str| {
+            vec![TransformStage {
+                name: "source".to_string(),
+                code: code.to_string(),
+            }]
+        };
+        inspector.record("a.js", stages("a"), 2);
+        inspector.record("b.js", stages("b"), 2);
+        inspector.record("a.js", stages("a2"), 2);
+        inspector.record("c.js", stages("c"), 2);
+        assert_eq!(inspector.get("a.js"), stages("a2"));
+        assert!(inspector.get("b.js").is_empty());
+        assert_eq!(inspector.get("c.js"), stages("c"));
+    }
+}
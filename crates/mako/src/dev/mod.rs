@@ -1,8 +1,10 @@
+pub mod inspect;
 pub(crate) mod update;
 mod watch;
 
 use std::net::{SocketAddr, TcpListener};
 use std::path::PathBuf;
+use std::sync::atomic::Ordering;
 use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant, UNIX_EPOCH};
 
@@ -148,6 +150,18 @@ impl DevServer {
                     Ok(not_found_response())
                 }
             }
+            "/__/inspect" => Ok(Self::json_response(&inspect::module_ids(&context))),
+            "/__/inspect/module" => {
+                let id = req.uri().query().and_then(|query| {
+                    url::form_urlencoded::parse(query.as_bytes())
+                        .find(|(key, _)| key == "id")
+                        .map(|(_, value)| value.to_string())
+                });
+                match id.and_then(|id| inspect::inspect_module(&context, &id)) {
+                    Some(module) => Ok(Self::json_response(&module)),
+                    None => Ok(not_found_response()),
+                }
+            }
             _ => {
                 // for bundle outputs
 
@@ -195,6 +209,14 @@ impl DevServer {
         }
     }
 
+    fn json_response<T: serde::Serialize>(value: &T) -> hyper::Response<Body> {
+        hyper::Response::builder()
+            .status(hyper::StatusCode::OK)
+            .header(CONTENT_TYPE, "application/json; charset=utf-8")
+            .body(hyper::Body::from(serde_json::to_string(value).unwrap()))
+            .unwrap()
+    }
+
     fn get_ips() -> Vec<String> {
         let mut ips = vec![];
         match get_if_addrs() {
@@ -232,7 +254,10 @@ impl DevServer {
             loop {
                 if let Ok(msg) = receiver.recv().await {
                     if sender
-                        .send(Message::text(format!(r#"{{"hash":"{}"}}"#, msg.hash)))
+                        .send(Message::text(format!(
+                            r#"{{"hash":"{}","reload":{}}}"#,
+                            msg.hash, msg.reload
+                        )))
                         .await
                         .is_err()
                     {
@@ -272,7 +297,7 @@ impl DevServer {
                 eprintln!("Error watching files: {:?}", result.err().unwrap());
                 continue;
             }
-            let paths = watch::Watcher::normalize_events(result.unwrap());
+            let paths = watcher.normalize_events(result.unwrap());
             if !paths.is_empty() {
                 let compiler = compiler.clone();
                 let txws = txws.clone();
@@ -288,7 +313,11 @@ impl DevServer {
                     eprintln!("Error rebuilding: {:?}", e);
                 }
             }
-            watcher.refresh_watch()?;
+            // new dirs and file dependencies of the rebuild are watched, failures don't stop
+            // watching the others
+            if let Err(e) = watcher.refresh_watch() {
+                eprintln!("Error refreshing watch: {:?}", e);
+            }
         }
         Ok(())
     }
@@ -320,6 +349,10 @@ impl DevServer {
         if let Err(e) = update_result {
             debug!("checking update status... failed");
             eprintln!("{}", e);
+            compiler
+                .context
+                .plugin_driver
+                .build_error(&e, &compiler.context)?;
             // do not return error, since it's already printed
             return Ok(());
         }
@@ -347,6 +380,10 @@ impl DevServer {
         // }
         if let Err(e) = next_hash {
             eprintln!("Error in watch: {:?}", e);
+            compiler
+                .context
+                .plugin_driver
+                .build_error(&e, &compiler.context)?;
             return Err(e);
         }
         let (next_snapshot_hash, next_hmr_hash, current_hmr_hash) = next_hash.unwrap();
@@ -370,6 +407,10 @@ impl DevServer {
 
         if let Err(e) = compiler.emit_dev_chunks(next_hmr_hash, current_hmr_hash) {
             debug!("  > build failed: {:?}", e);
+            compiler
+                .context
+                .plugin_driver
+                .build_error(&e, &compiler.context)?;
             return Err(e);
         }
         debug!("full rebuild...done");
@@ -407,7 +448,12 @@ impl DevServer {
         let receiver_count = txws.receiver_count();
         debug!("receiver count: {}", receiver_count);
         if receiver_count > 0 {
-            txws.send(WsMessage { hash: **hmr_hash }).unwrap();
+            let reload = compiler.context.full_reload.swap(false, Ordering::SeqCst);
+            txws.send(WsMessage {
+                hash: **hmr_hash,
+                reload,
+            })
+            .unwrap();
             debug!("send message to clients");
         }
 
@@ -429,4 +475,5 @@ pub struct Stats {
 #[derive(Clone, Debug)]
 struct WsMessage {
     hash: u64,
+    reload: bool,
 }
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Debug;
 use std::path::PathBuf;
@@ -10,7 +10,8 @@ use tracing::debug;
 use crate::build::BuildError;
 use crate::compiler::Compiler;
 use crate::generate::transform::transform_modules;
-use crate::module::{Dependency, Module, ModuleId, ResolveType};
+use crate::module::ModuleId;
+use crate::module_graph::patch::ModulePatch;
 use crate::resolve::{self, clear_resolver_cache};
 
 #[derive(Debug, Clone)]
@@ -30,6 +31,8 @@ pub struct UpdateResult {
     pub modified: HashSet<ModuleId>,
     // 依赖变更，典型的如 async import 变成 import
     pub dep_changed: HashSet<ModuleId>,
+    // 需要重新 transform 的模块Id，已经构建过的新依赖不包含在内
+    pub dirty: HashSet<ModuleId>,
 }
 
 impl UpdateResult {
@@ -155,12 +158,23 @@ impl Compiler {
         debug!("filtering paths...");
         let paths: Vec<(PathBuf, UpdateType)> = {
             let module_graph = self.context.module_graph.read().unwrap();
+            let file_dependencies = self.context.file_dependencies.read().unwrap();
             let mut new_paths = vec![];
             paths.into_iter().for_each(|(p, update_type)| {
                 if module_graph.has_module(&p.clone().into()) {
                     debug!("  > {} is filtered", p.to_string_lossy());
                     new_paths.push((p.clone(), update_type.clone()));
                 }
+                // modules which read the file in loaders or transforms are rebuilt
+                for dependent in file_dependencies.dependents(&p) {
+                    let dependent = PathBuf::from(dependent);
+                    if module_graph.has_module(&dependent.clone().into())
+                        && !new_paths.iter().any(|(path, _)| path == &dependent)
+                    {
+                        debug!("  > {} is filtered by file dependency", dependent.display());
+                        new_paths.push((dependent, UpdateType::Modify));
+                    }
+                }
                 let path = p.to_string_lossy().to_string();
                 let watch_parent_searches = vec!["?modules", "?watch=parent"];
                 for search in watch_parent_searches {
@@ -196,70 +210,85 @@ impl Compiler {
             }
         }
 
-        // 先做删除
+        // 删除的模块的依赖方需要重新构建
         debug!("remove: {:?}", &removed);
-        let (removed_module_ids, affected_module_ids) = self.build_by_remove(removed);
-        debug!("after build_by_remove");
-        debug!("  > removed_module_ids: {:?}", &removed_module_ids);
-        debug!(
-            "  > affected_module_ids: {:?} (these will be added to modified",
-            &affected_module_ids
-        );
-        update_result.removed.extend(removed_module_ids);
-        modified.extend(affected_module_ids.into_iter().map(|i| i.to_path()));
+        let removed = removed
+            .into_iter()
+            .map(ModuleId::from_path)
+            .collect::<Vec<_>>();
+        {
+            let module_graph = self.context.module_graph.read().unwrap();
+            for module_id in &removed {
+                let dependants = module_graph.dependant_module_ids(module_id);
+                debug!(
+                    "  > affected_module_ids: {:?} (these will be added to modified)",
+                    &dependants
+                );
+                modified.extend(dependants.into_iter().map(|i| i.to_path()));
+            }
+        }
+        modified.retain(|path| !removed.contains(&ModuleId::from_path(path.clone())));
+        modified.sort();
+        modified.dedup();
 
-        // 分析修改的模块，结果中会包含新增的模块
+        // 只构建修改和新增的模块本身，新的依赖在 patch 之后构建
         debug!("modify: {:?}", &modified);
-        let (modified_module_ids, dep_changed_module_ids, add_paths) =
-            self.build_by_modify(modified).map_err(|err| anyhow!(err))?;
-        debug!("after build_by_modify");
-        debug!("  > modified_module_ids: {:?}", &modified_module_ids);
-        debug!(
-            "  > add_paths: {:?} (these will be added to added)",
-            &add_paths
-        );
-
-        added.extend(add_paths);
-
-        update_result.modified.extend(modified_module_ids);
-
-        update_result.dep_changed.extend(dep_changed_module_ids);
-
-        // 最后做添加
+        let modified = self.build_patches(modified).map_err(|err| anyhow!(err))?;
         debug!("add: {:?}", &added);
-        let added_module_ids = self.build_by_add(&added)?;
-        update_result.added.extend(
-            added
-                .into_iter()
-                .map(ModuleId::from_path)
-                .collect::<HashSet<_>>(),
-        );
-        update_result.added.extend(added_module_ids);
+        let added = self.build_patches(added).map_err(|err| anyhow!(err))?;
+
+        let patch_result = self
+            .context
+            .module_graph
+            .write()
+            .unwrap()
+            .patch(added, modified, removed);
+        debug!("after patch: {:?}", &patch_result);
+
+        // modules which are not reachable anymore are removed too
+        self.context
+            .modules_with_missing_deps
+            .write()
+            .unwrap()
+            .retain(|id| !patch_result.removed.contains(&ModuleId::new(id.clone())));
+
+        // 最后构建新的依赖
+        let unbuilt = patch_result
+            .unbuilt
+            .iter()
+            .map(|module_id| module_id.to_path())
+            .collect::<Vec<_>>();
+        debug!("build unbuilt: {:?}", &unbuilt);
+        let built_module_ids = self.build_by_add(&unbuilt)?;
+
+        update_result.added.extend(patch_result.added);
+        update_result.added.extend(built_module_ids.iter().cloned());
+        update_result.modified.extend(patch_result.modified);
+        update_result.removed.extend(patch_result.removed);
+        update_result.dep_changed.extend(patch_result.dep_changed);
+        update_result.dirty.extend(patch_result.dirty);
+        update_result.dirty.extend(patch_result.unbuilt);
+        update_result.dirty.extend(built_module_ids);
+        self.context
+            .stats_info
+            .flush_grouped_warnings(self.context.args.verbose);
 
         debug!("update_result: {:?}", &update_result);
         Result::Ok(update_result)
     }
 
     pub fn transform_for_change(&self, update_result: &UpdateResult) -> Result<()> {
-        let mut changes: Vec<ModuleId> = vec![];
-        for module_id in &update_result.added {
-            changes.push(module_id.clone());
-        }
-        for module_id in &update_result.modified {
-            changes.push(module_id.clone());
-        }
-        transform_modules(changes, &self.context)?;
+        transform_modules(update_result.dirty.iter().cloned().collect(), &self.context)?;
         Ok(())
     }
 
-    fn build_by_modify(
-        &self,
-        modified: Vec<PathBuf>,
-    ) -> Result<(HashSet<ModuleId>, HashSet<ModuleId>, Vec<PathBuf>)> {
-        let result = modified
+    // build the changed modules only, the new dependencies are built after the module graph is
+    // patched
+    fn build_patches(&self, paths: Vec<PathBuf>) -> Result<Vec<ModulePatch>> {
+        paths
             .par_iter()
             .map(|entry| {
-                debug!("build by modify: {:?} start", entry);
+                debug!("build patch: {:?} start", entry);
                 // first build
                 let is_entry = {
                     let mut entries = self.context.config.entry.values();
@@ -295,90 +324,36 @@ impl Compiler {
                         .push(module.id.id.clone());
                 }
 
-                // diff
-                let module_graph = self.context.module_graph.read().unwrap();
-                let current_dependencies: Vec<(ModuleId, Dependency)> = module_graph
-                    .get_dependencies(&module.id)
-                    .into_iter()
-                    .map(|(module_id, dep)| (module_id.clone(), dep.clone()))
+                let dependencies = module
+                    .info
+                    .as_ref()
+                    .unwrap()
+                    .deps
+                    .resolved_deps
+                    .iter()
+                    .map(|dep| {
+                        let resolved_path = dep.resolver_resource.get_resolved_path();
+                        let is_external = dep.resolver_resource.get_external().is_some();
+                        let module_id = ModuleId::new(resolved_path.clone());
+                        let module = if is_external {
+                            Self::create_external_module(
+                                &dep.resolver_resource,
+                                self.context.clone(),
+                            )
+                        } else {
+                            Self::create_empty_module(&module_id)
+                        };
+                        (module, dep.dependency.clone())
+                    })
                     .collect();
-                drop(module_graph);
 
-                let mut add_modules: HashMap<ModuleId, Module> = HashMap::new();
-                let mut target_dependencies: Vec<(ModuleId, Dependency)> = vec![];
-                let resolved_deps = &module.info.as_ref().unwrap().deps.resolved_deps;
-                resolved_deps.iter().for_each(|dep| {
-                    let resolved_path = dep.resolver_resource.get_resolved_path();
-                    let is_external = dep.resolver_resource.get_external().is_some();
-                    let module_id = ModuleId::new(resolved_path.clone());
-                    let module = if is_external {
-                        Self::create_external_module(&dep.resolver_resource, self.context.clone())
-                    } else {
-                        Self::create_empty_module(&module_id)
-                    };
-                    target_dependencies.push((module_id.clone(), dep.dependency.clone()));
-                    add_modules.insert(module_id, module);
-                });
-
-                let mut d = diff(current_dependencies, target_dependencies);
-                // if added dep is a dynamic dependency, need to full re-group
-                d.added.iter().for_each(|added| {
-                    if added.1.resolve_type == ResolveType::DynamicImport {
-                        d.dep_changed.insert((module.id.clone(), added.1.clone()));
-                    }
-                });
-
-                debug!("build by modify: {:?} end", entry);
-                Result::Ok((module, d.added, d.removed, d.dep_changed, add_modules))
+                debug!("build patch: {:?} end", entry);
+                Result::Ok(ModulePatch {
+                    module,
+                    dependencies,
+                })
             })
-            .collect::<Result<Vec<_>>>();
-        let result = result?;
-
-        let mut added = vec![];
-        let mut modified_module_ids = HashSet::new();
-        let mut dep_changed_module_ids = HashSet::new();
-
-        let mut module_graph = self.context.module_graph.write().unwrap();
-        for (module, add, remove, dep_change, mut add_modules) in result {
-            // remove bind dependency
-            for (remove_module_id, dep) in remove {
-                module_graph.remove_dependency(&module.id, &remove_module_id, &dep);
-            }
-
-            // add bind dependency
-            for (add_module_id, dep) in &add {
-                // english: In theory, the add_module_id that add_modules should exist in must exist, but in actual scenarios, an unwrap() error still occurs, so add a guard check here
-                // TODO: Need to find the root cause
-                let add_module = add_modules.remove(add_module_id);
-                if add_module.is_none() {
-                    continue;
-                }
-                let add_module = add_module.unwrap();
-
-                // 只针对非 external 的模块设置 add Task
-                if add_module.info.is_none() {
-                    added.push(add_module_id.to_path());
-                }
-
-                module_graph.add_module(add_module);
-                module_graph.add_dependency(&module.id, add_module_id, dep.clone());
-            }
-
-            if !&dep_change.is_empty() {
-                dep_changed_module_ids.insert(module.id.clone());
-
-                for (dep_change_module_id, dep) in &dep_change {
-                    module_graph.add_dependency(&module.id, dep_change_module_id, dep.clone());
-                }
-            }
-
-            modified_module_ids.insert(module.id.clone());
-
-            // replace module
-            module_graph.replace_module(module);
-        }
-
-        Result::Ok((modified_module_ids, dep_changed_module_ids, added))
+            .collect::<Result<Vec<_>>>()
     }
 
     fn build_by_add(&self, added: &[PathBuf]) -> Result<HashSet<ModuleId>> {
@@ -393,67 +368,4 @@ impl Compiler {
             .collect();
         self.build(files)
     }
-
-    fn build_by_remove(&self, removed: Vec<PathBuf>) -> (HashSet<ModuleId>, HashSet<ModuleId>) {
-        let mut module_graph = self.context.module_graph.write().unwrap();
-        let mut removed_module_ids = HashSet::new();
-        let mut affected_module_ids = HashSet::new();
-        for path in removed {
-            let module_id = ModuleId::from_path(path);
-            let dependants = module_graph.dependant_module_ids(&module_id);
-            module_graph.remove_module_and_deps(&module_id);
-            affected_module_ids.extend(dependants);
-            removed_module_ids.insert(module_id);
-        }
-        (removed_module_ids, affected_module_ids)
-    }
-}
-
-pub struct Diff {
-    added: HashSet<(ModuleId, Dependency)>,
-    removed: HashSet<(ModuleId, Dependency)>,
-    dep_changed: HashSet<(ModuleId, Dependency)>,
-}
-
-// 对比两颗 Dependency 的差异
-fn diff(origin: Vec<(ModuleId, Dependency)>, target: Vec<(ModuleId, Dependency)>) -> Diff {
-    let origin_module_ids = origin
-        .iter()
-        .map(|(module_id, _dep)| module_id)
-        .collect::<HashSet<_>>();
-    let target_module_ids = target
-        .iter()
-        .map(|(module_id, _dep)| module_id)
-        .collect::<HashSet<_>>();
-
-    let mut added: HashSet<(ModuleId, Dependency)> = HashSet::new();
-    let mut removed: HashSet<(ModuleId, Dependency)> = HashSet::new();
-    let mut dep_changed: HashSet<(ModuleId, Dependency)> = HashSet::new();
-
-    target
-        .iter()
-        .filter(|(module_id, _dep)| !origin_module_ids.contains(module_id))
-        .for_each(|(module_id, dep)| {
-            added.insert((module_id.clone(), dep.clone()));
-        });
-
-    origin.iter().for_each(|(module_id, dep)| {
-        if target_module_ids.contains(module_id) {
-            let (_, t_dep) = target
-                .iter()
-                .find(|(t_module_id, _)| t_module_id == module_id)
-                .unwrap();
-            if dep.resolve_type != t_dep.resolve_type {
-                dep_changed.insert((module_id.clone(), t_dep.clone()));
-            }
-        } else {
-            removed.insert((module_id.clone(), dep.clone()));
-        }
-    });
-
-    Diff {
-        added,
-        removed,
-        dep_changed,
-    }
 }
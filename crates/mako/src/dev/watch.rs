@@ -10,6 +10,7 @@ use notify_debouncer_full::DebouncedEvent;
 use tracing::debug;
 
 use crate::compiler::Compiler;
+use crate::plugins;
 use crate::resolve::ResolverResource;
 
 pub struct Watcher<'a> {
@@ -40,6 +41,9 @@ impl<'a> Watcher<'a> {
         let t_watch = Instant::now();
 
         self.watch_dir_recursive(self.root.into(), &self.get_ignore_list(true))?;
+        // before the dirs of packages, so that a failure of them doesn't skip file dependencies
+        // which are added by the last rebuild
+        self.watch_file_dependencies();
 
         let module_graph = self.compiler.context.module_graph.read().unwrap();
         let mut dirs = HashSet::new();
@@ -92,10 +96,52 @@ impl<'a> Watcher<'a> {
         Ok(())
     }
 
+    // files registered by loaders and transforms are watched even if they are ignored, e.g.
+    // outside root or under node_modules, it runs on every refresh since modules register new
+    // files when they are rebuilt
+    fn watch_file_dependencies(&mut self) {
+        let files = self
+            .compiler
+            .context
+            .file_dependencies
+            .read()
+            .unwrap()
+            .files();
+        for file in Self::unwatched_files(files, &self.watched_files, &self.watched_dirs) {
+            // e.g. the file is removed, it's retried on the next refresh
+            if let Err(e) = self.watch_file_or_dir(file.clone(), &[]) {
+                debug!(
+                    "failed to watch file dependency {}: {:?}",
+                    file.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    fn unwatched_files(
+        files: HashSet<PathBuf>,
+        watched_files: &HashSet<PathBuf>,
+        watched_dirs: &HashSet<PathBuf>,
+    ) -> Vec<PathBuf> {
+        let mut files = files
+            .into_iter()
+            .filter(|file| {
+                !watched_files.contains(file)
+                    && !watched_dirs.iter().any(|dir| file.starts_with(dir))
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
     fn get_ignore_list(&self, with_output_dir: bool) -> Vec<PathBuf> {
-        let mut ignore_list = vec![".git", "node_modules", ".DS_Store", ".node"];
+        let mut ignore_list = [".git", "node_modules", ".DS_Store", ".node"]
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
         if with_output_dir {
-            ignore_list.push(self.compiler.context.config.output.path.to_str().unwrap());
+            ignore_list.push(self.compiler.context.config.output.path.clone());
         }
         ignore_list.extend(
             self.compiler
@@ -104,8 +150,18 @@ impl<'a> Watcher<'a> {
                 .watch
                 .ignore_paths
                 .iter()
-                .map(|p| p.as_str()),
+                .map(PathBuf::from),
+        );
+        ignore_list.extend(
+            self.compiler
+                .context
+                .config
+                .codegen
+                .iter()
+                .map(|codegen| plugins::codegen::target_dir(self.root, codegen)),
         );
+        let owned_writes = self.compiler.context.owned_writes.read().unwrap().clone();
+        ignore_list.extend(owned_writes);
 
         // node_modules of root dictionary and root dictionary's parent dictionaries should be ignored
         // for resolving the issue of "too many files open" in monorepo
@@ -157,10 +213,9 @@ impl<'a> Watcher<'a> {
     }
 
     fn should_ignore_watch(path: &Path, ignore_list: &[PathBuf]) -> bool {
-        let path = path.to_string_lossy();
         ignore_list
             .iter()
-            .any(|ignored| path.strip_prefix(ignored.to_str().unwrap()).is_some())
+            .any(|ignored| path.strip_prefix(ignored).is_ok())
     }
 
     fn should_ignore_event(path: &Path, kind: &EventKind) -> bool {
@@ -180,7 +235,7 @@ impl<'a> Watcher<'a> {
         ignore_list.iter().any(|ignored| path.ends_with(ignored))
     }
 
-    pub fn normalize_events(events: Vec<DebouncedEvent>) -> Vec<PathBuf> {
+    pub fn normalize_events(&self, events: Vec<DebouncedEvent>) -> Vec<PathBuf> {
         let mut paths = vec![];
         let mut create_paths = HashMap::new();
         events.iter().for_each(|debounced_event| {
@@ -189,6 +244,11 @@ impl<'a> Watcher<'a> {
                 if Self::should_ignore_event(path, kind) {
                     return;
                 }
+                // output dir and files written by plugins themselves should not trigger rebuild,
+                // otherwise it may end up with a rebuild loop
+                if self.compiler.context.is_owned_write(path) {
+                    return;
+                }
                 paths.push(path.clone());
                 if matches!(debounced_event.event.kind, EventKind::Create(_)) {
                     create_paths.insert(path.clone(), true);
@@ -199,6 +259,72 @@ impl<'a> Watcher<'a> {
         });
         paths.sort();
         paths.dedup();
-        paths
+        plugins::codegen::handle_changes(&self.compiler.context, paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+
+    use super::Watcher;
+
+    #[test]
+    fn test_should_ignore_watch() {
+        let ignore_list = vec![
+            PathBuf::from("/project/node_modules"),
+            PathBuf::from("/project/dist"),
+        ];
+        assert!(Watcher::should_ignore_watch(
+            Path::new("/project/node_modules/react/index.js"),
+            &ignore_list
+        ));
+        assert!(Watcher::should_ignore_watch(
+            Path::new("/project/dist"),
+            &ignore_list
+        ));
+        // paths are compared by components
+        assert!(!Watcher::should_ignore_watch(
+            Path::new("/project/dist2/index.js"),
+            &ignore_list
+        ));
+    }
+
+    #[test]
+    fn test_unwatched_files() {
+        let watched_files = HashSet::from([PathBuf::from("/other/a.graphql")]);
+        let watched_dirs = HashSet::from([PathBuf::from("/project/src")]);
+        let files = HashSet::from([
+            PathBuf::from("/project/src/schema.graphql"),
+            PathBuf::from("/other/a.graphql"),
+            PathBuf::from("/other/b.graphql"),
+            PathBuf::from("/project/node_modules/foo/schema.graphql"),
+        ]);
+        assert_eq!(
+            Watcher::unwatched_files(files, &watched_files, &watched_dirs),
+            vec![
+                PathBuf::from("/other/b.graphql"),
+                PathBuf::from("/project/node_modules/foo/schema.graphql"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_should_ignore_watch_non_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dist = Path::new(OsStr::from_bytes(b"/project/\xffdist"));
+        let ignore_list = vec![dist.to_path_buf()];
+        assert!(Watcher::should_ignore_watch(
+            &dist.join("index.js"),
+            &ignore_list
+        ));
+        assert!(!Watcher::should_ignore_watch(
+            Path::new("/project/src/index.js"),
+            &ignore_list
+        ));
     }
 }
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use indexmap::IndexMap;
+use pathdiff::diff_paths;
+use serde_json::{json, Value};
+
+use crate::compiler::{Args, Compiler};
+use crate::config::{Config, LibraryDtsConfig, LibraryFormat};
+use crate::plugin::Plugin;
+use crate::plugins::bundless_compiler::{normalize_extension, to_dist_path};
+
+// entry name -> (condition -> file), e.g. { "index": { "import": "./dist/esm/index.js" } }
+type LibraryExports = BTreeMap<String, BTreeMap<String, String>>;
+
+pub struct Library {}
+
+impl Library {
+    /**
+     * build every configured format into `{output.path}/{format}` in bundless mode,
+     * then roll up the declaration files and print the suggested package.json `exports`
+     *
+     * it's shared by the cli and the node binding, configs of formats are loaded with the
+     * same `default_config` as `config`, and `plugins` are used by compilers of all formats
+     */
+    pub fn compile(
+        root: &Path,
+        config: &Config,
+        default_config: Option<&str>,
+        plugins: Option<Vec<Arc<dyn Plugin>>>,
+    ) -> Result<()> {
+        let library = config
+            .library
+            .as_ref()
+            .ok_or_else(|| anyhow!("library is not configured"))?;
+        let mut exports: LibraryExports = BTreeMap::new();
+
+        for format in &library.formats {
+            let format_config = json!({
+                "mode": config.mode,
+                "output": {
+                    "mode": "bundless",
+                    "path": config.output.path.join(format.to_string()),
+                },
+                "cjs": *format == LibraryFormat::Cjs,
+                "library": false,
+            });
+            let mut format_config =
+                Config::new(root, default_config, Some(&format_config.to_string()))
+                    .map_err(|e| anyhow!("Load {} config failed: {}", format, e))?;
+            format_config.mode = config.mode.clone();
+
+            let compiler = Compiler::new(
+                format_config,
+                root.to_path_buf(),
+                Args {
+                    watch: false,
+                    verbose: false,
+                },
+                plugins.clone(),
+            )?;
+            compiler.compile()?;
+
+            let condition = match format {
+                LibraryFormat::Esm => "import",
+                LibraryFormat::Cjs => "require",
+            };
+            for (name, entry) in &compiler.context.config.entry {
+                let dist =
+                    normalize_extension(to_dist_path(entry.to_string_lossy(), &compiler.context));
+                exports
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(condition.to_string(), to_package_path(root, &dist));
+            }
+        }
+
+        if let Some(dts) = &library.dts {
+            Self::roll_up_dts(root, dts)?;
+            let types = to_package_path(root, &root.join(&dts.output));
+            exports.values_mut().for_each(|conditions| {
+                conditions.insert("types".to_string(), types.clone());
+            });
+        }
+
+        println!(
+            "{}\n{}",
+            "Suggested package.json exports:".green(),
+            serde_json::to_string_pretty(&exports_suggestion(&exports))?
+        );
+
+        // after all formats are built and d.ts is rolled up, for the cli and the node binding
+        if library.validate_package_json {
+            Self::validate_package_json(root)?;
+        }
+        Ok(())
+    }
+
+    // files referenced by main, module, types and exports of package.json should exist after
+    // build, or the published package is broken
+    fn validate_package_json(root: &Path) -> Result<()> {
+        let package_json_path = root.join("package.json");
+        if !package_json_path.exists() {
+            return Ok(());
+        }
+        let package_json: Value = serde_json::from_str(&fs::read_to_string(&package_json_path)?)
+            .map_err(|e| anyhow!("Parse package.json failed: {}", e))?;
+
+        let mut targets = vec![];
+        for field in ["main", "module", "types", "typings"] {
+            if let Some(Value::String(target)) = package_json.get(field) {
+                targets.push((field.to_string(), target.clone()));
+            }
+        }
+        if let Some(exports) = package_json.get("exports") {
+            collect_export_targets("exports".to_string(), exports, &mut targets);
+        }
+
+        let errors = targets
+            .iter()
+            .filter_map(|(field, target)| {
+                validate_target(root, field, target).map(|error| format!("  {}", error))
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(anyhow!(
+                "Validate package.json failed, referenced files are not emitted:\n{}",
+                errors.join("\n")
+            ));
+        }
+        Ok(())
+    }
+
+    fn roll_up_dts(root: &Path, dts: &LibraryDtsConfig) -> Result<()> {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let status = Command::new(shell)
+            .arg(flag)
+            .arg(&dts.command)
+            .current_dir(root)
+            .status()
+            .map_err(|e| anyhow!("Run dts command `{}` failed: {}", dts.command, e))?;
+        if !status.success() {
+            return Err(anyhow!(
+                "Run dts command `{}` failed with {}",
+                dts.command,
+                status
+            ));
+        }
+        Ok(())
+    }
+}
+
+// (field path, target) of string targets in exports, e.g. (`exports["."].import`, "./index.js"),
+// null targets which block subpaths are skipped
+fn collect_export_targets(field: String, exports: &Value, targets: &mut Vec<(String, String)>) {
+    match exports {
+        Value::String(target) => targets.push((field, target.clone())),
+        Value::Array(fallbacks) => fallbacks.iter().enumerate().for_each(|(i, value)| {
+            collect_export_targets(format!("{}[{}]", field, i), value, targets)
+        }),
+        Value::Object(map) => map.iter().for_each(|(key, value)| {
+            let field = if key.starts_with('.') {
+                format!("{}[\"{}\"]", field, key)
+            } else {
+                format!("{}.{}", field, key)
+            };
+            collect_export_targets(field, value, targets)
+        }),
+        _ => {}
+    }
+}
+
+fn validate_target(root: &Path, field: &str, target: &str) -> Option<String> {
+    if field.starts_with("exports") && !target.starts_with("./") {
+        return Some(format!(
+            "{}: \"{}\" should start with \"./\"",
+            field, target
+        ));
+    }
+    let existed = match target.split_once('*') {
+        // subpath patterns, at least one file should be matched
+        Some((prefix, suffix)) => {
+            let prefix = root.join(prefix).to_string_lossy().to_string();
+            let pattern = format!("{}**/*", glob::Pattern::escape(&prefix));
+            glob::glob(&pattern).ok().is_some_and(|mut paths| {
+                paths.flatten().any(|path| {
+                    path.is_file() && {
+                        let path = path.to_string_lossy();
+                        path.starts_with(&prefix) && path.ends_with(suffix)
+                    }
+                })
+            })
+        }
+        None => root.join(target).is_file(),
+    };
+    if existed {
+        None
+    } else {
+        Some(format!("{}: \"{}\" doesn't exist", field, target))
+    }
+}
+
+pub fn default_validate_package_json() -> bool {
+    true
+}
+
+fn to_package_path(root: &Path, path: &Path) -> String {
+    let relative_path = diff_paths(path, root).unwrap_or_else(|| path.to_path_buf());
+    format!("./{}", relative_path.to_string_lossy())
+}
+
+// index maps are serialized in insertion order, unlike the maps of serde_json
+fn exports_suggestion(
+    exports: &LibraryExports,
+) -> BTreeMap<&'static str, IndexMap<String, IndexMap<&'static str, String>>> {
+    let mut map = IndexMap::new();
+    for (name, conditions) in exports {
+        let key = if name == "index" {
+            ".".to_string()
+        } else {
+            format!("./{}", name)
+        };
+        // `types` should always come first
+        let mut value = IndexMap::new();
+        for condition in ["types", "import", "require"] {
+            if let Some(file) = conditions.get(condition) {
+                value.insert(condition, file.clone());
+            }
+        }
+        map.insert(key, value);
+    }
+    BTreeMap::from([("exports", map)])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use serde_json::Value;
+
+    use super::{collect_export_targets, exports_suggestion, validate_target};
+
+    #[test]
+    fn test_exports_suggestion() {
+        let mut exports = BTreeMap::new();
+        exports.insert(
+            "index".to_string(),
+            BTreeMap::from([
+                ("require".to_string(), "./dist/cjs/index.js".to_string()),
+                ("import".to_string(), "./dist/esm/index.js".to_string()),
+                ("types".to_string(), "./dist/index.d.ts".to_string()),
+            ]),
+        );
+        exports.insert(
+            "utils".to_string(),
+            BTreeMap::from([("import".to_string(), "./dist/esm/utils.js".to_string())]),
+        );
+        assert_eq!(
+            serde_json::to_string(&exports_suggestion(&exports)).unwrap(),
+            r#"{"exports":{".":{"types":"./dist/index.d.ts","import":"./dist/esm/index.js","require":"./dist/cjs/index.js"},"./utils":{"import":"./dist/esm/utils.js"}}}"#
+        );
+    }
+
+    #[test]
+    fn test_validate_export_targets() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("test/library/package-json");
+        let package_json: Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join("package.json")).unwrap())
+                .unwrap();
+        let mut targets = vec![];
+        collect_export_targets(
+            "exports".to_string(),
+            package_json.get("exports").unwrap(),
+            &mut targets,
+        );
+        let mut errors = targets
+            .iter()
+            .filter_map(|(field, target)| validate_target(&root, field, target))
+            .collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                r#"exports["./package.json"]: "package.json" should start with "./""#,
+                r#"exports["./utils"][0]: "./dist/esm/utils.js" doesn't exist"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "hmac-sha256";
+const HMAC_BLOCK_SIZE: usize = 64;
+
+/**
+ * sha256 of the emitted files with a hmac signature, so that deployment pipelines can verify
+ * the artifacts between build and cdn upload, e.g.
+ *
+ * {
+ *   "algorithm": "hmac-sha256",
+ *   "files": { "index.3f2a.js": "9f86d081..." },
+ *   "signature": "5b0d7f9e..."
+ * }
+ *
+ * the signature covers the json of files, files are relative to the output directory
+ */
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SignedManifest {
+    pub algorithm: String,
+    pub files: BTreeMap<String, String>,
+    pub signature: String,
+}
+
+impl SignedManifest {
+    pub fn sign<I: IntoIterator<Item = String>>(dist: &Path, files: I, key: &[u8]) -> Result<Self> {
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let content = fs::read(dist.join(&file))
+                    .map_err(|e| anyhow!("Read {} for the signed manifest failed: {}", file, e))?;
+                Ok((file, sha256_hex(&content)))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let signature = signature(&files, key)?;
+        Ok(Self {
+            algorithm: ALGORITHM.to_string(),
+            files,
+            signature,
+        })
+    }
+
+    // all the problems are reported in one error, e.g. tampered manifests and changed files
+    pub fn verify(&self, dist: &Path, key: &[u8]) -> Result<()> {
+        if self.algorithm != ALGORITHM {
+            return Err(anyhow!(
+                "Unsupported algorithm {} of the signed manifest",
+                self.algorithm
+            ));
+        }
+        if !constant_time_eq(
+            signature(&self.files, key)?.as_bytes(),
+            self.signature.as_bytes(),
+        ) {
+            return Err(anyhow!(
+                "Signature of the manifest mismatched, the manifest is tampered or the key is wrong"
+            ));
+        }
+        let problems = self
+            .files
+            .iter()
+            .filter_map(|(file, hash)| match fs::read(dist.join(file)) {
+                Ok(content) if &sha256_hex(&content) == hash => None,
+                Ok(_) => Some(format!("  {} is modified", file)),
+                Err(_) => Some(format!("  {} is missing", file)),
+            })
+            .collect::<Vec<_>>();
+        if !problems.is_empty() {
+            return Err(anyhow!(
+                "Files mismatched with the signed manifest:\n{}",
+                problems.join("\n")
+            ));
+        }
+        Ok(())
+    }
+}
+
+// verify the files of dist with the signed manifest file, e.g. `mako verify-manifest`
+pub fn verify_manifest(dist: &Path, manifest: &Path, key: &[u8]) -> Result<()> {
+    let content = fs::read(manifest)
+        .map_err(|e| anyhow!("Read signed manifest {} failed: {}", manifest.display(), e))?;
+    let manifest = serde_json::from_slice::<SignedManifest>(&content)
+        .map_err(|e| anyhow!("Parse signed manifest {} failed: {}", manifest.display(), e))?;
+    manifest.verify(dist, key)
+}
+
+fn signature(files: &BTreeMap<String, String>, key: &[u8]) -> Result<String> {
+    Ok(hex(&hmac_sha256(
+        key,
+        serde_json::to_string(files)?.as_bytes(),
+    )))
+}
+
+// rfc 2104
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut block = [0u8; HMAC_BLOCK_SIZE];
+    if key.len() > HMAC_BLOCK_SIZE {
+        let digest = Sha256::digest(key);
+        block[..digest.len()].copy_from_slice(&digest);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().to_vec()
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    hex(&Sha256::digest(content))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{hex, hmac_sha256, SignedManifest};
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // test case 2 of rfc 4231
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let dir = create_temp_dir("manifest_signature");
+        let dist = dir.path();
+        fs::create_dir_all(dist.join("static")).unwrap();
+        fs::write(dist.join("index.js"), "console.log(1);").unwrap();
+        fs::write(dist.join("static/a.png"), "png").unwrap();
+
+        let files = vec!["index.js".to_string(), "static/a.png".to_string()];
+        let manifest = SignedManifest::sign(dist, files, b"key").unwrap();
+        assert!(manifest.verify(dist, b"key").is_ok());
+        assert!(manifest
+            .verify(dist, b"other")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Signature of the manifest mismatched"));
+
+        fs::write(dist.join("index.js"), "console.log(2);").unwrap();
+        fs::remove_file(dist.join("static/a.png")).unwrap();
+        assert_eq!(
+            manifest.verify(dist, b"key").unwrap_err().to_string(),
+            "Files mismatched with the signed manifest:\n  index.js is modified\n  static/a.png is missing"
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod library;
+pub mod manifest_signature;
 pub mod node;
 pub mod rsc;
+pub mod symbolicate;
+pub mod why;
@@ -33,6 +33,15 @@ impl Node {
             config
                 .define
                 .insert("__filename".into(), Value::String("'/index.js'".into()));
+            // node globals which are constants in browsers, explicit define takes precedence
+            config
+                .define
+                .entry("process.browser".into())
+                .or_insert(Value::Bool(true));
+            config
+                .define
+                .entry("process.platform".into())
+                .or_insert(Value::String("\"browser\"".into()));
             // polyfill with equivalent modules
             for name in Self::get_polyfill_modules().iter() {
                 config.resolve.alias.push((
@@ -58,13 +67,39 @@ impl Node {
             config
                 .providers
                 .insert("Buffer".into(), ("buffer".into(), "Buffer".into()));
-            config.providers.insert(
-                "global".into(),
-                ("node-libs-browser-okam/polyfill/global".into(), "".into()),
-            );
+            // `global` is globalThis if all targets support it, otherwise it's polyfilled
+            if Self::targets_support_global_this(&config.targets) {
+                config
+                    .define
+                    .entry("global".into())
+                    .or_insert(Value::String("globalThis".into()));
+            } else if !config.define.contains_key("global") {
+                config.providers.insert(
+                    "global".into(),
+                    ("node-libs-browser-okam/polyfill/global".into(), "".into()),
+                );
+            }
         }
     }
 
+    // minimum versions which support globalThis, ie and unknown targets don't
+    fn targets_support_global_this(targets: &HashMap<String, f32>) -> bool {
+        targets.iter().all(|(name, version)| {
+            let min_version = match name.as_str() {
+                "chrome" | "and_chr" => 71.0,
+                "edge" => 79.0,
+                "firefox" | "and_ff" => 65.0,
+                "safari" => 12.1,
+                "ios" | "ios_saf" => 12.2,
+                "opera" => 58.0,
+                "samsung" => 10.0,
+                "node" => 12.0,
+                _ => return false,
+            };
+            *version >= min_version
+        })
+    }
+
     fn get_polyfill_modules() -> Vec<String> {
         vec![
             "assert",
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use regex::{Captures, Regex};
+use swc_core::base::sourcemap::SourceMap;
+
+use crate::plugins::error_mapping::{boundaries_file_name, ModuleBoundaries};
+
+/**
+ * resolve frames in stack traces of production chunks with the sourcemaps and the module
+ * boundaries (see errorMapping) in the output directory, e.g.
+ *
+ * at a (https://a.com/index.3f2a.js:1:1024)
+ * ->
+ * at a (src/utils.ts:12:5 ./src/utils.ts)
+ *
+ * frames which can't be resolved are kept as is
+ */
+pub struct Symbolicator {
+    dist: PathBuf,
+    // chunk path relative to dist -> (sourcemap, module boundaries)
+    chunks: HashMap<PathBuf, (Option<SourceMap>, Option<ModuleBoundaries>)>,
+}
+
+impl Symbolicator {
+    pub fn new(dist: PathBuf) -> Self {
+        Self {
+            dist,
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn symbolicate(&mut self, stack: &str) -> String {
+        let re = Regex::new(r"([^\s()@]+\.[cm]?js)(?:[?#][^\s():]*)?:(\d+):(\d+)").unwrap();
+        re.replace_all(stack, |captures: &Captures| {
+            let line = captures[2].parse().unwrap_or(0);
+            let column = captures[3].parse().unwrap_or(0);
+            self.resolve(&captures[1], line, column)
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+    }
+
+    // line and column are 1-based
+    fn resolve(&mut self, url: &str, line: usize, column: usize) -> Option<String> {
+        if line == 0 || column == 0 {
+            return None;
+        }
+        let chunk = self.find_chunk(url)?;
+        let (source_map, boundaries) = self.chunks.entry(chunk.clone()).or_insert_with(|| {
+            let source_map = fs::read(self.dist.join(format!("{}.map", chunk.display())))
+                .ok()
+                .and_then(|content| SourceMap::from_slice(&content).ok());
+            let boundaries = fs::read_to_string(
+                self.dist
+                    .join(boundaries_file_name(&chunk.to_string_lossy())),
+            )
+            .ok()
+            .and_then(|content| serde_json::from_str::<ModuleBoundaries>(&content).ok());
+            (source_map, boundaries)
+        });
+
+        let position = source_map.as_ref().and_then(|source_map| {
+            let token = source_map.lookup_token(line as u32 - 1, column as u32 - 1)?;
+            Some(format!(
+                "{}:{}:{}",
+                token.get_source()?,
+                token.get_src_line() + 1,
+                token.get_src_col() + 1
+            ))
+        });
+        let module_id = boundaries
+            .as_ref()
+            .and_then(|boundaries| boundaries.find(line, column));
+        match (position, module_id) {
+            (Some(position), Some(module_id)) => Some(format!("{} {}", position, module_id)),
+            (Some(position), None) => Some(position),
+            (None, Some(module_id)) => Some(format!("{}:{}:{} {}", url, line, column, module_id)),
+            (None, None) => None,
+        }
+    }
+
+    // the longest suffix of the url path which exists in dist,
+    // since the public path of the url is unknown
+    fn find_chunk(&self, url: &str) -> Option<PathBuf> {
+        let path = url.split("://").last().unwrap();
+        let segments = path.split('/').collect::<Vec<_>>();
+        (0..segments.len())
+            .map(|i| PathBuf::from(segments[i..].join("/")))
+            .find(|chunk| !chunk.as_os_str().is_empty() && self.dist.join(chunk).is_file())
+    }
+}
+
+pub fn symbolicate(dist: &Path, stack: &str) -> Result<String> {
+    Ok(Symbolicator::new(dist.to_path_buf()).symbolicate(stack))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::Symbolicator;
+    use crate::plugins::error_mapping::ModuleBoundaries;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_symbolicate_with_boundaries() {
+        let dir = create_temp_dir("symbolicate");
+        let dist = dir.path();
+        fs::create_dir_all(dist.join("static")).unwrap();
+        fs::write(dist.join("static/index.js"), "").unwrap();
+        fs::write(
+            dist.join("static/index.js.modules.json"),
+            serde_json::to_string(&ModuleBoundaries {
+                modules: vec!["./src/a.ts".to_string(), "./src/b.ts".to_string()],
+                boundaries: vec![(1, 10, 0), (1, 100, 1)],
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let mut symbolicator = Symbolicator::new(dist.to_path_buf());
+        assert_eq!(
+            symbolicator.symbolicate(
+                "Error: x\n    at a (https://a.com/static/index.js?v=1:1:50)\n    at b (https://a.com/vendor.js:1:1)"
+            ),
+            "Error: x\n    at a (https://a.com/static/index.js:1:50 ./src/a.ts)\n    at b (https://a.com/vendor.js:1:1)"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+
+/**
+ * explain why a module ends up in the bundle with the shortest import path from the entries,
+ * the module is a file relative to root or a package name, e.g. `mako why lodash`
+ *
+ * src/index.tsx
+ *   -> src/pages/home.tsx (./pages/home)
+ *   -> node_modules/lodash/lodash.js (lodash)
+ */
+pub fn why(module_graph: &ModuleGraph, root: &Path, module: &str) -> Result<String> {
+    let targets = match root.join(module).canonicalize() {
+        Ok(path) if path.is_file() => vec![ModuleId::from(path.to_string_lossy().to_string())],
+        _ => module_graph.query().package(module).ids(),
+    };
+    if targets.is_empty() {
+        return Err(anyhow!("{} is not in the bundle", module));
+    }
+    explain(module_graph, root, &targets)
+        .ok_or_else(|| anyhow!("{} is not reachable from the entries", module))
+}
+
+fn explain(module_graph: &ModuleGraph, root: &Path, targets: &[ModuleId]) -> Option<String> {
+    let mut entries = module_graph.get_entry_modules();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    let path = entries
+        .iter()
+        .flat_map(|entry| targets.iter().map(|target| module_graph.why(entry, target)))
+        .filter(|path| !path.is_empty())
+        .min_by_key(|path| path.len())?;
+
+    let mut lines = vec![relative_id(&path[0], root)];
+    for pair in path.windows(2) {
+        let source = module_graph
+            .get_dependencies(&pair[0])
+            .into_iter()
+            .find(|(id, _)| *id == &pair[1])
+            .map(|(_, dep)| dep.source.clone())
+            .unwrap_or_default();
+        lines.push(format!("  -> {} ({})", relative_id(&pair[1], root), source));
+    }
+    Some(lines.join("\n"))
+}
+
+fn relative_id(module_id: &ModuleId, root: &Path) -> String {
+    Path::new(&module_id.id)
+        .strip_prefix(root)
+        .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::module::{Dependency, Module, ModuleId, ResolveType};
+    use crate::module_graph::ModuleGraph;
+
+    #[test]
+    fn test_why() {
+        let mut module_graph = ModuleGraph::new();
+        let index = ModuleId::from("/root/src/index.ts");
+        let a = ModuleId::from("/root/src/a.ts");
+        let b = ModuleId::from("/root/src/b.ts");
+        module_graph.add_module(Module::new(index.clone(), true, None));
+        module_graph.add_module(Module::new(a.clone(), false, None));
+        module_graph.add_module(Module::new(b.clone(), false, None));
+        for (from, to, source) in [(&index, &a, "./a"), (&a, &b, "./b")] {
+            module_graph.add_dependency(
+                from,
+                to,
+                Dependency {
+                    source: source.to_string(),
+                    resolve_as: None,
+                    specifiers: vec![],
+                    resolve_type: ResolveType::Require,
+                    order: 0,
+                    span: None,
+                },
+            );
+        }
+        assert_eq!(
+            super::explain(&module_graph, Path::new("/root"), &[b]).unwrap(),
+            "src/index.ts\n  -> src/a.ts (./a)\n  -> src/b.ts (./b)"
+        );
+        assert!(super::explain(&module_graph, Path::new("/root"), &[]).is_none());
+        assert_eq!(
+            super::why(&module_graph, Path::new("/root"), "foo")
+                .unwrap_err()
+                .to_string(),
+            "foo is not in the bundle"
+        );
+    }
+}
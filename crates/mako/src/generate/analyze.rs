@@ -1,9 +1,22 @@
+use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
+use pathdiff::diff_paths;
+use serde::Serialize;
+use swc_core::base::sourcemap;
+use swc_core::common::{BytePos, Span, Spanned};
+use swc_core::ecma::ast::{
+    Decl, ExportNamedSpecifier, ExportSpecifier, Expr, Ident, Module as SwcModule, ModuleDecl,
+    ModuleExportName, ModuleItem, Stmt,
+};
+use swc_core::ecma::utils::find_pat_ids;
 
 use crate::compiler::Context;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::module::ModuleAst;
 use crate::stats::StatsJsonMap;
 
 pub struct Analyze {}
@@ -36,3 +49,305 @@ impl Analyze {
         Ok(())
     }
 }
+
+// (line, column) in the original source, both 0-based
+type SourcePos = (u32, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRange {
+    pub name: String,
+    pub start: SourcePos,
+    pub end: SourcePos,
+}
+
+// module id -> exported symbols and where they are declared
+pub type ExportRanges = HashMap<String, Vec<ExportRange>>;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSizeItem {
+    pub module: String,
+    pub symbol: String,
+    pub size: u64,
+}
+
+// bytes mapped into a module but outside of its exports, e.g. imports and private helpers
+pub const MODULE_LEVEL_SYMBOL: &str = "(module)";
+// bytes without mapping, e.g. runtime and module wrappers
+pub const UNMAPPED_SYMBOL: &str = "(unmapped)";
+
+// must be called before modules are transformed to cjs, since export decls are gone after that
+pub fn collect_export_ranges(context: &Arc<Context>) -> ExportRanges {
+    let module_graph = context.module_graph.read().unwrap();
+    let cm = &context.meta.script.cm;
+    let to_pos = |pos: BytePos| -> SourcePos {
+        let loc = cm.lookup_char_pos(pos);
+        ((loc.line - 1) as u32, loc.col.0 as u32)
+    };
+    module_graph
+        .modules()
+        .iter()
+        .filter_map(|module| match module.info.as_ref().map(|info| &info.ast) {
+            Some(ModuleAst::Script(ast)) => {
+                let ranges = module_export_spans(&ast.ast)
+                    .into_iter()
+                    .filter(|(_, span)| !span.is_dummy())
+                    .map(|(name, span)| ExportRange {
+                        name,
+                        start: to_pos(span.lo),
+                        end: to_pos(span.hi),
+                    })
+                    .collect::<Vec<_>>();
+                Some((module.id.id.clone(), ranges))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn module_export_spans(module: &SwcModule) -> Vec<(String, Span)> {
+    let mut local_decls: HashMap<String, Span> = HashMap::new();
+    module.body.iter().for_each(|item| {
+        if let ModuleItem::Stmt(Stmt::Decl(decl)) = item {
+            decl_names(decl).into_iter().for_each(|name| {
+                local_decls.insert(name, decl_span(decl));
+            });
+        }
+    });
+
+    let mut spans = vec![];
+    module.body.iter().for_each(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+            decl_names(&export_decl.decl)
+                .into_iter()
+                .for_each(|name| spans.push((name, export_decl.span)));
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export_default_decl)) => {
+            spans.push(("default".to_string(), export_default_decl.span));
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export_default_expr)) => {
+            // export default foo; the declaration of foo is attributed to default
+            if let Expr::Ident(ident) = &*export_default_expr.expr
+                && let Some(span) = local_decls.get(&ident.sym.to_string())
+            {
+                spans.push(("default".to_string(), *span));
+            }
+            spans.push(("default".to_string(), export_default_expr.span));
+        }
+        // export { a, b as c }, re-exports are attributed to the source module
+        ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named_export))
+            if named_export.src.is_none() =>
+        {
+            named_export.specifiers.iter().for_each(|specifier| {
+                if let ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) =
+                    specifier
+                    && let ModuleExportName::Ident(orig) = orig
+                    && let Some(span) = local_decls.get(&orig.sym.to_string())
+                {
+                    let name = match exported {
+                        Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                        Some(ModuleExportName::Str(str)) => str.value.to_string(),
+                        None => orig.sym.to_string(),
+                    };
+                    spans.push((name, *span));
+                }
+            });
+        }
+        _ => {}
+    });
+    spans
+}
+
+fn decl_names(decl: &Decl) -> Vec<String> {
+    match decl {
+        Decl::Class(class_decl) => vec![class_decl.ident.sym.to_string()],
+        Decl::Fn(fn_decl) => vec![fn_decl.ident.sym.to_string()],
+        Decl::Var(var_decl) => var_decl
+            .decls
+            .iter()
+            .flat_map(|declarator| find_pat_ids::<_, Ident>(&declarator.name))
+            .map(|ident| ident.sym.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn decl_span(decl: &Decl) -> Span {
+    match decl {
+        Decl::Class(class_decl) => class_decl.class.span,
+        Decl::Fn(fn_decl) => fn_decl.function.span,
+        Decl::Var(var_decl) => var_decl.span,
+        _ => decl.span(),
+    }
+}
+
+/**
+ * attribute bytes of the emitted js files to the exported symbols of original modules
+ * with their source maps, so that it still works after minification and concatenation,
+ * each mapping segment is counted as the bytes until the next segment on the same line
+ */
+pub fn attribute_symbol_sizes(
+    chunk_files: &[ChunkFile],
+    export_ranges: &ExportRanges,
+    root: &Path,
+) -> Result<Vec<SymbolSizeItem>> {
+    let mut sizes: HashMap<(String, String), u64> = HashMap::new();
+    for chunk_file in chunk_files {
+        if !matches!(chunk_file.file_type, ChunkFileType::JS) {
+            continue;
+        }
+        let Some(source_map) = &chunk_file.source_map else {
+            continue;
+        };
+        let source_map = sourcemap::SourceMap::from_slice(source_map)?;
+        let content = String::from_utf8_lossy(&chunk_file.content);
+        let lines = content.split('\n').collect::<Vec<_>>();
+
+        // dst line -> (dst col, module id, src pos)
+        let mut segments: HashMap<u32, Vec<(u32, Option<(String, SourcePos)>)>> = HashMap::new();
+        for token in source_map.tokens() {
+            let source = token.get_source().map(|source| {
+                let path = root.join(source);
+                (
+                    path.to_string_lossy().to_string(),
+                    (token.get_src_line(), token.get_src_col()),
+                )
+            });
+            segments
+                .entry(token.get_dst_line())
+                .or_default()
+                .push((token.get_dst_col(), source));
+        }
+
+        let mut mapped = 0;
+        for (line, mut line_segments) in segments {
+            let Some(text) = lines.get(line as usize) else {
+                continue;
+            };
+            // byte offset of each char, columns are counted by chars
+            let offsets = text
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(text.len()))
+                .collect::<Vec<_>>();
+            let byte_offset = |col: u32| offsets[(col as usize).min(offsets.len() - 1)] as u64;
+            line_segments.sort_by_key(|(col, _)| *col);
+            for (i, (col, source)) in line_segments.iter().enumerate() {
+                let end = line_segments
+                    .get(i + 1)
+                    .map(|(col, _)| byte_offset(*col))
+                    .unwrap_or(text.len() as u64);
+                let size = end.saturating_sub(byte_offset(*col));
+                let Some((module, pos)) = source else {
+                    continue;
+                };
+                let symbol = export_ranges
+                    .get(module)
+                    .and_then(|ranges| {
+                        ranges
+                            .iter()
+                            .find(|range| range.start <= *pos && *pos < range.end)
+                    })
+                    .map(|range| range.name.clone())
+                    .unwrap_or_else(|| MODULE_LEVEL_SYMBOL.to_string());
+                *sizes.entry((module.clone(), symbol)).or_default() += size;
+                mapped += size;
+            }
+        }
+        let unmapped = (chunk_file.content.len() as u64).saturating_sub(mapped);
+        *sizes
+            .entry((String::new(), UNMAPPED_SYMBOL.to_string()))
+            .or_default() += unmapped;
+    }
+
+    let mut items = sizes
+        .into_iter()
+        .filter(|(_, size)| *size > 0)
+        .map(|((module, symbol), size)| SymbolSizeItem {
+            module: diff_paths(&module, root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or(module),
+            symbol,
+            size,
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then_with(|| a.module.cmp(&b.module))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use swc_core::base::sourcemap::SourceMapBuilder;
+
+    use super::{attribute_symbol_sizes, ExportRange, SymbolSizeItem, UNMAPPED_SYMBOL};
+    use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+
+    #[test]
+    fn test_attribute_symbol_sizes() {
+        let content = "!function(){function a(){}function b(){}}();";
+        let mut builder = SourceMapBuilder::new(None);
+        builder.add(0, 12, 0, 0, Some("src/index.js"), None);
+        builder.add(0, 26, 2, 0, Some("src/index.js"), None);
+        builder.add(0, 40, 4, 0, Some("src/index.js"), None);
+        let mut source_map = vec![];
+        builder.into_sourcemap().to_writer(&mut source_map).unwrap();
+        let chunk_file = ChunkFile {
+            raw_hash: 0,
+            content: content.as_bytes().to_vec(),
+            source_map: Some(source_map),
+            hash: None,
+            file_name: "index.js".to_string(),
+            chunk_id: "index".to_string(),
+            file_type: ChunkFileType::JS,
+        };
+        let export_ranges = HashMap::from([(
+            "/root/src/index.js".to_string(),
+            vec![
+                ExportRange {
+                    name: "a".to_string(),
+                    start: (0, 0),
+                    end: (1, 1),
+                },
+                ExportRange {
+                    name: "b".to_string(),
+                    start: (2, 0),
+                    end: (3, 1),
+                },
+            ],
+        )]);
+        let items =
+            attribute_symbol_sizes(&[chunk_file], &export_ranges, Path::new("/root")).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                SymbolSizeItem {
+                    module: "src/index.js".to_string(),
+                    symbol: "a".to_string(),
+                    size: 14,
+                },
+                SymbolSizeItem {
+                    module: "src/index.js".to_string(),
+                    symbol: "b".to_string(),
+                    size: 14,
+                },
+                SymbolSizeItem {
+                    module: "".to_string(),
+                    symbol: UNMAPPED_SYMBOL.to_string(),
+                    size: 12,
+                },
+                SymbolSizeItem {
+                    module: "src/index.js".to_string(),
+                    symbol: "(module)".to_string(),
+                    size: 4,
+                },
+            ]
+        );
+    }
+}
@@ -34,6 +34,8 @@ pub struct Chunk {
     pub modules: IndexSet<ModuleId>,
     pub content: Option<String>,
     pub source_map: Option<String>,
+    // name of async chunks from magic comments or plugins, instead of the one from path
+    pub name: Option<String>,
 }
 
 impl Debug for Chunk {
@@ -57,10 +59,15 @@ impl Chunk {
             chunk_type,
             content: None,
             source_map: None,
+            name: None,
         }
     }
 
     pub fn filename(&self) -> String {
+        // import(/* makoChunkName: "foo" */ './foo') -> foo-async.js
+        if let (ChunkType::Async, Some(name)) = (&self.chunk_type, &self.name) {
+            return format!("{}-async.js", name);
+        }
         match &self.chunk_type {
             ChunkType::Runtime => "runtime.js".into(),
             // foo/bar.tsx -> bar.js
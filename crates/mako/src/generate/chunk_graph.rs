@@ -2,15 +2,19 @@ use core::fmt;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
 
+use indexmap::IndexSet;
 use petgraph::stable_graph::{DefaultIx, NodeIndex, StableDiGraph};
 use petgraph::visit::{Dfs, EdgeRef};
 use petgraph::Direction;
 use twox_hash::XxHash64;
 
 use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
+use crate::generate::optimize_chunk::POLYFILL_CHUNK_NAME;
 use crate::module::ModuleId;
 use crate::module_graph::ModuleGraph;
 
+pub const RUNTIME_CHUNK_ID: &str = "runtime";
+
 pub struct ChunkGraph {
     pub(crate) graph: StableDiGraph<Chunk, ()>,
     id_index_map: HashMap<ChunkId, NodeIndex<DefaultIx>>,
@@ -40,10 +44,11 @@ impl ChunkGraph {
         self.id_index_map.contains_key(chunk_id)
     }
 
+    // the runtime chunk has no modules but is still emitted
     pub fn get_chunks(&self) -> Vec<&Chunk> {
         self.get_all_chunks()
             .into_iter()
-            .filter(|c| !c.modules.is_empty())
+            .filter(|c| !c.modules.is_empty() || c.chunk_type == ChunkType::Runtime)
             .collect()
     }
 
@@ -98,11 +103,16 @@ impl ChunkGraph {
         self.graph.node_weights().map(|c| c.filename()).collect()
     }
 
-    pub fn full_hash(&self, module_graph: &ModuleGraph) -> u64 {
+    // the hmr hashes are derived from it, so the salt applies to them too
+    pub fn full_hash(&self, module_graph: &ModuleGraph, salt: &str) -> u64 {
         let mut chunks = self.get_all_chunks();
         chunks.sort_by_key(|c| c.id.id.clone());
 
         let mut hasher: XxHash64 = Default::default();
+        if !salt.is_empty() {
+            hasher.write(salt.as_bytes());
+            hasher.write_u8(0);
+        }
         for c in chunks {
             hasher.write_u64(c.hash(module_graph))
         }
@@ -126,11 +136,61 @@ impl ChunkGraph {
         let idx = self.id_index_map.get(chunk_id).unwrap();
         self.graph
             .neighbors_directed(*idx, Direction::Outgoing)
-            .filter(|idx| matches!(self.graph[*idx].chunk_type, ChunkType::Entry(_, _, _)))
+            .filter(|idx| {
+                matches!(
+                    self.graph[*idx].chunk_type,
+                    ChunkType::Entry(_, _, _) | ChunkType::Runtime
+                )
+            })
             .map(|idx| self.graph[idx].id.clone())
             .collect::<Vec<ChunkId>>()
     }
 
+    // initial chunks of an entry in load order
+    // runtime chunk and polyfill chunk go first and the entry chunk itself is the last one
+    pub fn entry_chunk_group(&self, chunk_id: &ChunkId) -> Vec<ChunkId> {
+        let mut chunk_ids = self.entry_dependencies_chunk(chunk_id);
+        chunk_ids.sort_by_key(|id| (id.id != RUNTIME_CHUNK_ID, id.id != POLYFILL_CHUNK_NAME));
+        chunk_ids.push(chunk_id.clone());
+        chunk_ids
+    }
+
+    // move the runtime of all entries to one chunk which entries depend on, so that the
+    // modules registry is shared and entries stay cacheable when only the runtime changes
+    pub fn extract_runtime_chunk(&mut self) {
+        let entries = self
+            .get_all_chunks()
+            .into_iter()
+            .filter(|chunk| matches!(chunk.chunk_type, ChunkType::Entry(_, _, false)))
+            .map(|chunk| chunk.id.clone())
+            .collect::<Vec<_>>();
+        let runtime_chunk_id = ChunkId::new(RUNTIME_CHUNK_ID.into());
+        if !self.has_chunk(&runtime_chunk_id) {
+            self.add_chunk(Chunk::new(runtime_chunk_id.clone(), ChunkType::Runtime));
+        }
+        for entry in entries {
+            if !self.dependents_chunk(&runtime_chunk_id).contains(&entry) {
+                self.add_edge(&entry, &runtime_chunk_id);
+            }
+        }
+    }
+
+    // chunks installed by the runtime of the chunk, the runtime chunk installs the ones of
+    // all entries
+    pub fn runtime_installable_chunks(&self, chunk_id: &ChunkId) -> Vec<ChunkId> {
+        let chunk = self.chunk(chunk_id).unwrap();
+        if chunk.chunk_type != ChunkType::Runtime {
+            return self.installable_descendants_chunk(chunk_id);
+        }
+        let mut entries = self.dependents_chunk(chunk_id);
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut chunk_ids = IndexSet::new();
+        for entry in entries {
+            chunk_ids.extend(self.installable_descendants_chunk(&entry));
+        }
+        chunk_ids.into_iter().collect()
+    }
+
     pub fn dependents_chunk(&self, chunk_id: &ChunkId) -> Vec<ChunkId> {
         let idx = self.id_index_map.get(chunk_id).unwrap();
         self.graph
@@ -259,3 +319,89 @@ impl fmt::Display for ChunkGraph {
         write!(f, "graph\n nodes:{:?}", &nodes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkGraph, RUNTIME_CHUNK_ID};
+    use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
+    use crate::generate::optimize_chunk::POLYFILL_CHUNK_NAME;
+
+    fn chunk(id: &str, chunk_type: ChunkType) -> Chunk {
+        let mut chunk = Chunk::new(ChunkId::from(id), chunk_type);
+        chunk.add_module(ChunkId::from(id));
+        chunk
+    }
+
+    #[test]
+    fn test_extract_runtime_chunk() {
+        let mut chunk_graph = ChunkGraph::new();
+        for (id, name) in [("/src/a.ts", "a"), ("/src/b.ts", "b")] {
+            chunk_graph.add_chunk(chunk(
+                id,
+                ChunkType::Entry(ChunkId::from(id), name.to_string(), false),
+            ));
+        }
+        chunk_graph.add_chunk(chunk("/src/lazy.ts", ChunkType::Async));
+        chunk_graph.add_edge(&ChunkId::from("/src/b.ts"), &ChunkId::from("/src/lazy.ts"));
+        chunk_graph.extract_runtime_chunk();
+        chunk_graph.extract_runtime_chunk();
+
+        let runtime = ChunkId::from(RUNTIME_CHUNK_ID);
+        assert!(chunk_graph
+            .get_chunks()
+            .iter()
+            .any(|chunk| chunk.id == runtime && chunk.filename() == "runtime.js"));
+        assert_eq!(
+            chunk_graph.entry_chunk_group(&ChunkId::from("/src/a.ts")),
+            vec![runtime.clone(), ChunkId::from("/src/a.ts")]
+        );
+        assert_eq!(chunk_graph.dependents_chunk(&runtime).len(), 2);
+        assert_eq!(
+            chunk_graph.runtime_installable_chunks(&runtime),
+            vec![ChunkId::from("/src/lazy.ts")]
+        );
+    }
+
+    #[test]
+    fn test_entry_chunk_group_with_polyfill_chunk() {
+        let mut chunk_graph = ChunkGraph::new();
+        let entry = ChunkId::from("/src/a.ts");
+        chunk_graph.add_chunk(chunk(
+            &entry.id,
+            ChunkType::Entry(entry.clone(), "a".to_string(), false),
+        ));
+        // chunks split from entries are shared entry chunks, as optimize_chunk creates them
+        for id in ["vendors", POLYFILL_CHUNK_NAME] {
+            chunk_graph.add_chunk(chunk(
+                id,
+                ChunkType::Entry(ChunkId::from(id), id.to_string(), true),
+            ));
+            chunk_graph.add_edge(&entry, &ChunkId::from(id));
+        }
+        assert_eq!(
+            chunk_graph.entry_chunk_group(&entry),
+            vec![
+                ChunkId::from(POLYFILL_CHUNK_NAME),
+                ChunkId::from("vendors"),
+                entry.clone()
+            ]
+        );
+
+        // the runtime is loaded before polyfills, which are loaded before the other chunks, and
+        // shared chunks don't depend on the runtime themselves
+        chunk_graph.extract_runtime_chunk();
+        assert_eq!(
+            chunk_graph.dependents_chunk(&ChunkId::from(RUNTIME_CHUNK_ID)),
+            vec![entry.clone()]
+        );
+        assert_eq!(
+            chunk_graph.entry_chunk_group(&entry),
+            vec![
+                ChunkId::from(RUNTIME_CHUNK_ID),
+                ChunkId::from(POLYFILL_CHUNK_NAME),
+                ChunkId::from("vendors"),
+                entry
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::compiler::{Compiler, Context};
+use crate::generate::chunk::ChunkType;
+
+/**
+ * an entry with all its initial chunks and their emitted files in load order,
+ * it's useful for custom html pipelines and ssr frameworks, e.g.
+ *
+ * let tags = compiler.entry_html_tags("index")?;
+ * // <link rel="stylesheet" href="/index.css" />
+ * // <script src="/vendors.js"></script>
+ * // <script src="/index.js"></script>
+ */
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkGroup {
+    pub name: String,
+    pub chunks: Vec<String>,
+    pub js: Vec<String>,
+    pub css: Vec<String>,
+}
+
+impl ChunkGroup {
+    // styles go first to avoid flash of unstyled content
+    pub fn html_tags(&self, public_path: &str) -> Vec<String> {
+        let mut tags = self.style_tags(public_path);
+        tags.extend(self.script_tags(public_path));
+        tags
+    }
+
+    pub fn style_tags(&self, public_path: &str) -> Vec<String> {
+        self.css
+            .iter()
+            .map(|file| {
+                format!(
+                    r#"<link rel="stylesheet" href="{}{}" />"#,
+                    public_path, file
+                )
+            })
+            .collect()
+    }
+
+    pub fn script_tags(&self, public_path: &str) -> Vec<String> {
+        self.js
+            .iter()
+            .map(|file| format!(r#"<script src="{}{}"></script>"#, public_path, file))
+            .collect()
+    }
+
+    // fetch files early when scripts are placed at the end of body
+    pub fn preload_tags(&self, public_path: &str) -> Vec<String> {
+        let styles = self.css.iter().map(|file| {
+            format!(
+                r#"<link rel="preload" href="{}{}" as="style" />"#,
+                public_path, file
+            )
+        });
+        let scripts = self.js.iter().map(|file| {
+            format!(
+                r#"<link rel="preload" href="{}{}" as="script" />"#,
+                public_path, file
+            )
+        });
+        styles.chain(scripts).collect()
+    }
+}
+
+pub fn chunk_groups(context: &Context) -> Vec<ChunkGroup> {
+    let chunk_graph = context.chunk_graph.read().unwrap();
+    let assets = context.stats_info.get_assets();
+    let mut chunk_groups = chunk_graph
+        .get_chunks()
+        .into_iter()
+        .filter_map(|chunk| match &chunk.chunk_type {
+            ChunkType::Entry(_, name, false) => {
+                let chunks = chunk_graph
+                    .entry_chunk_group(&chunk.id)
+                    .into_iter()
+                    .map(|id| id.id)
+                    .collect::<Vec<_>>();
+                let files = chunks
+                    .iter()
+                    .flat_map(|chunk_id| {
+                        assets
+                            .iter()
+                            .filter(move |asset| &asset.chunk_id == chunk_id)
+                            .map(|asset| asset.hashname.clone())
+                    })
+                    .collect::<Vec<_>>();
+                let filter_files = |ext: &str| {
+                    files
+                        .iter()
+                        .filter(|file| file.ends_with(ext))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                };
+                Some(ChunkGroup {
+                    name: name.clone(),
+                    js: filter_files(".js"),
+                    css: filter_files(".css"),
+                    chunks,
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    chunk_groups.sort_by(|a, b| a.name.cmp(&b.name));
+    chunk_groups
+}
+
+// when publicPath is runtime, files are referenced relatively
+pub fn html_public_path(context: &Context) -> &str {
+    let public_path = &context.config.public_path;
+    if public_path == "runtime" {
+        ""
+    } else {
+        public_path.as_str()
+    }
+}
+
+impl Compiler {
+    pub fn chunk_groups(&self) -> Vec<ChunkGroup> {
+        chunk_groups(&self.context)
+    }
+
+    pub fn chunk_group(&self, name: &str) -> Option<ChunkGroup> {
+        self.chunk_groups()
+            .into_iter()
+            .find(|chunk_group| chunk_group.name == name)
+    }
+
+    pub fn entry_html_tags(&self, name: &str) -> Result<Vec<String>> {
+        let chunk_group = self
+            .chunk_group(name)
+            .ok_or_else(|| anyhow!("entry {} not found", name))?;
+        Ok(chunk_group.html_tags(html_public_path(&self.context)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkGroup;
+
+    #[test]
+    fn test_html_tags() {
+        let chunk_group = ChunkGroup {
+            name: "index".to_string(),
+            chunks: vec!["polyfill".to_string(), "index".to_string()],
+            js: vec!["polyfill.js".to_string(), "index.js".to_string()],
+            css: vec!["index.css".to_string()],
+        };
+        assert_eq!(
+            chunk_group.html_tags("/"),
+            vec![
+                r#"<link rel="stylesheet" href="/index.css" />"#,
+                r#"<script src="/polyfill.js"></script>"#,
+                r#"<script src="/index.js"></script>"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::compiler::Compiler;
+use crate::generate::chunk::{ChunkId, ChunkType};
+
+const CHUNK_MANIFEST_NAME: &str = "chunk-manifest.json";
+
+/**
+ * the files to load entries and async chunks, so that servers can preload the chunks of lazy
+ * routes, chunks are keyed by the chunk ids which `import()` loads in the runtime, e.g.
+ *
+ * {
+ *   "publicPath": "/",
+ *   "entries": { "index": { "js": ["vendors.js", "index.js"], "css": ["index.css"] } },
+ *   "chunks": {
+ *     "src/pages/home.tsx": { "js": ["common-async.js", "src_pages_home_tsx-async.js"], "css": [] }
+ *   }
+ * }
+ *
+ * files are relative to the output path in load order, shared chunks go first
+ */
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkManifest {
+    public_path: String,
+    entries: BTreeMap<String, ChunkManifestFiles>,
+    chunks: BTreeMap<String, ChunkManifestFiles>,
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct ChunkManifestFiles {
+    js: Vec<String>,
+    css: Vec<String>,
+}
+
+impl ChunkManifest {
+    pub fn new(compiler: &Compiler) -> Self {
+        let context = &compiler.context;
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let mut files_of_chunks: HashMap<String, Vec<String>> = HashMap::new();
+        for asset in context.stats_info.get_assets() {
+            if !asset.hashname.ends_with(".map") {
+                files_of_chunks
+                    .entry(asset.chunk_id)
+                    .or_default()
+                    .push(asset.hashname);
+            }
+        }
+
+        let mut manifest = ChunkManifest {
+            public_path: context.config.public_path.clone(),
+            ..Default::default()
+        };
+        for chunk in chunk_graph.get_chunks() {
+            match &chunk.chunk_type {
+                ChunkType::Entry(_, name, false) => {
+                    manifest.entries.insert(
+                        name.clone(),
+                        files(&chunk_graph.entry_chunk_group(&chunk.id), &files_of_chunks),
+                    );
+                }
+                ChunkType::Async => {
+                    let mut chunk_ids = chunk_graph.sync_dependencies_chunk(&chunk.id);
+                    chunk_ids.push(chunk.id.clone());
+                    manifest
+                        .chunks
+                        .insert(chunk.id.id.clone(), files(&chunk_ids, &files_of_chunks));
+                }
+                _ => {}
+            }
+        }
+        manifest
+    }
+
+    pub fn write(&self, compiler: &Compiler) -> Result<()> {
+        let path = compiler
+            .context
+            .config
+            .output
+            .path
+            .join(CHUNK_MANIFEST_NAME);
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn files(
+    chunk_ids: &[ChunkId],
+    files_of_chunks: &HashMap<String, Vec<String>>,
+) -> ChunkManifestFiles {
+    let mut files = ChunkManifestFiles::default();
+    for chunk_id in chunk_ids {
+        for file in files_of_chunks.get(&chunk_id.id).into_iter().flatten() {
+            if file.ends_with(".css") {
+                files.css.push(file.clone());
+            } else {
+                files.js.push(file.clone());
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{files, ChunkManifestFiles};
+    use crate::generate::chunk::ChunkId;
+
+    #[test]
+    fn test_files() {
+        let files_of_chunks = HashMap::from([
+            (
+                "common".to_string(),
+                vec![
+                    "common-async.js".to_string(),
+                    "common-async.css".to_string(),
+                ],
+            ),
+            ("home".to_string(), vec!["home-async.js".to_string()]),
+        ]);
+        let chunk_ids = ["common", "missing", "home"].map(ChunkId::from);
+        assert_eq!(
+            files(&chunk_ids, &files_of_chunks),
+            ChunkManifestFiles {
+                js: vec!["common-async.js".to_string(), "home-async.js".to_string()],
+                css: vec!["common-async.css".to_string()],
+            }
+        );
+    }
+}
@@ -102,7 +102,7 @@ pub(crate) fn render_css_chunk(
     };
 
     let css_hash = if context.config.hash {
-        Some(file_content_hash(&css_code))
+        Some(file_content_hash(&css_code, &context.config.hash_salt))
     } else {
         None
     };
@@ -152,7 +152,7 @@ pub(crate) fn render_normal_js_chunk(
     let (buf, source_map) = util::render_module_js(&ast.ast, context)?;
 
     let hash = if context.config.hash {
-        Some(file_content_hash(&buf))
+        Some(file_content_hash(&buf, &context.config.hash_salt))
     } else {
         None
     };
@@ -183,7 +183,8 @@ pub(crate) fn render_entry_js_chunk(
         source_map,
         hash,
     } = ternary!(
-        context.args.watch,
+        // the runtime chunk has no modules to invalidate the cache with
+        context.args.watch && !matches!(chunk.chunk_type, ChunkType::Runtime),
         render_entry_chunk_js_without_full_hash,
         render_entry_chunk_js_without_full_hash_no_cache
     )(pot, js_map, css_map, chunk, context)?;
@@ -270,7 +271,8 @@ fn render_entry_chunk_js_without_full_hash(
     let mut ast = {
         crate::mako_profile_scope!("parse_runtime_entry");
 
-        let runtime_content = runtime_code(context)?;
+        let runtime_content =
+            runtime_code(context, matches!(chunk.chunk_type, ChunkType::Runtime))?;
 
         JsAst::build(
             "_mako_internal/runtime_entry.js",
@@ -308,7 +310,7 @@ fn render_entry_chunk_js_without_full_hash(
 
     let hash = if context.config.hash {
         crate::mako_profile_scope!("entryHash");
-        Some(file_content_hash(&buf))
+        Some(file_content_hash(&buf, &context.config.hash_salt))
     } else {
         None
     };
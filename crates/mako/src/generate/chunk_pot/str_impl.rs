@@ -12,12 +12,14 @@ use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
 
 use crate::ast::sourcemap::{build_source_map, RawSourceMap};
 use crate::compiler::Context;
-use crate::generate::chunk::Chunk;
+use crate::generate::chunk::{Chunk, ChunkType};
 use crate::generate::chunk_pot::ast_impl::{render_css_chunk, render_css_chunk_no_cache};
 use crate::generate::chunk_pot::util::runtime_code;
 use crate::generate::chunk_pot::ChunkPot;
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::generate::runtime::runtime_global;
 use crate::module::{Module, ModuleAst};
+use crate::stats::time_module_stage;
 use crate::ternary;
 
 pub(super) fn render_entry_js_chunk(
@@ -70,7 +72,8 @@ pub(super) fn render_entry_js_chunk(
     lines.push(init_install_css_chunk);
     lines.push(format!("var e = \"{}\";", pot.chunk_id));
 
-    let runtime_content = runtime_code(context)?.replace("_%full_hash%_", &hmr_hash.to_string());
+    let runtime_content = runtime_code(context, pot.chunk_type == ChunkType::Runtime)?
+        .replace("_%full_hash%_", &hmr_hash.to_string());
 
     let entry_prefix_code = "!(function(){\n";
 
@@ -113,10 +116,13 @@ pub(super) fn render_normal_js_chunk(
 ) -> Result<ChunkFile> {
     let (content_buf, source_map_buf) = {
         let pot = chunk_pot;
+        let global = runtime_global(context);
         let chunk_prefix_code = format!(
-            r#"((typeof globalThis !== 'undefined' ? globalThis : self)['{}'] = (typeof globalThis !== 'undefined' ? globalThis : self)['{}'] || []).push([
+            r#"({}['{}'] = {}['{}'] || []).push([
 ['{}'],"#,
+            global,
             context.config.output.chunk_loading_global,
+            global,
             context.config.output.chunk_loading_global,
             pot.chunk_id,
         );
@@ -130,8 +136,19 @@ pub(super) fn render_normal_js_chunk(
         let mut source_map_buf: Vec<u8> = vec![];
         sourcemap::SourceMap::from(chunk_raw_sourcemap).to_writer(&mut source_map_buf)?;
 
+        // entries registered to the runtime chunk are run with the entry module id
+        let entry_module_id = match &pot.chunk_type {
+            ChunkType::Entry(module_id, _, false) => {
+                format!(",\n'{}'", module_id.generate(context))
+            }
+            _ => String::new(),
+        };
+
         (
-            format!("{}\n{}]);", chunk_prefix_code, chunk_content),
+            format!(
+                "{}\n{}{}]);",
+                chunk_prefix_code, chunk_content, entry_module_id
+            ),
             source_map_buf,
         )
     };
@@ -185,7 +202,9 @@ fn emit_module_with_mapping(
                     Some(&mut source_mappings),
                 )),
             };
-            emitter.emit_module(&ast.ast)?;
+            time_module_stage(context, "codegen", &module.id.id, || {
+                emitter.emit_module(&ast.ast)
+            })?;
 
             let content = { String::from_utf8_lossy(&buf) };
             Ok((
@@ -329,7 +348,10 @@ mod tests {
                 minify: true,
                 ..Default::default()
             },
-            args: Args { watch: true },
+            args: Args {
+                watch: true,
+                verbose: false,
+            },
             ..Default::default()
         });
 
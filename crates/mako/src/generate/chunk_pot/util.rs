@@ -21,10 +21,13 @@ use twox_hash::XxHash64;
 
 use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::compiler::Context;
-use crate::config::{get_pkg_name, Mode};
+use crate::config::{get_pkg_name, ChunkLoading, Mode};
+use crate::generate::chunk::ChunkType;
 use crate::generate::chunk_pot::ChunkPot;
 use crate::generate::runtime::AppRuntimeTemplate;
 use crate::module::{relative_to_root, Module, ModuleAst};
+use crate::plugins;
+use crate::utils::salted_md5;
 
 pub(crate) fn render_module_js(
     ast: &SwcModule,
@@ -91,7 +94,7 @@ pub(crate) fn empty_module_fn_expr() -> FnExpr {
     }
 }
 
-pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
+pub(crate) fn runtime_code(context: &Arc<Context>, is_runtime_chunk: bool) -> Result<String> {
     let umd = context.config.umd.clone();
     let chunk_graph = context.chunk_graph.read().unwrap();
     let has_dynamic_chunks = chunk_graph.get_all_chunks().len() > 1;
@@ -101,8 +104,11 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
         has_hmr,
         umd,
         is_browser: matches!(context.config.platform, crate::config::Platform::Browser),
+        chunk_loading_import: context.config.output.chunk_loading == ChunkLoading::Import,
+        is_runtime_chunk,
         cjs: context.config.cjs,
         chunk_loading_global: context.config.output.chunk_loading_global.clone(),
+        global_namespace: context.config.output.global_namespace.clone(),
         pkg_name: get_pkg_name(&context.root),
         concatenate_enabled: context
             .config
@@ -177,6 +183,7 @@ pub(crate) fn pot_to_module_object(pot: &ChunkPot, context: &Arc<Context>) -> Re
 
                     let span = Span::dummy_with_cmt();
                     let id = relative_to_root(&module.0.id.id, &context.root);
+                    let banner = plugins::banner::module_banner(&id, context);
                     // to avoid comment broken by glob=**/* for context module
                     let id = id.replace("*/", "*\\/");
                     comments.add_leading(
@@ -187,6 +194,16 @@ pub(crate) fn pot_to_module_object(pot: &ChunkPot, context: &Arc<Context>) -> Re
                             text: id.into(),
                         },
                     );
+                    if let Some(banner) = banner {
+                        comments.add_leading(
+                            span.hi,
+                            Comment {
+                                kind: CommentKind::Block,
+                                span: DUMMY_SP,
+                                text: banner.into(),
+                            },
+                        );
+                    }
                     let pv: PropOrSpread = Prop::KeyValue(KeyValueProp {
                         key: quote_str!(span, module_id_str.clone()).into(),
                         value: fn_expr.into(),
@@ -206,17 +223,11 @@ pub(crate) fn pot_to_module_object(pot: &ChunkPot, context: &Arc<Context>) -> Re
     })
 }
 
-pub(crate) fn pot_to_chunk_module(
-    pot: &ChunkPot,
-    global: String,
-    context: &Arc<Context>,
-) -> Result<SwcModule> {
-    crate::mako_profile_function!();
-
-    let module_object = pot_to_module_object(pot, context)?;
-
-    // ((typeof globalThis !== 'undefined' ? globalThis : self)['makoChunk_global'] = (typeof globalThis !== 'undefined' ? globalThis : self)['makoChunk_global'] || []).push([["module_id"], { module object }])
-    let chunk_global_expr = CondExpr {
+// (typeof globalThis !== 'undefined' ? globalThis : self)
+// or with output.globalNamespace
+// ((typeof globalThis !== 'undefined' ? globalThis : self)['ns'] = (typeof globalThis !== 'undefined' ? globalThis : self)['ns'] || {})
+fn runtime_global_expr(context: &Arc<Context>) -> Expr {
+    let global_this = CondExpr {
         span: DUMMY_SP,
         test: UnaryExpr {
             span: DUMMY_SP,
@@ -228,8 +239,40 @@ pub(crate) fn pot_to_chunk_module(
         cons: quote_ident!("globalThis").into(),
         alt: quote_ident!("self").into(),
     }
-    .wrap_with_paren()
-    .computed_member::<Expr>(global.clone().into());
+    .wrap_with_paren();
+    match &context.config.output.global_namespace {
+        Some(namespace) => {
+            let namespace_expr =
+                global_this.computed_member::<Expr>(quote_str!(namespace.clone()).into());
+            namespace_expr
+                .clone()
+                .make_bin::<Expr>(
+                    BinaryOp::LogicalOr,
+                    ObjectLit {
+                        span: DUMMY_SP,
+                        props: vec![],
+                    }
+                    .into(),
+                )
+                .make_assign_to(AssignOp::Assign, namespace_expr.as_pat_or_expr())
+                .wrap_with_paren()
+        }
+        None => global_this,
+    }
+}
+
+pub(crate) fn pot_to_chunk_module(
+    pot: &ChunkPot,
+    global: String,
+    context: &Arc<Context>,
+) -> Result<SwcModule> {
+    crate::mako_profile_function!();
+
+    let module_object = pot_to_module_object(pot, context)?;
+
+    // ((typeof globalThis !== 'undefined' ? globalThis : self)['makoChunk_global'] = (typeof globalThis !== 'undefined' ? globalThis : self)['makoChunk_global'] || []).push([["module_id"], { module object }])
+    let chunk_global_expr =
+        runtime_global_expr(context).computed_member::<Expr>(global.clone().into());
     let chunk_global_obj = chunk_global_expr
         .clone()
         .make_bin::<Expr>(
@@ -243,15 +286,19 @@ pub(crate) fn pot_to_chunk_module(
         .make_assign_to(AssignOp::Assign, chunk_global_expr.clone().as_pat_or_expr())
         .wrap_with_paren()
         .make_member::<Ident>(quote_ident!("push"));
+    let mut chunk_register_elems = vec![
+        to_array_lit(vec![quote_str!(pot.chunk_id.clone()).as_arg()]).as_arg(),
+        module_object.as_arg(),
+    ];
+    // entries registered to the runtime chunk, [[ "module id"], { module object }, "entry module id"]
+    if let ChunkType::Entry(module_id, _, false) = &pot.chunk_type {
+        chunk_register_elems.push(quote_str!(module_id.generate(context)).as_arg());
+    }
     let chunk_register_stmt = chunk_global_obj
         .as_call(
             DUMMY_SP,
             // [[ "module id"], { module object }]
-            vec![to_array_lit(vec![
-                to_array_lit(vec![quote_str!(pot.chunk_id.clone()).as_arg()]).as_arg(),
-                module_object.as_arg(),
-            ])
-            .as_arg()],
+            vec![to_array_lit(chunk_register_elems).as_arg()],
         )
         .into_stmt();
 
@@ -321,9 +368,28 @@ fn to_module_fn_expr(module: &Module) -> Result<FnExpr> {
 
 pub const CHUNK_FILE_NAME_HASH_LENGTH: usize = 8;
 
-pub fn file_content_hash<T: AsRef<[u8]>>(content: T) -> String {
-    let digest = md5::compute(content);
+// salted by utils::salted_md5, hashes are unchanged with an empty salt
+pub fn file_content_hash<T: AsRef<[u8]>>(content: T, salt: &str) -> String {
+    let digest = salted_md5(content, salt);
     let mut hash = format!("{:x}", digest);
     hash.truncate(CHUNK_FILE_NAME_HASH_LENGTH);
     hash
 }
+
+#[cfg(test)]
+mod tests {
+    use super::file_content_hash;
+
+    #[test]
+    fn test_file_content_hash_with_salt() {
+        let hash = file_content_hash("content", "");
+        assert_eq!(hash, format!("{:x}", md5::compute("content"))[..8]);
+        assert_ne!(file_content_hash("content", "v2"), hash);
+        assert_ne!(
+            file_content_hash("content", "v2"),
+            file_content_hash("content", "v3")
+        );
+        // the salt and the content are separated
+        assert_ne!(file_content_hash("bc", "a"), file_content_hash("c", "ab"));
+    }
+}
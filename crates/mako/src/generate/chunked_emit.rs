@@ -0,0 +1,90 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose;
+use base64::Engine;
+
+/**
+ * write large chunks and source maps to disk in pieces of piece_size bytes, so that the
+ * rendered content is not copied into another buffer before it's written, and inline source
+ * maps are base64 encoded piece by piece instead of into a string of 4/3 of the size
+ *
+ * it's not a streaming codegen, chunks are still rendered into memory as a whole before they
+ * are written
+ */
+pub(crate) struct ChunkedWriter {
+    writer: BufWriter<File>,
+    piece_size: usize,
+    written: u64,
+}
+
+impl ChunkedWriter {
+    pub fn create(path: &Path, piece_size: usize) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file =
+            File::create(path).map_err(|e| anyhow!("Create {} failed: {}", path.display(), e))?;
+        Ok(Self {
+            writer: BufWriter::with_capacity(piece_size, file),
+            piece_size,
+            written: 0,
+        })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        for piece in bytes.chunks(self.piece_size) {
+            self.writer.write_all(piece)?;
+        }
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    // pieces are multiples of 3 bytes, so that there's no padding in the middle
+    pub fn write_base64(&mut self, bytes: &[u8]) -> Result<()> {
+        let base64_piece_size = (self.piece_size / 3).max(1) * 3;
+        for piece in bytes.chunks(base64_piece_size) {
+            let encoded = general_purpose::STANDARD.encode(piece);
+            self.writer.write_all(encoded.as_bytes())?;
+            self.written += encoded.len() as u64;
+        }
+        Ok(())
+    }
+
+    // the size of the written file
+    pub fn finish(mut self) -> Result<u64> {
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::ChunkedWriter;
+    use crate::utils::base64_encode;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_chunked_writer() {
+        let dir = create_temp_dir("chunked_emit");
+        let path = dir.path().join("nested/index.js");
+        let source_map = b"{\"version\":3,\"mappings\":\"AAAA\"}";
+
+        let mut writer = ChunkedWriter::create(&path, 4).unwrap();
+        writer.write(b"console.log(1);").unwrap();
+        writer.write(b"\n//# sourceMappingURL=").unwrap();
+        writer.write_base64(source_map).unwrap();
+        let size = writer.finish().unwrap();
+
+        let expected = format!(
+            "console.log(1);\n//# sourceMappingURL={}",
+            base64_encode(source_map)
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+        assert_eq!(size, expected.len() as u64);
+    }
+}
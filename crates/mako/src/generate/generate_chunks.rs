@@ -16,6 +16,7 @@ use crate::generate::chunk::{Chunk, ChunkType};
 use crate::generate::chunk_pot::{get_css_chunk_filename, ChunkPot, CHUNK_FILE_NAME_HASH_LENGTH};
 use crate::generate::transform::transform_css_generate;
 use crate::module::{ModuleAst, ModuleId};
+use crate::plugins;
 use crate::utils::thread_pool;
 
 #[derive(Clone)]
@@ -62,10 +63,13 @@ impl Compiler {
         let chunk_graph = self.context.chunk_graph.read().unwrap();
         let chunks = chunk_graph.get_chunks();
 
+        // with the runtime chunk, entries are registered like normal chunks and run by it
+        let runtime_chunk = self.context.config.runtime_chunk.is_some();
         let (entry_chunks, normal_chunks): (Vec<&Chunk>, Vec<&Chunk>) = chunks
             .into_iter()
             .partition(|chunk| match chunk.chunk_type {
-                ChunkType::Entry(_, _, false) | ChunkType::Worker(_) => true,
+                ChunkType::Runtime | ChunkType::Worker(_) => true,
+                ChunkType::Entry(_, _, false) => !runtime_chunk,
                 ChunkType::Entry(_, _, true) => false,
                 _ => false,
             });
@@ -75,7 +79,13 @@ impl Compiler {
             || self.generate_normal_chunk_files(normal_chunks),
         );
 
-        let normal_chunk_files = normal_chunk_files?;
+        let mut normal_chunk_files = normal_chunk_files?;
+        // before the hashed names of normal chunks are filled in entry chunks
+        normal_chunk_files
+            .par_iter_mut()
+            .try_for_each(|chunk_file| {
+                plugins::banner::add_banners(chunk_file, false, &self.context)
+            })?;
 
         let mut entry_chunk_files_with_placeholder = entry_chunk_files_with_placeholder?;
 
@@ -115,11 +125,23 @@ impl Compiler {
                 )?;
         }
 
-        let entry_chunk_files = entry_chunk_files_with_placeholder
+        let mut entry_chunk_files: Vec<ChunkFile> = entry_chunk_files_with_placeholder
             .into_iter()
             .flat_map(|e| e.0)
             .collect();
 
+        entry_chunk_files
+            .par_iter_mut()
+            .try_for_each(|chunk_file| {
+                plugins::banner::add_banners(chunk_file, true, &self.context)
+            })?;
+
+        plugins::chunk_integrity::embed_integrity(
+            &mut entry_chunk_files,
+            &normal_chunk_files,
+            &self.context,
+        )?;
+
         Ok([entry_chunk_files, normal_chunk_files].concat())
     }
 
@@ -136,7 +158,7 @@ impl Compiler {
                 let chunk_graph = self.context.chunk_graph.read().unwrap();
 
                 let (js_chunks_hash_placeholder, css_chunks_hash_placeholder) = chunk_graph
-                    .installable_descendants_chunk(&chunk.id)
+                    .runtime_installable_chunks(&chunk.id)
                     .iter()
                     .fold(
                         (ChunksHashPlaceholder::new(), ChunksHashPlaceholder::new()),
@@ -1,4 +1,4 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use std::vec;
 
 use tracing::debug;
@@ -9,6 +9,7 @@ use crate::dev::update::UpdateResult;
 use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
 use crate::generate::chunk_graph::ChunkGraph;
 use crate::module::{ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
 
 pub type GroupUpdateResult = Option<(Vec<ChunkId>, Vec<(ModuleId, ChunkId, ChunkType)>)>;
 
@@ -392,6 +393,10 @@ impl Compiler {
 
         let module_graph = self.context.module_graph.read().unwrap();
 
+        if matches!(chunk_type, ChunkType::Async) {
+            chunk.name = self.async_chunk_name(entry_module_id, &module_graph, chunk_graph);
+        }
+
         visit_modules(vec![entry_module_id.clone()], None, |head| {
             let parent_index = visited_modules
                 .iter()
@@ -438,6 +443,43 @@ impl Compiler {
         (chunk, dynamic_entries, worker_entries)
     }
 
+    // the chunk name given at the dynamic imports of the module, names colliding with
+    // other chunks are ignored with warnings
+    fn async_chunk_name(
+        &self,
+        module_id: &ModuleId,
+        module_graph: &ModuleGraph,
+        chunk_graph: &ChunkGraph,
+    ) -> Option<String> {
+        let mut names = module_graph
+            .get_dependents(module_id)
+            .into_iter()
+            .filter(|(_, dep)| dep.resolve_type == ResolveType::DynamicImport)
+            .filter_map(|(importer, dep)| {
+                let info = module_graph.get_module(importer)?.info.as_ref()?;
+                info.deps.chunk_names.get(&dep.source).cloned()
+            })
+            .collect::<BTreeSet<_>>();
+        let name = names.pop_first()?;
+        if !names.is_empty() {
+            self.context.stats_info.add_grouped_warning_once(
+                "dynamic imports have different chunk names, the first one is used".to_string(),
+                format!("{} uses {}", module_id.id, name),
+            );
+        }
+        if chunk_graph
+            .chunk_names()
+            .contains(&format!("{}-async.js", name))
+        {
+            self.context.stats_info.add_grouped_warning_once(
+                "chunk names collide with other chunks, they are ignored".to_string(),
+                format!("{} of {}", name, module_id.id),
+            );
+            return None;
+        }
+        Some(name)
+    }
+
     fn create_update_async_chunks(
         &self,
         async_module_ids: Vec<ModuleId>,
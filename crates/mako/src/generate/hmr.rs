@@ -9,6 +9,7 @@ use crate::ast::js_ast::{JSAstGenerated, JsAst};
 use crate::compiler::Compiler;
 use crate::generate::chunk::Chunk;
 use crate::generate::generate_chunks::modules_to_js_stmts;
+use crate::generate::runtime::runtime_global;
 use crate::module::ModuleId;
 
 impl Compiler {
@@ -22,10 +23,16 @@ impl Compiler {
         let module_graph = &self.context.module_graph.read().unwrap();
         let (js_stmts, _) = modules_to_js_stmts(module_ids, module_graph, &self.context).unwrap();
         let content = include_str!("../runtime/runtime_hmr.js").to_string();
-        let content = content.replace("__CHUNK_ID__", &chunk.id.id).replace(
-            "__runtime_code__",
-            &format!("runtime._h='{}';", current_hash),
-        );
+        let content = content
+            .replace(
+                "globalThis.",
+                &format!("{}.", runtime_global(&self.context)),
+            )
+            .replace("__CHUNK_ID__", &chunk.id.id)
+            .replace(
+                "__runtime_code__",
+                &format!("runtime._h='{}';", current_hash),
+            );
         let mut js_ast = JsAst::build(filename, content.as_str(), self.context.clone())
             /* safe */
             .unwrap();
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use pathdiff::diff_paths;
+use serde::Serialize;
+
+use crate::compiler::Compiler;
+use crate::generate::chunk::ChunkType;
+use crate::module::{ModuleAst, ResolveType};
+
+const METAFILE_NAME: &str = "metafile.json";
+
+/**
+ * esbuild compatible metafile, so that tools built for esbuild, e.g. bundle size analyzers,
+ * work with mako builds directly
+ *
+ * paths are relative to root, and since modules are not generated separately, bytesInOutput
+ * is the size of the module source
+ *
+ * ref: https://esbuild.github.io/api/#metafile
+ */
+#[derive(Serialize, Debug, Default)]
+pub struct Metafile {
+    inputs: BTreeMap<String, MetafileInput>,
+    outputs: BTreeMap<String, MetafileOutput>,
+}
+
+#[derive(Serialize, Debug)]
+struct MetafileInput {
+    bytes: u64,
+    imports: Vec<MetafileImport>,
+}
+
+#[derive(Serialize, Debug)]
+struct MetafileImport {
+    path: String,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    external: bool,
+    original: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MetafileOutput {
+    bytes: u64,
+    inputs: BTreeMap<String, MetafileOutputInput>,
+    imports: Vec<MetafileImport>,
+    exports: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry_point: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MetafileOutputInput {
+    bytes_in_output: u64,
+}
+
+impl Metafile {
+    pub fn new(compiler: &Compiler) -> Self {
+        let context = &compiler.context;
+        let root = &context.root;
+        let module_graph = context.module_graph.read().unwrap();
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let mut metafile = Metafile::default();
+
+        for module in module_graph.modules() {
+            let Some(info) = &module.info else {
+                continue;
+            };
+            if info.external.is_some() {
+                continue;
+            }
+            let imports = module_graph
+                .get_dependencies(&module.id)
+                .into_iter()
+                .map(|(dep_id, dep)| {
+                    let external = module_graph
+                        .get_module(dep_id)
+                        .and_then(|m| m.info.as_ref())
+                        .is_some_and(|info| info.external.is_some());
+                    MetafileImport {
+                        path: if external {
+                            dep.source.clone()
+                        } else {
+                            relative_path(root, &dep_id.id)
+                        },
+                        kind: import_kind(&dep.resolve_type),
+                        external,
+                        original: dep.source.clone(),
+                    }
+                })
+                .collect();
+            metafile.inputs.insert(
+                relative_path(root, &module.id.id),
+                MetafileInput {
+                    bytes: info.raw.len() as u64,
+                    imports,
+                },
+            );
+        }
+
+        let assets = context.stats_info.get_assets();
+        for chunk in chunk_graph.get_chunks() {
+            for asset in assets
+                .iter()
+                .filter(|asset| asset.chunk_id == chunk.id.id && !asset.hashname.ends_with(".map"))
+            {
+                let is_css = asset.hashname.ends_with(".css");
+                let inputs = chunk
+                    .get_modules()
+                    .iter()
+                    .filter_map(|module_id| {
+                        let info = module_graph.get_module(module_id)?.info.as_ref()?;
+                        if info.external.is_some()
+                            || matches!(info.ast, ModuleAst::Css(_)) != is_css
+                        {
+                            return None;
+                        }
+                        Some((
+                            relative_path(root, &module_id.id),
+                            MetafileOutputInput {
+                                bytes_in_output: info.raw.len() as u64,
+                            },
+                        ))
+                    })
+                    .collect();
+                let entry_point = match &chunk.chunk_type {
+                    ChunkType::Entry(module_id, _, false) if !is_css => {
+                        Some(relative_path(root, &module_id.id))
+                    }
+                    _ => None,
+                };
+                let output_path = context.config.output.path.join(&asset.hashname);
+                metafile.outputs.insert(
+                    relative_path(root, &output_path.to_string_lossy()),
+                    MetafileOutput {
+                        bytes: asset.size,
+                        inputs,
+                        imports: vec![],
+                        exports: vec![],
+                        entry_point,
+                    },
+                );
+            }
+        }
+        metafile
+    }
+
+    pub fn write(&self, compiler: &Compiler) -> Result<()> {
+        let path = compiler.context.config.output.path.join(METAFILE_NAME);
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn relative_path(root: &Path, path: &str) -> String {
+    diff_paths(path, root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn import_kind(resolve_type: &ResolveType) -> &'static str {
+    match resolve_type {
+        ResolveType::Import(_) | ResolveType::ExportNamed(_) | ResolveType::ExportAll => {
+            "import-statement"
+        }
+        ResolveType::Require => "require-call",
+        ResolveType::DynamicImport | ResolveType::Worker => "dynamic-import",
+        ResolveType::Css => "import-rule",
+    }
+}
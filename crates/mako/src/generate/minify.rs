@@ -23,7 +23,7 @@ pub fn minify_js(ast: &mut JsAst, context: &Arc<Context>) -> Result<()> {
             context.meta.script.cm.clone(),
             Default::default(),
             |handler| {
-                HELPERS.set(&Helpers::new(true), || {
+                HELPERS.set(&Helpers::new(context.config.swc_helpers.external()), || {
                     HANDLER.set(handler, || {
                         let unresolved_mark = ast.unresolved_mark;
                         let top_level_mark = ast.top_level_mark;
@@ -82,7 +82,7 @@ pub fn minify_css(stylesheet: &mut Stylesheet, context: &Arc<Context>) -> Result
     crate::mako_profile_function!();
     GLOBALS.set(&context.meta.css.globals, || {
         try_with_handler(context.meta.css.cm.clone(), Default::default(), |handler| {
-            HELPERS.set(&Helpers::new(true), || {
+            HELPERS.set(&Helpers::new(context.config.swc_helpers.external()), || {
                 HANDLER.set(handler, || {
                     minifier::minify(stylesheet, Default::default());
                     Ok(())
@@ -1,10 +1,14 @@
 pub(crate) mod analyze;
 pub(crate) mod chunk;
 pub(crate) mod chunk_graph;
+pub(crate) mod chunk_group;
+pub(crate) mod chunk_manifest;
 pub(crate) mod chunk_pot;
+pub(crate) mod chunked_emit;
 pub(crate) mod generate_chunks;
 pub(crate) mod group_chunk;
 pub(crate) mod hmr;
+pub(crate) mod metafile;
 pub(crate) mod minify;
 pub(crate) mod optimize_chunk;
 pub(crate) mod runtime;
@@ -13,23 +17,37 @@ pub(crate) mod transform;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::ops::DerefMut;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use analyze::Analyze;
+use analyze::{Analyze, ExportRanges};
 use anyhow::{anyhow, Result};
+use colored::Colorize;
 use indexmap::IndexSet;
+use pathdiff::diff_paths;
 use rayon::prelude::*;
 use serde::Serialize;
 use tracing::debug;
 
 use crate::compiler::{Compiler, Context};
-use crate::config::{DevtoolConfig, OutputMode, TreeShakingStrategy};
+use crate::config::{
+    ChunkedEmitConfig, DevtoolConfig, ModuleIdStrategy, OutputMode, TreeShakingStrategy,
+};
 use crate::dev::update::UpdateResult;
+use crate::generate::chunk::ChunkType;
+use crate::generate::chunk_graph::ChunkGraph;
+use crate::generate::chunk_manifest::ChunkManifest;
+use crate::generate::chunked_emit::ChunkedWriter;
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::generate::metafile::Metafile;
 use crate::module::{Dependency, ModuleId};
-use crate::stats::{create_stats_info, print_stats, write_stats};
+use crate::module_graph::module_ids::write_deterministic_id_records;
+use crate::module_graph::ModuleGraph;
+use crate::plugin::{PluginEmitAssetKind, PluginOutputKind};
+use crate::stats::{
+    create_stats_delta, create_stats_info, print_stats, write_stats, write_stats_delta,
+};
 use crate::utils::base64_encode;
 use crate::visitors::async_module::mark_async;
 
@@ -71,8 +89,37 @@ impl Compiler {
         mark_async(&module_ids, &self.context)
     }
 
+    // ids of new modules are assigned before anything in generate reads them, the first
+    // assignment reuses the ids recorded by the last build
+    fn assign_module_ids(&self) -> Result<()> {
+        if !matches!(
+            self.context.config.module_id_strategy,
+            ModuleIdStrategy::Deterministic
+        ) {
+            return Ok(());
+        }
+        let mut module_ids = self.context.module_ids.write().unwrap();
+        let module_graph = self.context.module_graph.read().unwrap();
+        let previous = if module_ids.is_empty() {
+            module_graph.recorded_deterministic_ids(&self.context.root)
+        } else {
+            module_ids.clone()
+        };
+        let assigned = module_graph.assign_deterministic_ids(
+            &self.context.root,
+            &self.context.config.hash_salt,
+            &previous,
+        );
+        if assigned != *module_ids {
+            write_deterministic_id_records(&self.context.root, &assigned)?;
+        }
+        *module_ids = assigned;
+        Ok(())
+    }
+
     pub fn generate(&self) -> Result<()> {
         self.context.plugin_driver.before_generate(&self.context)?;
+        self.assign_module_ids()?;
 
         debug!("generate");
         let t_generate = Instant::now();
@@ -121,6 +168,9 @@ impl Compiler {
 
         let t_group_chunks = Instant::now();
         self.group_chunk();
+        self.context
+            .stats_info
+            .flush_grouped_warnings(self.context.args.verbose);
         let t_group_chunks = t_group_chunks.elapsed();
 
         let t_optimize_chunks = Instant::now();
@@ -141,8 +191,18 @@ impl Compiler {
                 &mut module_graph,
                 &self.context,
             )?;
+            if self.context.config.runtime_chunk.is_some() {
+                chunk_graph.extract_runtime_chunk();
+            }
+            record_module_metadata(&mut module_graph, &chunk_graph, &self.context);
         }
 
+        // export decls are gone after transform, so collect them ahead
+        let export_ranges = self.symbol_analysis_enabled().then(|| {
+            debug!("collect export ranges");
+            analyze::collect_export_ranges(&self.context)
+        });
+
         // 为啥单独提前 transform modules？
         // 因为放 chunks 的循环里，一个 module 可能存在于多个 chunk 里，可能会被编译多遍
         let t_transform_modules = Instant::now();
@@ -157,7 +217,8 @@ impl Compiler {
         }
 
         let full_hash = self.full_hash();
-        let (t_generate_chunks, t_ast_to_code_and_write) = self.write_chunk_files(full_hash)?;
+        let (t_generate_chunks, t_ast_to_code_and_write) =
+            self.write_chunk_files(full_hash, export_ranges.as_ref())?;
 
         // write assets
         if config.emit_assets {
@@ -169,7 +230,17 @@ impl Compiler {
                     let asset_path = &self.context.root.join(k);
                     let asset_output_path = &config.output.path.join(v);
                     if asset_path.exists() {
-                        fs::copy(asset_path, asset_output_path)?;
+                        // assets may be renamed into sub directories by plugins
+                        if let Some(parent) = asset_output_path.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        let content = self.context.plugin_driver.transform_output(
+                            PluginOutputKind::Asset,
+                            v,
+                            fs::read(asset_path)?,
+                            &self.context,
+                        )?;
+                        fs::write(asset_output_path, content)?;
                     } else {
                         return Err(anyhow!("asset not found: {}", asset_path.display()));
                     }
@@ -190,6 +261,14 @@ impl Compiler {
             write_stats(&stats, self);
         }
 
+        if self.context.config.metafile {
+            Metafile::new(self).write(self)?;
+        }
+
+        if self.context.config.chunk_manifest {
+            ChunkManifest::new(self).write(self)?;
+        }
+
         // build_success hook
         self.context
             .plugin_driver
@@ -217,11 +296,36 @@ impl Compiler {
         Ok(())
     }
 
-    fn write_chunk_files(&self, full_hash: u64) -> Result<(Duration, Duration)> {
+    fn write_chunk_files(
+        &self,
+        full_hash: u64,
+        export_ranges: Option<&ExportRanges>,
+    ) -> Result<(Duration, Duration)> {
         // generate chunks
         let t_generate_chunks = Instant::now();
         debug!("generate chunks");
         let chunk_files = self.generate_chunk_files(full_hash)?;
+        if let Some(export_ranges) = export_ranges {
+            let symbol_sizes =
+                analyze::attribute_symbol_sizes(&chunk_files, export_ranges, &self.context.root)?;
+            let mut module_sizes: HashMap<ModuleId, usize> = HashMap::new();
+            for item in &symbol_sizes {
+                let module_id = ModuleId::new(
+                    self.context
+                        .root
+                        .join(&item.module)
+                        .to_string_lossy()
+                        .to_string(),
+                );
+                *module_sizes.entry(module_id).or_default() += item.size as usize;
+            }
+            let mut module_graph = self.context.module_graph.write().unwrap();
+            for (module_id, size) in module_sizes {
+                module_graph.set_transformed_size(&module_id, size);
+            }
+            drop(module_graph);
+            self.context.stats_info.set_symbol_sizes(symbol_sizes);
+        }
         self.context
             .plugin_driver
             .after_generate_chunk_files(&chunk_files, &self.context)?;
@@ -237,11 +341,30 @@ impl Compiler {
         Ok((t_generate_chunks, t_ast_to_code_and_write))
     }
 
+    // symbol attribution relies on source maps of the chunk files
+    fn symbol_analysis_enabled(&self) -> bool {
+        let config = &self.context.config;
+        if !config
+            .analyze
+            .as_ref()
+            .is_some_and(|analyze| analyze.symbols)
+        {
+            return false;
+        }
+        if config.devtool.is_none() {
+            let message = "analyze.symbols is ignored since devtool is disabled";
+            println!("{}: {}", "warning".to_string().yellow(), message);
+            self.context.stats_info.add_warning(message.to_string());
+            return false;
+        }
+        true
+    }
+
     fn generate_chunk_disk_file(&self, chunk_files: &Vec<ChunkFile>) -> Result<Duration> {
         let t_ast_to_code_and_write = Instant::now();
         debug!("ast to code and write");
         chunk_files.par_iter().try_for_each(|file| -> Result<()> {
-            self.emit_chunk_file(file);
+            self.emit_chunk_file(file)?;
             Ok(())
         })?;
         let t_ast_to_code_and_write = t_ast_to_code_and_write.elapsed();
@@ -258,13 +381,14 @@ impl Compiler {
             write_dev_chunk_file(&self.context, file)?;
             Ok(())
         })?;
+        self.context.stats_info.update_chunk_hashes(chunk_files);
         let t_ast_to_code_and_write = t_ast_to_code_and_write.elapsed();
 
         Ok(t_ast_to_code_and_write)
     }
 
-    pub fn emit_chunk_file(&self, chunk_file: &ChunkFile) {
-        emit_chunk_file(&self.context, chunk_file);
+    pub fn emit_chunk_file(&self, chunk_file: &ChunkFile) -> Result<()> {
+        emit_chunk_file(&self.context, chunk_file)
     }
 
     pub fn emit_dev_chunks(&self, current_hmr_hash: u64, last_hmr_hash: u64) -> Result<()> {
@@ -349,9 +473,22 @@ impl Compiler {
         // TODO: do not write to fs, using jsapi hooks to pass stats
         // why generate stats?
         // ref: https://github.com/umijs/mako/issues/1107
-        if self.context.config.stats.is_some() {
-            let stats = create_stats_info(0, self);
-            write_stats(&stats, self);
+        if let Some(stats_config) = &self.context.config.stats {
+            if stats_config.incremental {
+                let delta = create_stats_delta(current_hmr_hash, self);
+                write_stats_delta(&delta, self);
+            } else {
+                let stats = create_stats_info(0, self);
+                write_stats(&stats, self);
+            }
+        }
+
+        if self.context.config.metafile {
+            Metafile::new(self).write(self)?;
+        }
+
+        if self.context.config.chunk_manifest {
+            ChunkManifest::new(self).write(self)?;
         }
 
         let t_generate = t_generate.elapsed();
@@ -378,6 +515,7 @@ impl Compiler {
         last_hmr_hash: u64,
     ) -> Result<(u64, u64, u64)> {
         debug!("generate_hot_update_chunks start");
+        self.assign_module_ids()?;
 
         let last_chunk_names: HashSet<String> = {
             let chunk_graph = self.context.chunk_graph.read().unwrap();
@@ -389,10 +527,22 @@ impl Compiler {
         let t_generate = Instant::now();
         let t_group_chunks = Instant::now();
         let group_result = self.group_hot_update_chunk(&updated_modules);
+        self.context
+            .stats_info
+            .flush_grouped_warnings(self.context.args.verbose);
         let t_group_chunks = t_group_chunks.elapsed();
 
         let t_optimize_chunks = Instant::now();
         self.optimize_hot_update_chunk(&group_result);
+        {
+            let mut module_graph = self.context.module_graph.write().unwrap();
+            let mut chunk_graph = self.context.chunk_graph.write().unwrap();
+            // the chunk graph is recreated when chunks are fully re-grouped
+            if self.context.config.runtime_chunk.is_some() {
+                chunk_graph.extract_runtime_chunk();
+            }
+            record_module_metadata(&mut module_graph, &chunk_graph, &self.context);
+        }
         let t_optimize_chunks = t_optimize_chunks.elapsed();
 
         let t_transform_modules = Instant::now();
@@ -514,6 +664,36 @@ impl Compiler {
     }
 }
 
+// chunk facts of modules, recorded whenever chunks are regrouped
+fn record_module_metadata(
+    module_graph: &mut ModuleGraph,
+    chunk_graph: &ChunkGraph,
+    context: &Arc<Context>,
+) {
+    module_graph.clear_chunk_metadata();
+    // async chunks are identified by their root modules, which may be moved to other chunks
+    // by optimization, so that they are looked up by ids instead of the modules of chunks
+    let mut module_ids_by_chunk_id = HashMap::new();
+    for module_id in module_graph.get_module_ids() {
+        let size = module_graph
+            .get_module(&module_id)
+            .unwrap()
+            .get_module_size();
+        module_graph.set_source_size(&module_id, size);
+        module_ids_by_chunk_id.insert(module_id.generate(context), module_id);
+    }
+    for chunk in chunk_graph.get_all_chunks() {
+        for module_id in chunk.get_modules() {
+            module_graph.add_chunk_id(module_id, &chunk.id.id);
+        }
+        if chunk.chunk_type == ChunkType::Async
+            && let Some(root) = module_ids_by_chunk_id.get(&chunk.id.id)
+        {
+            module_graph.set_async_boundary(root, true);
+        }
+    }
+}
+
 fn write_dev_chunk_file(context: &Arc<Context>, chunk: &ChunkFile) -> Result<()> {
     crate::mako_profile_function!();
 
@@ -557,67 +737,62 @@ fn write_dev_chunk_file(context: &Arc<Context>, chunk: &ChunkFile) -> Result<()>
     Ok(())
 }
 
-fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
+fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) -> Result<()> {
     crate::mako_profile_function!(&chunk_file.file_name);
 
     let to: PathBuf = context.config.output.path.join(chunk_file.disk_name());
+    if let Some(chunked_emit) = &context.config.chunked_emit
+        && chunk_file.content.len() >= chunked_emit.threshold
+        && !context.plugin_driver.has_transform_output()
+    {
+        return emit_chunk_file_in_pieces(context, chunk_file, to, chunked_emit);
+    }
     let stats_info = &context.stats_info;
 
+    let mut code = Vec::new();
+    code.extend_from_slice(&chunk_file.content);
+
     match context.config.devtool {
         Some(DevtoolConfig::SourceMap) => {
-            let mut code = Vec::new();
-            code.extend_from_slice(&chunk_file.content);
-
-            if let Some(source_map) = &chunk_file.source_map {
+            // source map may be renamed or skipped by plugins
+            let source_map_disk_name = match &chunk_file.source_map {
+                Some(_) => context.plugin_driver.emit_asset(
+                    PluginEmitAssetKind::SourceMap,
+                    chunk_file.source_map_disk_name(),
+                    context,
+                )?,
+                None => None,
+            };
+            if let Some(source_map) = &chunk_file.source_map
+                && let Some(source_map_disk_name) = source_map_disk_name
+            {
+                let source_map_path = context.config.output.path.join(&source_map_disk_name);
+                let source_map = context.plugin_driver.transform_output(
+                    PluginOutputKind::SourceMap,
+                    &source_map_disk_name,
+                    source_map.clone(),
+                    context,
+                )?;
                 let size = source_map.len() as u64;
                 stats_info.add_assets(
                     size,
                     chunk_file.source_map_name(),
                     chunk_file.chunk_id.clone(),
                     to.clone(),
-                    chunk_file.source_map_disk_name(),
+                    source_map_disk_name.clone(),
                 );
-                fs::write(
-                    context
-                        .config
-                        .output
-                        .path
-                        .join(chunk_file.source_map_disk_name()),
-                    source_map,
-                )
-                .unwrap();
-
-                let source_map_url_line = match chunk_file.file_type {
-                    ChunkFileType::JS => {
-                        format!(
-                            "\n//# sourceMappingURL={}",
-                            chunk_file.source_map_disk_name()
-                        )
-                    }
-                    ChunkFileType::Css => {
-                        format!(
-                            "\n/*# sourceMappingURL={}*/",
-                            chunk_file.source_map_disk_name()
-                        )
-                    }
-                };
-                code.extend_from_slice(source_map_url_line.as_bytes());
-            }
+                if let Some(parent) = source_map_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&source_map_path, source_map)
+                    .map_err(|e| anyhow!("Write {} failed: {}", source_map_path.display(), e))?;
 
-            let size = code.len() as u64;
-            stats_info.add_assets(
-                size,
-                chunk_file.file_name.clone(),
-                chunk_file.chunk_id.clone(),
-                to.clone(),
-                chunk_file.disk_name(),
-            );
-            fs::write(to, &code).unwrap();
+                code.extend_from_slice(
+                    source_map_url_line(chunk_file, &source_map_disk_name).as_bytes(),
+                );
+            }
         }
         Some(DevtoolConfig::InlineSourceMap) => {
-            let mut code = Vec::new();
-            code.extend_from_slice(&chunk_file.content);
-
             if let Some(source_map) = &chunk_file.source_map {
                 code.extend_from_slice(
                     format!(
@@ -627,31 +802,113 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
                     .as_bytes(),
                 );
             }
+        }
+        None => {}
+    }
 
-            let size = code.len() as u64;
-            stats_info.add_assets(
-                size,
-                chunk_file.file_name.clone(),
-                chunk_file.chunk_id.clone(),
-                to.clone(),
-                chunk_file.disk_name(),
-            );
-            fs::write(to, code).unwrap();
+    let code = context.plugin_driver.transform_output(
+        PluginOutputKind::Chunk,
+        &chunk_file.disk_name(),
+        code,
+        context,
+    )?;
+    stats_info.add_assets(
+        code.len() as u64,
+        chunk_file.file_name.clone(),
+        chunk_file.chunk_id.clone(),
+        to.clone(),
+        chunk_file.disk_name(),
+    );
+    fs::write(&to, &code).map_err(|e| anyhow!("Write {} failed: {}", to.display(), e))?;
+
+    Ok(())
+}
+
+// large chunks are written in pieces from the rendered content without copying it, chunks
+// are written in memory instead if plugins have transform_output hooks, which need the whole
+// content
+fn emit_chunk_file_in_pieces(
+    context: &Arc<Context>,
+    chunk_file: &ChunkFile,
+    to: PathBuf,
+    chunked_emit: &ChunkedEmitConfig,
+) -> Result<()> {
+    crate::mako_profile_function!(&chunk_file.file_name);
+
+    let stats_info = &context.stats_info;
+    let mut writer = ChunkedWriter::create(&to, chunked_emit.piece_size)?;
+    writer.write(&chunk_file.content)?;
+
+    match context.config.devtool {
+        Some(DevtoolConfig::SourceMap) => {
+            let source_map_disk_name = match &chunk_file.source_map {
+                Some(_) => context.plugin_driver.emit_asset(
+                    PluginEmitAssetKind::SourceMap,
+                    chunk_file.source_map_disk_name(),
+                    context,
+                )?,
+                None => None,
+            };
+            if let Some(source_map) = &chunk_file.source_map
+                && let Some(source_map_disk_name) = source_map_disk_name
+            {
+                let source_map_path = context.config.output.path.join(&source_map_disk_name);
+                let mut source_map_writer =
+                    ChunkedWriter::create(&source_map_path, chunked_emit.piece_size)?;
+                source_map_writer.write(source_map)?;
+                stats_info.add_assets(
+                    source_map_writer.finish()?,
+                    chunk_file.source_map_name(),
+                    chunk_file.chunk_id.clone(),
+                    to.clone(),
+                    source_map_disk_name.clone(),
+                );
+                writer.write(source_map_url_line(chunk_file, &source_map_disk_name).as_bytes())?;
+            }
         }
-        None => {
-            stats_info.add_assets(
-                chunk_file.content.len() as u64,
-                chunk_file.file_name.clone(),
-                chunk_file.chunk_id.clone(),
-                to.clone(),
-                chunk_file.disk_name(),
-            );
+        Some(DevtoolConfig::InlineSourceMap) => {
+            if let Some(source_map) = &chunk_file.source_map {
+                writer.write(
+                    "\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,".as_bytes(),
+                )?;
+                writer.write_base64(source_map)?;
+            }
+        }
+        None => {}
+    }
 
-            fs::write(to, &chunk_file.content).unwrap();
+    stats_info.add_assets(
+        writer.finish()?,
+        chunk_file.file_name.clone(),
+        chunk_file.chunk_id.clone(),
+        to.clone(),
+        chunk_file.disk_name(),
+    );
+
+    Ok(())
+}
+
+fn source_map_url_line(chunk_file: &ChunkFile, source_map_disk_name: &str) -> String {
+    let source_map_url = relative_url(&chunk_file.disk_name(), source_map_disk_name);
+    match chunk_file.file_type {
+        ChunkFileType::JS => {
+            format!("\n//# sourceMappingURL={}", source_map_url)
+        }
+        ChunkFileType::Css => {
+            format!("\n/*# sourceMappingURL={}*/", source_map_url)
         }
     }
 }
 
+// relative url from a file to another in output dir
+// e.g. index.js -> maps/index.js.map => maps/index.js.map
+fn relative_url(from: &str, to: &str) -> String {
+    let from_dir = Path::new(from).parent().unwrap_or(Path::new(""));
+    diff_paths(to, from_dir)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|| to.to_string())
+}
+
 fn to_hot_update_chunk_name(chunk_name: &String, hash: u64) -> String {
     match chunk_name.rsplit_once('.') {
         None => {
@@ -674,3 +931,48 @@ struct HotUpdateManifest {
     // #[serde(rename(serialize = "c"))]
     // removed_modules: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::compiler::{Args, Compiler};
+    use crate::config::Config;
+    use crate::generate::chunk::ChunkId;
+    use crate::generate::chunk_graph::RUNTIME_CHUNK_ID;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_runtime_chunk_of_hot_update() {
+        let dir = create_temp_dir("runtime_chunk_hot_update");
+        let root = dir.path();
+        fs::write(root.join("a.js"), "import('./c');").unwrap();
+        fs::write(root.join("b.js"), "console.log('b');").unwrap();
+        fs::write(root.join("c.js"), "export default 'c';").unwrap();
+        let config = Config::new(
+            root,
+            None,
+            Some(r#"{"entry":{"a":"./a.js","b":"./b.js"},"runtimeChunk":"single","hmr":false}"#),
+        )
+        .unwrap();
+        let args = Args {
+            watch: true,
+            verbose: false,
+        };
+        let compiler = Compiler::new(config, root.to_path_buf(), args, None).unwrap();
+        compiler.compile().unwrap();
+
+        // changed resolve types of dependencies lead to a full re-group of chunks
+        fs::write(root.join("a.js"), "import c from './c'; console.log(c);").unwrap();
+        let update_result = compiler.update(vec![root.join("a.js")]).unwrap();
+        assert!(!update_result.dep_changed.is_empty());
+        compiler
+            .generate_hot_update_chunks(update_result, 0, 0)
+            .unwrap();
+
+        let chunk_graph = compiler.context.chunk_graph.read().unwrap();
+        let runtime = ChunkId::from(RUNTIME_CHUNK_ID);
+        assert!(chunk_graph.has_chunk(&runtime));
+        assert_eq!(chunk_graph.dependents_chunk(&runtime).len(), 2);
+    }
+}
@@ -18,6 +18,9 @@ use crate::generate::group_chunk::GroupUpdateResult;
 use crate::module::{Module, ModuleId, ModuleInfo};
 use crate::resolve::{ResolvedResource, ResolverResource};
 
+pub const POLYFILL_CHUNK_NAME: &str = "polyfill";
+pub const LICENSE_CHUNK_PREFIX: &str = "license-";
+
 pub struct OptimizeChunksInfo {
     pub group_options: OptimizeChunkGroup,
     pub module_to_chunks: IndexMap<ModuleId, Vec<ChunkId>>,
@@ -231,6 +234,15 @@ impl Compiler {
                     }
                 }
 
+                // check licenses of the package
+                if !optimize_info.group_options.licenses.is_empty()
+                    && !self.get_package_license(module_id).is_some_and(|license| {
+                        license_matches(&optimize_info.group_options.licenses, &license)
+                    })
+                {
+                    continue;
+                }
+
                 // check min shared count of chunks
                 if optimize_info.group_options.min_chunks > 1
                     && chunks
@@ -613,7 +625,59 @@ impl Compiler {
         }
     }
 
+    // license of the package the module is in, `license` of package.json or the legacy
+    // `licenses` array joined with OR
+    fn get_package_license(&self, module_id: &ModuleId) -> Option<String> {
+        let module_graph = self.context.module_graph.read().unwrap();
+        match module_graph.get_module(module_id) {
+            Some(Module {
+                info:
+                    Some(ModuleInfo {
+                        resolved_resource:
+                            Some(ResolverResource::Resolved(ResolvedResource(resolution))),
+                        ..
+                    }),
+                ..
+            }) => resolution
+                .package_json()
+                .and_then(|p| package_license(p.raw_json())),
+            _ => None,
+        }
+    }
+
     fn get_optimize_chunk_options(&self) -> Option<CodeSplittingAdvancedOptions> {
+        let options = self.get_code_splitting_options();
+        let optimization = self.context.config.optimization.as_ref();
+        let polyfill_chunk = optimization.is_some_and(|o| o.polyfill_chunk.is_some_and(|p| p));
+        let isolate_licenses = optimization
+            .and_then(|o| o.isolate_licenses.as_ref())
+            .filter(|licenses| !licenses.is_empty());
+
+        if !polyfill_chunk && isolate_licenses.is_none() {
+            return options;
+        }
+
+        let mut options = options.unwrap_or(CodeSplittingAdvancedOptions {
+            // do not merge async chunks if code splitting is disabled
+            min_size: 0,
+            groups: vec![],
+        });
+        // isolate modules with the licenses, which should never be mixed into shared chunks
+        if let Some(licenses) = isolate_licenses {
+            for license in licenses.iter().rev() {
+                options
+                    .groups
+                    .insert(0, code_splitting_license_group(license));
+            }
+        }
+        // hoist polyfills shared by entries into a standalone chunk
+        if polyfill_chunk {
+            options.groups.insert(0, code_splitting_polyfill_group());
+        }
+        Some(options)
+    }
+
+    fn get_code_splitting_options(&self) -> Option<CodeSplittingAdvancedOptions> {
         match &self.context.config.code_splitting {
             Some(CodeSplitting {
                 strategy: CodeSplittingStrategy::Auto,
@@ -646,6 +710,74 @@ impl Compiler {
     }
 }
 
+fn code_splitting_polyfill_group() -> OptimizeChunkGroup {
+    OptimizeChunkGroup {
+        name: POLYFILL_CHUNK_NAME.to_string(),
+        allow_chunks: OptimizeAllowChunks::Entry,
+        test: Regex::new(r"[/\\]node_modules[/\\](core-js|regenerator-runtime)[/\\]").ok(),
+        min_chunks: 2,
+        min_size: 1,
+        // polyfills should win over any other groups
+        priority: i8::MAX,
+        ..Default::default()
+    }
+}
+
+fn code_splitting_license_group(license: &str) -> OptimizeChunkGroup {
+    OptimizeChunkGroup {
+        name: format!(
+            "{}{}",
+            LICENSE_CHUNK_PREFIX,
+            license
+                .to_lowercase()
+                .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "-")
+        ),
+        allow_chunks: OptimizeAllowChunks::All,
+        min_size: 1,
+        max_size: usize::MAX,
+        licenses: vec![license.to_string()],
+        // licenses should win over any other groups except polyfills
+        priority: i8::MAX,
+        ..Default::default()
+    }
+}
+
+fn package_license(package_json: &serde_json::Value) -> Option<String> {
+    let license = |value: &serde_json::Value| match value {
+        serde_json::Value::String(license) => Some(license.clone()),
+        serde_json::Value::Object(license) => license
+            .get("type")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string()),
+        _ => None,
+    };
+    if let Some(value) = package_json.get("license") {
+        return license(value);
+    }
+    let licenses = package_json
+        .get("licenses")?
+        .as_array()?
+        .iter()
+        .filter_map(license)
+        .collect::<Vec<_>>();
+    (!licenses.is_empty()).then(|| licenses.join(" OR "))
+}
+
+// licenses match by spdx ids in the expression, ignoring case, and versions if not specified,
+// e.g. `LGPL` matches `(MIT OR LGPL-3.0-only)`
+fn license_matches(licenses: &[String], expression: &str) -> bool {
+    let expression = expression.to_lowercase();
+    let ids = expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|id| !matches!(*id, "" | "or" | "and" | "with"))
+        .collect::<Vec<_>>();
+    licenses.iter().any(|license| {
+        let license = license.to_lowercase();
+        ids.iter()
+            .any(|id| *id == license || id.starts_with(&format!("{}-", license)))
+    })
+}
+
 fn code_splitting_strategy_auto() -> CodeSplittingAdvancedOptions {
     CodeSplittingAdvancedOptions {
         groups: vec![
@@ -720,3 +852,65 @@ fn md5_chunk_ids(chunk_ids: &[ChunkId]) -> String {
     let hash = general_purpose::URL_SAFE.encode(digest.0);
     hash[..8].to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        code_splitting_license_group, code_splitting_polyfill_group, license_matches,
+        package_license, POLYFILL_CHUNK_NAME,
+    };
+
+    #[test]
+    fn test_package_license() {
+        assert_eq!(
+            package_license(&json!({ "license": "MIT" })),
+            Some("MIT".to_string())
+        );
+        assert_eq!(
+            package_license(&json!({ "license": { "type": "LGPL-2.1" } })),
+            Some("LGPL-2.1".to_string())
+        );
+        assert_eq!(
+            package_license(&json!({ "licenses": [{ "type": "MIT" }, { "type": "GPL-2.0" }] })),
+            Some("MIT OR GPL-2.0".to_string())
+        );
+        assert_eq!(package_license(&json!({})), None);
+    }
+
+    #[test]
+    fn test_license_matches() {
+        let licenses = vec!["LGPL".to_string(), "MPL-2.0".to_string()];
+        assert!(license_matches(&licenses, "LGPL-3.0-only"));
+        assert!(license_matches(&licenses, "(MIT OR lgpl-2.1)"));
+        assert!(license_matches(&licenses, "MPL-2.0"));
+        assert!(!license_matches(&licenses, "MPL-1.1"));
+        assert!(!license_matches(&licenses, "MIT"));
+        assert!(!license_matches(&licenses, "LGPLv3"));
+    }
+
+    #[test]
+    fn test_license_group_name() {
+        assert_eq!(
+            code_splitting_license_group("LGPL-3.0").name,
+            "license-lgpl-3.0"
+        );
+        assert_eq!(
+            code_splitting_license_group("GPL-2.0 WITH Classpath-exception-2.0").name,
+            "license-gpl-2.0-with-classpath-exception-2.0"
+        );
+    }
+
+    #[test]
+    fn test_polyfill_group() {
+        let group = code_splitting_polyfill_group();
+        assert_eq!(group.name, POLYFILL_CHUNK_NAME);
+        assert_eq!(group.priority, i8::MAX);
+        let test = group.test.unwrap();
+        assert!(test.is_match("/project/node_modules/core-js/modules/es.array.at.js"));
+        assert!(test.is_match("C:\\project\\node_modules\\regenerator-runtime\\runtime.js"));
+        assert!(!test.is_match("/project/node_modules/core-js-pure/modules/es.array.at.js"));
+        assert!(!test.is_match("/project/src/core-js/index.js"));
+    }
+}
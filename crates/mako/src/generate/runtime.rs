@@ -1,5 +1,21 @@
 use sailfish::TemplateOnce;
 
+use crate::compiler::Context;
+
+pub const GLOBAL_THIS: &str = "(typeof globalThis !== 'undefined' ? globalThis : self)";
+
+// the object which runtime state (chunk loading global, hmr callbacks, etc.) is attached to
+pub fn runtime_global(context: &Context) -> String {
+    match &context.config.output.global_namespace {
+        Some(namespace) => format!(
+            "({global}['{namespace}'] = {global}['{namespace}'] || {{}})",
+            global = GLOBAL_THIS,
+            namespace = namespace,
+        ),
+        None => GLOBAL_THIS.to_string(),
+    }
+}
+
 #[derive(TemplateOnce)]
 #[template(path = "app_runtime.stpl")]
 pub struct AppRuntimeTemplate {
@@ -9,6 +25,27 @@ pub struct AppRuntimeTemplate {
     pub cjs: bool,
     pub pkg_name: Option<String>,
     pub chunk_loading_global: String,
+    pub global_namespace: Option<String>,
     pub is_browser: bool,
+    pub chunk_loading_import: bool,
+    // the runtime chunk shared by entries, which are run when they are registered
+    pub is_runtime_chunk: bool,
     pub concatenate_enabled: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{runtime_global, GLOBAL_THIS};
+    use crate::compiler::Context;
+
+    #[test]
+    fn test_runtime_global() {
+        let mut context = Context::default();
+        assert_eq!(runtime_global(&context), GLOBAL_THIS);
+        context.config.output.global_namespace = Some("app".to_string());
+        assert_eq!(
+            runtime_global(&context),
+            "((typeof globalThis !== 'undefined' ? globalThis : self)['app'] = (typeof globalThis !== 'undefined' ? globalThis : self)['app'] || {})"
+        );
+    }
+}
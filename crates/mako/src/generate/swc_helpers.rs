@@ -1,5 +1,11 @@
 use indexmap::IndexSet;
 
+pub const GENERATE_HELPERS: [&str; 3] = [
+    "_interop_require_default",
+    "_interop_require_wildcard",
+    "_export_star",
+];
+
 pub struct SwcHelpers {
     pub helpers: IndexSet<String>,
 }
@@ -14,12 +20,13 @@ impl SwcHelpers {
         Self { helpers }
     }
 
-    pub fn full_helpers() -> IndexSet<String> {
-        let mut helpers = IndexSet::new();
-        helpers.insert("@swc/helpers/_/_interop_require_default".into());
-        helpers.insert("@swc/helpers/_/_interop_require_wildcard".into());
-        helpers.insert("@swc/helpers/_/_export_star".into());
-        helpers
+    // helpers injected by the cjs transform of generate, which are provided by the runtime,
+    // imported from the configured source, e.g. `@swc/helpers/_/_export_star`
+    pub fn full_helpers(source: &str) -> IndexSet<String> {
+        GENERATE_HELPERS
+            .iter()
+            .map(|name| format!("{}/_/{}", source, name))
+            .collect()
     }
 }
 
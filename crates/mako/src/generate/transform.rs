@@ -23,15 +23,18 @@ use tracing::debug;
 
 use crate::ast::js_ast::JsAst;
 use crate::compiler::{Compiler, Context};
+use crate::generate::swc_helpers::SwcHelpers;
 use crate::module::{Dependency, ModuleAst, ModuleId, ModuleType, ResolveType};
 use crate::utils::thread_pool;
 use crate::visitors::async_module::{mark_async, AsyncModule};
 use crate::visitors::css_imports::CSSImports;
 use crate::visitors::dep_replacer::{DepReplacer, DependenciesToReplace};
 use crate::visitors::dynamic_import::DynamicImport;
+use crate::visitors::inline_requires::InlineRequires;
 use crate::visitors::mako_require::MakoRequire;
 use crate::visitors::meta_url_replacer::MetaUrlReplacer;
 use crate::visitors::optimize_define_utils::OptimizeDefineUtils;
+use crate::visitors::swc_helpers_source::{SwcHelpersSource, SWC_HELPERS_SOURCE};
 
 impl Compiler {
     pub fn transform_all(&self, async_deps_map: HashMap<ModuleId, Vec<Dependency>>) -> Result<()> {
@@ -148,16 +151,16 @@ pub fn transform_modules_in_thread(
 }
 
 fn insert_swc_helper_replace(map: &mut HashMap<String, (String, String)>, context: &Arc<Context>) {
-    let helpers = vec![
-        "@swc/helpers/_/_interop_require_default",
-        "@swc/helpers/_/_interop_require_wildcard",
-        "@swc/helpers/_/_export_star",
-    ];
-
-    helpers.into_iter().for_each(|h| {
-        let m_id: ModuleId = h.to_string().into();
-        map.insert(m_id.id.clone(), (m_id.generate(context), h.to_string()));
-    });
+    // helpers are inlined into modules otherwise
+    if !context.config.swc_helpers.external() {
+        return;
+    }
+    SwcHelpers::full_helpers(&context.config.swc_helpers.source)
+        .into_iter()
+        .for_each(|h| {
+            let m_id: ModuleId = h.clone().into();
+            map.insert(m_id.id.clone(), (m_id.generate(context), h));
+        });
 }
 
 pub struct TransformJsParam<'a> {
@@ -186,7 +189,8 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
             context.meta.script.cm.clone(),
             Default::default(),
             |handler| {
-                HELPERS.set(&Helpers::new(true), || {
+                let swc_helpers = &context.config.swc_helpers;
+                HELPERS.set(&Helpers::new(swc_helpers.external()), || {
                     HANDLER.set(handler, || {
                         let unresolved_mark = ast.unresolved_mark;
                         let top_level_mark = ast.top_level_mark;
@@ -195,6 +199,12 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                         ast.ast
                             .visit_mut_with(&mut import_analyzer(import_interop, true));
                         ast.ast.visit_mut_with(&mut inject_helpers(unresolved_mark));
+                        // the runtime registers helpers as modules of the configured source
+                        if swc_helpers.external() && swc_helpers.source != SWC_HELPERS_SOURCE {
+                            ast.ast.visit_mut_with(&mut SwcHelpersSource::new(
+                                swc_helpers.source.clone(),
+                            ));
+                        }
 
                         ast.ast.visit_mut_with(&mut common_js(
                             unresolved_mark,
@@ -225,6 +235,19 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                             unresolved_mark,
                         });
 
+                        // async modules await the required dependencies at the top level
+                        if !wrap_async
+                            && context
+                                .config
+                                .experimental
+                                .react_native
+                                .as_ref()
+                                .is_some_and(|react_native| react_native.inline_requires)
+                        {
+                            ast.ast
+                                .visit_mut_with(&mut InlineRequires { unresolved_mark });
+                        }
+
                         // transform async module
                         if wrap_async {
                             let mut async_module =
@@ -241,7 +264,9 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                         };
                         ast.ast.visit_mut_with(&mut dep_replacer);
 
-                        let mut meta_url_replacer = MetaUrlReplacer {};
+                        let mut meta_url_replacer = MetaUrlReplacer {
+                            platform: context.config.platform.clone(),
+                        };
                         ast.ast.visit_mut_with(&mut meta_url_replacer);
 
                         let mut dynamic_import = DynamicImport::new(context.clone(), dep_map);
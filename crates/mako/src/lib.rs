@@ -5,18 +5,23 @@
 
 pub mod ast;
 mod build;
+#[cfg(any(test, feature = "builder-api"))]
+pub mod builder;
+pub mod cache;
 pub mod cli;
 pub mod compiler;
 pub mod config;
 pub mod dev;
-mod features;
+pub mod features;
 mod generate;
 mod module;
 mod module_graph;
 pub mod plugin;
 mod plugins;
 mod resolve;
-mod stats;
+pub mod stats;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 pub mod utils;
 mod visitors;
 
@@ -9,6 +9,10 @@ use clap::Parser;
 use mako::compiler::{self, Args};
 #[cfg(not(feature = "profile"))]
 use mako::dev;
+use mako::features::library::Library;
+use mako::features::manifest_signature::verify_manifest;
+use mako::features::symbolicate::symbolicate;
+use mako::features::why::why;
 use mako::utils::logger::init_logger;
 #[cfg(feature = "profile")]
 use mako::utils::profile_gui::ProfileApp;
@@ -40,16 +44,41 @@ async fn run() -> Result<()> {
 
     // cli
     let cli = cli::Cli::parse();
+    if let Some(cli::Command::Symbolicate { dist, stack }) = &cli.command {
+        let stack = match stack {
+            Some(stack) => std::fs::read_to_string(stack)?,
+            None => std::io::read_to_string(std::io::stdin())?,
+        };
+        println!("{}", symbolicate(dist, &stack)?);
+        return Ok(());
+    }
+    if let Some(cli::Command::VerifyManifest {
+        dist,
+        manifest,
+        key_env,
+    }) = &cli.command
+    {
+        let key = std::env::var(key_env)
+            .map_err(|_| anyhow!("Environment variable {} of the key is not set", key_env))?;
+        verify_manifest(dist, &dist.join(manifest), key.as_bytes())?;
+        println!("All files are verified with {}", manifest.display());
+        return Ok(());
+    }
+    // root is required when there's no subcommand
+    let (cli_root, mode) = match &cli.command {
+        Some(cli::Command::Why { root, mode, .. }) => (root.clone(), mode.clone()),
+        _ => (cli.root.unwrap(), cli.mode),
+    };
     debug!(
         "cli: watch = {}, mode = {}, root = {}",
         cli.watch,
-        cli.mode,
-        cli.root.to_str().unwrap()
+        mode,
+        cli_root.to_str().unwrap()
     );
-    let root = if cli.root.is_absolute() {
-        cli.root
+    let root = if cli_root.is_absolute() {
+        cli_root
     } else {
-        std::env::current_dir()?.join(cli.root)
+        std::env::current_dir()?.join(cli_root)
     };
     let root = root
         .canonicalize()
@@ -62,17 +91,36 @@ async fn run() -> Result<()> {
             "mode": "{}"
         }}
         "#,
-        cli.mode
+        mode
     );
     let mut config = config::Config::new(&root, None, Some(cli_args.as_str()))
         .map_err(|e| anyhow!(format!("Load config failed: {}", e)))?;
 
-    config.mode = cli.mode;
+    config.mode = mode;
+    if cli.detect_circular && config.circular_dependency.is_none() {
+        config.circular_dependency = Some(config::CircularDependencyConfig {
+            runtime: config::CircularDependencyLevel::Warn,
+            dynamic: config::CircularDependencyLevel::Warn,
+            overrides: vec![],
+        });
+    }
 
     debug!("config: {:?}", config);
 
+    // library mode builds every format with its own compiler
+    if config.library.is_some() {
+        if cli.watch {
+            return Err(anyhow!("library mode does not support watch yet"));
+        }
+        return Library::compile(&root, &config, None, None);
+    }
+
     // compiler
-    let compiler = compiler::Compiler::new(config, root.clone(), Args { watch: cli.watch }, None)?;
+    let args = Args {
+        watch: cli.watch,
+        verbose: cli.verbose,
+    };
+    let compiler = compiler::Compiler::new(config, root.clone(), args, None)?;
     let compiler = Arc::new(compiler);
 
     #[cfg(feature = "profile")]
@@ -93,6 +141,20 @@ async fn run() -> Result<()> {
             eprintln!("{}", e);
             std::process::exit(1);
         }
+        if let Some(graph_path) = &cli.graph {
+            let module_graph = compiler.context.module_graph.read().unwrap();
+            let content = if graph_path.extension().is_some_and(|ext| ext == "json") {
+                serde_json::to_string_pretty(&module_graph.export_json(&root))?
+            } else {
+                module_graph.export_dot(&root)
+            };
+            std::fs::write(graph_path, content)?;
+        }
+        if let Some(cli::Command::Why { module, .. }) = &cli.command {
+            let module_graph = compiler.context.module_graph.read().unwrap();
+            println!("{}", why(&module_graph, &root, module)?);
+            return Ok(());
+        }
         if cli.watch {
             let d = dev::DevServer::new(root.clone(), compiler);
             // TODO: when in Dev Mode, Dev Server should start asap, and provider a loading  while in first compiling
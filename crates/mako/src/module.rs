@@ -22,6 +22,7 @@ use crate::build::analyze_deps::AnalyzeDepsResult;
 use crate::compiler::Context;
 use crate::config::ModuleIdStrategy;
 use crate::resolve::ResolverResource;
+use crate::utils::salted_md5;
 
 pub type Dependencies = HashSet<Dependency>;
 
@@ -30,10 +31,71 @@ pub struct Dependency {
     pub source: String,
     pub resolve_as: Option<String>,
     pub resolve_type: ResolveType,
+    // symbols imported by esm imports and re-exports, empty for other types of dependencies
+    pub specifiers: Vec<ImportedSymbol>,
     pub order: usize,
     pub span: Option<Span>,
 }
 
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize)]
+pub enum ImportedSymbol {
+    // e.g. import a from './a'; export { default as a } from './a';
+    Default,
+    // e.g. import * as a from './a'; export * as a from './a';
+    Namespace,
+    // the name exported by the imported module
+    // e.g. import { a as b } from './a'; export { a } from './a';
+    Named(String),
+}
+
+impl ImportedSymbol {
+    pub fn from_import(decl: &ImportDecl) -> Vec<Self> {
+        decl.specifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::Named(named) if named.is_type_only => None,
+                ImportSpecifier::Named(named) => Some(Self::from_name(
+                    named
+                        .imported
+                        .as_ref()
+                        .map_or(named.local.sym.to_string(), module_export_name),
+                )),
+                ImportSpecifier::Default(_) => Some(Self::Default),
+                ImportSpecifier::Namespace(_) => Some(Self::Namespace),
+            })
+            .collect()
+    }
+
+    pub fn from_named_export(decl: &NamedExport) -> Vec<Self> {
+        decl.specifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                ExportSpecifier::Named(named) if named.is_type_only => None,
+                ExportSpecifier::Named(named) => {
+                    Some(Self::from_name(module_export_name(&named.orig)))
+                }
+                ExportSpecifier::Default(_) => Some(Self::Default),
+                ExportSpecifier::Namespace(_) => Some(Self::Namespace),
+            })
+            .collect()
+    }
+
+    fn from_name(name: String) -> Self {
+        if name == "default" {
+            Self::Default
+        } else {
+            Self::Named(name)
+        }
+    }
+}
+
+fn module_export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Default)]
     pub struct ImportType: u16 {
@@ -147,6 +209,11 @@ impl ResolveType {
     pub fn is_dynamic_esm(&self) -> bool {
         matches!(self, ResolveType::DynamicImport)
     }
+
+    // dependencies which are loaded in separate chunks
+    pub fn is_async_boundary(&self) -> bool {
+        matches!(self, ResolveType::DynamicImport | ResolveType::Worker)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -186,21 +253,30 @@ impl Default for ModuleInfo {
     }
 }
 
-fn md5_hash(source_str: &str, lens: usize) -> String {
-    let digest = md5::compute(source_str);
+fn md5_hash(source_str: &str, salt: &str, lens: usize) -> String {
+    let digest = salted_md5(source_str, salt);
     let hash = general_purpose::URL_SAFE.encode(digest.0);
     hash[..lens].to_string()
 }
 
 pub fn generate_module_id(origin_module_id: String, context: &Arc<Context>) -> String {
     match context.config.module_id_strategy {
-        ModuleIdStrategy::Hashed => md5_hash(&origin_module_id, 8),
+        ModuleIdStrategy::Hashed => md5_hash(&origin_module_id, &context.config.hash_salt, 8),
         ModuleIdStrategy::Named => {
             // readable ids for debugging usage
             let absolute_path = PathBuf::from(origin_module_id);
             let relative_path = diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
             relative_path.to_string_lossy().to_string()
         }
+        // assigned at the start of generate, modules that are not assigned yet fall back to
+        // the hashed ids, e.g. the client components of rsc which are collected in build
+        ModuleIdStrategy::Deterministic => context
+            .module_ids
+            .read()
+            .unwrap()
+            .get(&origin_module_id)
+            .cloned()
+            .unwrap_or_else(|| md5_hash(&origin_module_id, &context.config.hash_salt, 8)),
     }
 }
 
@@ -486,3 +562,16 @@ fn empty_module_fn_expr() -> FnExpr {
         function: func.into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::md5_hash;
+
+    #[test]
+    fn test_md5_hash_with_salt() {
+        let hash = md5_hash("/root/src/index.ts", "", 8);
+        assert_eq!(hash.len(), 8);
+        assert_eq!(md5_hash("/root/src/index.ts", "", 8), hash);
+        assert_ne!(md5_hash("/root/src/index.ts", "v2", 8), hash);
+    }
+}
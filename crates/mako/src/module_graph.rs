@@ -1,7 +1,14 @@
-use std::collections::{HashMap, HashSet};
+pub mod export;
+pub mod metadata;
+pub mod module_ids;
+pub mod patch;
+pub mod query;
+
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 
 use fixedbitset::FixedBitSet;
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::{DefaultIx, NodeIndex};
 use petgraph::prelude::{Dfs, EdgeRef};
 use petgraph::stable_graph::{StableDiGraph, WalkNeighbors};
@@ -9,13 +16,14 @@ use petgraph::visit::IntoEdgeReferences;
 use petgraph::Direction;
 use tracing::debug;
 
-use crate::module::{Dependencies, Dependency, Module, ModuleId, ResolveType};
+use crate::module::{Dependencies, Dependency, ImportedSymbol, Module, ModuleId, ResolveType};
 
 #[derive(Debug)]
 pub struct ModuleGraph {
     id_index_map: HashMap<ModuleId, NodeIndex<DefaultIx>>,
     pub graph: StableDiGraph<Module, Dependencies>,
     entries: HashSet<ModuleId>,
+    metadata: HashMap<ModuleId, metadata::ModuleMetadata>,
 }
 
 impl ModuleGraph {
@@ -24,6 +32,7 @@ impl ModuleGraph {
             id_index_map: HashMap::new(),
             graph: StableDiGraph::new(),
             entries: HashSet::new(),
+            metadata: HashMap::new(),
         }
     }
 
@@ -31,7 +40,81 @@ impl ModuleGraph {
         self.entries.iter().cloned().collect()
     }
 
+    // entries can be changed after the first build, e.g. by lazy compilation or mpa with the dev
+    // server, chunks are regrouped by the next generate
+    pub fn add_entry(&mut self, mut module: Module) {
+        module.is_entry = true;
+        self.add_module(module);
+    }
+
+    // false if the module is not in the graph
+    pub fn mark_as_entry(&mut self, module_id: &ModuleId) -> bool {
+        let Some(module) = self.get_module_mut(module_id) else {
+            return false;
+        };
+        module.is_entry = true;
+        self.entries.insert(module_id.clone());
+        true
+    }
+
+    // modules which are only reachable from the entry are removed (the entry itself too if no
+    // other entries import it), the removed ones are returned in order of ids
+    pub fn remove_entry(&mut self, module_id: &ModuleId) -> Vec<ModuleId> {
+        if !self.entries.remove(module_id) {
+            return vec![];
+        }
+        if let Some(module) = self.get_module_mut(module_id) {
+            module.is_entry = false;
+        }
+        let reachable = self.reachable_modules();
+        let mut removed = self
+            .reachable_from([module_id])
+            .into_iter()
+            .filter(|id| !reachable.contains(id))
+            .collect::<Vec<_>>();
+        removed.sort();
+        for id in &removed {
+            self.remove_module(id);
+        }
+        removed
+    }
+
+    // modules which are imported by entries directly or indirectly, including the entries
+    pub fn reachable_modules(&self) -> HashSet<ModuleId> {
+        self.reachable_from(self.entries.iter())
+    }
+
+    fn reachable_from<'a>(
+        &self,
+        starts: impl IntoIterator<Item = &'a ModuleId>,
+    ) -> HashSet<ModuleId> {
+        let mut visited = HashSet::new();
+        let mut queue = starts
+            .into_iter()
+            .filter_map(|id| self.id_index_map.get(id).copied())
+            .collect::<VecDeque<_>>();
+        while let Some(index) = queue.pop_front() {
+            if !visited.insert(index) {
+                continue;
+            }
+            queue.extend(
+                self.graph
+                    .neighbors_directed(index, Direction::Outgoing)
+                    .filter(|next| !visited.contains(next)),
+            );
+        }
+        visited
+            .into_iter()
+            .map(|index| self.graph[index].id.clone())
+            .collect()
+    }
+
     pub fn add_module(&mut self, module: Module) {
+        // replace the node in place if the module is added again, or the old node is left dangling
+        if self.has_module(&module.id) {
+            self.replace_module(module);
+            return;
+        }
         // TODO: module.id 能否用引用以减少内存占用？
         let id_for_map = module.id.clone();
         let id_for_entry = module.id.clone();
@@ -70,12 +153,14 @@ impl ModuleGraph {
         self.remove_module(module_id)
     }
 
-    #[allow(dead_code)]
+    // the edges from and to the module are removed too
     pub fn remove_module(&mut self, module_id: &ModuleId) -> Module {
         let index = self
             .id_index_map
             .remove(module_id)
             .unwrap_or_else(|| panic!("module_id {:?} not found in the module graph", module_id));
+        self.entries.remove(module_id);
+        self.metadata.remove(module_id);
         self.graph.remove_node(index).unwrap()
     }
 
@@ -97,6 +182,11 @@ impl ModuleGraph {
             .id_index_map
             .get(&module.id)
             .unwrap_or_else(|| panic!("module_id {:?} should in the module graph", module.id));
+        if module.is_entry {
+            self.entries.insert(module.id.clone());
+        } else {
+            self.entries.remove(&module.id);
+        }
         self.graph[*i] = module;
     }
 
@@ -195,6 +285,48 @@ impl ModuleGraph {
         deps
     }
 
+    // all the modules which import the module directly or indirectly, the nearest ones first,
+    // e.g. to walk up from a changed file to its entries
+    pub fn get_dependents_recursive(&self, module_id: &ModuleId) -> Vec<ModuleId> {
+        let mut visited = HashSet::from([module_id.clone()]);
+        let mut queue = VecDeque::from([module_id.clone()]);
+        let mut dependents = vec![];
+        while let Some(current) = queue.pop_front() {
+            for (dependent, _) in self.get_dependents(&current) {
+                if visited.insert(dependent.clone()) {
+                    dependents.push(dependent.clone());
+                    queue.push_back(dependent.clone());
+                }
+            }
+        }
+        dependents
+    }
+
+    // symbols of `to` which are imported by `from`, require, dynamic import and export * use the
+    // whole module so they are regarded as namespace imports
+    pub fn get_imported_symbols(&self, from: &ModuleId, to: &ModuleId) -> Vec<ImportedSymbol> {
+        let (Some(from), Some(to)) = (self.id_index_map.get(from), self.id_index_map.get(to))
+        else {
+            return vec![];
+        };
+        let Some(edge) = self.graph.find_edge(*from, *to) else {
+            return vec![];
+        };
+        let mut symbols = self.graph[edge]
+            .iter()
+            .flat_map(|dep| match dep.resolve_type {
+                ResolveType::Import(_) | ResolveType::ExportNamed(_) => dep.specifiers.clone(),
+                ResolveType::ExportAll | ResolveType::Require | ResolveType::DynamicImport => {
+                    vec![ImportedSymbol::Namespace]
+                }
+                ResolveType::Css | ResolveType::Worker => vec![],
+            })
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
     pub fn get_dependencies_info(
         &self,
         module_id: &ModuleId,
@@ -345,6 +477,28 @@ impl ModuleGraph {
         (result, cyclic)
     }
 
+    /**
+     * groups of mutually dependent modules, i.e. strongly connected components with more than
+     * one module, the largest ones first, so that they can be treated as atomic units, e.g. by
+     * scope hoisting and chunk splitting
+     */
+    pub fn scc_groups(&self) -> Vec<Vec<ModuleId>> {
+        let mut groups = tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| {
+                let mut group = group
+                    .into_iter()
+                    .map(|index| self.graph[index].id.clone())
+                    .collect::<Vec<_>>();
+                group.sort();
+                group
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        groups
+    }
+
     pub fn get_reference(&self) -> Vec<String> {
         let mut references = self
             .graph
@@ -386,3 +540,191 @@ impl Default for ModuleGraph {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ModuleGraph;
+    use crate::module::{Dependency, ImportedSymbol, Module, ResolveType};
+    use crate::utils::test_helper::{add_module_edges, create_module_graph, dependency, module_id};
+
+    #[test]
+    fn test_remove_module() {
+        let mut graph = create_graph();
+        graph.remove_module(&module_id("a"));
+        assert!(!graph.has_module(&module_id("a")));
+        assert!(graph.get_entry_modules().is_empty());
+        assert_eq!(graph.get_reference(), vec!["b -> c"]);
+        assert_eq!(graph.dependant_module_ids(&module_id("b")), vec![]);
+    }
+
+    #[test]
+    fn test_remove_dependency() {
+        let mut graph = create_graph();
+        graph.add_dependency(
+            &module_id("a"),
+            &module_id("b"),
+            dependency("./b2", ResolveType::Require, 1),
+        );
+        graph.remove_dependency(
+            &module_id("a"),
+            &module_id("b"),
+            &dependency("b", ResolveType::Require, 0),
+        );
+        assert_eq!(graph.get_reference(), vec!["a -> b", "b -> c"]);
+        graph.remove_dependency(
+            &module_id("a"),
+            &module_id("b"),
+            &dependency("./b2", ResolveType::Require, 1),
+        );
+        assert_eq!(graph.get_reference(), vec!["b -> c"]);
+    }
+
+    #[test]
+    fn test_replace_module() {
+        let mut graph = create_graph();
+        graph.replace_module(Module::new(module_id("a"), false, None));
+        graph.replace_module(Module::new(module_id("c"), true, None));
+        assert_eq!(graph.get_entry_modules(), vec![module_id("c")]);
+        assert_eq!(graph.get_reference(), vec!["a -> b", "b -> c"]);
+
+        graph.add_module(Module::new(module_id("b"), false, None));
+        assert_eq!(graph.modules().len(), 3);
+        assert_eq!(graph.get_reference(), vec!["a -> b", "b -> c"]);
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut graph = create_graph();
+        graph.add_entry(Module::new(module_id("d"), false, None));
+        add_module_edges(
+            &mut graph,
+            &[
+                ("d", "c", ResolveType::Require),
+                ("d", "e", ResolveType::Require),
+            ],
+        );
+        assert!(graph.get_module(&module_id("d")).unwrap().is_entry);
+        assert_eq!(graph.reachable_modules().len(), 5);
+
+        assert_eq!(
+            graph.remove_entry(&module_id("d")),
+            vec![module_id("d"), module_id("e")]
+        );
+        assert_eq!(graph.get_entry_modules(), vec![module_id("a")]);
+        assert_eq!(graph.get_reference(), vec!["a -> b", "b -> c"]);
+        assert!(graph.remove_entry(&module_id("b")).is_empty());
+
+        assert!(graph.mark_as_entry(&module_id("b")));
+        assert!(!graph.mark_as_entry(&module_id("d")));
+        assert_eq!(graph.remove_entry(&module_id("a")), vec![module_id("a")]);
+        assert_eq!(graph.get_entry_modules(), vec![module_id("b")]);
+        assert!(graph.get_module(&module_id("b")).unwrap().is_entry);
+        assert_eq!(graph.get_reference(), vec!["b -> c"]);
+    }
+
+    #[test]
+    fn test_get_dependents_recursive() {
+        let mut graph = create_graph();
+        add_module_edges(&mut graph, &[("c", "a", ResolveType::Require)]);
+        assert_eq!(
+            graph.get_dependents_recursive(&module_id("c")),
+            vec![module_id("b"), module_id("a")]
+        );
+        assert_eq!(
+            graph
+                .get_dependents(&module_id("b"))
+                .into_iter()
+                .map(|(id, dep)| (id.clone(), dep.source.clone()))
+                .collect::<Vec<_>>(),
+            vec![(module_id("a"), "b".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_get_imported_symbols() {
+        let mut graph = create_graph();
+        graph.add_dependency(
+            &module_id("a"),
+            &module_id("b"),
+            Dependency {
+                resolve_type: ResolveType::Import(Default::default()),
+                specifiers: vec![
+                    ImportedSymbol::Named("x".to_string()),
+                    ImportedSymbol::Default,
+                ],
+                ..dependency("b", ResolveType::Require, 1)
+            },
+        );
+        assert_eq!(
+            graph.get_imported_symbols(&module_id("a"), &module_id("b")),
+            vec![
+                ImportedSymbol::Default,
+                ImportedSymbol::Namespace,
+                ImportedSymbol::Named("x".to_string())
+            ]
+        );
+        assert!(graph
+            .get_imported_symbols(&module_id("a"), &module_id("c"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_toposort() {
+        let mut graph = create_graph();
+        add_module_edges(
+            &mut graph,
+            &[
+                ("a", "d", ResolveType::Require),
+                ("c", "a", ResolveType::Require),
+            ],
+        );
+        let (sorted, cycles) = graph.toposort();
+        assert_eq!(
+            sorted,
+            vec![
+                module_id("a"),
+                module_id("d"),
+                module_id("b"),
+                module_id("c")
+            ]
+        );
+        assert_eq!(
+            cycles,
+            vec![vec![module_id("a"), module_id("b"), module_id("c")]]
+        );
+    }
+
+    #[test]
+    fn test_scc_groups() {
+        let mut graph = create_graph();
+        assert!(graph.scc_groups().is_empty());
+        add_module_edges(
+            &mut graph,
+            &[
+                ("c", "a", ResolveType::Require),
+                ("a", "d", ResolveType::Require),
+                ("d", "e", ResolveType::Require),
+                ("e", "d", ResolveType::Require),
+                ("e", "f", ResolveType::Require),
+            ],
+        );
+        assert_eq!(
+            graph.scc_groups(),
+            vec![
+                vec![module_id("a"), module_id("b"), module_id("c")],
+                vec![module_id("d"), module_id("e")]
+            ]
+        );
+    }
+
+    // a -> b -> c, a is the entry
+    fn create_graph() -> ModuleGraph {
+        create_module_graph(
+            "a",
+            &[
+                ("a", "b", ResolveType::Require),
+                ("b", "c", ResolveType::Require),
+            ],
+        )
+    }
+}
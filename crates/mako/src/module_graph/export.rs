@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use petgraph::prelude::EdgeRef;
+use petgraph::visit::IntoEdgeReferences;
+use serde::Serialize;
+
+use crate::module::{Module, ModuleId, ModuleType, ResolveType};
+use crate::module_graph::ModuleGraph;
+
+#[derive(Serialize, Debug)]
+pub struct ModuleGraphJson {
+    pub nodes: Vec<ModuleGraphNode>,
+    pub edges: Vec<ModuleGraphEdge>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleGraphNode {
+    pub id: String,
+    // script, css, raw, external or placeholder
+    pub module_type: &'static str,
+    pub size: usize,
+    pub is_entry: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ModuleGraphEdge {
+    pub from: String,
+    pub to: String,
+    pub source: String,
+    // import, export, require, dynamic-import, css or worker
+    pub kind: &'static str,
+    pub order: usize,
+}
+
+/**
+ * export the module graph for bundle debugging, ids are relative to root and sorted so that
+ * the outputs are stable across builds
+ *
+ * e.g.
+ * dot -Tsvg graph.dot -o graph.svg
+ */
+impl ModuleGraph {
+    pub fn export_json(&self, root: &Path) -> ModuleGraphJson {
+        let mut nodes = self
+            .graph
+            .node_weights()
+            .map(|module| ModuleGraphNode {
+                id: relative_id(&module.id, root),
+                module_type: module_type(module),
+                size: module.get_module_size(),
+                is_entry: module.is_entry,
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut edges = self
+            .graph
+            .edge_references()
+            .flat_map(|edge| {
+                let from = relative_id(&self.graph[edge.source()].id, root);
+                let to = relative_id(&self.graph[edge.target()].id, root);
+                edge.weight().iter().map(move |dep| ModuleGraphEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    source: dep.source.clone(),
+                    kind: resolve_kind(&dep.resolve_type),
+                    order: dep.order,
+                })
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| (&a.from, a.order, &a.to).cmp(&(&b.from, b.order, &b.to)));
+
+        ModuleGraphJson { nodes, edges }
+    }
+
+    pub fn export_dot(&self, root: &Path) -> String {
+        let json = self.export_json(root);
+        let mut lines = vec!["digraph {".to_string()];
+        for node in &json.nodes {
+            let mut attrs = vec![format!(
+                "label=\"{}\\n{} bytes\"",
+                escape(&node.id),
+                node.size
+            )];
+            if node.is_entry {
+                attrs.push("shape=box".to_string());
+            }
+            if node.module_type == "external" {
+                attrs.push("style=dashed".to_string());
+            }
+            lines.push(format!(
+                "  \"{}\" [{}];",
+                escape(&node.id),
+                attrs.join(", ")
+            ));
+        }
+        for edge in &json.edges {
+            let mut attrs = vec![format!("label=\"{}\"", edge.kind)];
+            if edge.kind == "dynamic-import" || edge.kind == "worker" {
+                attrs.push("style=dashed".to_string());
+            }
+            lines.push(format!(
+                "  \"{}\" -> \"{}\" [{}];",
+                escape(&edge.from),
+                escape(&edge.to),
+                attrs.join(", ")
+            ));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+fn relative_id(module_id: &ModuleId, root: &Path) -> String {
+    Path::new(&module_id.id)
+        .strip_prefix(root)
+        .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string())
+}
+
+fn module_type(module: &Module) -> &'static str {
+    if module.is_external() {
+        return "external";
+    }
+    match module.get_module_type() {
+        ModuleType::Script => "script",
+        ModuleType::Css => "css",
+        ModuleType::Raw => "raw",
+        ModuleType::PlaceHolder => "placeholder",
+    }
+}
+
+fn resolve_kind(resolve_type: &ResolveType) -> &'static str {
+    match resolve_type {
+        ResolveType::Import(_) => "import",
+        ResolveType::ExportNamed(_) | ResolveType::ExportAll => "export",
+        ResolveType::Require => "require",
+        ResolveType::DynamicImport => "dynamic-import",
+        ResolveType::Css => "css",
+        ResolveType::Worker => "worker",
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::module::ResolveType;
+    use crate::module_graph::ModuleGraph;
+    use crate::utils::test_helper::create_module_graph;
+
+    #[test]
+    fn test_export_dot() {
+        assert_eq!(
+            create_graph().export_dot(Path::new("/root")),
+            r#"digraph {
+  "src/a.ts" [label="src/a.ts\n0 bytes"];
+  "src/index.ts" [label="src/index.ts\n0 bytes", shape=box];
+  "src/index.ts" -> "src/a.ts" [label="require"];
+  "src/index.ts" -> "src/a.ts" [label="dynamic-import", style=dashed];
+}"#
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let json = create_graph().export_json(Path::new("/root"));
+        assert_eq!(
+            serde_json::to_value(&json.edges[1]).unwrap(),
+            serde_json::json!({
+                "from": "src/index.ts",
+                "to": "src/a.ts",
+                "source": "/root/src/a.ts",
+                "kind": "dynamic-import",
+                "order": 1
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&json.nodes[0]).unwrap(),
+            serde_json::json!({
+                "id": "src/a.ts",
+                "moduleType": "placeholder",
+                "size": 0,
+                "isEntry": false
+            })
+        );
+    }
+
+    fn create_graph() -> ModuleGraph {
+        create_module_graph(
+            "/root/src/index.ts",
+            &[
+                ("/root/src/index.ts", "/root/src/a.ts", ResolveType::Require),
+                (
+                    "/root/src/index.ts",
+                    "/root/src/a.ts",
+                    ResolveType::DynamicImport,
+                ),
+            ],
+        )
+    }
+}
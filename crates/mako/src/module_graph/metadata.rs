@@ -0,0 +1,120 @@
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+
+/**
+ * per-module facts of a build which are shared by chunking, stats and the analyzer, so that
+ * they don't each keep their own maps keyed by module ids
+ *
+ * - source_size, bytes of the loaded source, recorded before generate
+ * - chunk_ids, chunks the module ends up in, recorded after chunks are optimized
+ * - is_async_boundary, whether the module is the root of an async chunk
+ * - transformed_size, bytes of the emitted code attributed to the module, only recorded with
+ *   analyze.symbols since it's calculated with source maps
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleMetadata {
+    pub source_size: Option<usize>,
+    pub transformed_size: Option<usize>,
+    pub chunk_ids: Vec<String>,
+    pub is_async_boundary: bool,
+}
+
+impl ModuleGraph {
+    pub fn metadata(&self, module_id: &ModuleId) -> Option<&ModuleMetadata> {
+        self.metadata.get(module_id)
+    }
+
+    // None if the module is not in the graph
+    pub fn metadata_mut(&mut self, module_id: &ModuleId) -> Option<&mut ModuleMetadata> {
+        if !self.has_module(module_id) {
+            return None;
+        }
+        Some(self.metadata.entry(module_id.clone()).or_default())
+    }
+
+    pub fn source_size(&self, module_id: &ModuleId) -> Option<usize> {
+        self.metadata(module_id).and_then(|m| m.source_size)
+    }
+
+    pub fn set_source_size(&mut self, module_id: &ModuleId, size: usize) {
+        if let Some(metadata) = self.metadata_mut(module_id) {
+            metadata.source_size = Some(size);
+        }
+    }
+
+    pub fn transformed_size(&self, module_id: &ModuleId) -> Option<usize> {
+        self.metadata(module_id).and_then(|m| m.transformed_size)
+    }
+
+    pub fn set_transformed_size(&mut self, module_id: &ModuleId, size: usize) {
+        if let Some(metadata) = self.metadata_mut(module_id) {
+            metadata.transformed_size = Some(size);
+        }
+    }
+
+    pub fn chunk_ids(&self, module_id: &ModuleId) -> &[String] {
+        self.metadata(module_id)
+            .map_or(&[], |m| m.chunk_ids.as_slice())
+    }
+
+    pub fn add_chunk_id(&mut self, module_id: &ModuleId, chunk_id: &str) {
+        if let Some(metadata) = self.metadata_mut(module_id)
+            && !metadata.chunk_ids.iter().any(|id| id == chunk_id)
+        {
+            metadata.chunk_ids.push(chunk_id.to_string());
+        }
+    }
+
+    pub fn is_async_boundary(&self, module_id: &ModuleId) -> bool {
+        self.metadata(module_id)
+            .is_some_and(|m| m.is_async_boundary)
+    }
+
+    pub fn set_async_boundary(&mut self, module_id: &ModuleId, is_async_boundary: bool) {
+        if let Some(metadata) = self.metadata_mut(module_id) {
+            metadata.is_async_boundary = is_async_boundary;
+        }
+    }
+
+    // chunks are regrouped on every generate, so are the chunk facts
+    pub fn clear_chunk_metadata(&mut self) {
+        self.metadata.values_mut().for_each(|metadata| {
+            metadata.chunk_ids.clear();
+            metadata.is_async_boundary = false;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::module::{Module, ModuleId};
+    use crate::module_graph::ModuleGraph;
+
+    #[test]
+    fn test_metadata() {
+        let mut module_graph = ModuleGraph::new();
+        let a = ModuleId::new("a".to_string());
+        let b = ModuleId::new("b".to_string());
+        module_graph.add_module(Module::new(a.clone(), true, None));
+
+        module_graph.set_source_size(&a, 10);
+        module_graph.add_chunk_id(&a, "index");
+        module_graph.add_chunk_id(&a, "index");
+        module_graph.add_chunk_id(&a, "common");
+        module_graph.set_async_boundary(&a, true);
+        module_graph.set_source_size(&b, 10);
+        assert_eq!(module_graph.source_size(&a), Some(10));
+        assert_eq!(module_graph.transformed_size(&a), None);
+        assert_eq!(module_graph.chunk_ids(&a), &["index", "common"]);
+        assert!(module_graph.is_async_boundary(&a));
+        assert!(module_graph.metadata(&b).is_none());
+
+        module_graph.clear_chunk_metadata();
+        assert!(module_graph.chunk_ids(&a).is_empty());
+        assert!(!module_graph.is_async_boundary(&a));
+        assert_eq!(module_graph.source_size(&a), Some(10));
+
+        module_graph.remove_module(&a);
+        assert!(module_graph.metadata(&a).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use pathdiff::diff_paths;
+
+use crate::module_graph::ModuleGraph;
+use crate::utils::salted_md5;
+
+// ids have at least 3 digits, the range grows when it's more than 80% occupied
+const MIN_ID_DIGITS: u32 = 3;
+
+fn records_path(root: &Path) -> PathBuf {
+    root.join("node_modules/.cache_mako/module-ids.json")
+}
+
+/**
+ * assign the ids of the `deterministic` moduleIdStrategy, which are small integers hashed
+ * from the paths relative to root, so that the ids are short and mostly the same across builds
+ *
+ * ids of previous assignments are kept, which are the ids of the last rebuild in watch, or the
+ * ids recorded by the last build for cold builds, so that neither the growth of the range nor
+ * the conflicts with new modules change the ids of existing modules, conflicts are resolved by
+ * rehashing with a suffix in the order of the paths
+ */
+impl ModuleGraph {
+    pub fn assign_deterministic_ids(
+        &self,
+        root: &Path,
+        salt: &str,
+        previous: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        let mut assigned = HashMap::new();
+        let mut used = HashSet::new();
+        let mut names = vec![];
+        let modules = self.modules();
+        let range = id_range(modules.len());
+        for module in modules {
+            let origin = &module.id.id;
+            if let Some(id) = previous.get(origin)
+                && used.insert(id.clone())
+            {
+                assigned.insert(origin.clone(), id.clone());
+            } else {
+                names.push((relative_name(origin, root), origin.clone()));
+            }
+        }
+        names.sort();
+
+        for (name, origin) in names {
+            let mut salt_suffix = 0usize;
+            let id = loop {
+                let key = if salt_suffix == 0 {
+                    name.clone()
+                } else {
+                    format!("{}{}", name, salt_suffix)
+                };
+                let id = (hash_number(&key, salt) % range).to_string();
+                if used.insert(id.clone()) {
+                    break id;
+                }
+                salt_suffix += 1;
+            };
+            assigned.insert(origin, id);
+        }
+        assigned
+    }
+
+    // ids recorded by the last build, of the modules in the graph
+    pub fn recorded_deterministic_ids(&self, root: &Path) -> HashMap<String, String> {
+        let Some(records) = fs::read_to_string(records_path(root))
+            .ok()
+            .and_then(|content| serde_json::from_str::<BTreeMap<String, String>>(&content).ok())
+        else {
+            return HashMap::new();
+        };
+        self.modules()
+            .into_iter()
+            .filter_map(|module| {
+                let origin = &module.id.id;
+                let id = records.get(&relative_name(origin, root))?;
+                Some((origin.clone(), id.clone()))
+            })
+            .collect()
+    }
+}
+
+// record the ids with the paths relative to root, which are reused by the next cold build
+pub fn write_deterministic_id_records(root: &Path, ids: &HashMap<String, String>) -> Result<()> {
+    let records = ids
+        .iter()
+        .map(|(origin, id)| (relative_name(origin, root), id.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let path = records_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&records)?)
+        .map_err(|e| anyhow!("Write {} failed: {}", path.display(), e))
+}
+
+fn relative_name(origin: &str, root: &Path) -> String {
+    let path = Path::new(origin);
+    diff_paths(path, root)
+        .map_or(origin.to_string(), |p| p.to_string_lossy().to_string())
+        .replace('\\', "/")
+}
+
+fn id_range(count: usize) -> u64 {
+    let mut range = 10u64.pow(MIN_ID_DIGITS);
+    while (count as u64) * 10 >= range * 8 {
+        range *= 10;
+    }
+    range
+}
+
+fn hash_number(key: &str, salt: &str) -> u64 {
+    let digest = salted_md5(key, salt);
+    u64::from_le_bytes(digest.0[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::path::Path;
+
+    use super::write_deterministic_id_records;
+    use crate::module::{Module, ModuleId};
+    use crate::module_graph::ModuleGraph;
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_assign_deterministic_ids() {
+        let root = Path::new("/root");
+        let mut module_graph = ModuleGraph::new();
+        for path in ["/root/src/index.ts", "/root/src/a.ts", "/root/src/b.ts"] {
+            module_graph.add_module(Module::new(ModuleId::from(path), false, None));
+        }
+        let ids = module_graph.assign_deterministic_ids(root, "", &HashMap::new());
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids.values().collect::<HashSet<_>>().len(), 3);
+        assert!(ids
+            .values()
+            .all(|id| id.parse::<u64>().is_ok_and(|id| id < 1000)));
+        assert_eq!(
+            module_graph.assign_deterministic_ids(root, "", &HashMap::new()),
+            ids
+        );
+
+        // existing ids are kept, the new module gets a fresh one
+        let c = ModuleId::from("/root/src/c.ts");
+        module_graph.add_module(Module::new(c.clone(), false, None));
+        let mut previous = ids.clone();
+        previous.insert("/root/src/a.ts".to_string(), "1".to_string());
+        let next = module_graph.assign_deterministic_ids(root, "", &previous);
+        assert_eq!(next["/root/src/a.ts"], "1");
+        assert_eq!(next["/root/src/b.ts"], ids["/root/src/b.ts"]);
+        assert!(next.contains_key(&c.id));
+        assert_eq!(next.values().collect::<HashSet<_>>().len(), 4);
+    }
+
+    #[test]
+    fn test_recorded_deterministic_ids() {
+        let dir = create_temp_dir("module_ids");
+        let root = dir.path();
+        let mut module_graph = ModuleGraph::new();
+        let a = root.join("src/a.ts").to_string_lossy().to_string();
+        module_graph.add_module(Module::new(ModuleId::from(a.as_str()), false, None));
+        assert!(module_graph.recorded_deterministic_ids(root).is_empty());
+
+        // the recorded ids of a cold build are kept, even if they'd be hashed differently
+        let b = root.join("src/b.ts").to_string_lossy().to_string();
+        write_deterministic_id_records(
+            root,
+            &HashMap::from([(a.clone(), "1".to_string()), (b, "2".to_string())]),
+        )
+        .unwrap();
+        let recorded = module_graph.recorded_deterministic_ids(root);
+        assert_eq!(recorded, HashMap::from([(a.clone(), "1".to_string())]));
+        let ids = module_graph.assign_deterministic_ids(root, "", &recorded);
+        assert_eq!(ids[&a], "1");
+    }
+
+    #[test]
+    fn test_id_range() {
+        assert_eq!(super::id_range(0), 1000);
+        assert_eq!(super::id_range(799), 1000);
+        assert_eq!(super::id_range(800), 10000);
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use petgraph::visit::Dfs;
+
+use crate::module::{Dependency, Module, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+
+/**
+ * a module built from a changed file, with the modules which its dependencies are resolved to,
+ * the modules of dependencies are empty modules (or external modules) which are only added to
+ * the graph if they don't exist
+ */
+pub struct ModulePatch {
+    pub module: Module,
+    pub dependencies: Vec<(Module, Dependency)>,
+}
+
+#[derive(Default, Debug)]
+pub struct ModuleGraphPatchResult {
+    // the added modules and the new modules of dependencies
+    pub added: HashSet<ModuleId>,
+    pub modified: HashSet<ModuleId>,
+    // the removed modules and the modules which are not reachable from entries anymore
+    pub removed: HashSet<ModuleId>,
+    // modules whose dependencies are changed, e.g. async import to import
+    pub dep_changed: HashSet<ModuleId>,
+    // empty modules of new dependencies, which should be built by the caller
+    pub unbuilt: HashSet<ModuleId>,
+    // modules which should be transformed again, modules which are built already are not
+    // included even if they are imported by new dependencies
+    pub dirty: HashSet<ModuleId>,
+}
+
+impl ModuleGraph {
+    /**
+     * patch the graph with changed modules for watch rebuilds, instead of rebuilding the graph
+     *
+     * 1. removed modules and their edges are removed
+     * 2. added and modified modules are put into the graph, edges are diffed with the current ones
+     * 3. the modules which are not reachable from entries anymore are removed
+     */
+    pub fn patch(
+        &mut self,
+        added: Vec<ModulePatch>,
+        modified: Vec<ModulePatch>,
+        removed: Vec<ModuleId>,
+    ) -> ModuleGraphPatchResult {
+        let mut result = ModuleGraphPatchResult::default();
+        // targets of the removed edges, which may be not reachable anymore
+        let mut detached = HashSet::new();
+
+        for module_id in removed {
+            if !self.has_module(&module_id) {
+                continue;
+            }
+            detached.extend(self.dependence_module_ids(&module_id));
+            self.remove_module(&module_id);
+            result.removed.insert(module_id);
+        }
+
+        for module_patch in added {
+            let module_id = self.apply_module_patch(module_patch, &mut result, &mut detached);
+            result.added.insert(module_id.clone());
+            result.dirty.insert(module_id);
+        }
+        for module_patch in modified {
+            let module_id = self.apply_module_patch(module_patch, &mut result, &mut detached);
+            result.modified.insert(module_id.clone());
+            result.dirty.insert(module_id);
+        }
+
+        self.remove_unreachable(detached, &mut result);
+        result
+    }
+
+    fn apply_module_patch(
+        &mut self,
+        module_patch: ModulePatch,
+        result: &mut ModuleGraphPatchResult,
+        detached: &mut HashSet<ModuleId>,
+    ) -> ModuleId {
+        let ModulePatch {
+            module,
+            dependencies,
+        } = module_patch;
+        let module_id = module.id.clone();
+        // edges of the module are kept when it's replaced, so that they can be diffed
+        self.add_module(module);
+
+        let current_dependencies = self
+            .get_dependencies(&module_id)
+            .into_iter()
+            .map(|(id, dep)| (id.clone(), dep.clone()))
+            .collect::<Vec<_>>();
+        let target_dependencies = dependencies
+            .iter()
+            .map(|(module, dep)| (module.id.clone(), dep.clone()))
+            .collect::<Vec<_>>();
+        let mut dependency_modules = dependencies
+            .into_iter()
+            .map(|(module, _)| (module.id.clone(), module))
+            .collect::<HashMap<_, _>>();
+        let d = diff(current_dependencies, target_dependencies);
+
+        for (to, dep) in &d.removed {
+            self.remove_dependency(&module_id, to, dep);
+            detached.insert(to.clone());
+        }
+
+        for (to, dep) in &d.added {
+            if !self.has_module(to)
+                && let Some(dependency_module) = dependency_modules.remove(to)
+            {
+                // external modules are created with info, only empty modules need to be built
+                if dependency_module.info.is_none() {
+                    result.unbuilt.insert(to.clone());
+                }
+                result.added.insert(to.clone());
+                self.add_module(dependency_module);
+            }
+            self.add_dependency(&module_id, to, dep.clone());
+            // if added dep is a dynamic dependency, need to full re-group
+            if dep.resolve_type == ResolveType::DynamicImport {
+                result.dep_changed.insert(module_id.clone());
+            }
+        }
+
+        for (to, origin_deps, deps) in &d.dep_changed {
+            // the whole edge weight is replaced, e.g. the specifiers or the order are changed
+            origin_deps
+                .iter()
+                .for_each(|dep| self.remove_dependency(&module_id, to, dep));
+            deps.iter()
+                .for_each(|dep| self.add_dependency(&module_id, to, dep.clone()));
+            // chunks are only grouped by resolve types
+            let resolve_types = |deps: &Vec<Dependency>| {
+                deps.iter()
+                    .map(|dep| dep.resolve_type)
+                    .collect::<HashSet<_>>()
+            };
+            if resolve_types(origin_deps) != resolve_types(deps) {
+                result.dep_changed.insert(module_id.clone());
+            }
+        }
+
+        module_id
+    }
+
+    // the modules which are only imported by the removed edges are removed too, so that they
+    // are not left in chunks, e.g. the only import of a module is deleted
+    fn remove_unreachable(
+        &mut self,
+        detached: HashSet<ModuleId>,
+        result: &mut ModuleGraphPatchResult,
+    ) {
+        if detached.is_empty() || self.entries.is_empty() {
+            return;
+        }
+
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|module_id| self.id_index_map[module_id]);
+        let mut dfs = Dfs::new(&self.graph, entries.next().unwrap());
+        loop {
+            while dfs.next(&self.graph).is_some() {}
+            match entries.next() {
+                Some(entry) => dfs.move_to(entry),
+                None => break,
+            }
+        }
+        let reachable = dfs.discovered;
+
+        let mut unreachable = HashSet::new();
+        let mut queue = detached.into_iter().collect::<VecDeque<_>>();
+        while let Some(module_id) = queue.pop_front() {
+            let Some(index) = self.id_index_map.get(&module_id) else {
+                continue;
+            };
+            if reachable.contains(index.index()) || !unreachable.insert(module_id.clone()) {
+                continue;
+            }
+            queue.extend(self.dependence_module_ids(&module_id));
+        }
+
+        for module_id in unreachable {
+            self.remove_module(&module_id);
+            result.added.remove(&module_id);
+            result.modified.remove(&module_id);
+            result.dep_changed.remove(&module_id);
+            result.unbuilt.remove(&module_id);
+            result.dirty.remove(&module_id);
+            result.removed.insert(module_id);
+        }
+    }
+}
+
+struct Diff {
+    added: Vec<(ModuleId, Dependency)>,
+    removed: Vec<(ModuleId, Dependency)>,
+    // module id, the origin dependencies and the target dependencies of the edge
+    dep_changed: Vec<(ModuleId, Vec<Dependency>, Vec<Dependency>)>,
+}
+
+// 对比两颗 Dependency 的差异
+fn diff(origin: Vec<(ModuleId, Dependency)>, target: Vec<(ModuleId, Dependency)>) -> Diff {
+    // an edge may have several dependencies, e.g. import and require of the same module
+    let group = |deps: Vec<(ModuleId, Dependency)>| {
+        let mut edges: HashMap<ModuleId, Vec<Dependency>> = HashMap::new();
+        deps.into_iter()
+            .for_each(|(module_id, dep)| edges.entry(module_id).or_default().push(dep));
+        edges
+    };
+    let mut origin_edges = group(origin);
+    let target_edges = group(target);
+
+    let mut added = vec![];
+    let mut dep_changed = vec![];
+    for (module_id, target_deps) in target_edges {
+        match origin_edges.remove(&module_id) {
+            None => added.extend(target_deps.into_iter().map(|dep| (module_id.clone(), dep))),
+            Some(origin_deps) => {
+                let origin_set = origin_deps.iter().collect::<HashSet<_>>();
+                let target_set = target_deps.iter().collect::<HashSet<_>>();
+                if origin_set != target_set {
+                    dep_changed.push((module_id, origin_deps, target_deps));
+                }
+            }
+        }
+    }
+    let removed = origin_edges
+        .into_iter()
+        .flat_map(|(module_id, deps)| deps.into_iter().map(move |dep| (module_id.clone(), dep)))
+        .collect();
+
+    Diff {
+        added,
+        removed,
+        dep_changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::ModulePatch;
+    use crate::module::{Module, ModuleId, ResolveType};
+    use crate::module_graph::ModuleGraph;
+    use crate::utils::test_helper::{add_module_edges, create_module_graph, dependency, module_id};
+
+    #[test]
+    fn test_patch_add_dependency() {
+        let mut graph = create_graph();
+        let result = graph.patch(
+            vec![],
+            vec![module_patch(
+                "a",
+                vec![
+                    ("b", ResolveType::Require),
+                    ("c", ResolveType::Require),
+                    ("d", ResolveType::DynamicImport),
+                ],
+            )],
+            vec![],
+        );
+        // c is in the graph already
+        assert_eq!(result.added, ids(&["d"]));
+        assert_eq!(result.unbuilt, ids(&["d"]));
+        assert_eq!(result.dirty, ids(&["a"]));
+        assert_eq!(result.dep_changed, ids(&["a"]));
+        assert!(result.removed.is_empty());
+        assert_eq!(
+            graph.get_reference(),
+            vec!["a -> b", "a -> c", "a -> d", "b -> c"]
+        );
+    }
+
+    #[test]
+    fn test_patch_remove_unreachable() {
+        let mut graph = create_graph();
+        add_module_edges(&mut graph, &[("a", "c", ResolveType::Require)]);
+        let result = graph.patch(
+            vec![],
+            vec![module_patch("a", vec![("c", ResolveType::Require)])],
+            vec![],
+        );
+        assert_eq!(result.removed, ids(&["b"]));
+        assert_eq!(graph.get_reference(), vec!["a -> c"]);
+
+        let result = graph.patch(vec![], vec![module_patch("a", vec![])], vec![]);
+        assert_eq!(result.removed, ids(&["c"]));
+        assert_eq!(graph.modules().len(), 1);
+    }
+
+    #[test]
+    fn test_patch_remove_module() {
+        let mut graph = create_graph();
+        let result = graph.patch(
+            vec![],
+            vec![module_patch("a", vec![])],
+            vec![module_id("b")],
+        );
+        assert_eq!(result.removed, ids(&["b", "c"]));
+        assert_eq!(result.modified, ids(&["a"]));
+        assert!(graph.get_reference().is_empty());
+    }
+
+    #[test]
+    fn test_patch_dep_changed() {
+        let mut graph = create_graph();
+        let result = graph.patch(
+            vec![],
+            vec![module_patch("a", vec![("b", ResolveType::DynamicImport)])],
+            vec![],
+        );
+        assert_eq!(result.dep_changed, ids(&["a"]));
+        assert!(result.added.is_empty());
+        assert_eq!(
+            graph
+                .get_dependencies(&module_id("a"))
+                .into_iter()
+                .map(|(_, dep)| dep.resolve_type)
+                .collect::<Vec<_>>(),
+            vec![ResolveType::DynamicImport]
+        );
+    }
+
+    #[test]
+    fn test_patch_dependency_updated() {
+        let mut graph = create_graph();
+        let mut patch = module_patch("a", vec![("b", ResolveType::Require)]);
+        patch.dependencies[0].1.order = 1;
+        patch.dependencies[0].1.source = "./b.js".to_string();
+        let result = graph.patch(vec![], vec![patch], vec![]);
+        // the resolve type is the same, chunks don't need to be re-grouped
+        assert!(result.dep_changed.is_empty());
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(
+            graph
+                .get_dependencies(&module_id("a"))
+                .into_iter()
+                .map(|(_, dep)| (dep.source.clone(), dep.order))
+                .collect::<Vec<_>>(),
+            vec![("./b.js".to_string(), 1)]
+        );
+    }
+
+    // a -> b -> c, a is the entry
+    fn create_graph() -> ModuleGraph {
+        create_module_graph(
+            "a",
+            &[
+                ("a", "b", ResolveType::Require),
+                ("b", "c", ResolveType::Require),
+            ],
+        )
+    }
+
+    fn module_patch(id: &str, deps: Vec<(&str, ResolveType)>) -> ModulePatch {
+        ModulePatch {
+            module: Module::new(module_id(id), id == "a", None),
+            dependencies: deps
+                .into_iter()
+                .map(|(to, resolve_type)| {
+                    (
+                        Module::new(module_id(to), false, None),
+                        dependency(to, resolve_type, 0),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> HashSet<ModuleId> {
+        ids.iter().map(|i| module_id(i)).collect()
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use glob_match::glob_match;
+
+use crate::generate::chunk::ChunkId;
+use crate::generate::chunk_graph::ChunkGraph;
+use crate::module::{Module, ModuleId, ModuleInfo};
+use crate::module_graph::ModuleGraph;
+use crate::resolve::{ResolvedResource, ResolverResource};
+
+type ModuleFilter<'a> = Box<dyn Fn(&Module) -> bool + 'a>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncClosure {
+    // modules loaded together with the entry, the entry first and then in bfs order of the
+    // dependencies
+    pub modules: Vec<ModuleId>,
+    // targets of dynamic imports and workers, in the order they are found, a target may be in
+    // modules too if it's also imported synchronously
+    pub async_boundaries: Vec<ModuleId>,
+}
+
+/**
+ * Chainable query of modules in the module graph, for plugins to do common analyses without
+ * walking the graph themselves.
+ *
+ * e.g.
+ * module_graph
+ *     .query()
+ *     .package("lodash")
+ *     .max_depth(2)
+ *     .map(|m| m.id.id.clone());
+ */
+pub struct ModuleGraphQuery<'a> {
+    module_graph: &'a ModuleGraph,
+    filters: Vec<ModuleFilter<'a>>,
+}
+
+impl ModuleGraph {
+    pub fn query(&self) -> ModuleGraphQuery<'_> {
+        ModuleGraphQuery {
+            module_graph: self,
+            filters: vec![],
+        }
+    }
+
+    // shortest depth of modules from the entries, entries are of depth 0
+    pub fn depths_from_entries(&self) -> HashMap<ModuleId, usize> {
+        let mut depths = HashMap::new();
+        let mut queue = VecDeque::new();
+        for entry in self.get_entry_modules() {
+            depths.insert(entry.clone(), 0);
+            queue.push_back(entry);
+        }
+        while let Some(module_id) = queue.pop_front() {
+            let depth = depths[&module_id];
+            for (dep_id, _) in self.get_dependencies(&module_id) {
+                if !depths.contains_key(dep_id) {
+                    depths.insert(dep_id.clone(), depth + 1);
+                    queue.push_back(dep_id.clone());
+                }
+            }
+        }
+        depths
+    }
+
+    // shortest import path from the entry to the target, both included, empty if the target is
+    // not reachable from the entry
+    pub fn why(&self, from_entry: &ModuleId, target: &ModuleId) -> Vec<ModuleId> {
+        if !self.has_module(from_entry) || !self.has_module(target) {
+            return vec![];
+        }
+        let mut parents: HashMap<ModuleId, Option<ModuleId>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        parents.insert(from_entry.clone(), None);
+        queue.push_back(from_entry.clone());
+        while let Some(module_id) = queue.pop_front() {
+            if &module_id == target {
+                let mut path = vec![module_id.clone()];
+                let mut current = &module_id;
+                while let Some(Some(parent)) = parents.get(current) {
+                    path.push(parent.clone());
+                    current = parent;
+                }
+                path.reverse();
+                return path;
+            }
+            for (dep_id, _) in self.get_dependencies(&module_id) {
+                if !parents.contains_key(dep_id) {
+                    parents.insert(dep_id.clone(), Some(module_id.clone()));
+                    queue.push_back(dep_id.clone());
+                }
+            }
+        }
+        vec![]
+    }
+
+    // walk outgoing edges from the entry but stop at async boundaries, i.e. what a chunk of the
+    // entry contains and which chunks it loads on demand
+    pub fn traverse_sync_closure(&self, entry: &ModuleId) -> SyncClosure {
+        let mut closure = SyncClosure {
+            modules: vec![],
+            async_boundaries: vec![],
+        };
+        if !self.has_module(entry) {
+            return closure;
+        }
+        let mut visited = HashSet::from([entry.clone()]);
+        let mut async_visited = HashSet::new();
+        let mut queue = VecDeque::from([entry.clone()]);
+        while let Some(module_id) = queue.pop_front() {
+            let mut deps = self.get_dependencies(&module_id);
+            deps.sort_by_key(|(_, dep)| dep.order);
+            for (dep_id, dep) in deps {
+                if dep.resolve_type.is_async_boundary() {
+                    if async_visited.insert(dep_id.clone()) {
+                        closure.async_boundaries.push(dep_id.clone());
+                    }
+                } else if visited.insert(dep_id.clone()) {
+                    queue.push_back(dep_id.clone());
+                }
+            }
+            closure.modules.push(module_id);
+        }
+        closure
+    }
+}
+
+impl<'a> ModuleGraphQuery<'a> {
+    pub fn filter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Module) -> bool + 'a,
+    {
+        self.filters.push(Box::new(f));
+        self
+    }
+
+    // glob pattern matched against the module id, e.g. `**/src/**/*.ts`
+    pub fn path_glob(self, pattern: &str) -> Self {
+        let pattern = pattern.to_string();
+        self.filter(move |module| glob_match(&pattern, &module.id.id))
+    }
+
+    // modules whose nearest package.json has the name
+    pub fn package(self, name: &str) -> Self {
+        let name = name.to_string();
+        self.filter(move |module| package_name(module).map_or(false, |n| n == name))
+    }
+
+    pub fn chunk(self, chunk_graph: &'a ChunkGraph, chunk_id: &ChunkId) -> Self {
+        let chunk = chunk_graph.chunk(chunk_id);
+        self.filter(move |module| chunk.map_or(false, |c| c.has_module(&module.id)))
+    }
+
+    // modules reachable from the entries within the depth, entries are of depth 0
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        let depths = self.module_graph.depths_from_entries();
+        self.filter(move |module| {
+            depths
+                .get(&module.id)
+                .map_or(false, |depth| *depth <= max_depth)
+        })
+    }
+
+    pub fn collect(self) -> Vec<&'a Module> {
+        let module_graph = self.module_graph;
+        module_graph
+            .modules()
+            .into_iter()
+            .filter(|module| self.filters.iter().all(|f| f(module)))
+            .collect()
+    }
+
+    pub fn ids(self) -> Vec<ModuleId> {
+        self.map(|module| module.id.clone())
+    }
+
+    pub fn map<T, F>(self, f: F) -> Vec<T>
+    where
+        F: Fn(&'a Module) -> T,
+    {
+        self.collect().into_iter().map(f).collect()
+    }
+
+    pub fn count(self) -> usize {
+        self.collect().len()
+    }
+}
+
+fn package_name(module: &Module) -> Option<String> {
+    match &module.info {
+        Some(ModuleInfo {
+            resolved_resource: Some(ResolverResource::Resolved(ResolvedResource(resolution))),
+            ..
+        }) => resolution.package_json().and_then(|r| r.name.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::module::{ModuleId, ResolveType};
+    use crate::module_graph::ModuleGraph;
+    use crate::utils::test_helper::{add_module_edges, create_module_graph};
+
+    #[test]
+    fn test_path_glob() {
+        let module_graph = create_graph();
+        assert_eq!(
+            ids(module_graph.query().path_glob("/src/**").ids()),
+            vec!["/src/a.ts", "/src/index.ts"]
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let module_graph = create_graph();
+        assert_eq!(
+            ids(module_graph.query().max_depth(1).ids()),
+            vec!["/node_modules/b/index.js", "/src/a.ts", "/src/index.ts"]
+        );
+        assert_eq!(module_graph.query().max_depth(2).count(), 4);
+    }
+
+    #[test]
+    fn test_filters_are_chained() {
+        let module_graph = create_graph();
+        assert_eq!(
+            ids(module_graph
+                .query()
+                .max_depth(1)
+                .filter(|m| m.is_node_module())
+                .ids()),
+            vec!["/node_modules/b/index.js"]
+        );
+    }
+
+    #[test]
+    fn test_why() {
+        let module_graph = create_graph();
+        let index = ModuleId::from("/src/index.ts");
+        assert_eq!(
+            module_graph
+                .why(&index, &ModuleId::from("/node_modules/c/index.js"))
+                .into_iter()
+                .map(|m| m.id)
+                .collect::<Vec<_>>(),
+            vec!["/src/index.ts", "/src/a.ts", "/node_modules/c/index.js"]
+        );
+        assert_eq!(module_graph.why(&index, &index), vec![index.clone()]);
+        assert!(module_graph
+            .why(
+                &ModuleId::from("/src/a.ts"),
+                &ModuleId::from("/node_modules/b/index.js")
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_traverse_sync_closure() {
+        let mut module_graph = create_graph();
+        add_module_edges(
+            &mut module_graph,
+            &[
+                ("/src/a.ts", "/src/lazy.ts", ResolveType::DynamicImport),
+                ("/src/index.ts", "/src/lazy.ts", ResolveType::DynamicImport),
+                ("/src/lazy.ts", "/src/a.ts", ResolveType::Require),
+                ("/src/index.ts", "/src/worker.ts", ResolveType::Worker),
+            ],
+        );
+        let index = ModuleId::from("/src/index.ts");
+        let lazy = ModuleId::from("/src/lazy.ts");
+
+        let closure = module_graph.traverse_sync_closure(&index);
+        assert_eq!(closure.modules[0], index);
+        assert_eq!(
+            ids(closure.modules),
+            vec![
+                "/node_modules/b/index.js",
+                "/node_modules/c/index.js",
+                "/src/a.ts",
+                "/src/index.ts"
+            ]
+        );
+        assert_eq!(
+            ids(closure.async_boundaries),
+            vec!["/src/lazy.ts", "/src/worker.ts"]
+        );
+
+        let closure = module_graph.traverse_sync_closure(&lazy);
+        assert_eq!(
+            ids(closure.modules),
+            vec!["/node_modules/c/index.js", "/src/a.ts", "/src/lazy.ts"]
+        );
+        assert_eq!(closure.async_boundaries, vec![lazy]);
+    }
+
+    fn ids(module_ids: Vec<ModuleId>) -> Vec<String> {
+        let mut ids = module_ids.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    // index -> a -> c
+    //       -> b
+    fn create_graph() -> ModuleGraph {
+        create_module_graph(
+            "/src/index.ts",
+            &[
+                ("/src/index.ts", "/src/a.ts", ResolveType::Require),
+                (
+                    "/src/index.ts",
+                    "/node_modules/b/index.js",
+                    ResolveType::Require,
+                ),
+                (
+                    "/src/a.ts",
+                    "/node_modules/c/index.js",
+                    ResolveType::Require,
+                ),
+            ],
+        )
+    }
+}
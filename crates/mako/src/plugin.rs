@@ -8,6 +8,8 @@ use swc_core::common::Mark;
 use swc_core::ecma::ast::Module;
 
 use crate::ast::file::{Content, File};
+use crate::ast::pipeline::CustomTransformStage;
+use crate::cache::CacheBackend;
 use crate::compiler::{Args, Compiler, Context};
 use crate::config::Config;
 use crate::generate::chunk_graph::ChunkGraph;
@@ -33,6 +35,81 @@ pub struct PluginTransformJsParam<'a> {
     pub unresolved_mark: Mark,
 }
 
+impl PluginLoadParam<'_> {
+    // the loaded content depends on the file, edits to it rebuild the module in watch mode
+    pub fn add_file_dependency<T: AsRef<Path>>(&self, file: T, context: &Context) {
+        context.add_file_dependency(&self.file.path.to_string_lossy(), file);
+    }
+}
+
+impl PluginTransformJsParam<'_> {
+    // the transformed module depends on the file, e.g. a graphql schema read by a codegen
+    // transform, edits to it rebuild the module in watch mode
+    pub fn add_file_dependency<T: AsRef<Path>>(&self, file: T, context: &Context) {
+        context.add_file_dependency(self.path, file);
+    }
+}
+
+pub struct PluginTransformStagesParam<'a> {
+    pub file: &'a File,
+    pub top_level_mark: Mark,
+    pub unresolved_mark: Mark,
+}
+
+impl PluginTransformStagesParam<'_> {
+    // the visitors of the stages depend on the file, edits to it rebuild the module in watch mode
+    pub fn add_file_dependency<T: AsRef<Path>>(&self, file: T, context: &Context) {
+        context.add_file_dependency(&self.file.path.to_string_lossy(), file);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginEmitAssetKind {
+    // static assets, e.g. images and fonts
+    Asset,
+    // source map of chunk files
+    SourceMap,
+}
+
+pub struct PluginEmitAssetParam<'a> {
+    pub kind: PluginEmitAssetKind,
+    // path relative to output.path
+    pub filename: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginEmitAsset {
+    Skip,
+    Rename(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginOutputKind {
+    // js and css chunk files
+    Chunk,
+    SourceMap,
+    Asset,
+}
+
+pub struct PluginTransformOutputParam<'a> {
+    pub kind: PluginOutputKind,
+    // path relative to output.path
+    pub filename: &'a str,
+}
+
+pub struct PluginChunkBannerParam<'a> {
+    pub chunk_file: &'a ChunkFile,
+    // chunk files of entries and workers
+    pub is_entry: bool,
+}
+
+pub struct PluginChunkNameParam<'a> {
+    // path of the module with the dynamic import
+    pub importer: &'a str,
+    // source of the dynamic import, e.g. `./pages/home`
+    pub source: &'a str,
+}
+
 #[derive(Clone)]
 pub struct PluginGenerateEndParams {
     pub is_first_compile: bool,
@@ -57,6 +134,11 @@ pub trait Plugin: Any + Send + Sync {
         Ok(None)
     }
 
+    // remote backend of the persistent cache, replaces cache.remote
+    fn cache_backend(&self, _config: &Config, _root: &Path) -> Option<Arc<dyn CacheBackend>> {
+        None
+    }
+
     fn next_build(&self, _next_build_param: &NextBuildParam) -> bool {
         true
     }
@@ -78,6 +160,16 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
+    // custom visitors of the js transform, which are placed before or after the named stages,
+    // e.g. after env_replacer, the order can be overridden with the transformStages config
+    fn transform_stages(
+        &self,
+        _param: &PluginTransformStagesParam,
+        _context: &Arc<Context>,
+    ) -> Result<Vec<CustomTransformStage>> {
+        Ok(vec![])
+    }
+
     fn after_generate_transform_js(
         &self,
         _param: &PluginTransformJsParam,
@@ -115,6 +207,11 @@ pub trait Plugin: Any + Send + Sync {
         Ok(None)
     }
 
+    // called when a build or a rebuild in watch mode fails
+    fn build_error(&self, _error: &anyhow::Error, _context: &Arc<Context>) -> Result<()> {
+        Ok(())
+    }
+
     fn generate_beg(&self, _context: &Arc<Context>) -> Result<()> {
         Ok(())
     }
@@ -155,6 +252,65 @@ pub trait Plugin: Any + Send + Sync {
     fn before_write_fs(&self, _path: &Path, _content: &[u8]) -> Result<()> {
         Ok(())
     }
+
+    // rename or skip an asset before it's emitted
+    // chunk files are not included since their names are referenced by runtime
+    fn emit_asset(
+        &self,
+        _param: &PluginEmitAssetParam,
+        _context: &Arc<Context>,
+    ) -> Result<Option<PluginEmitAsset>> {
+        Ok(None)
+    }
+
+    // texts prepended to chunk files, e.g. license headers or shebangs, source maps are
+    // adjusted for the prepended lines
+    fn chunk_banner(
+        &self,
+        _param: &PluginChunkBannerParam,
+        _context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    // texts appended to chunk files, before the source map url
+    fn chunk_footer(
+        &self,
+        _param: &PluginChunkBannerParam,
+        _context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    // override the name of the async chunk split at a dynamic import, `name` is the one
+    // from the magic comment or the previous plugins, chunk files are named `[name]-async.js`
+    fn chunk_name(
+        &self,
+        _param: &PluginChunkNameParam,
+        _name: Option<String>,
+        _context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    // transform the final bytes of a file right before it's written, e.g. signing or obfuscation,
+    // stats and manifest see the transformed sizes, but hashes in filenames are computed
+    // from the content before transform
+    // plugins implementing it should return true in has_transform_output
+    fn transform_output(
+        &self,
+        _param: &PluginTransformOutputParam,
+        _content: &mut Vec<u8>,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    // chunks are not written in pieces by chunkedEmit if true, since transform_output needs
+    // the whole content in memory
+    fn has_transform_output(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -192,6 +348,12 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn cache_backend(&self, config: &Config, root: &Path) -> Option<Arc<dyn CacheBackend>> {
+        self.plugins
+            .iter()
+            .find_map(|plugin| plugin.cache_backend(config, root))
+    }
+
     pub fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         for plugin in &self.plugins {
             let ret = plugin.load(param, context)?;
@@ -229,6 +391,23 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn transform_stages(
+        &self,
+        param: &PluginTransformStagesParam,
+        context: &Arc<Context>,
+    ) -> Result<Vec<CustomTransformStage>> {
+        let mut stages = vec![];
+        for plugin in &self.plugins {
+            stages.extend(plugin.transform_stages(param, context)?);
+        }
+        for stage in stages.iter_mut() {
+            if let Some(order) = context.config.transform_stages.get(&stage.name) {
+                stage.order = order.clone();
+            }
+        }
+        Ok(stages)
+    }
+
     pub fn after_generate_transform_js(
         &self,
         param: &PluginTransformJsParam,
@@ -289,6 +468,13 @@ impl PluginDriver {
         Ok(None)
     }
 
+    pub fn build_error(&self, error: &anyhow::Error, context: &Arc<Context>) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.build_error(error, context)?;
+        }
+        Ok(())
+    }
+
     pub fn generate_end(
         &self,
         param: &PluginGenerateEndParams,
@@ -370,4 +556,84 @@ impl PluginDriver {
 
         Ok(())
     }
+
+    // returns the final filename, or None if the asset should be skipped
+    pub fn emit_asset(
+        &self,
+        kind: PluginEmitAssetKind,
+        filename: String,
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        let mut filename = filename;
+        for p in &self.plugins {
+            let param = PluginEmitAssetParam {
+                kind,
+                filename: &filename,
+            };
+            match p.emit_asset(&param, context)? {
+                Some(PluginEmitAsset::Skip) => return Ok(None),
+                Some(PluginEmitAsset::Rename(new_filename)) => filename = new_filename,
+                None => {}
+            }
+        }
+        Ok(Some(filename))
+    }
+
+    pub fn chunk_banner(
+        &self,
+        param: &PluginChunkBannerParam,
+        context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        let mut banners = Vec::new();
+        for p in &self.plugins {
+            banners.extend(p.chunk_banner(param, context)?);
+        }
+        Ok(banners)
+    }
+
+    pub fn chunk_footer(
+        &self,
+        param: &PluginChunkBannerParam,
+        context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        let mut footers = Vec::new();
+        for p in &self.plugins {
+            footers.extend(p.chunk_footer(param, context)?);
+        }
+        Ok(footers)
+    }
+
+    pub fn chunk_name(
+        &self,
+        param: &PluginChunkNameParam,
+        name: Option<String>,
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        let mut name = name;
+        for p in &self.plugins {
+            if let Some(new_name) = p.chunk_name(param, name.clone(), context)? {
+                name = Some(new_name);
+            }
+        }
+        Ok(name)
+    }
+
+    pub fn transform_output(
+        &self,
+        kind: PluginOutputKind,
+        filename: &str,
+        content: Vec<u8>,
+        context: &Arc<Context>,
+    ) -> Result<Vec<u8>> {
+        let mut content = content;
+        let param = PluginTransformOutputParam { kind, filename };
+        for p in &self.plugins {
+            p.transform_output(&param, &mut content, context)?;
+        }
+        Ok(content)
+    }
+
+    pub fn has_transform_output(&self) -> bool {
+        self.plugins.iter().any(|p| p.has_transform_output())
+    }
 }
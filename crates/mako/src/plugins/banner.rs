@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use glob_match::glob_match;
+use serde_json::Value;
+
+use crate::compiler::Context;
+use crate::config::ChunkBannerConfig;
+use crate::generate::chunk_pot::util::file_content_hash;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::plugin::{Plugin, PluginChunkBannerParam};
+
+/**
+ * prepend `banner` and append `footer` to chunk files, e.g.
+ *
+ * { "banner": { "js": "#!/usr/bin/env node", "entryOnly": true } }
+ *
+ * texts are added as is, so comments should be written with the comment syntax
+ */
+pub struct BannerPlugin {}
+
+impl Plugin for BannerPlugin {
+    fn name(&self) -> &str {
+        "banner"
+    }
+
+    fn chunk_banner(
+        &self,
+        param: &PluginChunkBannerParam,
+        context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        Ok(chunk_text(context.config.banner.as_ref(), param)
+            .into_iter()
+            .collect())
+    }
+
+    fn chunk_footer(
+        &self,
+        param: &PluginChunkBannerParam,
+        context: &Arc<Context>,
+    ) -> Result<Vec<String>> {
+        Ok(chunk_text(context.config.footer.as_ref(), param)
+            .into_iter()
+            .collect())
+    }
+}
+
+fn chunk_text(
+    config: Option<&ChunkBannerConfig>,
+    param: &PluginChunkBannerParam,
+) -> Option<String> {
+    let config = config?;
+    if config.entry_only && !param.is_entry {
+        return None;
+    }
+    match param.chunk_file.file_type {
+        ChunkFileType::JS => config.js.clone(),
+        ChunkFileType::Css => config.css.clone(),
+    }
+}
+
+// add banners and footers of plugins to the chunk file, it's called before the content of chunk
+// files is used by others, e.g. chunk integrity, and the hash is computed again with them
+pub(crate) fn add_banners(
+    chunk_file: &mut ChunkFile,
+    is_entry: bool,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let param = PluginChunkBannerParam {
+        chunk_file,
+        is_entry,
+    };
+    let banners = context.plugin_driver.chunk_banner(&param, context)?;
+    let footers = context.plugin_driver.chunk_footer(&param, context)?;
+
+    if !banners.is_empty() {
+        let banner = format!("{}\n", banners.join("\n"));
+        if let Some(source_map) = &chunk_file.source_map {
+            let lines = banner.matches('\n').count();
+            chunk_file.source_map = Some(offset_source_map(source_map, lines)?);
+        }
+        chunk_file.content.splice(0..0, banner.into_bytes());
+    }
+    if !footers.is_empty() {
+        if !chunk_file.content.ends_with(b"\n") {
+            chunk_file.content.push(b'\n');
+        }
+        chunk_file
+            .content
+            .extend_from_slice(footers.join("\n").as_bytes());
+    }
+    if (!banners.is_empty() || !footers.is_empty()) && chunk_file.hash.is_some() {
+        chunk_file.hash = Some(file_content_hash(
+            &chunk_file.content,
+            &context.config.hash_salt,
+        ));
+    }
+    Ok(())
+}
+
+// generated lines are shifted down by the prepended lines, an empty line in mappings is `;`
+fn offset_source_map(source_map: &[u8], lines: usize) -> Result<Vec<u8>> {
+    let mut source_map: Value = serde_json::from_slice(source_map)?;
+    let mappings = source_map
+        .get("mappings")
+        .and_then(|mappings| mappings.as_str().map(|m| m.to_string()))
+        .ok_or_else(|| anyhow!("Add banner failed: mappings not found in the source map"))?;
+    source_map["mappings"] = Value::String(format!("{}{}", ";".repeat(lines), mappings));
+    Ok(serde_json::to_vec(&source_map)?)
+}
+
+// comment of the module in chunks, which is matched by the globs of path relative to root
+pub(crate) fn module_banner(relative_path: &str, context: &Context) -> Option<String> {
+    let config = context.config.banner.as_ref()?;
+    let relative_path = relative_path.trim_start_matches("./");
+    config
+        .modules
+        .iter()
+        .find(|module| {
+            module
+                .include
+                .iter()
+                .any(|glob| glob_match(glob, relative_path))
+        })
+        .map(|module| module.text.replace("*/", "*\\/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::Value;
+
+    use super::{add_banners, offset_source_map, BannerPlugin};
+    use crate::compiler::Context;
+    use crate::generate::chunk_pot::util::file_content_hash;
+    use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+    use crate::plugin::PluginDriver;
+
+    #[test]
+    fn test_add_banners_before_hash() {
+        let mut context = Context {
+            plugin_driver: PluginDriver::new(vec![Arc::new(BannerPlugin {})]),
+            ..Default::default()
+        };
+        context.config.banner = serde_json::from_str(r#"{ "js": "/*! license */" }"#).unwrap();
+        let context = Arc::new(context);
+        let mut chunk_file = ChunkFile {
+            raw_hash: 0,
+            content: b"console.log(1);".to_vec(),
+            source_map: None,
+            hash: Some(file_content_hash(b"console.log(1);", "")),
+            file_name: "index.js".to_string(),
+            chunk_id: "index".to_string(),
+            file_type: ChunkFileType::JS,
+        };
+        add_banners(&mut chunk_file, true, &context).unwrap();
+        assert_eq!(chunk_file.content, b"/*! license */\nconsole.log(1);");
+        assert_eq!(
+            chunk_file.hash,
+            Some(file_content_hash(&chunk_file.content, ""))
+        );
+    }
+
+    #[test]
+    fn test_offset_source_map() {
+        let source_map = br#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA;AACA"}"#;
+        let source_map: Value =
+            serde_json::from_slice(&offset_source_map(source_map, 2).unwrap()).unwrap();
+        assert_eq!(source_map["mappings"], ";;AAAA;AACA");
+        assert_eq!(source_map["sources"][0], "a.js");
+    }
+}
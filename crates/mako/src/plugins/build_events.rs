@@ -0,0 +1,207 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+
+/**
+ * notify build lifecycle events, for dashboards or desktop notifications without wrapping
+ * the process
+ *
+ * events are posted as json to `url`, and written as json lines to the unix `socket`, e.g.
+ * {"event":"start","mode":"development","root":"/path/to/project"}
+ * {"event":"success","isFirstCompile":true,"time":1024,"assets":3,"size":40960,"warnings":0}
+ * {"event":"failure","error":"..."}
+ *
+ * failures of notifications are reported as warnings, they never fail the build
+ */
+pub struct BuildEventsPlugin {}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "camelCase")]
+enum BuildEvent {
+    #[serde(rename_all = "camelCase")]
+    Start {
+        mode: String,
+        root: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Success {
+        is_first_compile: bool,
+        time: u64,
+        assets: usize,
+        size: u64,
+        warnings: usize,
+    },
+    Failure {
+        error: String,
+    },
+}
+
+impl Plugin for BuildEventsPlugin {
+    fn name(&self) -> &str {
+        "build_events"
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<Option<()>> {
+        notify(
+            context,
+            &BuildEvent::Start {
+                mode: context.config.mode.to_string(),
+                root: context.root.to_string_lossy().to_string(),
+            },
+        );
+        Ok(None)
+    }
+
+    fn generate_end(
+        &self,
+        params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        let assets = context.stats_info.get_assets();
+        notify(
+            context,
+            &BuildEvent::Success {
+                is_first_compile: params.is_first_compile,
+                time: params.time,
+                assets: assets.len(),
+                size: assets.iter().map(|asset| asset.size).sum(),
+                warnings: context.stats_info.get_warnings().len(),
+            },
+        );
+        Ok(None)
+    }
+
+    fn build_error(&self, error: &anyhow::Error, context: &Arc<Context>) -> Result<()> {
+        notify(
+            context,
+            &BuildEvent::Failure {
+                error: strip_ansi(&error.to_string()),
+            },
+        );
+        Ok(())
+    }
+}
+
+fn notify(context: &Arc<Context>, event: &BuildEvent) {
+    let Some(config) = &context.config.build_events else {
+        return;
+    };
+    let body = serde_json::to_string(event).unwrap();
+    let mut results = vec![];
+    if let Some(url) = &config.url {
+        results.push(post(url, body.clone()));
+    }
+    if let Some(socket) = &config.socket {
+        results.push(write_socket(&context.root.join(socket), &body));
+    }
+    for err in results.into_iter().filter_map(Result::err) {
+        println!(
+            "{}: buildEvents: failed to notify: {}",
+            "warning".to_string().yellow(),
+            err
+        );
+    }
+}
+
+// the hooks may be called inside the tokio runtime of the dev server, so the request is sent
+// with its own runtime in another thread
+fn post(url: &str, body: String) -> Result<()> {
+    let url = url.to_string();
+    std::thread::spawn(move || -> Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let client: Client<HttpConnector> = Client::new();
+            let req = Request::builder()
+                .method(Method::POST)
+                .uri(&url)
+                .header("content-type", "application/json")
+                .body(Body::from(body))?;
+            let res = client.request(req).await?;
+            if !res.status().is_success() {
+                return Err(anyhow!("POST {} failed with {}", url, res.status()));
+            }
+            Ok(())
+        })
+    })
+    .join()
+    .map_err(|_| anyhow!("the notification thread panicked"))?
+}
+
+#[cfg(unix)]
+fn write_socket(path: &Path, body: &str) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path)
+        .map_err(|e| anyhow!("failed to connect to {}: {}", path.display(), e))?;
+    stream.write_all(format!("{}\n", body).as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_socket(_path: &Path, _body: &str) -> Result<()> {
+    Err(anyhow!("socket is only supported on unix"))
+}
+
+fn strip_ansi(s: &str) -> String {
+    let ansi_regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    ansi_regex.replace_all(s, "").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_ansi, BuildEvent};
+
+    #[test]
+    fn test_event_json() {
+        assert_eq!(
+            serde_json::to_string(&BuildEvent::Success {
+                is_first_compile: true,
+                time: 1024,
+                assets: 3,
+                size: 40960,
+                warnings: 0,
+            })
+            .unwrap(),
+            r#"{"event":"success","isFirstCompile":true,"time":1024,"assets":3,"size":40960,"warnings":0}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&BuildEvent::Failure {
+                error: strip_ansi("\x1b[31mModule not found\x1b[0m")
+            })
+            .unwrap(),
+            r#"{"event":"failure","error":"Module not found"}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_socket() {
+        use std::io::Read;
+        use std::os::unix::net::UnixListener;
+
+        let dir = crate::utils::test_helper::create_temp_dir("build_events");
+        let path = dir.path().join("events.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        super::write_socket(&path, r#"{"event":"failure","error":""}"#).unwrap();
+        let mut received = String::new();
+        listener
+            .accept()
+            .unwrap()
+            .0
+            .read_to_string(&mut received)
+            .unwrap();
+        assert_eq!(received, "{\"event\":\"failure\",\"error\":\"\"}\n");
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use crate::compiler::{Compiler, Context};
+use crate::config::BundlePolicyConfig;
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::plugins::duplicate_packages::{import_chain, relative_path};
+
+/**
+ * enforce bundlePolicy
+ *
+ * - forbidden packages (or packages not in the allow list) fail the build as soon as they are
+ *   resolved during the graph construction, see check_package_allowed
+ * - size caps of packages are checked after build, since sizes are only known by then
+ *
+ * errors come with the import chain, e.g.
+ *
+ * moment is forbidden by bundlePolicy
+ *   src/index.tsx -> src/utils/date.ts -> node_modules/moment/moment.js
+ */
+pub struct BundlePolicyPlugin {}
+
+impl Plugin for BundlePolicyPlugin {
+    fn name(&self) -> &str {
+        "bundle_policy"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        let Some(config) = &context.config.bundle_policy else {
+            return Ok(());
+        };
+        if config.max_size.is_empty() {
+            return Ok(());
+        }
+        let module_graph = context.module_graph.read().unwrap();
+
+        // package name -> (size, first module)
+        let mut packages: BTreeMap<String, (usize, ModuleId)> = BTreeMap::new();
+        let mut modules = module_graph.modules();
+        modules.sort_by(|a, b| a.id.id.cmp(&b.id.id));
+        for module in modules {
+            if let Some(package_file) = package_file(&module.id.id)
+                && let Some(name) = package_name(&package_file)
+                && config.max_size.contains_key(name)
+            {
+                let (size, _) = packages
+                    .entry(name.to_string())
+                    .or_insert_with(|| (0, module.id.clone()));
+                *size += module.get_module_size();
+            }
+        }
+
+        let errors = packages
+            .into_iter()
+            .filter(|(name, (size, _))| *size > config.max_size[name])
+            .map(|(name, (size, module_id))| {
+                format!(
+                    "{} is {} bytes, which exceeds {} bytes of bundlePolicy.maxSize\n  {}",
+                    name,
+                    size,
+                    config.max_size[&name],
+                    chain_to_string(&import_chain(&module_graph, &module_id), context)
+                )
+            })
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(anyhow!(errors.join("\n")));
+        }
+        Ok(())
+    }
+}
+
+// called when a new module is resolved, before it's built
+pub(crate) fn check_package_allowed(
+    path: &str,
+    importer: &ModuleId,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let Some(config) = &context.config.bundle_policy else {
+        return Ok(());
+    };
+    let Some(package_file) = package_file(path) else {
+        return Ok(());
+    };
+    let (target, reason) = if let Some(entry) = forbidden_by(config, &package_file) {
+        (entry.to_string(), "is forbidden by bundlePolicy")
+    } else if !is_allowed(config, &package_file) {
+        (package_file, "is not in bundlePolicy.allow")
+    } else {
+        return Ok(());
+    };
+    let mut chain = import_chain(module_graph, importer);
+    chain.push(ModuleId::new(path.to_string()));
+    Err(anyhow!(
+        "{} {}\n  {}",
+        target,
+        reason,
+        chain_to_string(&chain, context)
+    ))
+}
+
+fn forbidden_by<'a>(config: &'a BundlePolicyConfig, package_file: &str) -> Option<&'a String> {
+    config
+        .forbid
+        .iter()
+        .find(|entry| matches_entry(entry, package_file))
+}
+
+fn is_allowed(config: &BundlePolicyConfig, package_file: &str) -> bool {
+    config.allow.as_ref().map_or(true, |allow| {
+        allow.iter().any(|entry| matches_entry(entry, package_file))
+    })
+}
+
+// entries are package names, or files and directories in packages, e.g. `lodash/lodash.js`
+// for the full build of lodash
+fn matches_entry(entry: &str, package_file: &str) -> bool {
+    let entry = entry.replace('\\', "/");
+    let entry = entry.trim_end_matches('/');
+    package_file == entry
+        || package_file
+            .strip_prefix(entry)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+// path of the file relative to node_modules, by the last node_modules in it, e.g.
+// `@scope/foo/lib/index.js`, separators are normalized to `/`
+fn package_file(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let package_file = &path[path.rfind("node_modules/")? + "node_modules/".len()..];
+    package_name(package_file)?;
+    Some(package_file.to_string())
+}
+
+// name of the package of a package file
+fn package_name(package_file: &str) -> Option<&str> {
+    let mut segments = package_file.splitn(3, '/');
+    let first = segments.next().filter(|first| !first.is_empty())?;
+    if first.starts_with('@') {
+        let second = segments.next()?;
+        Some(&package_file[..first.len() + 1 + second.len()])
+    } else {
+        Some(first)
+    }
+}
+
+fn chain_to_string(chain: &[ModuleId], context: &Arc<Context>) -> String {
+    chain
+        .iter()
+        .map(|id| relative_path(&id.id, &context.root))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{forbidden_by, is_allowed, package_file, package_name};
+    use crate::config::BundlePolicyConfig;
+
+    #[test]
+    fn test_package_file() {
+        assert_eq!(package_file("/root/src/index.ts"), None);
+        assert_eq!(
+            package_file("/root/node_modules/moment/moment.js").as_deref(),
+            Some("moment/moment.js")
+        );
+        assert_eq!(
+            package_file("/root/node_modules/.pnpm/lodash@4.17.21/node_modules/lodash/lodash.js")
+                .as_deref(),
+            Some("lodash/lodash.js")
+        );
+        assert_eq!(
+            package_file("C:\\root\\node_modules\\@scope\\foo\\lib\\index.js").as_deref(),
+            Some("@scope/foo/lib/index.js")
+        );
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("moment/moment.js"), Some("moment"));
+        assert_eq!(package_name("@scope/foo/lib/index.js"), Some("@scope/foo"));
+    }
+
+    #[test]
+    fn test_forbid_and_allow() {
+        let config: BundlePolicyConfig =
+            serde_json::from_str(r#"{ "forbid": ["moment"], "allow": ["moment", "react"] }"#)
+                .unwrap();
+        assert!(forbidden_by(&config, "moment/moment.js").is_some());
+        assert!(forbidden_by(&config, "react/index.js").is_none());
+        assert!(forbidden_by(&config, "moment-timezone/index.js").is_none());
+        assert!(is_allowed(&config, "react/index.js"));
+        assert!(!is_allowed(&config, "lodash/lodash.js"));
+    }
+
+    #[test]
+    fn test_forbid_package_files() {
+        let config: BundlePolicyConfig = serde_json::from_str(
+            r#"{ "forbid": ["lodash/lodash.js", "antd\\dist\\"], "allow": ["lodash/fp", "antd"] }"#,
+        )
+        .unwrap();
+        // the full build of lodash is forbidden, but not the per-method modules
+        assert_eq!(
+            forbidden_by(&config, "lodash/lodash.js").map(|entry| entry.as_str()),
+            Some("lodash/lodash.js")
+        );
+        assert!(forbidden_by(&config, "lodash/map.js").is_none());
+        assert!(forbidden_by(&config, "antd/dist/antd.js").is_some());
+        assert!(forbidden_by(&config, "antd/es/button/index.js").is_none());
+        assert!(is_allowed(&config, "lodash/fp/map.js"));
+        assert!(!is_allowed(&config, "lodash/map.js"));
+    }
+}
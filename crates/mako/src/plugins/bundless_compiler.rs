@@ -9,12 +9,14 @@ use pathdiff::diff_paths;
 use rayon::prelude::*;
 use swc_core::common::errors::HANDLER;
 use swc_core::common::GLOBALS;
+use swc_core::ecma::transforms::base::feature::FeatureFlag;
 use swc_core::ecma::transforms::base::fixer::fixer;
 use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
 use swc_core::ecma::transforms::base::hygiene;
 use swc_core::ecma::transforms::base::hygiene::hygiene_with_config;
+use swc_core::ecma::transforms::module::common_js;
 use swc_core::ecma::transforms::module::import_analysis::import_analyzer;
-use swc_core::ecma::transforms::module::util::ImportInterop;
+use swc_core::ecma::transforms::module::util::{Config as ModuleConfig, ImportInterop};
 use swc_core::ecma::visit::VisitMutWith;
 use swc_error_reporters::handler::try_with_handler;
 use tracing::warn;
@@ -239,6 +241,29 @@ fn transform_js_generate(
                             let mut dynamic_import = DynamicImport::new(context.clone(), dep_map);
                             ast.ast.visit_mut_with(&mut dynamic_import);
 
+                            // output cjs format, e.g. the cjs build of library mode
+                            if context.config.cjs {
+                                ast.ast.visit_mut_with(&mut common_js(
+                                    ast.unresolved_mark,
+                                    ModuleConfig {
+                                        import_interop: Some(import_interop),
+                                        preserve_import_meta: true,
+                                        allow_top_level_this: true,
+                                        ..Default::default()
+                                    },
+                                    FeatureFlag::empty(),
+                                    Some(
+                                        context
+                                            .meta
+                                            .script
+                                            .origin_comments
+                                            .read()
+                                            .unwrap()
+                                            .get_swc_comments(),
+                                    ),
+                                ));
+                            }
+
                             ast.ast
                                 .visit_mut_with(&mut hygiene_with_config(hygiene::Config {
                                     top_level_mark,
@@ -290,7 +315,7 @@ pub fn to_dist_path<P: AsRef<str>>(abs_path: P, context: &Arc<Context>) -> PathB
     }
 }
 
-fn normalize_extension(to: PathBuf) -> PathBuf {
+pub(crate) fn normalize_extension(to: PathBuf) -> PathBuf {
     if let Some(ext) = to.extension() {
         let ext = ext.to_str().unwrap();
 
@@ -0,0 +1,55 @@
+/* mako/runtime/chunkIntegrity */
+!(function () {
+  // chunk id -> fnv-1a hash of the chunk content, filled after chunks are generated
+  var chunkIntegrity = JSON.parse(atob('__mako_chunk_integrity__'));
+  var onCorruptChunk = __mako_on_corrupt_chunk__;
+  // 32-bit fnv-1a over utf-16 code units
+  function hash(text) {
+    var h = 0x811c9dc5;
+    for (var i = 0; i < text.length; i++) {
+      h ^= text.charCodeAt(i);
+      h = Math.imul(h, 0x01000193);
+    }
+    return (h >>> 0).toString(16);
+  }
+  // the source map link is appended after the integrity is computed
+  function stripSourceMappingURL(text) {
+    return text.replace(/\n\/\/# sourceMappingURL=[^\n]*$/, '');
+  }
+  var loadScript = requireModule.loadScript;
+  if (!loadScript) return;
+  requireModule.loadScript = function (url, done, key) {
+    var chunkId =
+      key && key.indexOf('chunk-') === 0 ? key.slice('chunk-'.length) : null;
+    var expected = chunkId !== null && chunkIntegrity[chunkId];
+    if (!expected || typeof fetch !== 'function') {
+      return loadScript(url, done, key);
+    }
+    fetch(url)
+      .then(function (res) {
+        if (!res.ok) throw new Error(res.status);
+        return res.text();
+      })
+      .then(
+        function (text) {
+          var actual = hash(stripSourceMappingURL(text));
+          if (actual !== expected) {
+            if (onCorruptChunk) {
+              onCorruptChunk({
+                chunkId: chunkId,
+                url: url,
+                expected: expected,
+                actual: actual,
+              });
+            }
+            return done({ type: 'corrupt', target: { src: url } });
+          }
+          (0, eval)(text + '\n//# sourceURL=' + url);
+          done({ type: 'load', target: { src: url } });
+        },
+        function () {
+          done({ type: 'error', target: { src: url } });
+        },
+      );
+  };
+})();
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::compiler::Context;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::plugin::Plugin;
+use crate::utils::base64_encode;
+
+const INTEGRITY_PLACEHOLDER: &str = "__mako_chunk_integrity__";
+
+/**
+ * verify async js chunks in the loading runtime, for the cases subresource integrity isn't
+ * applicable, e.g. importScripts in workers
+ *
+ * chunks are fetched, hashed and compared with the hashes embedded in entry chunks before
+ * they are evaluated, corrupted chunks (e.g. truncated cdn responses) fail to load with the
+ * `corrupt` type and are reported to `chunkIntegrity.onCorruptChunk`
+ */
+pub struct ChunkIntegrityPlugin {}
+
+impl ChunkIntegrityPlugin {
+    // the emitted content of chunks is unknown if plugins transform the output
+    fn enabled(context: &Context) -> bool {
+        context.config.chunk_integrity.is_some()
+            && !context.args.watch
+            && !context.plugin_driver.has_transform_output()
+    }
+}
+
+impl Plugin for ChunkIntegrityPlugin {
+    fn name(&self) -> &str {
+        "chunk_integrity"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        let Some(config) = &context.config.chunk_integrity else {
+            return Ok(vec![]);
+        };
+        if !Self::enabled(context) {
+            return Ok(vec![]);
+        }
+        let on_corrupt_chunk = config.on_corrupt_chunk.as_deref().unwrap_or("null");
+        Ok(vec![include_str!("chunk_integrity.js")
+            .replace("__mako_on_corrupt_chunk__", on_corrupt_chunk)])
+    }
+}
+
+// fill the integrity of normal js chunks into the runtime of entry chunks,
+// it's called after the content of normal chunks is settled, the source map link which is
+// appended when chunks are emitted is stripped in the runtime before hashing
+pub(crate) fn embed_integrity(
+    entry_chunk_files: &mut [ChunkFile],
+    normal_chunk_files: &[ChunkFile],
+    context: &Arc<Context>,
+) -> Result<()> {
+    if !ChunkIntegrityPlugin::enabled(context) {
+        if context.config.chunk_integrity.is_some()
+            && !context.args.watch
+            && context.plugin_driver.has_transform_output()
+        {
+            let message = "chunkIntegrity is ignored since plugins transform the output";
+            println!("{}: {}", "warning".to_string().yellow(), message);
+            context.stats_info.add_warning(message.to_string());
+        }
+        return Ok(());
+    }
+    let integrity = normal_chunk_files
+        .iter()
+        .filter(|chunk_file| matches!(chunk_file.file_type, ChunkFileType::JS))
+        .map(|chunk_file| {
+            (
+                chunk_file.chunk_id.clone(),
+                hash(&String::from_utf8_lossy(&chunk_file.content)),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    let encoded = base64_encode(ascii_json(&serde_json::to_string(&integrity)?));
+    for chunk_file in entry_chunk_files
+        .iter_mut()
+        .filter(|chunk_file| matches!(chunk_file.file_type, ChunkFileType::JS))
+    {
+        let position = chunk_file
+            .content
+            .windows(INTEGRITY_PLACEHOLDER.len())
+            .position(|w| w == INTEGRITY_PLACEHOLDER.as_bytes())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Generate \"{}\" failed, placeholder of chunk integrity not existed in chunk file.",
+                    chunk_file.chunk_id
+                )
+            })?;
+        chunk_file.content.splice(
+            position..position + INTEGRITY_PLACEHOLDER.len(),
+            encoded.bytes(),
+        );
+    }
+    Ok(())
+}
+
+// same as the hash function in chunk_integrity.js
+fn hash(text: &str) -> String {
+    let hash = text.encode_utf16().fold(0x811c9dc5u32, |hash, unit| {
+        (hash ^ unit as u32).wrapping_mul(0x01000193)
+    });
+    format!("{:x}", hash)
+}
+
+// escape non-ascii chars, since the json is decoded by atob in the runtime
+fn ascii_json(json: &str) -> String {
+    json.encode_utf16()
+        .map(|unit| {
+            if unit < 0x80 {
+                (unit as u8 as char).to_string()
+            } else {
+                format!("\\u{:04x}", unit)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ascii_json, hash};
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(""), "811c9dc5");
+        assert_eq!(hash("a"), "e40c292c");
+        assert_ne!(hash("console.log(1);"), hash("console.log(1)"));
+    }
+
+    #[test]
+    fn test_ascii_json() {
+        assert_eq!(ascii_json(r#"{"页面":"1"}"#), r#"{"\u9875\u9762":"1"}"#);
+    }
+}
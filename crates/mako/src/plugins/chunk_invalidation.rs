@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::compiler::Context;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::module::ModuleId;
+use crate::plugin::Plugin;
+use crate::plugins::duplicate_packages::relative_path;
+
+pub(crate) fn default_records_path() -> PathBuf {
+    PathBuf::from("node_modules/.cache_mako/chunk-records.json")
+}
+
+/**
+ * explain why long-term caches are busted, by recording the modules of each js chunk
+ * and diffing them with the records of the last build, e.g.
+ *
+ * chunk vendors.js is invalidated, vendors.3f2a1b.js -> vendors.9c8d7e.js
+ *   changed: node_modules/foo/index.js
+ *   added: node_modules/bar/index.js
+ */
+pub struct ChunkInvalidationPlugin {}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct Records {
+    // chunk file name without hash -> record
+    chunks: BTreeMap<String, ChunkRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ChunkRecord {
+    file: String,
+    hash: String,
+    // module path relative to root -> content hash
+    modules: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ChunkDiff {
+    changed: Vec<String>,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl Plugin for ChunkInvalidationPlugin {
+    fn name(&self) -> &str {
+        "chunk_invalidation"
+    }
+
+    fn after_generate_chunk_files(
+        &self,
+        chunk_files: &[ChunkFile],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let Some(config) = &context.config.chunk_invalidation else {
+            return Ok(());
+        };
+        if context.args.watch {
+            return Ok(());
+        }
+        let records_path = context.root.join(&config.records);
+        let last_records = fs::read_to_string(&records_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Records>(&content).ok());
+        let records = create_records(chunk_files, context);
+
+        if let Some(last_records) = last_records {
+            for (name, record) in &records.chunks {
+                let Some(last_record) = last_records.chunks.get(name) else {
+                    continue;
+                };
+                if last_record.hash == record.hash {
+                    continue;
+                }
+                println!("{}", report(name, last_record, record));
+            }
+        }
+
+        if let Some(parent) = records_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(records_path, serde_json::to_string_pretty(&records)?)?;
+        Ok(())
+    }
+}
+
+fn create_records(chunk_files: &[ChunkFile], context: &Arc<Context>) -> Records {
+    let module_graph = context.module_graph.read().unwrap();
+    let chunk_graph = context.chunk_graph.read().unwrap();
+    let mut records = Records::default();
+    for chunk_file in chunk_files {
+        if !matches!(chunk_file.file_type, ChunkFileType::JS) {
+            continue;
+        }
+        let Some(chunk) = chunk_graph.chunk(&ModuleId::new(chunk_file.chunk_id.clone())) else {
+            continue;
+        };
+        let modules = chunk
+            .modules
+            .iter()
+            .filter_map(|module_id| {
+                let info = module_graph.get_module(module_id)?.info.as_ref()?;
+                Some((
+                    relative_path(&module_id.id, &context.root),
+                    format!("{:x}", md5::compute(&info.raw)),
+                ))
+            })
+            .collect();
+        records.chunks.insert(
+            chunk_file.file_name.clone(),
+            ChunkRecord {
+                file: chunk_file.disk_name(),
+                hash: format!("{:x}", md5::compute(&chunk_file.content)),
+                modules,
+            },
+        );
+    }
+    records
+}
+
+fn diff(last_record: &ChunkRecord, record: &ChunkRecord) -> ChunkDiff {
+    let mut diff = ChunkDiff::default();
+    for (module, hash) in &record.modules {
+        match last_record.modules.get(module) {
+            Some(last_hash) if last_hash != hash => diff.changed.push(module.clone()),
+            Some(_) => {}
+            None => diff.added.push(module.clone()),
+        }
+    }
+    for module in last_record.modules.keys() {
+        if !record.modules.contains_key(module) {
+            diff.removed.push(module.clone());
+        }
+    }
+    diff
+}
+
+fn report(name: &str, last_record: &ChunkRecord, record: &ChunkRecord) -> String {
+    let mut message = format!("chunk {} is invalidated", name.yellow());
+    if last_record.file != record.file {
+        message.push_str(&format!(", {} -> {}", last_record.file, record.file));
+    }
+    let diff = diff(last_record, record);
+    if diff == ChunkDiff::default() {
+        message.push_str(
+            "\n  no module is changed, module ids, the runtime or the config may be changed",
+        );
+        return message;
+    }
+    for (kind, modules) in [
+        ("changed", &diff.changed),
+        ("added", &diff.added),
+        ("removed", &diff.removed),
+    ] {
+        for module in modules {
+            message.push_str(&format!("\n  {}: {}", kind, module));
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{diff, ChunkDiff, ChunkRecord};
+
+    #[test]
+    fn test_diff() {
+        let last_record = record(&[("a.js", "1"), ("b.js", "2"), ("c.js", "3")]);
+        let current_record = record(&[("a.js", "1"), ("b.js", "4"), ("d.js", "5")]);
+        assert_eq!(
+            diff(&last_record, &current_record),
+            ChunkDiff {
+                changed: vec!["b.js".to_string()],
+                added: vec!["d.js".to_string()],
+                removed: vec!["c.js".to_string()],
+            }
+        );
+    }
+
+    fn record(modules: &[(&str, &str)]) -> ChunkRecord {
+        ChunkRecord {
+            file: "vendors.js".to_string(),
+            hash: "".to_string(),
+            modules: modules
+                .iter()
+                .map(|(module, hash)| (module.to_string(), hash.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+/* mako/runtime/chunkLoadTelemetry */
+!(function () {
+  var ensure = requireModule.ensure;
+  if (!ensure) return;
+  var STORAGE_KEY = 'mako:chunkLoadLatency';
+  var defaultLatency = __mako_chunk_load_latency__;
+  var latencyOverride = null;
+  var records = [];
+  var listeners = [];
+  function now() {
+    return typeof performance !== 'undefined' ? performance.now() : Date.now();
+  }
+  function getLatency() {
+    if (latencyOverride !== null) return latencyOverride;
+    try {
+      var stored = localStorage.getItem(STORAGE_KEY);
+      if (stored !== null) return Number(stored) || 0;
+    } catch (e) {}
+    return defaultLatency;
+  }
+  function report(record) {
+    records.push(record);
+    listeners.forEach(function (listener) {
+      listener(record);
+    });
+  }
+  requireModule.ensure = function (chunkId) {
+    var latency = getLatency();
+    var start = now();
+    var delayed = latency
+      ? new Promise(function (resolve) {
+          setTimeout(resolve, latency);
+        })
+      : Promise.resolve();
+    return delayed
+      .then(function () {
+        return ensure(chunkId);
+      })
+      .then(
+        function (result) {
+          report({
+            chunkId: chunkId,
+            status: 'loaded',
+            start: start,
+            duration: now() - start,
+            latency: latency,
+          });
+          return result;
+        },
+        function (error) {
+          report({
+            chunkId: chunkId,
+            status: 'failed',
+            start: start,
+            duration: now() - start,
+            latency: latency,
+          });
+          throw error;
+        },
+      );
+  };
+  global.__mako_chunk_load_telemetry__ = {
+    // loads of chunks, including the ones already loaded
+    getRecords: function () {
+      return records.slice();
+    },
+    clearRecords: function () {
+      records = [];
+    },
+    onChunkLoad: function (listener) {
+      listeners.push(listener);
+      return function () {
+        listeners = listeners.filter(function (l) {
+          return l !== listener;
+        });
+      };
+    },
+    getLatency: getLatency,
+    // persisted in localStorage, so it applies to chunks loaded before the app starts after reload
+    setLatency: function (ms) {
+      latencyOverride = ms === null || ms === undefined ? null : Number(ms) || 0;
+      try {
+        if (ms === null || ms === undefined) {
+          localStorage.removeItem(STORAGE_KEY);
+        } else {
+          localStorage.setItem(STORAGE_KEY, String(ms));
+        }
+      } catch (e) {}
+    },
+  };
+})();
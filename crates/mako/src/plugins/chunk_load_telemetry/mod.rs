@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::compiler::Context;
+use crate::plugin::Plugin;
+
+/**
+ * record the loading of async chunks in the dev runtime, and optionally delay them to
+ * simulate slow networks
+ *
+ * the records and the latency are exposed by `__mako_chunk_load_telemetry__` on the global,
+ * e.g. `__mako_chunk_load_telemetry__.setLatency(3000)` in the devtools console
+ */
+pub struct ChunkLoadTelemetryPlugin {}
+
+impl Plugin for ChunkLoadTelemetryPlugin {
+    fn name(&self) -> &str {
+        "chunk_load_telemetry"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        let Some(config) = &context.config.chunk_load_telemetry else {
+            return Ok(vec![]);
+        };
+        // it's for the dev server only, never shipped in builds
+        if !context.args.watch {
+            return Ok(vec![]);
+        }
+        Ok(vec![include_str!("chunk_load_telemetry.js").replace(
+            "__mako_chunk_load_latency__",
+            &config.latency.to_string(),
+        )])
+    }
+}
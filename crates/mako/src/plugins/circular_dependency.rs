@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+
+use crate::compiler::{Compiler, Context};
+use crate::config::{CircularDependencyConfig, CircularDependencyLevel};
+use crate::module::{ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+
+pub(crate) fn default_runtime_level() -> CircularDependencyLevel {
+    CircularDependencyLevel::Warn
+}
+
+pub(crate) fn default_dynamic_level() -> CircularDependencyLevel {
+    CircularDependencyLevel::Off
+}
+
+/**
+ * report circular dependencies by the kind of edges in the cycle
+ *
+ * - runtime: all edges are synchronous imports / requires, modules may see each other
+ *   uninitialized
+ * - dynamic: the cycle is broken by a dynamic import or worker, which is usually safe
+ *
+ * type only imports are erased before the module graph is built, so they never form cycles,
+ * and cycles of which all modules are in node_modules are ignored
+ *
+ * e.g.
+ * warning: runtime circular dependency src/a.ts -> src/b.ts -> src/a.ts
+ * warning: dynamic circular dependency src/a.ts -> src/b.ts ~> src/a.ts
+ */
+pub struct CircularDependencyPlugin {}
+
+impl Plugin for CircularDependencyPlugin {
+    fn name(&self) -> &str {
+        "circular_dependency"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        let Some(config) = &context.config.circular_dependency else {
+            return Ok(());
+        };
+        let module_graph = context.module_graph.read().unwrap();
+        let mut errors = vec![];
+        for cycle in find_cycles(&module_graph) {
+            let message = format!(
+                "{} circular dependency {}",
+                cycle.kind,
+                cycle.to_string(&context.root)
+            );
+            match cycle_level(config, &cycle, &context.root) {
+                CircularDependencyLevel::Off => {}
+                CircularDependencyLevel::Warn => {
+                    println!("{}: {}", "warning".to_string().yellow(), message);
+                    context.stats_info.add_warning(message);
+                }
+                CircularDependencyLevel::Error => errors.push(message),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!(errors.join("\n")));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CycleKind {
+    Runtime,
+    Dynamic,
+}
+
+impl std::fmt::Display for CycleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CycleKind::Runtime => write!(f, "runtime"),
+            CycleKind::Dynamic => write!(f, "dynamic"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cycle {
+    kind: CycleKind,
+    // modules in the cycle and whether the edge to the next one is synchronous,
+    // the last one points back to the first one
+    modules: Vec<(ModuleId, bool)>,
+}
+
+impl Cycle {
+    fn to_string(&self, root: &Path) -> String {
+        let mut s = relative_path(&self.modules[0].0.id, root);
+        for (i, (_, sync)) in self.modules.iter().enumerate() {
+            let (next, _) = &self.modules[(i + 1) % self.modules.len()];
+            s.push_str(if *sync { " -> " } else { " ~> " });
+            s.push_str(&relative_path(&next.id, root));
+        }
+        s
+    }
+}
+
+fn cycle_level(
+    config: &CircularDependencyConfig,
+    cycle: &Cycle,
+    root: &Path,
+) -> CircularDependencyLevel {
+    let mut level = match cycle.kind {
+        CycleKind::Runtime => config.runtime,
+        CycleKind::Dynamic => config.dynamic,
+    };
+    // the last matched override wins
+    for rule in &config.overrides {
+        let dir = root.join(&rule.dir);
+        if cycle
+            .modules
+            .iter()
+            .all(|(id, _)| Path::new(&id.id).starts_with(&dir))
+            && let Some(rule_level) = match cycle.kind {
+                CycleKind::Runtime => rule.runtime,
+                CycleKind::Dynamic => rule.dynamic,
+            }
+        {
+            level = rule_level;
+        }
+    }
+    level
+}
+
+fn find_cycles(module_graph: &ModuleGraph) -> Vec<Cycle> {
+    let mut ids = module_graph.get_module_ids();
+    ids.sort_by(|a, b| a.id.cmp(&b.id));
+    let index_map = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id, i))
+        .collect::<HashMap<_, _>>();
+
+    // (from, to) -> is synchronous, an edge is synchronous if any of its dependencies is
+    let mut edges: BTreeMap<(usize, usize), bool> = BTreeMap::new();
+    for (from, id) in ids.iter().enumerate() {
+        for (dep_id, dep) in module_graph.get_dependencies(id) {
+            let to = index_map[dep_id];
+            if from == to {
+                continue;
+            }
+            let sync = !matches!(
+                dep.resolve_type,
+                ResolveType::DynamicImport | ResolveType::Worker
+            );
+            *edges.entry((from, to)).or_default() |= sync;
+        }
+    }
+
+    let mut all_graph = DiGraph::<(), ()>::new();
+    let mut sync_graph = DiGraph::<(), ()>::new();
+    for _ in &ids {
+        all_graph.add_node(());
+        sync_graph.add_node(());
+    }
+    for (&(from, to), &sync) in &edges {
+        all_graph.add_edge(NodeIndex::new(from), NodeIndex::new(to), ());
+        if sync {
+            sync_graph.add_edge(NodeIndex::new(from), NodeIndex::new(to), ());
+        }
+    }
+
+    let components = |graph: &DiGraph<(), ()>| {
+        let mut components = tarjan_scc(graph)
+            .into_iter()
+            .filter(|c| c.len() > 1)
+            .map(|c| {
+                let mut c = c.into_iter().map(|n| n.index()).collect::<Vec<_>>();
+                c.sort();
+                c
+            })
+            .collect::<Vec<_>>();
+        components.sort();
+        components
+    };
+    let sync_components = components(&sync_graph);
+
+    let mut cycles = vec![];
+    for component in &sync_components {
+        let start = component[0];
+        if let Some(path) = shortest_path(&edges, component, true, &[start], start) {
+            cycles.push(to_cycle(&ids, &edges, CycleKind::Runtime, path));
+        }
+    }
+    // components which are not synchronous cycles as a whole have dynamic edges in them
+    for component in components(&all_graph) {
+        if sync_components.contains(&component) {
+            continue;
+        }
+        let members = component.iter().collect::<HashSet<_>>();
+        let Some((&(from, to), _)) = edges
+            .iter()
+            .find(|((from, to), sync)| !**sync && members.contains(from) && members.contains(to))
+        else {
+            continue;
+        };
+        if let Some(path) = shortest_path(&edges, &component, false, &[from, to], from) {
+            cycles.push(to_cycle(&ids, &edges, CycleKind::Dynamic, path));
+        }
+    }
+
+    cycles
+        .into_iter()
+        .filter(|cycle| cycle.modules.iter().any(|(id, _)| !is_node_module(id)))
+        .collect()
+}
+
+// bfs from the last node of `prefix` to `target` inside the component,
+// returns the whole path without the trailing target
+fn shortest_path(
+    edges: &BTreeMap<(usize, usize), bool>,
+    component: &[usize],
+    sync_only: bool,
+    prefix: &[usize],
+    target: usize,
+) -> Option<Vec<usize>> {
+    let members = component.iter().collect::<HashSet<_>>();
+    let mut queue = VecDeque::from([prefix.to_vec()]);
+    let mut visited = prefix.iter().copied().collect::<HashSet<_>>();
+    while let Some(path) = queue.pop_front() {
+        let last = *path.last().unwrap();
+        for (&(_, to), &sync) in edges.range((last, 0)..=(last, usize::MAX)) {
+            if !members.contains(&to) || (sync_only && !sync) {
+                continue;
+            }
+            if to == target && path.len() > 1 {
+                return Some(path);
+            }
+            if visited.insert(to) {
+                let mut path = path.clone();
+                path.push(to);
+                queue.push_back(path);
+            }
+        }
+    }
+    None
+}
+
+fn to_cycle(
+    ids: &[ModuleId],
+    edges: &BTreeMap<(usize, usize), bool>,
+    kind: CycleKind,
+    path: Vec<usize>,
+) -> Cycle {
+    let modules = path
+        .iter()
+        .enumerate()
+        .map(|(i, from)| {
+            let to = path[(i + 1) % path.len()];
+            (ids[*from].clone(), edges[&(*from, to)])
+        })
+        .collect();
+    Cycle { kind, modules }
+}
+
+fn is_node_module(id: &ModuleId) -> bool {
+    id.id.contains("node_modules")
+}
+
+fn relative_path(path: &str, root: &Path) -> String {
+    pathdiff::diff_paths(path, root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{find_cycles, CycleKind};
+    use crate::module::ResolveType;
+    use crate::utils::test_helper::create_module_graph;
+
+    #[test]
+    fn test_runtime_cycle() {
+        let cycles = find_cycles(&create_module_graph(
+            "/src/index.ts",
+            &[
+                ("/src/index.ts", "/src/a.ts", ResolveType::Require),
+                ("/src/a.ts", "/src/b.ts", ResolveType::Require),
+                ("/src/b.ts", "/src/a.ts", ResolveType::Require),
+            ],
+        ));
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].kind, CycleKind::Runtime);
+        assert_eq!(
+            cycles[0].to_string(Path::new("/")),
+            "src/a.ts -> src/b.ts -> src/a.ts"
+        );
+    }
+
+    #[test]
+    fn test_dynamic_cycle() {
+        let cycles = find_cycles(&create_module_graph(
+            "/src/index.ts",
+            &[
+                ("/src/index.ts", "/src/a.ts", ResolveType::Require),
+                ("/src/a.ts", "/src/b.ts", ResolveType::Require),
+                ("/src/b.ts", "/src/a.ts", ResolveType::DynamicImport),
+            ],
+        ));
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].kind, CycleKind::Dynamic);
+        assert_eq!(
+            cycles[0].to_string(Path::new("/")),
+            "src/b.ts ~> src/a.ts -> src/b.ts"
+        );
+    }
+
+    #[test]
+    fn test_node_modules_cycle_ignored() {
+        let cycles = find_cycles(&create_module_graph(
+            "/src/index.ts",
+            &[
+                (
+                    "/src/index.ts",
+                    "/node_modules/a/index.js",
+                    ResolveType::Require,
+                ),
+                (
+                    "/node_modules/a/index.js",
+                    "/node_modules/a/b.js",
+                    ResolveType::Require,
+                ),
+                (
+                    "/node_modules/a/b.js",
+                    "/node_modules/a/index.js",
+                    ResolveType::Require,
+                ),
+            ],
+        ));
+        assert!(cycles.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::compiler::Context;
+use crate::config::CodegenConfig;
+use crate::plugin::Plugin;
+
+/**
+ * run external codegen steps, e.g. `wasm-pack build`, before builds and when their sources
+ * change in watch mode, the output dirs are resolved as packages (see Config::new), and changes
+ * of them trigger rebuilds as normal modules
+ */
+pub struct CodegenPlugin {}
+
+impl Plugin for CodegenPlugin {
+    fn name(&self) -> &str {
+        "codegen"
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<Option<()>> {
+        for codegen in &context.config.codegen {
+            run(&context.root, codegen)?;
+        }
+        Ok(None)
+    }
+}
+
+// run the codegen steps whose sources are changed, and leave other paths to the rebuild,
+// failures are printed since the last output is still usable
+pub(crate) fn handle_changes(context: &Arc<Context>, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let (sources, paths): (Vec<_>, Vec<_>) = paths.into_iter().partition(|path| {
+        context
+            .config
+            .codegen
+            .iter()
+            .any(|codegen| is_source(&context.root, codegen, path))
+    });
+    for codegen in &context.config.codegen {
+        if sources
+            .iter()
+            .any(|path| is_source(&context.root, codegen, path))
+            && let Err(e) = run(&context.root, codegen)
+        {
+            eprintln!("{}: {}", "error".to_string().red(), e);
+        }
+    }
+    paths
+}
+
+// build artifacts of the codegen step, which should neither be watched nor trigger the step
+pub(crate) fn target_dir(root: &Path, codegen: &CodegenConfig) -> PathBuf {
+    root.join(&codegen.dir).join("target")
+}
+
+// the output dir may be inside dir (e.g. pkg of wasm-pack), changes of it are rebuilt as modules
+fn is_source(root: &Path, codegen: &CodegenConfig, path: &Path) -> bool {
+    path.starts_with(root.join(&codegen.dir))
+        && !path.starts_with(target_dir(root, codegen))
+        && !path.starts_with(root.join(&codegen.output))
+}
+
+fn run(root: &Path, codegen: &CodegenConfig) -> Result<()> {
+    println!("Running codegen `{}`...", codegen.command);
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let status = Command::new(shell)
+        .arg(flag)
+        .arg(&codegen.command)
+        .current_dir(root.join(&codegen.dir))
+        .status()
+        .map_err(|e| anyhow!("Run codegen `{}` failed: {}", codegen.command, e))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Run codegen `{}` failed with {}",
+            codegen.command,
+            status
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::CodegenConfig;
+
+    #[test]
+    fn test_is_source() {
+        let codegen = CodegenConfig {
+            dir: "crates/wasm".to_string(),
+            command: "wasm-pack build".to_string(),
+            output: "crates/wasm/pkg".to_string(),
+            name: Some("wasm".to_string()),
+        };
+        let is_source =
+            |path: &str| super::is_source(Path::new("/root"), &codegen, Path::new(path));
+        assert!(is_source("/root/crates/wasm/src/lib.rs"));
+        assert!(is_source("/root/crates/wasm/Cargo.toml"));
+        assert!(!is_source("/root/crates/wasm/target/debug/build.rs"));
+        assert!(!is_source("/root/crates/wasm/pkg/wasm.js"));
+        assert!(!is_source("/root/src/index.ts"));
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::compiler::{Compiler, Context};
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::resolve::{ResolvedResource, ResolverResource};
+
+// multiple copies of these packages break hooks and hmr
+const SINGLETON_PACKAGES: [&str; 2] = ["react", "react-dom"];
+
+/**
+ * warn when there are multiple copies of react / react-dom or dedupePackages bundled
+ * with the import chain of each copy, e.g.
+ *
+ * warning: 2 copies of react are bundled
+ *   /root/node_modules/react
+ *     src/index.tsx -> node_modules/react/index.js
+ *   /root/node_modules/foo/node_modules/react
+ *     src/index.tsx -> node_modules/foo/index.js -> node_modules/foo/node_modules/react/index.js
+ */
+pub struct DuplicatePackagesPlugin {}
+
+impl Plugin for DuplicatePackagesPlugin {
+    fn name(&self) -> &str {
+        "duplicate_packages"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        let module_graph = context.module_graph.read().unwrap();
+        let duplicates = find_duplicate_packages(&module_graph, &context.config.dedupe_packages);
+        for (name, copies) in duplicates {
+            let mut message = format!(
+                "{}: {} copies of {} are bundled, use {} to bundle only one of them",
+                "warning".to_string().yellow(),
+                copies.len(),
+                name.yellow(),
+                "dedupePackages".to_string().yellow(),
+            );
+            let mut plain_message = format!(
+                "{} copies of {} are bundled, use dedupePackages to bundle only one of them",
+                copies.len(),
+                name,
+            );
+            for (dir, module_id) in copies {
+                let chain = import_chain(&module_graph, &module_id)
+                    .iter()
+                    .map(|id| relative_path(&id.id, &context.root))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let copy = format!("\n  {}\n    {}", dir.to_string_lossy(), chain);
+                message.push_str(&copy);
+                plain_message.push_str(&copy);
+            }
+            println!("{}", message);
+            context.stats_info.add_warning(plain_message);
+        }
+        Ok(())
+    }
+}
+
+// package name -> (package dir -> first module found in it)
+fn find_duplicate_packages(
+    module_graph: &ModuleGraph,
+    dedupe_packages: &[String],
+) -> BTreeMap<String, BTreeMap<PathBuf, ModuleId>> {
+    let mut packages: BTreeMap<String, BTreeMap<PathBuf, ModuleId>> = BTreeMap::new();
+    module_graph.modules().iter().for_each(|module| {
+        if let Some(ResolverResource::Resolved(ResolvedResource(resolution))) = module
+            .info
+            .as_ref()
+            .and_then(|info| info.resolved_resource.as_ref())
+            && let Some(package_json) = resolution.package_json()
+            && let Some(name) = &package_json.name
+            && (SINGLETON_PACKAGES.contains(&name.as_str()) || dedupe_packages.contains(name))
+        {
+            packages
+                .entry(name.clone())
+                .or_default()
+                .entry(package_json.directory().to_path_buf())
+                .or_insert_with(|| module.id.clone());
+        }
+    });
+    packages.retain(|_, copies| copies.len() > 1);
+    packages
+}
+
+// the shortest import chain from an entry to the module
+pub(crate) fn import_chain(module_graph: &ModuleGraph, module_id: &ModuleId) -> Vec<ModuleId> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([vec![module_id.clone()]]);
+    visited.insert(module_id.clone());
+    while let Some(chain) = queue.pop_front() {
+        let last = chain.last().unwrap();
+        let dependents = module_graph.get_dependents(last);
+        let is_entry = module_graph
+            .get_module(last)
+            .is_some_and(|module| module.is_entry);
+        if is_entry || dependents.is_empty() {
+            return chain.into_iter().rev().collect();
+        }
+        for (dependent, _) in dependents {
+            if visited.insert(dependent.clone()) {
+                let mut chain = chain.clone();
+                chain.push(dependent.clone());
+                queue.push_back(chain);
+            }
+        }
+    }
+    vec![module_id.clone()]
+}
+
+pub(crate) fn relative_path(path: &str, root: &Path) -> String {
+    pathdiff::diff_paths(path, root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::compiler::Context;
+use crate::config::EnvBannerConfig;
+use crate::plugin::Plugin;
+
+pub(crate) fn default_console() -> bool {
+    true
+}
+
+/**
+ * show which environment the page is running in, e.g. a "STAGING" ribbon or a console banner,
+ * by injecting a snippet into the runtime of entries
+ *
+ * placeholders in text are replaced with the build metadata:
+ * [mode], [version] (of package.json) and [builtAt] (ISO time of the build)
+ */
+pub struct EnvBannerPlugin {}
+
+impl Plugin for EnvBannerPlugin {
+    fn name(&self) -> &str {
+        "env_banner"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        let Some(config) = &context.config.env_banner else {
+            return Ok(vec![]);
+        };
+        if !config.modes.is_empty() && !config.modes.contains(&context.config.mode) {
+            return Ok(vec![]);
+        }
+        let text = render_text(config, context);
+        let built_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        Ok(vec![banner_code(config, &text_expr(&text, built_at)?)])
+    }
+}
+
+fn render_text(config: &EnvBannerConfig, context: &Arc<Context>) -> String {
+    let mut text = config
+        .text
+        .replace("[mode]", &context.config.mode.to_string());
+    if text.contains("[version]") {
+        let version = std::fs::read_to_string(context.root.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|package_json| package_json["version"].as_str().map(|v| v.to_string()))
+            .unwrap_or_default();
+        text = text.replace("[version]", &version);
+    }
+    text
+}
+
+// js expression of the text, [builtAt] is formatted in the browser to avoid a date time
+// dependency
+fn text_expr(text: &str, built_at: u128) -> Result<String> {
+    let parts = text
+        .split("[builtAt]")
+        .map(serde_json::to_string)
+        .collect::<serde_json::Result<Vec<_>>>()?;
+    Ok(parts.join(&format!(" + new Date({}).toISOString() + ", built_at)))
+}
+
+fn banner_code(config: &EnvBannerConfig, text_expr: &str) -> String {
+    let mut code = format!(
+        "/* mako/runtime/envBanner */\n!(function () {{\n  var text = {};\n",
+        text_expr
+    );
+    if config.console {
+        code.push_str(
+            "  console.log('%c ' + text + ' ', 'background:#ff4d4f;color:#fff;border-radius:2px;padding:2px 4px;');\n",
+        );
+    }
+    if config.ribbon {
+        code.push_str(
+            r#"  if (typeof document !== 'undefined') {
+    var show = function () {
+      var ribbon = document.createElement('div');
+      ribbon.textContent = text;
+      ribbon.setAttribute('data-mako-env-banner', '');
+      ribbon.style.cssText =
+        'position:fixed;top:0;right:0;z-index:2147483647;padding:2px 8px;' +
+        'background:#ff4d4f;color:#fff;font:12px/18px sans-serif;pointer-events:none;opacity:0.85;';
+      document.body.appendChild(ribbon);
+    };
+    if (document.body) show();
+    else document.addEventListener('DOMContentLoaded', show);
+  }
+"#,
+        );
+    }
+    code.push_str("})();\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{banner_code, text_expr};
+    use crate::config::EnvBannerConfig;
+
+    #[test]
+    fn test_text_expr() {
+        assert_eq!(text_expr("STAGING", 0).unwrap(), r#""STAGING""#);
+        assert_eq!(
+            text_expr("STAGING \"1.0.0\" [builtAt]", 1700000000000).unwrap(),
+            r#""STAGING \"1.0.0\" " + new Date(1700000000000).toISOString() + """#
+        );
+    }
+
+    #[test]
+    fn test_banner_code() {
+        let config = EnvBannerConfig {
+            text: "STAGING".to_string(),
+            modes: vec![],
+            console: true,
+            ribbon: false,
+        };
+        let code = banner_code(&config, r#""STAGING""#);
+        assert!(code.contains(r#"var text = "STAGING";"#));
+        assert!(code.contains("console.log"));
+        assert!(!code.contains("document"));
+    }
+}
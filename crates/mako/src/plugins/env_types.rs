@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::Result;
+use swc_core::common::GLOBALS;
+use swc_core::ecma::ast::{
+    ArrayLit, Expr, Ident, KeyValueProp, Lit, ObjectLit, ParenExpr, Prop, PropName, PropOrSpread,
+    UnaryExpr, UnaryOp,
+};
+use swc_core::ecma::atoms::js_word;
+
+use crate::compiler::Context;
+use crate::plugin::Plugin;
+use crate::visitors::env_replacer::build_env_map;
+
+pub(crate) fn default_env_types_path() -> String {
+    "mako-env.d.ts".to_string()
+}
+
+/**
+ * generate a d.ts under root at build start, which declares the keys of `import.meta.env`
+ * and `process.env` substituted by EnvReplacer with the literal types of their values, so
+ * that TypeScript users get autocompletion and typo checks
+ *
+ * e.g. with `define: { API_URL: "\"/api\"" }` in production mode
+ * ->
+ * interface ImportMetaEnv {
+ *   readonly API_URL: "/api";
+ *   readonly MODE: "production";
+ * }
+ */
+pub struct EnvTypesPlugin {}
+
+impl Plugin for EnvTypesPlugin {
+    fn name(&self) -> &str {
+        "env_types"
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<Option<()>> {
+        let Some(config) = &context.config.env_types else {
+            return Ok(None);
+        };
+        let content = render_env_types(context)?;
+        let path = context.root.join(&config.path);
+        // write only when changed, so that watchers of editors and tsc are not triggered
+        if fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+            fs::write(path, content)?;
+        }
+        Ok(None)
+    }
+}
+
+fn render_env_types(context: &Arc<Context>) -> Result<String> {
+    let mut define = context.config.define.clone();
+    define
+        .entry("NODE_ENV".to_string())
+        .or_insert_with(|| format!("\"{}\"", context.config.mode).into());
+    let env_map = GLOBALS.set(&context.meta.script.globals, || {
+        build_env_map(define, context)
+    })?;
+
+    // dotted keys are member expression defines, not env keys
+    let mut env_types: BTreeMap<String, String> = env_map
+        .iter()
+        .filter(|(key, _)| !key.contains('.'))
+        .map(|(key, expr)| (key.to_string(), ts_type(expr)))
+        .collect();
+    // values of runtimeEnv are only known at runtime
+    for key in &context.config.runtime_env {
+        env_types.insert(key.clone(), "string".to_string());
+    }
+
+    let mut meta_env_types = env_types.clone();
+    if let Some(mode) = meta_env_types.remove("NODE_ENV") {
+        meta_env_types.insert("MODE".to_string(), mode);
+    }
+
+    Ok(format!(
+        r#"// generated by mako from define, do not edit
+interface ImportMetaEnv {{
+{}}}
+
+interface ImportMeta {{
+  readonly env: ImportMetaEnv;
+}}
+
+declare namespace NodeJS {{
+  interface ProcessEnv {{
+{}  }}
+}}
+"#,
+        render_members(&meta_env_types, "  "),
+        render_members(&env_types, "    "),
+    ))
+}
+
+fn render_members(types: &BTreeMap<String, String>, indent: &str) -> String {
+    types
+        .iter()
+        .map(|(key, ts_type)| format!("{}readonly {}: {};\n", indent, ts_key(key), ts_type))
+        .collect()
+}
+
+// keys which are not valid identifiers are quoted, e.g. "a-b"
+fn ts_key(key: &str) -> String {
+    let is_ident = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_ident {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap()
+    }
+}
+
+// literal type of the define value, `unknown` for values which can't be evaluated statically
+fn ts_type(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => serde_json::to_string(&*s.value).unwrap(),
+        Expr::Lit(Lit::Num(n)) => n.value.to_string(),
+        Expr::Lit(Lit::Bool(b)) => b.value.to_string(),
+        Expr::Lit(Lit::Null(_)) => "null".to_string(),
+        Expr::Tpl(_) => "string".to_string(),
+        Expr::Ident(Ident {
+            sym: js_word!("undefined"),
+            ..
+        }) => "undefined".to_string(),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg: box Expr::Lit(Lit::Num(n)),
+            ..
+        }) => format!("-{}", n.value),
+        Expr::Paren(ParenExpr { expr, .. }) => ts_type(expr),
+        Expr::Array(ArrayLit { elems, .. }) => {
+            let mut types = vec![];
+            for elem in elems {
+                match elem {
+                    Some(elem) if elem.spread.is_none() => types.push(ts_type(&elem.expr)),
+                    Some(_) => return "unknown[]".to_string(),
+                    None => types.push("undefined".to_string()),
+                }
+            }
+            format!("readonly [{}]", types.join(", "))
+        }
+        Expr::Object(ObjectLit { props, .. }) => {
+            let mut members = vec![];
+            for prop in props {
+                let PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) = prop
+                else {
+                    return "Record<string, unknown>".to_string();
+                };
+                let key = match key {
+                    PropName::Ident(ident) => ts_key(&ident.sym),
+                    PropName::Str(s) => ts_key(&s.value),
+                    _ => return "Record<string, unknown>".to_string(),
+                };
+                members.push(format!("readonly {}: {}", key, ts_type(value)));
+            }
+            format!("{{ {} }}", members.join("; "))
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use maplit::hashmap;
+    use serde_json::json;
+
+    use super::render_env_types;
+    use crate::compiler::Context;
+
+    #[test]
+    fn test_render_env_types() {
+        let mut context = Context::default();
+        context.config.define = hashmap! {
+            "API_URL".to_string() => json!("\"/api\""),
+            "DEBUG".to_string() => json!(false),
+            "RETRY".to_string() => json!("-1"),
+            "FEATURES".to_string() => json!({ "a": true, "b-c": [1, "\"x\""] }),
+            "BUILD".to_string() => json!("Date.now()"),
+            "__APP__.version".to_string() => json!("\"1.0.0\""),
+        };
+        context.config.runtime_env = vec!["TOKEN".to_string()];
+        let content = render_env_types(&Arc::new(context)).unwrap();
+        assert_eq!(
+            content,
+            r#"// generated by mako from define, do not edit
+interface ImportMetaEnv {
+  readonly API_URL: "/api";
+  readonly BUILD: unknown;
+  readonly DEBUG: false;
+  readonly FEATURES: { readonly a: true; readonly "b-c": readonly [1, "x"] };
+  readonly MODE: "development";
+  readonly RETRY: -1;
+  readonly TOKEN: string;
+}
+
+interface ImportMeta {
+  readonly env: ImportMetaEnv;
+}
+
+declare namespace NodeJS {
+  interface ProcessEnv {
+    readonly API_URL: "/api";
+    readonly BUILD: unknown;
+    readonly DEBUG: false;
+    readonly FEATURES: { readonly a: true; readonly "b-c": readonly [1, "x"] };
+    readonly NODE_ENV: "development";
+    readonly RETRY: -1;
+    readonly TOKEN: string;
+  }
+}
+"#
+        );
+    }
+}
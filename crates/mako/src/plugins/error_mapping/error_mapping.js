@@ -0,0 +1,52 @@
+/* mako/runtime/errorMapping */
+!(function () {
+  var loaded = {};
+  function loadBoundaries(url) {
+    if (!loaded[url]) {
+      loaded[url] = fetch(url + '.modules.json')
+        .then(function (res) {
+          return res.ok ? res.json() : null;
+        })
+        .catch(function () {
+          return null;
+        });
+    }
+    return loaded[url];
+  }
+  // boundaries are sorted by [line, column]
+  function findModule(boundaries, line, column) {
+    var moduleId = null;
+    for (var i = 0; i < boundaries.boundaries.length; i++) {
+      var b = boundaries.boundaries[i];
+      if (b[0] < line || (b[0] === line && b[1] <= column)) {
+        moduleId = boundaries.modules[b[2]];
+      } else {
+        break;
+      }
+    }
+    return moduleId;
+  }
+  // resolve frames of the error's stack to module ids, e.g.
+  // [{ url: 'https://a.com/index.3f2a.js', line: 1, column: 1024, moduleId: './src/index.tsx' }]
+  requireModule.mapError = function (error) {
+    var re = /((?:https?|file):\/\/[^\s()]+?):(\d+):(\d+)/g;
+    var stack = (error && error.stack) || '';
+    var frames = [];
+    var match;
+    while ((match = re.exec(stack))) {
+      frames.push({ url: match[1], line: +match[2], column: +match[3] });
+    }
+    return Promise.all(
+      frames.map(function (frame) {
+        return loadBoundaries(frame.url.split(/[?#]/)[0]).then(
+          function (boundaries) {
+            frame.moduleId = boundaries
+              ? findModule(boundaries, frame.line, frame.column)
+              : null;
+            return frame;
+          },
+        );
+      }),
+    );
+  };
+})();
@@ -0,0 +1,154 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::compiler::Context;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::module::ModuleId;
+use crate::plugin::Plugin;
+
+/**
+ * map errors in production chunks back to module ids
+ *
+ * boundaries of modules in each js chunk are emitted to `<chunk>.modules.json`, which is
+ * loaded by `__mako_require__.mapError(error)` at runtime, and by `mako symbolicate`
+ * together with sourcemaps
+ */
+pub struct ErrorMappingPlugin {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct ModuleBoundaries {
+    pub modules: Vec<String>,
+    // [line, column, index of modules], 1-based like the positions in stack traces,
+    // sorted by position
+    pub boundaries: Vec<(usize, usize, usize)>,
+}
+
+impl ModuleBoundaries {
+    pub fn find(&self, line: usize, column: usize) -> Option<&str> {
+        self.boundaries
+            .iter()
+            .take_while(|(l, c, _)| *l < line || (*l == line && *c <= column))
+            .last()
+            .map(|(_, _, index)| self.modules[*index].as_str())
+    }
+}
+
+pub(crate) fn boundaries_file_name(chunk_file_name: &str) -> String {
+    format!("{}.modules.json", chunk_file_name)
+}
+
+impl Plugin for ErrorMappingPlugin {
+    fn name(&self) -> &str {
+        "error_mapping"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        if context.config.error_mapping && !context.args.watch {
+            Ok(vec![include_str!("error_mapping.js").to_string()])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn after_generate_chunk_files(
+        &self,
+        chunk_files: &[ChunkFile],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        if !context.config.error_mapping || context.args.watch {
+            return Ok(());
+        }
+        let module_graph = context.module_graph.read().unwrap();
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        for chunk_file in chunk_files {
+            if !matches!(chunk_file.file_type, ChunkFileType::JS) {
+                continue;
+            }
+            let Some(chunk) = chunk_graph.chunk(&ModuleId::new(chunk_file.chunk_id.clone())) else {
+                continue;
+            };
+            let module_ids = chunk
+                .modules
+                .iter()
+                .filter(|module_id| module_graph.has_module(module_id))
+                .map(|module_id| module_id.generate(context))
+                .collect::<HashSet<_>>();
+            let boundaries =
+                find_module_boundaries(&String::from_utf8_lossy(&chunk_file.content), &module_ids);
+            let output_path = context
+                .config
+                .output
+                .path
+                .join(boundaries_file_name(&chunk_file.disk_name()));
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output_path, serde_json::to_string(&boundaries)?)?;
+        }
+        Ok(())
+    }
+}
+
+// modules are emitted as `"id": function(module, exports, __mako_require__) {}` in chunks,
+// which may be minified to `"id":function(e,t,r){}`, `"id":(e,t,r)=>{}` or `"id"(e,t,r){}`
+fn find_module_boundaries(code: &str, module_ids: &HashSet<String>) -> ModuleBoundaries {
+    let re = Regex::new(
+        r#"(["'])((?:[^"'\\\n]|\\.)+)["']\s*(?::\s*(?:function\s*\(|\([^()]*\)\s*=>)|\()"#,
+    )
+    .unwrap();
+    let mut modules = vec![];
+    let mut indexes = HashMap::new();
+    let mut boundaries = vec![];
+    let line_starts = std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(i, _)| i + 1))
+        .collect::<Vec<_>>();
+    for captures in re.captures_iter(code) {
+        let id = &captures[2];
+        if !module_ids.contains(id) || indexes.contains_key(id) {
+            continue;
+        }
+        let start = captures.get(0).unwrap().start();
+        let line = line_starts.partition_point(|line_start| *line_start <= start);
+        // columns in stack traces are counted in utf-16 code units
+        let column = code[line_starts[line - 1]..start].encode_utf16().count() + 1;
+        indexes.insert(id.to_string(), modules.len());
+        boundaries.push((line, column, modules.len()));
+        modules.push(id.to_string());
+    }
+    ModuleBoundaries {
+        modules,
+        boundaries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::find_module_boundaries;
+
+    #[test]
+    fn test_find_module_boundaries() {
+        let code = r#"!function(){var e={"./a.js":function(e,t,r){var o={"x":function(){}}},
+"./b.js":(e,t,r)=>{},'./c.js'(e,t,r){}}}();"#;
+        let module_ids = ["./a.js", "./b.js", "./c.js"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<HashSet<_>>();
+        let boundaries = find_module_boundaries(code, &module_ids);
+        assert_eq!(boundaries.modules, vec!["./a.js", "./b.js", "./c.js"]);
+        assert_eq!(
+            boundaries.boundaries,
+            vec![(1, 20, 0), (2, 1, 1), (2, 22, 2)]
+        );
+        assert_eq!(boundaries.find(1, 10), None);
+        assert_eq!(boundaries.find(1, 60), Some("./a.js"));
+        assert_eq!(boundaries.find(2, 10), Some("./b.js"));
+        assert_eq!(boundaries.find(2, 30), Some("./c.js"));
+    }
+}
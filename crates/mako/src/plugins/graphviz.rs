@@ -1,5 +1,5 @@
 use std::fmt::Debug;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
@@ -36,9 +36,13 @@ impl Plugin for Graphviz {
     }
 
     fn generate_beg(&self, context: &Arc<Context>) -> Result<()> {
-        Graphviz::write_graph(
+        fs::write(
             context.root.join("_mako_module_graph_origin.dot"),
-            &context.module_graph.read().unwrap().graph,
+            context
+                .module_graph
+                .read()
+                .unwrap()
+                .export_dot(&context.root),
         )?;
         Ok(())
     }
@@ -61,9 +65,13 @@ impl Plugin for Graphviz {
             &context.chunk_graph.read().unwrap().graph,
         )?;
 
-        Graphviz::write_graph(
+        fs::write(
             context.root.join("_mako_module_graph_finale.dot"),
-            &context.module_graph.read().unwrap().graph,
+            context
+                .module_graph
+                .read()
+                .unwrap()
+                .export_dot(&context.root),
         )?;
 
         Ok(None)
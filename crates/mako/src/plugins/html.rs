@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex};
+use url::Url;
+
+use crate::compiler::Context;
+use crate::generate::chunk_group::{chunk_groups, html_public_path, ChunkGroup};
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+use crate::plugins::import_map::import_map_tag;
+use crate::plugins::manifest::build_manifest;
+
+#[derive(Default)]
+pub struct HtmlPlugin {
+    // html written by the last generation, keyed by the output path
+    outputs: Mutex<HashMap<PathBuf, HtmlOutput>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HtmlOutput {
+    html: String,
+    scripts: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum HtmlUpdate {
+    Unchanged,
+    Write,
+    // the injected scripts are changed, which can't be applied with hmr
+    WriteAndReload,
+}
+
+/**
+ * the html only changes when the injected assets are changed, since the template is not
+ * watched, so it's skipped if it's the same as the last one to avoid needless reloads of the
+ * tools watching the output. css-only changes are applied with hmr, the html is rewritten
+ * without a full reload
+ */
+fn html_update(last: Option<&HtmlOutput>, next: &HtmlOutput) -> HtmlUpdate {
+    match last {
+        None => HtmlUpdate::Write,
+        Some(last) if last == next => HtmlUpdate::Unchanged,
+        Some(last) if last.scripts != next.scripts => HtmlUpdate::WriteAndReload,
+        Some(_) => HtmlUpdate::Write,
+    }
+}
+
+pub(crate) fn default_html_file_name() -> String {
+    "[name].html".to_string()
+}
+
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    {{ entry.styles }}
+  </head>
+  <body>
+    <div id="root"></div>
+    {{ entry.scripts }}
+  </body>
+</html>
+"#;
+
+impl Plugin for HtmlPlugin {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    // generate_end is called after every (re)build, so html is kept up to date in watch mode
+    fn generate_end(
+        &self,
+        params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        let Some(html_config) = &context.config.html else {
+            return Ok(None);
+        };
+        let read_template = |template: Option<&PathBuf>| match template {
+            Some(template) => {
+                let path = context.root.join(template);
+                fs::read_to_string(&path)
+                    .map_err(|e| anyhow!("failed to read html template {}: {}", path.display(), e))
+            }
+            None => Ok(DEFAULT_TEMPLATE.to_string()),
+        };
+        let template = read_template(html_config.template.as_ref())?;
+        let manifest = serde_json::to_string(&build_manifest(context))?;
+        let public_path = html_public_path(context);
+        let import_map = import_map_tag(context)?;
+        let preconnect_links = preconnect_tags(public_path, &html_config.preconnect);
+        let mut outputs = self.outputs.lock().unwrap();
+        for chunk_group in chunk_groups(context) {
+            let page_template = match html_config.templates.get(&chunk_group.name) {
+                Some(path) => Some(read_template(Some(path))?),
+                None => None,
+            };
+            let html = render_html(
+                page_template.as_ref().unwrap_or(&template),
+                &chunk_group,
+                public_path,
+                &manifest,
+                &import_map,
+                &preconnect_links,
+            )?;
+            let filename = html_config.filename.replace("[name]", &chunk_group.name);
+            let output_path = context.config.output.path.join(filename);
+            let output = HtmlOutput {
+                html,
+                scripts: chunk_group.script_tags(public_path).join("\n"),
+            };
+            match html_update(outputs.get(&output_path), &output) {
+                HtmlUpdate::Unchanged if output_path.exists() => continue,
+                HtmlUpdate::WriteAndReload if !params.is_first_compile => {
+                    context.full_reload.store(true, Ordering::SeqCst);
+                }
+                _ => {}
+            }
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&output_path, &output.html)?;
+            outputs.insert(output_path, output);
+        }
+        Ok(None)
+    }
+}
+
+// scheme and host of absolute or protocol-relative urls, e.g. https://cdn.example.com
+fn origin_of(url: &str) -> Option<String> {
+    if let Some(rest) = url.strip_prefix("//") {
+        let origin = origin_of(&format!("https://{}", rest))?;
+        return Some(origin.trim_start_matches("https:").to_string());
+    }
+    let url = Url::parse(url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(url.origin().ascii_serialization())
+}
+
+/**
+ * connections to the origin of publicPath and the extra origins (e.g. api hosts) are set up
+ * early, so that the first async chunk and requests don't wait for dns, tcp and tls
+ *
+ * extra origins are usually requested with cors (e.g. fetch), so they are preconnected with
+ * crossorigin, dns-prefetch is the fallback of browsers which don't support preconnect
+ */
+fn preconnect_tags(public_path: &str, extra_origins: &[String]) -> String {
+    let mut origins = vec![];
+    if let Some(origin) = origin_of(public_path) {
+        origins.push((origin, false));
+    }
+    for origin in extra_origins {
+        let origin = origin_of(origin).unwrap_or_else(|| origin.clone());
+        if !origins.iter().any(|(o, _)| *o == origin) {
+            origins.push((origin, true));
+        }
+    }
+    origins
+        .iter()
+        .flat_map(|(origin, crossorigin)| {
+            [
+                format!(
+                    r#"<link rel="preconnect" href="{}"{} />"#,
+                    origin,
+                    if *crossorigin { " crossorigin" } else { "" }
+                ),
+                format!(r#"<link rel="dns-prefetch" href="{}" />"#, origin),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/**
+ * variables are referenced in templates as {{ name }}, unknown ones are kept as is
+ *
+ * - entry.name, name of the entry
+ * - entry.scripts, <script> tags of js files in load order
+ * - entry.styles, <link rel="stylesheet"> tags of css files
+ * - entry.js, entry.css, json arrays of urls, for templates to write their own tags
+ * - preloadLinks, <link rel="preload"> tags of js and css files
+ * - preconnectLinks, <link rel="preconnect"> and <link rel="dns-prefetch"> tags of the
+ *   cross-origin publicPath and html.preconnect
+ * - manifest, json of the asset manifest
+ * - importMap, <script type="importmap"> of importMap.emit
+ * - publicPath
+ *
+ * if none of entry.scripts and entry.styles are referenced, styles are injected
+ * before </head> and scripts before </body>, the import map is injected before </head>
+ * if it's not referenced, preconnect links are injected at the beginning of <head> if they
+ * are not referenced
+ */
+fn render_html(
+    template: &str,
+    chunk_group: &ChunkGroup,
+    public_path: &str,
+    manifest: &str,
+    import_map: &str,
+    preconnect_links: &str,
+) -> Result<String> {
+    let urls = |files: &[String]| {
+        files
+            .iter()
+            .map(|file| format!("{}{}", public_path, file))
+            .collect::<Vec<_>>()
+    };
+    let scripts = chunk_group.script_tags(public_path).join("\n");
+    let styles = chunk_group.style_tags(public_path).join("\n");
+    let variables: HashMap<&str, String> = HashMap::from([
+        ("entry.name", chunk_group.name.clone()),
+        ("entry.js", serde_json::to_string(&urls(&chunk_group.js))?),
+        ("entry.css", serde_json::to_string(&urls(&chunk_group.css))?),
+        (
+            "preloadLinks",
+            chunk_group.preload_tags(public_path).join("\n"),
+        ),
+        ("manifest", manifest.to_string()),
+        ("publicPath", public_path.to_string()),
+    ]);
+
+    let variable_regex = Regex::new(r"\{\{\s*([\w.]+)\s*\}\}").unwrap();
+    let mut has_entry_tags = false;
+    let mut has_import_map = false;
+    let mut has_preconnect_links = false;
+    let html = variable_regex.replace_all(template, |caps: &Captures| {
+        let name = &caps[1];
+        match name {
+            "preconnectLinks" => {
+                has_preconnect_links = true;
+                preconnect_links.to_string()
+            }
+            "importMap" => {
+                has_import_map = true;
+                import_map.to_string()
+            }
+            "entry.scripts" => {
+                has_entry_tags = true;
+                scripts.clone()
+            }
+            "entry.styles" => {
+                has_entry_tags = true;
+                styles.clone()
+            }
+            _ => variables
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string()),
+        }
+    });
+
+    let mut html = html.to_string();
+    if !has_preconnect_links {
+        html = inject_after_head_open(&html, preconnect_links);
+    }
+    if !has_import_map {
+        html = inject_before(&html, "</head>", import_map);
+    }
+    if !has_entry_tags {
+        html = inject_before(&html, "</head>", &styles);
+        html = inject_before(&html, "</body>", &scripts);
+    }
+    Ok(html)
+}
+
+fn inject_after_head_open(html: &str, content: &str) -> String {
+    if content.is_empty() {
+        return html.to_string();
+    }
+    let head_open = html
+        .find("<head>")
+        .or_else(|| html.find("<head "))
+        .and_then(|start| html[start..].find('>').map(|end| start + end + 1));
+    match head_open {
+        Some(index) => format!("{}\n{}{}", &html[..index], content, &html[index..]),
+        None => inject_before(html, "</head>", content),
+    }
+}
+
+fn inject_before(html: &str, tag: &str, content: &str) -> String {
+    if content.is_empty() {
+        return html.to_string();
+    }
+    match html.rfind(tag) {
+        Some(index) => format!("{}{}\n{}", &html[..index], content, &html[index..]),
+        None => format!("{}\n{}", html, content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{html_update, preconnect_tags, render_html, HtmlOutput, HtmlUpdate};
+    use crate::generate::chunk_group::ChunkGroup;
+
+    fn chunk_group() -> ChunkGroup {
+        ChunkGroup {
+            name: "index".to_string(),
+            chunks: vec!["index".to_string()],
+            js: vec!["vendors.js".to_string(), "index.js".to_string()],
+            css: vec!["index.css".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let html = render_html(
+            "<head>{{ preloadLinks }}{{entry.styles}}</head><body>{{ entry.scripts }}<script>var js = {{ entry.js }}; var m = {{ manifest }}; {{ unknown }}</script></body>",
+            &chunk_group(),
+            "/",
+            "{}",
+            "",
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            html,
+            r#"<head><link rel="preload" href="/index.css" as="style" />
+<link rel="preload" href="/vendors.js" as="script" />
+<link rel="preload" href="/index.js" as="script" /><link rel="stylesheet" href="/index.css" /></head><body><script src="/vendors.js"></script>
+<script src="/index.js"></script><script>var js = ["/vendors.js","/index.js"]; var m = {}; {{ unknown }}</script></body>"#
+        );
+    }
+
+    #[test]
+    fn test_auto_inject() {
+        let html = render_html(
+            "<head></head><body></body>",
+            &chunk_group(),
+            "/",
+            "{}",
+            "",
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            html,
+            r#"<head><link rel="stylesheet" href="/index.css" />
+</head><body><script src="/vendors.js"></script>
+<script src="/index.js"></script>
+</body>"#
+        );
+    }
+
+    #[test]
+    fn test_import_map() {
+        let import_map = r#"<script type="importmap">{"imports":{}}</script>"#;
+        let html = render_html(
+            "<head><link /></head><body>{{ entry.scripts }}</body>",
+            &chunk_group(),
+            "/",
+            "{}",
+            import_map,
+            "",
+        )
+        .unwrap();
+        assert_eq!(
+            html,
+            r#"<head><link /><script type="importmap">{"imports":{}}</script>
+</head><body><script src="/vendors.js"></script>
+<script src="/index.js"></script></body>"#
+        );
+    }
+
+    #[test]
+    fn test_preconnect() {
+        let preconnect_links = preconnect_tags(
+            "https://cdn.example.com/static/",
+            &["https://api.example.com/v1".to_string()],
+        );
+        assert_eq!(
+            preconnect_links,
+            r#"<link rel="preconnect" href="https://cdn.example.com" />
+<link rel="dns-prefetch" href="https://cdn.example.com" />
+<link rel="preconnect" href="https://api.example.com" crossorigin />
+<link rel="dns-prefetch" href="https://api.example.com" />"#
+        );
+        assert_eq!(preconnect_tags("/", &[]), "");
+        assert_eq!(
+            preconnect_tags("//cdn.example.com/", &[]),
+            r#"<link rel="preconnect" href="//cdn.example.com" />
+<link rel="dns-prefetch" href="//cdn.example.com" />"#
+        );
+
+        let html = render_html(
+            "<html><head lang=\"en\"><title></title></head><body></body></html>",
+            &chunk_group(),
+            "https://cdn.example.com/",
+            "{}",
+            "",
+            &preconnect_tags("https://cdn.example.com/", &[]),
+        )
+        .unwrap();
+        assert!(html.starts_with(
+            r#"<html><head lang="en">
+<link rel="preconnect" href="https://cdn.example.com" />
+<link rel="dns-prefetch" href="https://cdn.example.com" /><title>"#
+        ));
+    }
+
+    #[test]
+    fn test_html_update() {
+        let output = |html: &str, scripts: &str| HtmlOutput {
+            html: html.to_string(),
+            scripts: scripts.to_string(),
+        };
+        let last = output("<script></script>", "<script></script>");
+        assert_eq!(html_update(None, &last), HtmlUpdate::Write);
+        assert_eq!(
+            html_update(Some(&last), &last.clone()),
+            HtmlUpdate::Unchanged
+        );
+        assert_eq!(
+            html_update(
+                Some(&last),
+                &output("<link /><script></script>", "<script></script>")
+            ),
+            HtmlUpdate::Write
+        );
+        assert_eq!(
+            html_update(
+                Some(&last),
+                &output("<script src></script>", "<script src></script>")
+            ),
+            HtmlUpdate::WriteAndReload
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Serialize;
+use swc_core::ecma::ast::{
+    ImportDecl, ImportDefaultSpecifier, ImportNamedSpecifier, ImportSpecifier, Module, ModuleDecl,
+    ModuleExportName, ModuleItem, Str,
+};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::compiler::Context;
+use crate::config::IconImportConfig;
+use crate::module::{Dependency, ResolveType};
+use crate::plugin::{Plugin, PluginTransformJsParam};
+use crate::resolve::{resolve, ResolverResource};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IconImportRewrite {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+// metadata of a module in stats, the bytes saved are not reported since they can't be told
+// from the files of the barrel and the icon, which import other modules
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct IconImportsMetadata<'a> {
+    count: usize,
+    rewrites: &'a [IconImportRewrite],
+}
+
+// rewrite `import { Check } from "lucide-react"` to
+// `import Check from "lucide-react/dist/esm/icons/check"`,
+// imports whose deep path can't be resolved are left untouched
+pub struct IconImportVisitor<'a, F>
+where
+    F: Fn(&str) -> bool,
+{
+    config: &'a [IconImportConfig],
+    resolvable: F,
+    pub rewrites: Vec<IconImportRewrite>,
+}
+
+impl<'a, F> IconImportVisitor<'a, F>
+where
+    F: Fn(&str) -> bool,
+{
+    pub fn new(config: &'a [IconImportConfig], resolvable: F) -> Self {
+        Self {
+            config,
+            resolvable,
+            rewrites: vec![],
+        }
+    }
+
+    fn rewrite(&mut self, decl: &mut ImportDecl) -> Vec<ModuleItem> {
+        let configs = self.config;
+        let Some(config) = configs
+            .iter()
+            .find(|config| config.library_name == *decl.src.value)
+        else {
+            return vec![];
+        };
+        let from = decl.src.value.to_string();
+
+        let mut expanded = vec![];
+        let mut kept = vec![];
+        for specifier in std::mem::take(&mut decl.specifiers) {
+            let ImportSpecifier::Named(ImportNamedSpecifier {
+                span,
+                local,
+                imported,
+                is_type_only: false,
+            }) = &specifier
+            else {
+                kept.push(specifier);
+                continue;
+            };
+            let name = match imported {
+                Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                Some(ModuleExportName::Str(_)) => {
+                    kept.push(specifier);
+                    continue;
+                }
+                None => local.sym.to_string(),
+            };
+            let to = render_template(&config.template, &name);
+            if !(self.resolvable)(&to) {
+                kept.push(specifier);
+                continue;
+            }
+
+            let mut stmt = decl.clone();
+            stmt.specifiers = vec![ImportSpecifier::Default(ImportDefaultSpecifier {
+                span: *span,
+                local: local.clone(),
+            })];
+            stmt.src = Box::new(Str {
+                span: decl.src.span,
+                value: to.clone().into(),
+                raw: None,
+            });
+            expanded.push(ModuleItem::ModuleDecl(ModuleDecl::Import(stmt)));
+            self.rewrites.push(IconImportRewrite {
+                name,
+                from: from.clone(),
+                to,
+            });
+        }
+        decl.specifiers = kept;
+        expanded
+    }
+}
+
+impl<'a, F> VisitMut for IconImportVisitor<'a, F>
+where
+    F: Fn(&str) -> bool,
+{
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let mut body = Vec::with_capacity(module.body.len());
+        for mut item in module.body.drain(..) {
+            let mut expanded = vec![];
+            let mut keep = true;
+            if let ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) = &mut item
+                && !decl.type_only
+            {
+                let had_specifiers = !decl.specifiers.is_empty();
+                expanded = self.rewrite(decl);
+                // drop the barrel import when every specifier is rewritten
+                keep = !(had_specifiers && decl.specifiers.is_empty());
+            }
+            if keep {
+                body.push(item);
+            }
+            body.extend(expanded);
+        }
+        module.body = body;
+    }
+}
+
+// supported placeholders: [name] (e.g. ArrowUp) and [kebabName] (e.g. arrow-up)
+fn render_template(template: &str, name: &str) -> String {
+    template
+        .replace("[name]", name)
+        .replace("[kebabName]", &to_kebab_case(name))
+}
+
+fn to_kebab_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase()
+            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            result.push('-');
+        }
+        result.push(c.to_ascii_lowercase());
+        prev = Some(c);
+    }
+    result
+}
+
+pub struct IconImportsPlugin {}
+
+impl Plugin for IconImportsPlugin {
+    fn name(&self) -> &str {
+        "icon_imports"
+    }
+
+    fn transform_js(
+        &self,
+        param: &PluginTransformJsParam,
+        ast: &mut Module,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        if param.path.contains("node_modules") || context.config.icon_imports.is_empty() {
+            return Ok(());
+        }
+
+        let resolvable = |source: &str| {
+            let dep = Dependency {
+                source: source.to_string(),
+                resolve_as: None,
+                specifiers: vec![],
+                resolve_type: ResolveType::Import(Default::default()),
+                order: 0,
+                span: None,
+            };
+            matches!(
+                resolve(param.path, &dep, &context.resolvers, context),
+                Ok(ResolverResource::Resolved(_))
+            )
+        };
+        let mut visitor = IconImportVisitor::new(&context.config.icon_imports, resolvable);
+        ast.visit_mut_with(&mut visitor);
+
+        if !visitor.rewrites.is_empty() {
+            context.stats_info.set_module_metadata(
+                param.path,
+                "iconImports",
+                &IconImportsMetadata {
+                    count: visitor.rewrites.len(),
+                    rewrites: &visitor.rewrites,
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_kebab_case, IconImportRewrite, IconImportVisitor};
+    use crate::ast::tests::TestUtils;
+    use crate::config::IconImportConfig;
+
+    #[test]
+    fn test_rewrite() {
+        let (code, rewrites) = run(r#"
+import { Check, ArrowUp as Up } from "lucide-react";
+console.log(Check, Up);
+"#);
+        assert_eq!(
+            code,
+            r#"
+import Check from "lucide-react/dist/esm/icons/check";
+import Up from "lucide-react/dist/esm/icons/arrow-up";
+console.log(Check, Up);
+"#
+            .trim()
+        );
+        assert_eq!(
+            rewrites[1],
+            IconImportRewrite {
+                name: "ArrowUp".to_string(),
+                from: "lucide-react".to_string(),
+                to: "lucide-react/dist/esm/icons/arrow-up".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_keep_unresolved_and_other_specifiers() {
+        let (code, rewrites) = run(r#"
+import lucide, { Check, Missing } from "lucide-react";
+import { X } from "other";
+"#);
+        assert_eq!(
+            code,
+            r#"
+import lucide, { Missing } from "lucide-react";
+import Check from "lucide-react/dist/esm/icons/check";
+import { X } from "other";
+"#
+            .trim()
+        );
+        assert_eq!(rewrites.len(), 1);
+    }
+
+    #[test]
+    fn test_kebab_case() {
+        assert_eq!(to_kebab_case("Check"), "check");
+        assert_eq!(to_kebab_case("ArrowUp10"), "arrow-up10");
+        assert_eq!(to_kebab_case("Columns3Cog"), "columns3-cog");
+    }
+
+    fn run(code: &str) -> (String, Vec<IconImportRewrite>) {
+        let config = vec![IconImportConfig {
+            library_name: "lucide-react".to_string(),
+            template: "lucide-react/dist/esm/icons/[kebabName]".to_string(),
+        }];
+        let mut test_utils = TestUtils::gen_js_ast(code);
+        let mut visitor = IconImportVisitor::new(&config, |source: &str| {
+            source != "lucide-react/dist/esm/icons/missing"
+        });
+        let code = test_utils.transform_js(&mut visitor).js_ast_to_code();
+        (code, visitor.rewrites)
+    }
+}
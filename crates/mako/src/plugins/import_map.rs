@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+
+const IMPORT_MAP_FILE_NAME: &str = "importmap.json";
+
+/**
+ * bare specifiers mapped by importMap are externals imported natively by the browser,
+ * e.g. to load react from a cdn, they are async modules resolved with `import()`
+ *
+ * with emit, the mapped specifiers that are actually used are written to importmap.json
+ * and injected into html as <script type="importmap">
+ */
+pub struct ImportMapPlugin {}
+
+impl Plugin for ImportMapPlugin {
+    fn name(&self) -> &str {
+        "import_map"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        if context.config.import_map.is_none() {
+            return Ok(vec![]);
+        }
+        Ok(vec![r#"/* mako/runtime/importMap */
+requireModule.importModule = function (specifier) {
+  return import(specifier).then(function (ns) {
+    var exports = { __esModule: true };
+    for (var key in ns) exports[key] = ns[key];
+    return exports;
+  });
+};"#
+        .to_string()])
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        if !context.config.import_map.as_ref().is_some_and(|c| c.emit) {
+            return Ok(None);
+        }
+        let imports = used_imports(context);
+        let content = serde_json::to_string_pretty(&serde_json::json!({ "imports": imports }))?;
+        fs::create_dir_all(&context.config.output.path)?;
+        fs::write(
+            context.config.output.path.join(IMPORT_MAP_FILE_NAME),
+            content,
+        )?;
+        Ok(None)
+    }
+}
+
+// specifier -> url of the import map entries used by modules
+pub(crate) fn used_imports(context: &Arc<Context>) -> BTreeMap<String, String> {
+    let module_graph = context.module_graph.read().unwrap();
+    module_graph
+        .modules()
+        .into_iter()
+        .filter_map(|module| {
+            let resource = module.info.as_ref()?.resolved_resource.as_ref()?;
+            let url = resource.get_import_map()?;
+            Some((resource.get_resolved_path(), url))
+        })
+        .collect()
+}
+
+// <script type="importmap"> of the used imports, empty if not emitted
+pub(crate) fn import_map_tag(context: &Arc<Context>) -> Result<String> {
+    if !context.config.import_map.as_ref().is_some_and(|c| c.emit) {
+        return Ok(String::new());
+    }
+    let imports = used_imports(context);
+    if imports.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(format!(
+        "<script type=\"importmap\">{}</script>",
+        serde_json::to_string(&serde_json::json!({ "imports": imports }))?
+    ))
+}
@@ -0,0 +1,1359 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{anyhow, Result};
+use glob_match::glob_match;
+use indexmap::{IndexMap, IndexSet};
+use regex::Regex;
+use swc_core::common::sync::Lrc;
+use swc_core::common::{FileName, Mark, SourceMap, Span, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::{
+    CallExpr, Callee, ExportSpecifier, Expr, ExprStmt, Function, Ident, ImportDecl,
+    ImportDefaultSpecifier, ImportNamedSpecifier, ImportSpecifier, ImportStarAsSpecifier,
+    MemberExpr, MemberProp, ModuleDecl, ModuleItem, NamedExport, Stmt, TsExprWithTypeArgs,
+    TsInterfaceDecl, TsType, TsTypeAliasDecl, VarDeclKind, WithStmt,
+};
+use swc_core::ecma::parser::{parse_file_as_script, EsConfig, Syntax};
+use swc_core::ecma::utils::{quote_ident, quote_str, ExprFactory};
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::config::InjectFilterConfig;
+use crate::utils::ParseRegex;
+
+pub(crate) struct MyInjector<'a> {
+    unresolved_mark: Mark,
+    injects: HashMap<String, &'a Inject>,
+    // injects of dotted paths like `React.createElement`, the member expressions are rewritten
+    // to the injected bindings
+    member_injects: HashMap<String, &'a Inject>,
+    // side-effect only injects, which are injected without usage check
+    bare_injects: Vec<&'a Inject>,
+    will_inject: IndexSet<(&'a Inject, SyntaxContext)>,
+    is_cjs: bool,
+    // depth of `with` statement bodies, unresolved idents there may be properties of the object
+    with_depth: usize,
+    // whether each enclosing function scope (and the module) has a direct `eval` call, which
+    // may declare vars the resolver can't see, only possible in sloppy mode, i.e. cjs
+    eval_scopes: Vec<bool>,
+    // injects not applied because all their usages are in `with` or `eval` scopes
+    pub skipped: IndexSet<String>,
+}
+
+impl<'a> MyInjector<'a> {
+    pub fn new(unresolved_mark: Mark, injects: HashMap<String, &'a Inject>) -> Self {
+        let (bare_injects, injects): (HashMap<_, _>, HashMap<_, _>) =
+            injects.into_iter().partition(|(_, inject)| inject.bare);
+        let mut bare_injects = bare_injects.into_values().collect::<Vec<_>>();
+        bare_injects.sort_by(|a, b| a.cmp_order(b));
+        let (member_injects, injects) = injects
+            .into_iter()
+            .partition(|(name, _)| name.contains('.'));
+        Self {
+            unresolved_mark,
+            will_inject: Default::default(),
+            injects,
+            member_injects,
+            bare_injects,
+            is_cjs: true,
+            with_depth: 0,
+            eval_scopes: vec![],
+            skipped: Default::default(),
+        }
+    }
+}
+
+impl MyInjector<'_> {
+    // dotted path of member expressions like `a.b.c` rooted at an unresolved ident,
+    // with the syntax context of the root
+    fn member_path(&self, member: &MemberExpr) -> Option<(String, SyntaxContext)> {
+        let MemberProp::Ident(prop) = &member.prop else {
+            return None;
+        };
+        let (obj_path, ctxt) = match &*member.obj {
+            Expr::Ident(ident) if ident.span.ctxt.outer() == self.unresolved_mark => {
+                (ident.sym.to_string(), ident.span.ctxt)
+            }
+            Expr::Member(obj) => self.member_path(obj)?,
+            _ => return None,
+        };
+        Some((format!("{}.{}", obj_path, prop.sym), ctxt))
+    }
+
+    fn in_dynamic_scope(&self) -> bool {
+        self.with_depth > 0 || self.eval_scopes.iter().any(|&has_eval| has_eval)
+    }
+
+    fn has_direct_eval<N: VisitWith<DirectEvalFinder>>(&self, n: &N) -> bool {
+        if !self.is_cjs {
+            return false;
+        }
+        let mut finder = DirectEvalFinder {
+            unresolved_mark: self.unresolved_mark,
+            found: false,
+        };
+        n.visit_children_with(&mut finder);
+        finder.found
+    }
+}
+
+// finds `eval(...)` calls of the function scope, nested functions are not visited since vars
+// declared by eval there are not visible outside
+struct DirectEvalFinder {
+    unresolved_mark: Mark,
+    found: bool,
+}
+
+impl Visit for DirectEvalFinder {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee
+            && let Expr::Ident(ident) = &**callee
+            && &*ident.sym == "eval"
+            && ident.span.ctxt.outer() == self.unresolved_mark
+        {
+            self.found = true;
+            return;
+        }
+        call.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+}
+
+impl VisitMut for MyInjector<'_> {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if !self.member_injects.is_empty()
+            && let Expr::Member(member) = expr
+            && let Some((path, ctxt)) = self.member_path(member)
+            && let Some(&inject) = self.member_injects.get(&path)
+        {
+            if self.in_dynamic_scope() {
+                self.skipped.insert(path);
+                return;
+            }
+            self.will_inject.insert((inject, ctxt));
+            *expr = quote_ident!(Span { ctxt, ..DUMMY_SP }, inject.local_name()).into();
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_ident(&mut self, n: &mut Ident) {
+        if self.injects.is_empty() {
+            return;
+        }
+
+        if n.span.ctxt.outer() == self.unresolved_mark {
+            let name = n.sym.to_string();
+
+            if self.in_dynamic_scope() {
+                if self.injects.contains_key(&name) {
+                    self.skipped.insert(name);
+                }
+                return;
+            }
+
+            if let Some(inject) = self.injects.remove(&name) {
+                self.will_inject.insert((inject, n.span.ctxt));
+            }
+        }
+    }
+
+    fn visit_mut_with_stmt(&mut self, n: &mut WithStmt) {
+        n.obj.visit_mut_with(self);
+        self.with_depth += 1;
+        n.body.visit_mut_with(self);
+        self.with_depth -= 1;
+    }
+
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let has_eval = self.has_direct_eval(n);
+        self.eval_scopes.push(has_eval);
+        n.visit_mut_children_with(self);
+        self.eval_scopes.pop();
+    }
+
+    // types are erased, identifiers in type positions (e.g. `let x: my.Foo`) are not usages
+    fn visit_mut_ts_type(&mut self, _: &mut TsType) {}
+
+    fn visit_mut_ts_interface_decl(&mut self, _: &mut TsInterfaceDecl) {}
+
+    fn visit_mut_ts_type_alias_decl(&mut self, _: &mut TsTypeAliasDecl) {}
+
+    // `implements my.Foo` of classes
+    fn visit_mut_ts_expr_with_type_args(&mut self, _: &mut TsExprWithTypeArgs) {}
+
+    fn visit_mut_named_export(&mut self, named_export: &mut NamedExport) {
+        if named_export.src.is_some() {
+            named_export.visit_mut_children_with(self);
+        } else {
+            for spec in named_export.specifiers.iter_mut() {
+                match spec {
+                    ExportSpecifier::Namespace(_) | ExportSpecifier::Default(_) => {
+                        spec.visit_mut_with(self);
+                    }
+                    ExportSpecifier::Named(named) => {
+                        // skip the exported name
+                        named.orig.visit_mut_with(self);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_mut_module(&mut self, n: &mut swc_core::ecma::ast::Module) {
+        n.visit_mut_children_with(self);
+
+        // usages outside of `with` and `eval` scopes are injected anyway
+        let injected = self
+            .will_inject
+            .iter()
+            .map(|(inject, _)| inject.name.as_str())
+            .collect::<Vec<_>>();
+        self.skipped
+            .retain(|name| !injected.contains(&name.as_str()));
+
+        let bare_stmts = self.bare_injects.iter().map(|&inject| {
+            if self.is_cjs || inject.prefer_require {
+                inject.clone().into_bare_require(self.unresolved_mark)
+            } else {
+                import_decl(&inject.from, vec![])
+            }
+        });
+
+        // injected in the configured order, rather than the order they are found in the ast
+        let mut will_inject = self.will_inject.iter().copied().collect::<Vec<_>>();
+        will_inject.sort_by(|(a, _), (b, _)| a.cmp_order(b));
+
+        let mut requires = vec![];
+        let mut snippets = vec![];
+        // specifiers from the same source are grouped into one import declaration
+        let mut imports: IndexMap<&str, Vec<ImportSpecifier>> = IndexMap::new();
+        for (inject, ctxt) in will_inject {
+            if let Some(snippet) = &inject.snippet {
+                snippets.extend(
+                    snippet_with_ctxt(snippet, ctxt)
+                        .into_iter()
+                        .map(ModuleItem::from),
+                );
+            } else if self.is_cjs || inject.prefer_require {
+                requires.push(inject.clone().into_require_with(ctxt, self.unresolved_mark));
+            } else {
+                imports
+                    .entry(inject.from.as_str())
+                    .or_default()
+                    .push(inject.clone().into_specifier(ctxt));
+            }
+        }
+        let import_decls = imports.into_iter().flat_map(|(from, specifiers)| {
+            group_specifiers(specifiers)
+                .into_iter()
+                .map(move |specifiers| import_decl(from, specifiers))
+        });
+
+        let stmts = bare_stmts
+            .chain(import_decls)
+            .chain(requires)
+            .chain(snippets)
+            .collect::<Vec<_>>();
+        n.body.splice(0..0, stmts);
+    }
+
+    fn visit_mut_module_items(&mut self, module_items: &mut Vec<ModuleItem>) {
+        let has_esm = module_items.iter().any(|item| match item {
+            ModuleItem::ModuleDecl(_) => true,
+            ModuleItem::Stmt(_) => false,
+        });
+
+        self.is_cjs = !has_esm;
+
+        let has_eval = self.has_direct_eval(module_items);
+        self.eval_scopes.push(has_eval);
+        module_items.visit_mut_children_with(self);
+        self.eval_scopes.pop();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum InjectFilter {
+    // matched against the absolute path
+    Regex(Regex),
+    // matched against the path relative to root
+    Globs(Vec<String>),
+}
+
+impl InjectFilter {
+    pub fn from_config(config: &Option<InjectFilterConfig>) -> Result<Option<Self>> {
+        Ok(match config {
+            Some(InjectFilterConfig::Regex(regex)) => {
+                Some(regex.clone()).parse_into_regex()?.map(Self::Regex)
+            }
+            Some(InjectFilterConfig::Globs(globs)) => Some(Self::Globs(globs.clone())),
+            None => None,
+        })
+    }
+
+    pub fn is_match(&self, path: &str, relative_path: &str) -> bool {
+        match self {
+            Self::Regex(regex) => regex.is_match(path),
+            Self::Globs(globs) => globs
+                .iter()
+                .any(|glob| glob_match(glob.trim_start_matches("./"), relative_path)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Inject {
+    pub from: String,
+    pub name: String,
+    pub named: Option<String>,
+    pub namespace: Option<bool>,
+    // node_modules is excluded if not specified
+    pub exclude: Option<InjectFilter>,
+    pub include: Option<InjectFilter>,
+    pub prefer_require: bool,
+    // import "from" for side effects only, name is not bound
+    pub bare: bool,
+    // statements spliced at the top of modules instead of an import, with dummy spans
+    pub snippet: Option<Vec<Stmt>>,
+    // injects are emitted in ascending order, and then by name
+    pub order: i32,
+}
+
+impl Eq for Inject {}
+
+impl PartialEq for Inject {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Hash for Inject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.name.as_bytes());
+    }
+}
+
+impl Inject {
+    fn cmp_order(&self, other: &Self) -> Ordering {
+        self.order
+            .cmp(&other.order)
+            .then_with(|| self.name.cmp(&other.name))
+    }
+
+    // name of the binding, dotted paths are joined with `_`, e.g. `React_createElement`
+    fn local_name(&self) -> String {
+        self.name.replace('.', "_")
+    }
+
+    // require("from");
+    fn into_bare_require(self, unresolved_mark: Mark) -> ModuleItem {
+        let stmt: Stmt = ExprStmt {
+            span: DUMMY_SP,
+            expr: quote_ident!(DUMMY_SP.apply_mark(unresolved_mark), "require")
+                .as_call(DUMMY_SP, vec![quote_str!(self.from).as_arg()])
+                .into(),
+        }
+        .into();
+        stmt.into()
+    }
+
+    fn into_require_with(self, ctxt: SyntaxContext, unresolved_mark: Mark) -> ModuleItem {
+        let name_span = Span { ctxt, ..DUMMY_SP };
+
+        let require_source_expr = quote_ident!(DUMMY_SP.apply_mark(unresolved_mark), "require")
+            .as_call(DUMMY_SP, vec![quote_str!(self.from).as_arg()]);
+
+        let stmt: Stmt = match (&self.named, &self.namespace) {
+            // import { named as x }
+            (Some(named), None | Some(false)) => MemberExpr {
+                span: Default::default(),
+                obj: require_source_expr.into(),
+                prop: quote_ident!(named.to_string()).into(),
+            }
+            .into_var_decl(
+                VarDeclKind::Var,
+                quote_ident!(name_span, self.local_name()).into(),
+            )
+            .into(),
+            // import * as x
+            (None, Some(true)) => require_source_expr
+                .into_var_decl(
+                    VarDeclKind::Var,
+                    quote_ident!(name_span, self.local_name()).into(),
+                )
+                .into(),
+
+            // import x from "x"
+            (None, None | Some(false)) => MemberExpr {
+                span: DUMMY_SP,
+                obj: require_source_expr.into(),
+                prop: quote_ident!("default").into(),
+            }
+            .into_var_decl(
+                VarDeclKind::Var,
+                quote_ident!(name_span, self.local_name()).into(),
+            )
+            .into(),
+            (Some(_), Some(true)) => {
+                panic!("Cannot use both `named` and `namespaced`")
+            }
+        };
+
+        stmt.into()
+    }
+
+    fn into_specifier(self, ctxt: SyntaxContext) -> ImportSpecifier {
+        let name_span = Span { ctxt, ..DUMMY_SP };
+        match (&self.named, &self.namespace) {
+            // import { named as x }
+            (Some(named), None | Some(false)) => ImportNamedSpecifier {
+                span: DUMMY_SP,
+                local: quote_ident!(name_span, self.local_name()),
+                imported: if *named == self.local_name() {
+                    None
+                } else {
+                    Some(quote_ident!(named.to_string()).into())
+                },
+                is_type_only: false,
+            }
+            .into(),
+
+            // import * as x
+            (None, Some(true)) => ImportStarAsSpecifier {
+                span: DUMMY_SP,
+                local: quote_ident!(name_span, self.local_name()),
+            }
+            .into(),
+
+            // import x
+            (None, None | Some(false)) => ImportDefaultSpecifier {
+                span: DUMMY_SP,
+                local: quote_ident!(name_span, self.local_name()),
+            }
+            .into(),
+
+            (Some(_), Some(true)) => {
+                panic!("Cannot use both `named` and `namespaced`")
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_snippet(code: &str) -> Result<Vec<Stmt>> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, code.to_string());
+    let script = parse_file_as_script(
+        &fm,
+        Syntax::Es(EsConfig::default()),
+        Default::default(),
+        None,
+        &mut vec![],
+    )
+    .map_err(|e| anyhow!("{}", e.kind().msg()))?;
+    Ok(snippet_with_ctxt(&script.body, SyntaxContext::empty()))
+}
+
+// identifiers of snippets share the syntax context of the usages, so the declared names
+// bind them, and free variables like `process` stay unresolved
+fn snippet_with_ctxt(snippet: &[Stmt], ctxt: SyntaxContext) -> Vec<Stmt> {
+    let mut setter = SpanSetter(Span { ctxt, ..DUMMY_SP });
+    snippet
+        .iter()
+        .map(|stmt| {
+            let mut stmt = stmt.clone();
+            stmt.visit_mut_with(&mut setter);
+            stmt
+        })
+        .collect()
+}
+
+struct SpanSetter(Span);
+
+impl VisitMut for SpanSetter {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = self.0;
+    }
+}
+
+// import { a, b as c } from "from";
+fn import_decl(from: &str, specifiers: Vec<ImportSpecifier>) -> ModuleItem {
+    let decl: ModuleDecl = ImportDecl {
+        span: DUMMY_SP,
+        specifiers,
+        type_only: false,
+        with: None,
+        src: quote_str!(from).into(),
+    }
+    .into();
+    decl.into()
+}
+
+// a default specifier can be combined with named specifiers or a namespace specifier,
+// but named and namespace specifiers can't be combined, neither can multiple defaults
+// or namespaces
+fn group_specifiers(specifiers: Vec<ImportSpecifier>) -> Vec<Vec<ImportSpecifier>> {
+    let mut defaults = vec![];
+    let mut namespaces = vec![];
+    let mut named = vec![];
+    for specifier in specifiers {
+        match specifier {
+            ImportSpecifier::Default(_) => defaults.push(specifier),
+            ImportSpecifier::Namespace(_) => namespaces.push(specifier),
+            ImportSpecifier::Named(_) => named.push(specifier),
+        }
+    }
+    let mut defaults = defaults.into_iter();
+    let mut namespaces = namespaces.into_iter();
+    let mut first = defaults.next().into_iter().collect::<Vec<_>>();
+    if !named.is_empty() {
+        first.extend(named);
+    } else {
+        first.extend(namespaces.next());
+    }
+    std::iter::once(first)
+        .filter(|group| !group.is_empty())
+        .chain(defaults.chain(namespaces).map(|specifier| vec![specifier]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use maplit::hashmap;
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::transforms::base::resolver;
+
+    use super::*;
+    use crate::ast::file::{File, JsContent};
+    use crate::ast::js_ast::JsAst;
+    use crate::build::analyze_deps::AnalyzeDeps;
+    use crate::compiler::{Args, Context};
+    use crate::module::ModuleAst;
+
+    fn apply_inject_to_code(injects: HashMap<String, &Inject>, code: &str) -> String {
+        apply_inject_to_file("cut.js", injects, code)
+    }
+
+    fn apply_inject_to_file(path: &str, injects: HashMap<String, &Inject>, code: &str) -> String {
+        apply_inject_with_skipped(path, injects, code).0
+    }
+
+    fn apply_inject_with_skipped(
+        path: &str,
+        injects: HashMap<String, &Inject>,
+        code: &str,
+    ) -> (String, Vec<String>) {
+        let mut context = Context::default();
+        context.config.devtool = None;
+        let context = Arc::new(context);
+        let mut ast = JsAst::build(path, code, context.clone()).unwrap();
+        let mut injector = MyInjector::new(ast.unresolved_mark, injects);
+        let is_ts = path.ends_with(".ts") || path.ends_with(".tsx");
+        GLOBALS.set(&context.meta.script.globals, || {
+            ast.ast.visit_mut_with(&mut resolver(
+                ast.unresolved_mark,
+                ast.top_level_mark,
+                is_ts,
+            ));
+            ast.ast.visit_mut_with(&mut injector);
+        });
+        (
+            ast.generate(context.clone()).unwrap().code,
+            injector.skipped.into_iter().collect(),
+        )
+    }
+
+    #[test]
+    fn no_inject() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"let my = 1;my.call("toast");"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"let my = 1;
+my.call("toast");
+"#
+        );
+    }
+
+    #[test]
+    fn inject_from_default() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import my from "mock-lib";
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_from_default() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib").default;
+my.call("toast");
+"#
+        );
+    }
+
+    #[test]
+    fn inject_from_named() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: Some("her".to_string()),
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");export { }"#,
+        );
+        assert_eq!(
+            code,
+            r#"import { her as my } from "mock-lib";
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_from_named() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: Some("her".to_string()),
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast")"#,
+        );
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib").her;
+my.call("toast");
+"#
+        );
+    }
+
+    #[test]
+    fn inject_from_named_same_name() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: Some("my".to_string()),
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import { my } from "mock-lib";
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_from_named_same_name() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: Some("my".to_string()),
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib").my;
+my.call("toast");
+"#
+        );
+    }
+
+    #[test]
+    fn inject_from_namespace() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: Some(true),
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import * as my from "mock-lib";
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_from_namespace() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: Some(true),
+            exclude: None,
+            include: None,
+            prefer_require: false,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib");
+my.call("toast");
+"#
+        );
+    }
+
+    #[test]
+    fn injected_require_treat_as_dep() {
+        let code = r#"my.call("toast");"#;
+        let context = Context {
+            args: Args {
+                watch: true,
+                verbose: false,
+            },
+            ..Context::default()
+        };
+        let context = Arc::new(context);
+        let file = File::with_content(
+            "cut.js".to_string(),
+            crate::ast::file::Content::Js(JsContent {
+                content: code.to_string(),
+                ..Default::default()
+            }),
+            context.clone(),
+        );
+        let mut ast = JsAst::new(&file, context.clone()).unwrap();
+
+        GLOBALS.set(&context.meta.script.globals, || {
+            let unresolved_mark = ast.unresolved_mark;
+            let top_level_mark = ast.top_level_mark;
+            ast.ast
+                .visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+            let injects = Inject {
+                name: "my".to_string(),
+                named: None,
+                from: "mock-lib".to_string(),
+                namespace: Some(true),
+                exclude: None,
+                include: None,
+                prefer_require: false,
+                bare: false,
+                order: 0,
+                snippet: None,
+            };
+            ast.ast.visit_mut_with(&mut MyInjector::new(
+                ast.unresolved_mark,
+                hashmap! {"my".to_string() =>&injects},
+            ));
+        });
+
+        let module_ast = ModuleAst::Script(ast);
+        let deps = AnalyzeDeps::analyze_deps(&module_ast, &file, context.clone()).unwrap();
+        println!("deps: {:?}", deps);
+
+        assert_eq!(deps.resolved_deps.len(), 0);
+        assert_eq!(deps.missing_deps.len(), 1);
+    }
+
+    #[test]
+    fn inject_prefer_require() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: true,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"my.call("toast");export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib").default;
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn dont_inject_named_exported() {
+        let i = Inject {
+            name: "my".to_string(),
+            named: None,
+            from: "mock-lib".to_string(),
+            namespace: None,
+            exclude: None,
+            include: None,
+            prefer_require: true,
+            bare: false,
+            order: 0,
+            snippet: None,
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "my".to_string() =>&i
+            },
+            r#"let foo=1;export {foo as my}"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"let foo = 1;
+export { foo as my };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_bare() {
+        let polyfill = Inject {
+            name: "polyfill".to_string(),
+            from: "core-js/stable".to_string(),
+            bare: true,
+            ..Default::default()
+        };
+        let my = Inject {
+            name: "my".to_string(),
+            from: "mock-lib".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "polyfill".to_string() => &polyfill,
+                "my".to_string() => &my,
+            },
+            r#"let polyfill = 1;my.call("toast");export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import "core-js/stable";
+import my from "mock-lib";
+let polyfill = 1;
+my.call("toast");
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_bare() {
+        let polyfill = Inject {
+            name: "polyfill".to_string(),
+            from: "core-js/stable".to_string(),
+            bare: true,
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "polyfill".to_string() => &polyfill
+            },
+            r#"console.log(1);"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"require("core-js/stable");
+console.log(1);
+"#
+        );
+    }
+
+    #[test]
+    fn inject_member_expr() {
+        let i = Inject {
+            name: "React.createElement".to_string(),
+            named: Some("createElement".to_string()),
+            from: "react".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "React.createElement".to_string() => &i
+            },
+            r#"React.createElement("div");React.Fragment;export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import { createElement as React_createElement } from "react";
+React_createElement("div");
+React.Fragment;
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_in_cjs_member_expr() {
+        let i = Inject {
+            name: "process.nextTick".to_string(),
+            named: Some("nextTick".to_string()),
+            from: "process".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "process.nextTick".to_string() => &i
+            },
+            r#"process.nextTick(fn);process.nextTick.call(null, fn);"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var process_nextTick = require("process").nextTick;
+process_nextTick(fn);
+process_nextTick.call(null, fn);
+"#
+        );
+    }
+
+    #[test]
+    fn dont_inject_member_expr_of_local() {
+        let i = Inject {
+            name: "React.createElement".to_string(),
+            named: Some("createElement".to_string()),
+            from: "react".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "React.createElement".to_string() => &i
+            },
+            r#"const React = {};React.createElement("div");"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"const React = {};
+React.createElement("div");
+"#
+        );
+    }
+
+    #[test]
+    fn inject_grouped_by_source_in_order() {
+        let use_state = Inject {
+            name: "useState".to_string(),
+            named: Some("useState".to_string()),
+            from: "react".to_string(),
+            order: 1,
+            ..Default::default()
+        };
+        let use_effect = Inject {
+            name: "useEffect".to_string(),
+            named: Some("useEffect".to_string()),
+            from: "react".to_string(),
+            order: 2,
+            ..Default::default()
+        };
+        let react = Inject {
+            name: "React".to_string(),
+            from: "react".to_string(),
+            order: 3,
+            ..Default::default()
+        };
+        let lodash = Inject {
+            name: "_".to_string(),
+            from: "lodash".to_string(),
+            order: 0,
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "useState".to_string() => &use_state,
+                "useEffect".to_string() => &use_effect,
+                "React".to_string() => &react,
+                "_".to_string() => &lodash,
+            },
+            r#"useEffect(React.noop);useState(_.noop);export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import _ from "lodash";
+import React, { useState, useEffect } from "react";
+useEffect(React.noop);
+useState(_.noop);
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn group_namespace_and_named() {
+        let named = Inject {
+            name: "a".to_string(),
+            named: Some("a".to_string()),
+            from: "mock-lib".to_string(),
+            order: 0,
+            ..Default::default()
+        };
+        let namespace = Inject {
+            name: "ns".to_string(),
+            namespace: Some(true),
+            from: "mock-lib".to_string(),
+            order: 1,
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_code(
+            hashmap! {
+                "a".to_string() => &named,
+                "ns".to_string() => &namespace,
+            },
+            r#"ns(a);export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import { a } from "mock-lib";
+import * as ns from "mock-lib";
+ns(a);
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn dont_inject_in_type_positions() {
+        let i = Inject {
+            name: "my".to_string(),
+            from: "mock-lib".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_file(
+            "cut.ts",
+            hashmap! {
+                "my".to_string() => &i
+            },
+            r#"let x: my.Foo;type A = my.Bar<my>;interface B extends my.Baz {}
+class C implements my.Foo {}export { }"#,
+        );
+
+        assert!(!code.contains("mock-lib"), "{}", code);
+    }
+
+    #[test]
+    fn inject_in_value_positions_of_ts() {
+        let i = Inject {
+            name: "my".to_string(),
+            from: "mock-lib".to_string(),
+            ..Default::default()
+        };
+
+        let code = apply_inject_to_file(
+            "cut.ts",
+            hashmap! {
+                "my".to_string() => &i
+            },
+            r#"let x: my.Foo = my.create();export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"import my from "mock-lib";
+let x: my.Foo = my.create();
+export { };
+"#
+        );
+    }
+
+    fn my_inject() -> Inject {
+        Inject {
+            name: "my".to_string(),
+            from: "mock-lib".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dont_inject_shadowed_in_iife() {
+        let i = my_inject();
+        let (code, skipped) = apply_inject_with_skipped(
+            "cut.js",
+            hashmap! { "my".to_string() => &i },
+            r#"(function(){ var my = 1; my.call(); })();(function(my){ my.call(); })();"#,
+        );
+
+        assert!(!code.contains("require"));
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn inject_outside_of_shadowing_scope() {
+        let i = my_inject();
+        let code = apply_inject_to_code(
+            hashmap! { "my".to_string() => &i },
+            r#"(function(){ let my = 1; my.call(); })();my.call();"#,
+        );
+
+        assert!(code.starts_with(r#"var my = require("mock-lib").default;"#));
+        assert_eq!(code.matches("require").count(), 1);
+    }
+
+    #[test]
+    fn skip_inject_in_with_stmt() {
+        let i = my_inject();
+        let (code, skipped) = apply_inject_with_skipped(
+            "cut.js",
+            hashmap! { "my".to_string() => &i },
+            r#"with (obj) { my.call(); }"#,
+        );
+
+        assert!(!code.contains("require"));
+        assert_eq!(skipped, vec!["my".to_string()]);
+    }
+
+    #[test]
+    fn skip_inject_in_eval_scope_of_cjs() {
+        let i = my_inject();
+        let (code, skipped) = apply_inject_with_skipped(
+            "cut.js",
+            hashmap! { "my".to_string() => &i },
+            r#"function f() { eval("var my = 1"); return function() { return my; }; }"#,
+        );
+
+        assert!(!code.contains("require"));
+        assert_eq!(skipped, vec!["my".to_string()]);
+    }
+
+    #[test]
+    fn inject_outside_of_eval_scope() {
+        let i = my_inject();
+        let (code, skipped) = apply_inject_with_skipped(
+            "cut.js",
+            hashmap! { "my".to_string() => &i },
+            r#"function f() { eval("var my = 1"); my.call(); } function g() { my.call(); }"#,
+        );
+
+        assert!(code.starts_with(r#"var my = require("mock-lib").default;"#));
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn inject_in_eval_scope_of_esm() {
+        let i = my_inject();
+        let (code, skipped) = apply_inject_with_skipped(
+            "cut.js",
+            hashmap! { "my".to_string() => &i },
+            r#"eval("1"); my.call(); export {}"#,
+        );
+
+        assert!(code.starts_with(r#"import my from "mock-lib";"#));
+        assert!(skipped.is_empty());
+    }
+
+    fn dev_snippet() -> Inject {
+        Inject {
+            name: "__DEV__".to_string(),
+            snippet: Some(
+                parse_snippet(r#"const __DEV__ = process.env.NODE_ENV !== "production";"#).unwrap(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn inject_snippet() {
+        let i = dev_snippet();
+        let code = apply_inject_to_code(
+            hashmap! { "__DEV__".to_string() => &i },
+            r#"if (__DEV__) console.log(1);export { }"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"const __DEV__ = process.env.NODE_ENV !== "production";
+if (__DEV__) console.log(1);
+export { };
+"#
+        );
+    }
+
+    #[test]
+    fn inject_snippet_after_imports() {
+        let i = dev_snippet();
+        let j = my_inject();
+        let code = apply_inject_to_code(
+            hashmap! { "__DEV__".to_string() => &i, "my".to_string() => &j },
+            r#"if (__DEV__) my.call();"#,
+        );
+
+        assert_eq!(
+            code,
+            r#"var my = require("mock-lib").default;
+const __DEV__ = process.env.NODE_ENV !== "production";
+if (__DEV__) my.call();
+"#
+        );
+    }
+
+    #[test]
+    fn dont_inject_snippet_of_shadowed() {
+        let i = dev_snippet();
+        let code = apply_inject_to_code(
+            hashmap! { "__DEV__".to_string() => &i },
+            r#"const __DEV__ = false;if (__DEV__) console.log(1);"#,
+        );
+
+        assert!(!code.contains("process"));
+    }
+
+    #[test]
+    fn parse_invalid_snippet() {
+        assert!(parse_snippet("const = 1").is_err());
+    }
+}
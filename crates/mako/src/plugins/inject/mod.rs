@@ -0,0 +1,296 @@
+mod injector;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use injector::{parse_snippet, MyInjector};
+pub(crate) use injector::{Inject, InjectFilter};
+use swc_core::ecma::ast::Module;
+use swc_core::ecma::visit::VisitMutWith;
+
+use crate::compiler::Context;
+use crate::config::InjectItem;
+use crate::plugin::{Plugin, PluginTransformJsParam};
+
+/**
+ * inject imports of free variables used in modules, configured by `inject`, e.g.
+ *
+ * { "Buffer": { "from": "buffer", "named": "Buffer" } }
+ *
+ * Buffer.from("a");
+ * ->
+ * import { Buffer } from "buffer";
+ * Buffer.from("a");
+ */
+pub struct InjectPlugin {
+    pub injects: HashMap<String, Inject>,
+}
+
+impl Plugin for InjectPlugin {
+    fn name(&self) -> &str {
+        "inject"
+    }
+
+    fn transform_js(
+        &self,
+        param: &PluginTransformJsParam,
+        ast: &mut Module,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        apply_injects(&self.injects, param, ast, context)
+    }
+}
+
+pub(crate) fn create_injects(
+    config: &HashMap<String, InjectItem>,
+) -> Result<HashMap<String, Inject>> {
+    config
+        .iter()
+        .map(|(name, item)| {
+            if item.named.is_some() && item.namespace == Some(true) {
+                return Err(anyhow!(
+                    "Config Error inject \"{}\": named and namespace can't be used together",
+                    name
+                ));
+            }
+            if let Some(code) = &item.code {
+                if !item.from.is_empty()
+                    || item.named.is_some()
+                    || item.namespace.is_some()
+                    || item.bare.is_some()
+                    || item.prefer_require.is_some()
+                {
+                    return Err(anyhow!(
+                        "Config Error inject \"{}\": code can't be used with from, named, namespace, bare or preferRequire",
+                        name
+                    ));
+                }
+                if name.contains('.') {
+                    return Err(anyhow!(
+                        "Config Error inject \"{}\": code can't be injected for member expressions",
+                        name
+                    ));
+                }
+                let snippet = parse_snippet(code).map_err(|e| {
+                    anyhow!("Config Error inject \"{}\": invalid code, {}", name, e)
+                })?;
+                return Ok((
+                    name.clone(),
+                    Inject {
+                        name: name.clone(),
+                        exclude: InjectFilter::from_config(&item.exclude)?,
+                        include: InjectFilter::from_config(&item.include)?,
+                        snippet: Some(snippet),
+                        order: item.order,
+                        ..Default::default()
+                    },
+                ));
+            }
+            if item.from.is_empty() {
+                return Err(anyhow!(
+                    "Config Error inject \"{}\": either from or code is required",
+                    name
+                ));
+            }
+            let bare = item.bare.unwrap_or(false);
+            if bare && (item.named.is_some() || item.namespace == Some(true)) {
+                return Err(anyhow!(
+                    "Config Error inject \"{}\": bare can't be used with named or namespace",
+                    name
+                ));
+            }
+            Ok((
+                name.clone(),
+                Inject {
+                    from: item.from.clone(),
+                    name: name.clone(),
+                    named: item.named.clone(),
+                    namespace: item.namespace,
+                    exclude: InjectFilter::from_config(&item.exclude)?,
+                    include: InjectFilter::from_config(&item.include)?,
+                    prefer_require: item.prefer_require.unwrap_or(false),
+                    bare,
+                    snippet: None,
+                    order: item.order,
+                },
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn apply_injects(
+    injects: &HashMap<String, Inject>,
+    param: &PluginTransformJsParam,
+    ast: &mut Module,
+    context: &Arc<Context>,
+) -> Result<()> {
+    if injects.is_empty() {
+        return Ok(());
+    }
+
+    let relative_path = Path::new(param.path)
+        .strip_prefix(&context.root)
+        .map_or(param.path.to_string(), |p| p.to_string_lossy().to_string());
+    let matched_injects = injects
+        .iter()
+        .filter(|(_, inject)| qualified(param.path, &relative_path, inject))
+        .map(|(name, inject)| (name.clone(), inject))
+        .collect::<HashMap<_, _>>();
+
+    if matched_injects.is_empty() {
+        return Ok(());
+    }
+
+    let mut injector = MyInjector::new(param.unresolved_mark, matched_injects);
+    ast.visit_mut_with(&mut injector);
+    for name in injector.skipped {
+        context.stats_info.add_grouped_warning(
+            format!(
+                "inject \"{}\" is skipped, it's only used in `with` statements or scopes of `eval`, which may bind it at runtime",
+                name
+            ),
+            relative_path.clone(),
+        );
+    }
+    Ok(())
+}
+
+fn qualified(path: &str, relative_path: &str, inject: &Inject) -> bool {
+    let excluded = match &inject.exclude {
+        Some(exclude) => exclude.is_match(path, relative_path),
+        None => path.contains("/node_modules/"),
+    };
+    if excluded {
+        return false;
+    }
+    inject
+        .include
+        .as_ref()
+        .map_or(true, |include| include.is_match(path, relative_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use regex::Regex;
+
+    use super::{create_injects, qualified, Inject, InjectFilter};
+    use crate::config::InjectItem;
+
+    #[test]
+    fn test_qualify_all_none() {
+        let inject = Inject {
+            include: None,
+            exclude: None,
+            ..Default::default()
+        };
+        assert!(qualified("src/index.js", "src/index.js", &inject));
+    }
+
+    #[test]
+    fn test_qualify_only_include() {
+        let inject = Inject {
+            include: Some(InjectFilter::Regex(Regex::new("src").unwrap())),
+            exclude: None,
+            ..Default::default()
+        };
+        assert!(qualified("src/index.js", "src/index.js", &inject));
+        assert!(!qualified("lib/index.js", "lib/index.js", &inject));
+    }
+
+    #[test]
+    fn test_qualify_only_exclude() {
+        let inject = Inject {
+            include: None,
+            exclude: Some(InjectFilter::Regex(Regex::new("src").unwrap())),
+            ..Default::default()
+        };
+        assert!(!qualified("src/index.js", "src/index.js", &inject));
+        assert!(qualified("lib/index.js", "lib/index.js", &inject));
+    }
+
+    #[test]
+    fn test_qualify_both() {
+        let inject = Inject {
+            include: Some(InjectFilter::Regex(Regex::new("index.js").unwrap())),
+            exclude: Some(InjectFilter::Regex(Regex::new("src").unwrap())),
+            ..Default::default()
+        };
+        assert!(!qualified("src/a.js", "src/a.js", &inject));
+        assert!(!qualified("src/index.js", "src/index.js", &inject));
+        assert!(qualified("lib/index.js", "lib/index.js", &inject));
+    }
+
+    #[test]
+    fn test_qualify_node_modules_excluded_by_default() {
+        let inject = Inject::default();
+        assert!(!qualified(
+            "/root/node_modules/foo/index.js",
+            "node_modules/foo/index.js",
+            &inject
+        ));
+    }
+
+    #[test]
+    fn test_qualify_globs() {
+        let inject = Inject {
+            include: Some(InjectFilter::Globs(vec!["./src/**/*.ts".to_string()])),
+            exclude: Some(InjectFilter::Globs(vec!["src/**/*.test.ts".to_string()])),
+            ..Default::default()
+        };
+        assert!(qualified("/root/src/utils/a.ts", "src/utils/a.ts", &inject));
+        assert!(!qualified(
+            "/root/src/utils/a.test.ts",
+            "src/utils/a.test.ts",
+            &inject
+        ));
+        assert!(!qualified(
+            "/root/src/utils/a.js",
+            "src/utils/a.js",
+            &inject
+        ));
+        // not matched by include
+        assert!(!qualified(
+            "/root/node_modules/foo/index.ts",
+            "node_modules/foo/index.ts",
+            &inject
+        ));
+    }
+
+    #[test]
+    fn test_create_injects() {
+        let config: HashMap<String, InjectItem> = serde_json::from_str(
+            r#"{ "Buffer": { "from": "buffer", "named": "Buffer" }, "utils": { "from": "./utils", "namespace": true, "order": -1 } }"#,
+        )
+        .unwrap();
+        let injects = create_injects(&config).unwrap();
+        assert_eq!(injects["Buffer"].named, Some("Buffer".to_string()));
+        assert_eq!(injects["Buffer"].order, 0);
+        assert_eq!(injects["utils"].order, -1);
+
+        let config: HashMap<String, InjectItem> = serde_json::from_str(
+            r#"{ "Buffer": { "from": "buffer", "named": "Buffer", "namespace": true } }"#,
+        )
+        .unwrap();
+        assert!(create_injects(&config).is_err());
+
+        let config: HashMap<String, InjectItem> =
+            serde_json::from_str(r#"{ "__DEV__": { "code": "const __DEV__ = true;" } }"#).unwrap();
+        let injects = create_injects(&config).unwrap();
+        assert!(injects["__DEV__"].snippet.is_some());
+
+        let config: HashMap<String, InjectItem> = serde_json::from_str(
+            r#"{ "__DEV__": { "from": "dev", "code": "const __DEV__ = true;" } }"#,
+        )
+        .unwrap();
+        assert!(create_injects(&config).is_err());
+
+        assert!(serde_json::from_str::<HashMap<String, InjectItem>>(
+            r#"{ "Buffer": { "from": "buffer", "name": "Buffer" } }"#,
+        )
+        .is_err());
+    }
+}
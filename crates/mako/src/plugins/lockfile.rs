@@ -0,0 +1,445 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use oxc_resolver::PackageJson;
+use twox_hash::XxHash64;
+
+use crate::compiler::{Compiler, Context};
+use crate::module::Module;
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::resolve::{ResolvedResource, ResolverResource};
+
+const LOCKFILE_NAMES: [&str; 3] = ["package-lock.json", "pnpm-lock.yaml", "yarn.lock"];
+
+const DEPENDENCY_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/**
+ * cross check bundled packages against the lockfile of the project, and warn when
+ *
+ * - the version of an installed package doesn't match the lockfile, e.g. node_modules is stale
+ * - a package is imported but not declared in the package.json of the importer, which is a
+ *   phantom dependency that only works because of hoisting
+ *
+ * package-lock.json (v2+), pnpm-lock.yaml and yarn.lock are supported
+ */
+pub struct LockfilePlugin {}
+
+impl Plugin for LockfilePlugin {
+    fn name(&self) -> &str {
+        "lockfile"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        if !context.config.lockfile_check {
+            return Ok(());
+        }
+        let module_graph = context.module_graph.read().unwrap();
+        let mut warnings = vec![];
+        match Lockfile::load(&context.root) {
+            Ok(Some(lockfile)) => {
+                warnings.extend(check_versions(&module_graph, &lockfile, context))
+            }
+            Ok(None) => warnings.push("no lockfile is found, versions are not checked".to_string()),
+            Err(e) => warnings.push(format!("versions are not checked, {}", e)),
+        }
+        warnings.extend(check_phantom_dependencies(&module_graph, &context.root));
+        for warning in warnings {
+            println!("{}: {}", "warning".to_string().yellow(), warning);
+            context.stats_info.add_warning(warning);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Lockfile {
+    // installed path relative to root -> version
+    Npm(HashMap<String, String>),
+    // package name -> locked versions
+    Pnpm(HashMap<String, HashSet<String>>),
+    Yarn(HashMap<String, HashSet<String>>),
+}
+
+impl Lockfile {
+    fn load(root: &Path) -> Result<Option<Self>> {
+        let path = root.join("package-lock.json");
+        if path.exists() {
+            return Ok(Some(Lockfile::Npm(parse_npm_lockfile(
+                &fs::read_to_string(path)?,
+            )?)));
+        }
+        let path = root.join("pnpm-lock.yaml");
+        if path.exists() {
+            return Ok(Some(Lockfile::Pnpm(parse_pnpm_lockfile(
+                &fs::read_to_string(path)?,
+            ))));
+        }
+        let path = root.join("yarn.lock");
+        if path.exists() {
+            return Ok(Some(Lockfile::Yarn(parse_yarn_lockfile(
+                &fs::read_to_string(path)?,
+            ))));
+        }
+        Ok(None)
+    }
+
+    fn file_name(&self) -> &str {
+        match self {
+            Lockfile::Npm(_) => "package-lock.json",
+            Lockfile::Pnpm(_) => "pnpm-lock.yaml",
+            Lockfile::Yarn(_) => "yarn.lock",
+        }
+    }
+
+    // the locked versions if the installed package doesn't match
+    fn mismatch(&self, name: &str, version: &str, relative_dir: &str) -> Option<Vec<String>> {
+        let locked = match self {
+            Lockfile::Npm(packages) => packages.get(relative_dir).into_iter().cloned().collect(),
+            Lockfile::Pnpm(packages) | Lockfile::Yarn(packages) => {
+                let mut versions = packages
+                    .get(name)
+                    .map(|versions| versions.iter().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                versions.sort();
+                versions
+            }
+        };
+        if locked.iter().any(|v| v == version) {
+            None
+        } else {
+            Some(locked)
+        }
+    }
+}
+
+fn check_versions(
+    module_graph: &ModuleGraph,
+    lockfile: &Lockfile,
+    context: &Arc<Context>,
+) -> Vec<String> {
+    // package dir -> (name, version)
+    let mut packages = BTreeMap::new();
+    for module in module_graph.modules() {
+        if let Some(package_json) = package_json_of(module)
+            && let Some(name) = &package_json.name
+            && let Some(version) = package_json.raw_json().get("version").and_then(|v| v.as_str())
+            // linked workspace packages are not installed from registries
+            && package_json.directory().to_string_lossy().contains("node_modules")
+        {
+            packages.insert(
+                package_json.directory().to_path_buf(),
+                (name.clone(), version.to_string()),
+            );
+        }
+    }
+
+    let mut warnings = vec![];
+    for (dir, (name, version)) in packages {
+        let relative_dir = relative_path(&dir, &context.root);
+        if let Some(locked) = lockfile.mismatch(&name, &version, &relative_dir) {
+            let message = if locked.is_empty() {
+                format!(
+                    "{}@{} in {} is not in {}",
+                    name,
+                    version,
+                    relative_dir,
+                    lockfile.file_name()
+                )
+            } else {
+                format!(
+                    "{}@{} in {} doesn't match {} of {}, try to reinstall dependencies",
+                    name,
+                    version,
+                    relative_dir,
+                    locked.join(", "),
+                    lockfile.file_name()
+                )
+            };
+            warnings.push(message);
+        }
+    }
+    warnings
+}
+
+fn check_phantom_dependencies(module_graph: &ModuleGraph, root: &Path) -> Vec<String> {
+    // (importer package dir, imported package name) -> first importer
+    let mut phantoms = BTreeMap::new();
+    let mut module_ids = module_graph.get_module_ids();
+    module_ids.sort_by(|a, b| a.id.cmp(&b.id));
+    for module_id in &module_ids {
+        let Some(importer) = module_graph.get_module(module_id).and_then(package_json_of) else {
+            continue;
+        };
+        // nested package.json like esm/package.json with only `type` field is not a package
+        if importer.name.is_none()
+            && importer
+                .directory()
+                .to_string_lossy()
+                .contains("node_modules")
+        {
+            continue;
+        }
+        for (dep_id, _) in module_graph.get_dependencies(module_id) {
+            let Some(imported) = module_graph.get_module(dep_id).and_then(package_json_of) else {
+                continue;
+            };
+            if imported.directory() == importer.directory()
+                || !imported
+                    .directory()
+                    .to_string_lossy()
+                    .contains("node_modules")
+            {
+                continue;
+            }
+            let Some(name) = &imported.name else {
+                continue;
+            };
+            let declared = DEPENDENCY_FIELDS.iter().any(|field| {
+                importer
+                    .raw_json()
+                    .get(field)
+                    .and_then(|deps| deps.get(name))
+                    .is_some()
+            });
+            if !declared {
+                phantoms
+                    .entry((importer.directory().to_path_buf(), name.clone()))
+                    .or_insert_with(|| module_id.id.clone());
+            }
+        }
+    }
+
+    phantoms
+        .into_iter()
+        .map(|((importer_dir, name), importer)| {
+            format!(
+                "{} is imported by {} but not declared in {}, it's a phantom dependency",
+                name,
+                relative_path(Path::new(&importer), root),
+                relative_path(&importer_dir.join("package.json"), root),
+            )
+        })
+        .collect()
+}
+
+fn package_json_of(module: &Module) -> Option<Arc<PackageJson>> {
+    match module
+        .info
+        .as_ref()
+        .and_then(|info| info.resolved_resource.as_ref())
+    {
+        Some(ResolverResource::Resolved(ResolvedResource(resolution))) => {
+            resolution.package_json().cloned()
+        }
+        _ => None,
+    }
+}
+
+// package-lock.json v2+, { "packages": { "node_modules/foo": { "version": "1.0.0" } } }
+fn parse_npm_lockfile(content: &str) -> Result<HashMap<String, String>> {
+    let json: serde_json::Value = serde_json::from_str(content)?;
+    let packages = json
+        .get("packages")
+        .and_then(|packages| packages.as_object())
+        .ok_or_else(|| anyhow!("package-lock.json of lockfileVersion 1 is not supported"))?;
+    Ok(packages
+        .iter()
+        .filter_map(|(path, package)| {
+            package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(|version| (path.clone(), version.to_string()))
+        })
+        .collect())
+}
+
+// keys of packages and snapshots, e.g.
+// /foo@1.0.0:, /@scope/foo@1.0.0(react@18.0.0):, '@scope/foo@1.0.0':, /foo/1.0.0:
+fn parse_pnpm_lockfile(content: &str) -> HashMap<String, HashSet<String>> {
+    let mut packages: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.is_empty() {
+            in_packages = line == "packages:" || line == "snapshots:";
+            continue;
+        }
+        let Some(key) = line.strip_prefix("  ") else {
+            continue;
+        };
+        if !in_packages || key.starts_with(' ') || !key.ends_with(':') {
+            continue;
+        }
+        let key = key
+            .trim_end_matches(':')
+            .trim_matches(|c| c == '\'' || c == '"');
+        let key = key.strip_prefix('/').unwrap_or(key);
+        let key = key.split('(').next().unwrap();
+        if let Some((name, version)) = split_name_version(key, '@').or_else(|| {
+            // lockfile v5
+            split_name_version(key, '/')
+        }) {
+            packages.entry(name).or_default().insert(version);
+        }
+    }
+    packages
+}
+
+// "foo@^1.0.0", foo@^1.1.0:
+//   version "1.1.0"
+// or yarn berry
+// "foo@npm:^1.0.0":
+//   version: 1.1.0
+fn parse_yarn_lockfile(content: &str) -> HashMap<String, HashSet<String>> {
+    let mut packages: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut name = None;
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.starts_with('#') && line.ends_with(':') {
+            let spec = line
+                .split(',')
+                .next()
+                .unwrap()
+                .trim_end_matches(':')
+                .trim_matches('"');
+            name = split_name_version(spec, '@').map(|(name, _)| name);
+        } else if let Some(version) = line
+            .trim_start()
+            .strip_prefix("version")
+            .filter(|_| line.starts_with("  ") && !line.starts_with("   "))
+            && let Some(name) = &name
+        {
+            let version = version.trim_start_matches(':').trim().trim_matches('"');
+            packages
+                .entry(name.clone())
+                .or_default()
+                .insert(version.to_string());
+        }
+    }
+    packages
+}
+
+// split at the last separator, the leading @ of scoped packages is not a separator
+fn split_name_version(spec: &str, separator: char) -> Option<(String, String)> {
+    let index = spec[1..].rfind(separator)? + 1;
+    let (name, version) = (&spec[..index], &spec[index + 1..]);
+    if name.is_empty() || version.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
+
+fn relative_path(path: &Path, root: &Path) -> String {
+    pathdiff::diff_paths(path, root)
+        .unwrap_or_else(|| PathBuf::from(path))
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+// content hash of the lockfile, 0 if there's none, e.g. to invalidate caches of dependencies
+pub(crate) fn lockfile_hash(root: &Path) -> u64 {
+    LOCKFILE_NAMES
+        .iter()
+        .find_map(|name| fs::read(root.join(name)).ok())
+        .map_or(0, |content| {
+            let mut hasher = XxHash64::default();
+            content.hash(&mut hasher);
+            hasher.finish()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{parse_npm_lockfile, parse_pnpm_lockfile, parse_yarn_lockfile, Lockfile};
+
+    #[test]
+    fn test_npm_lockfile() {
+        let packages = parse_npm_lockfile(
+            r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app" },
+    "node_modules/foo": { "version": "1.0.0" },
+    "node_modules/bar/node_modules/foo": { "version": "2.0.0" }
+  }
+}"#,
+        )
+        .unwrap();
+        let lockfile = Lockfile::Npm(packages);
+        assert_eq!(lockfile.mismatch("foo", "1.0.0", "node_modules/foo"), None);
+        assert_eq!(
+            lockfile.mismatch("foo", "1.0.0", "node_modules/bar/node_modules/foo"),
+            Some(vec!["2.0.0".to_string()])
+        );
+        assert_eq!(
+            lockfile.mismatch("foo", "1.0.0", "node_modules/baz/node_modules/foo"),
+            Some(vec![])
+        );
+        assert!(parse_npm_lockfile(r#"{ "lockfileVersion": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn test_pnpm_lockfile() {
+        let packages = parse_pnpm_lockfile(
+            r#"lockfileVersion: '6.0'
+
+importers:
+  .:
+    dependencies:
+      foo:
+        specifier: ^1.0.0
+        version: 1.0.0
+
+packages:
+  /foo@1.0.0:
+    resolution: {integrity: sha512-xxx}
+  /@scope/bar@2.0.0(react@18.2.0):
+    resolution: {integrity: sha512-xxx}
+  '@scope/baz@3.0.0':
+    resolution: {integrity: sha512-xxx}
+  /qux/4.0.0:
+    resolution: {integrity: sha512-xxx}
+"#,
+        );
+        assert_eq!(packages["foo"], HashSet::from(["1.0.0".to_string()]));
+        assert_eq!(packages["@scope/bar"], HashSet::from(["2.0.0".to_string()]));
+        assert_eq!(packages["@scope/baz"], HashSet::from(["3.0.0".to_string()]));
+        assert_eq!(packages["qux"], HashSet::from(["4.0.0".to_string()]));
+        assert_eq!(packages.len(), 4);
+    }
+
+    #[test]
+    fn test_yarn_lockfile() {
+        let packages = parse_yarn_lockfile(
+            r#"# yarn lockfile v1
+
+"@scope/foo@^1.0.0", "@scope/foo@^1.1.0":
+  version "1.1.0"
+  resolved "https://registry.yarnpkg.com/@scope/foo/-/foo-1.1.0.tgz"
+  dependencies:
+    bar "^2.0.0"
+
+bar@^2.0.0:
+  version "2.0.1"
+
+"baz@npm:^3.0.0":
+  version: 3.0.0
+"#,
+        );
+        assert_eq!(packages["@scope/foo"], HashSet::from(["1.1.0".to_string()]));
+        assert_eq!(packages["bar"], HashSet::from(["2.0.1".to_string()]));
+        assert_eq!(packages["baz"], HashSet::from(["3.0.0".to_string()]));
+        assert_eq!(packages.len(), 3);
+    }
+}
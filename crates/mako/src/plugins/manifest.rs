@@ -2,11 +2,12 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use regex::Regex;
 use serde_json;
 
 use crate::compiler::Context;
+use crate::features::manifest_signature::SignedManifest;
 use crate::plugin::Plugin;
 use crate::stats::StatsJsonMap;
 
@@ -16,6 +17,14 @@ pub(crate) fn default_manifest_file_name() -> String {
     "asset-manifest.json".to_string()
 }
 
+pub(crate) fn default_signature_key_env() -> String {
+    "MAKO_MANIFEST_KEY".to_string()
+}
+
+pub(crate) fn default_signature_file_name() -> String {
+    "asset-manifest.sig.json".to_string()
+}
+
 impl Plugin for ManifestPlugin {
     fn name(&self) -> &str {
         "manifest"
@@ -23,28 +32,63 @@ impl Plugin for ManifestPlugin {
 
     fn build_success(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<Option<()>> {
         if let Some(manifest_config) = &context.config.manifest {
-            let assets = &context.stats_info.get_assets();
-            let mut manifest: BTreeMap<String, String> = BTreeMap::new();
             let file_name = manifest_config.file_name.clone();
-            let base_path = manifest_config.base_path.clone();
-
-            let path = normalize_path(base_path);
-
-            for asset in assets {
-                let key = format!("{}{}", path, remove_key_hash(&asset.hashname));
-                manifest.insert(key, asset.hashname.clone());
-            }
-
+            let manifest = build_manifest(context);
             let manifest_json = serde_json::to_string_pretty(&manifest)?;
 
             let output_path = context.config.output.path.join(file_name);
 
             fs::write(output_path, manifest_json).unwrap();
+
+            // files of the dev server may be in memory only
+            if let Some(signature_config) = &manifest_config.signature
+                && !context.args.watch
+            {
+                let key = std::env::var(&signature_config.key_env).map_err(|_| {
+                    anyhow!(
+                        "The key of the signed manifest is not found in the environment variable {}",
+                        signature_config.key_env
+                    )
+                })?;
+                let output_path = &context.config.output.path;
+                let mut files = context
+                    .stats_info
+                    .get_assets()
+                    .into_iter()
+                    .map(|asset| asset.hashname)
+                    // assets may be skipped by plugins
+                    .filter(|file| output_path.join(file).is_file())
+                    .collect::<Vec<_>>();
+                files.push(manifest_config.file_name.clone());
+                let signed = SignedManifest::sign(output_path, files, key.as_bytes())?;
+                fs::write(
+                    output_path.join(&signature_config.file_name),
+                    serde_json::to_string_pretty(&signed)?,
+                )?;
+            }
         }
         Ok(None)
     }
 }
 
+// original file names to emitted ones, e.g. index.js -> index.a1b2c3d4.js
+pub(crate) fn build_manifest(context: &Context) -> BTreeMap<String, String> {
+    let base_path = context
+        .config
+        .manifest
+        .as_ref()
+        .map(|manifest_config| manifest_config.base_path.clone())
+        .unwrap_or_default();
+    let path = normalize_path(base_path);
+
+    let mut manifest: BTreeMap<String, String> = BTreeMap::new();
+    for asset in &context.stats_info.get_assets() {
+        let key = format!("{}{}", path, remove_key_hash(&asset.hashname));
+        manifest.insert(key, asset.hashname.clone());
+    }
+    manifest
+}
+
 fn normalize_path(mut path: String) -> String {
     if !path.is_empty() && !path.ends_with('/') {
         path.push('/');
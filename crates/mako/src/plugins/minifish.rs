@@ -5,7 +5,6 @@ This module is highly customized for internal experimental use.
 And maybe removed in the future.
 */
 
-mod inject;
 mod unsimplify;
 
 use std::collections::HashMap;
@@ -13,8 +12,6 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-pub(crate) use inject::Inject;
-use inject::MyInjector;
 use rayon::prelude::*;
 use serde::Serialize;
 use swc_core::ecma::visit::VisitMutWith;
@@ -26,6 +23,7 @@ use crate::compiler::Context;
 use crate::module::{Dependency as ModuleDependency, ModuleAst, ResolveType};
 use crate::plugin::{Plugin, PluginLoadParam, PluginParseParam, PluginTransformJsParam};
 use crate::plugins::bundless_compiler::to_dist_path;
+use crate::plugins::inject::{apply_injects, Inject};
 use crate::stats::StatsJsonMap;
 
 pub struct MinifishPlugin {
@@ -34,28 +32,14 @@ pub struct MinifishPlugin {
     pub inject: Option<HashMap<String, Inject>>,
 }
 
-impl MinifishPlugin {
-    fn qualified(path: &str, inject: &Inject) -> bool {
-        if let Some(exclude) = &inject.exclude {
-            if exclude.is_match(path) {
-                return false;
-            }
-        }
-        inject
-            .include
-            .as_ref()
-            .map_or(true, |include| include.is_match(path))
-    }
-}
-
 impl Plugin for MinifishPlugin {
     fn name(&self) -> &str {
         "minifish_plugin"
     }
 
-    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if param.file.extname == "json" || param.file.extname == "json5" {
-            let root = _context.root.clone();
+            let root = context.root.clone();
             let to = param.file.pathname.clone();
 
             let relative = to
@@ -104,26 +88,10 @@ impl Plugin for MinifishPlugin {
         &self,
         param: &PluginTransformJsParam,
         ast: &mut swc_core::ecma::ast::Module,
-        _context: &Arc<Context>,
+        context: &Arc<Context>,
     ) -> Result<()> {
         if let Some(inject) = &self.inject {
-            if inject.is_empty() {
-                return Ok(());
-            }
-
-            let mut matched_injects = HashMap::new();
-
-            for (k, i) in inject {
-                if Self::qualified(param.path, i) {
-                    matched_injects.insert(k.clone(), i);
-                }
-            }
-
-            if matched_injects.is_empty() {
-                return Ok(());
-            }
-
-            ast.visit_mut_with(&mut MyInjector::new(param.unresolved_mark, matched_injects));
+            apply_injects(inject, param, ast, context)?;
         }
         Ok(())
     }
@@ -141,9 +109,9 @@ impl Plugin for MinifishPlugin {
     fn before_resolve(
         &self,
         deps: &mut Vec<ModuleDependency>,
-        _context: &Arc<Context>,
+        context: &Arc<Context>,
     ) -> Result<()> {
-        let src_root = _context
+        let src_root = context
             .config
             .output
             .preserve_modules_root
@@ -151,7 +119,7 @@ impl Plugin for MinifishPlugin {
             .ok_or_else(|| {
                 anyhow!(
                     "output.preserve_modules_root {:?} is not a valid utf8 string",
-                    _context.config.output.preserve_modules_root
+                    context.config.output.preserve_modules_root
                 )
             })?;
 
@@ -238,54 +206,3 @@ struct Dependency {
     module: String,
     import_type: ResolveType,
 }
-
-#[cfg(test)]
-mod tests {
-    use regex::Regex;
-
-    use super::*;
-
-    #[test]
-    fn test_qualify_all_none() {
-        let inject = Inject {
-            include: None,
-            exclude: None,
-            ..Default::default()
-        };
-        assert!(MinifishPlugin::qualified("src/index.js", &inject));
-    }
-
-    #[test]
-    fn test_qualify_only_include() {
-        let inject = Inject {
-            include: Some(Regex::new("src").unwrap()),
-            exclude: None,
-            ..Default::default()
-        };
-        assert!(MinifishPlugin::qualified("src/index.js", &inject));
-        assert!(!MinifishPlugin::qualified("lib/index.js", &inject));
-    }
-
-    #[test]
-    fn test_qualify_only_exclude() {
-        let inject = Inject {
-            include: None,
-            exclude: Some(Regex::new("src").unwrap()),
-            ..Default::default()
-        };
-        assert!(!MinifishPlugin::qualified("src/index.js", &inject));
-        assert!(MinifishPlugin::qualified("lib/index.js", &inject));
-    }
-
-    #[test]
-    fn test_qualify_both() {
-        let inject = Inject {
-            include: Some(Regex::new("index.js").unwrap()),
-            exclude: Some(Regex::new("src").unwrap()),
-            ..Default::default()
-        };
-        assert!(!MinifishPlugin::qualified("src/a.js", &inject));
-        assert!(!MinifishPlugin::qualified("src/index.js", &inject));
-        assert!(MinifishPlugin::qualified("lib/index.js", &inject));
-    }
-}
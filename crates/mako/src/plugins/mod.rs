@@ -1,16 +1,37 @@
 pub mod async_runtime;
+pub mod banner;
+pub mod build_events;
+pub mod bundle_policy;
 pub mod bundless_compiler;
+pub mod chunk_integrity;
+pub mod chunk_invalidation;
+pub mod chunk_load_telemetry;
+pub mod circular_dependency;
+pub mod codegen;
 pub mod context_module;
 pub mod copy;
+pub mod duplicate_packages;
 pub mod emotion;
+pub mod env_banner;
+pub mod env_types;
+pub mod error_mapping;
 pub mod graphviz;
 pub mod hmr_runtime;
+pub mod html;
+pub mod icon_imports;
 pub mod ignore;
 pub mod import;
+pub mod import_map;
+pub mod inject;
 pub mod invalid_webpack_syntax;
+pub mod lockfile;
 pub mod manifest;
 pub mod minifish;
+pub mod mpa;
+pub mod polyfill_report;
+pub mod preact;
 pub mod runtime;
+pub mod split_by_key;
 pub mod ssu;
 pub mod tree_shaking;
 pub mod wasm_runtime;
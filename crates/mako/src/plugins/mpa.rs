@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::compiler::Context;
+use crate::config::{
+    CodeSplitting, CodeSplittingAdvancedOptions, CodeSplittingStrategy,
+    CodeSplittingStrategyOptions, OptimizeAllowChunks, OptimizeChunkGroup,
+};
+use crate::generate::chunk_group::{chunk_groups, html_public_path};
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+
+pub(crate) fn default_pages_dir() -> PathBuf {
+    PathBuf::from("src/pages")
+}
+
+pub(crate) fn default_pages_manifest_file_name() -> String {
+    "pages-manifest.json".to_string()
+}
+
+const PAGE_ENTRY_FILES: [&str; 4] = ["index.tsx", "index.ts", "index.jsx", "index.js"];
+
+/**
+ * multi-page applications by convention, every sub directory of pagesDir with an index file
+ * is a page, e.g.
+ *
+ * src/pages/home/index.tsx  -> entry home, home.html
+ * src/pages/about/index.tsx -> entry about, about.html
+ * src/pages/about/index.html   the html template of about
+ *
+ * pages share the vendors and common chunks, and the files of each page are written to the
+ * pages manifest, so that servers can render the pages without parsing html
+ */
+pub struct MpaPlugin {}
+
+pub(crate) struct Page {
+    pub name: String,
+    pub entry: PathBuf,
+    pub template: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct PageManifest {
+    html: Option<String>,
+    js: Vec<String>,
+    css: Vec<String>,
+}
+
+pub(crate) fn collect_pages(root: &Path, pages_dir: &Path) -> Result<Vec<Page>> {
+    let dir = root.join(pages_dir);
+    let dir_entries = fs::read_dir(&dir)
+        .map_err(|e| anyhow!("mpa: failed to read pages dir {}: {}", dir.display(), e))?;
+    let mut pages = vec![];
+    for dir_entry in dir_entries {
+        let path = dir_entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(entry) = PAGE_ENTRY_FILES
+            .iter()
+            .map(|file| path.join(file))
+            .find(|file| file.exists())
+        else {
+            continue;
+        };
+        let template = path.join("index.html");
+        pages.push(Page {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            entry,
+            template: template.exists().then_some(template),
+        });
+    }
+    if pages.is_empty() {
+        return Err(anyhow!("mpa: no pages found in {}", dir.display()));
+    }
+    pages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(pages)
+}
+
+// modules shared by pages are split out, so that they are cached across pages
+pub(crate) fn mpa_code_splitting() -> CodeSplitting {
+    CodeSplitting {
+        strategy: CodeSplittingStrategy::Advanced,
+        options: Some(CodeSplittingStrategyOptions::Advanced(
+            CodeSplittingAdvancedOptions {
+                groups: vec![
+                    OptimizeChunkGroup {
+                        name: "vendors".to_string(),
+                        test: Regex::new(r"[/\\]node_modules[/\\]").ok(),
+                        allow_chunks: OptimizeAllowChunks::All,
+                        min_chunks: 2,
+                        priority: -10,
+                        ..Default::default()
+                    },
+                    OptimizeChunkGroup {
+                        name: "common".to_string(),
+                        allow_chunks: OptimizeAllowChunks::All,
+                        min_chunks: 2,
+                        min_size: 1,
+                        priority: -20,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        )),
+    }
+}
+
+impl Plugin for MpaPlugin {
+    fn name(&self) -> &str {
+        "mpa"
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        let Some(mpa_config) = &context.config.mpa else {
+            return Ok(None);
+        };
+        let public_path = html_public_path(context);
+        let url = |file: &String| format!("{}{}", public_path, file);
+        let manifest = chunk_groups(context)
+            .into_iter()
+            .map(|chunk_group| {
+                let html =
+                    context.config.html.as_ref().map(|html_config| {
+                        html_config.filename.replace("[name]", &chunk_group.name)
+                    });
+                let page = PageManifest {
+                    html,
+                    js: chunk_group.js.iter().map(url).collect(),
+                    css: chunk_group.css.iter().map(url).collect(),
+                };
+                (chunk_group.name, page)
+            })
+            .collect::<BTreeMap<_, _>>();
+        fs::write(
+            context.config.output.path.join(&mpa_config.manifest),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_pages;
+
+    #[test]
+    fn test_collect_pages() {
+        let root = std::env::current_dir().unwrap().join("test/config/mpa");
+        let pages = collect_pages(&root, "src/pages".as_ref()).unwrap();
+        assert_eq!(
+            pages
+                .iter()
+                .map(|page| page.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["about", "home"]
+        );
+        assert_eq!(
+            pages[0].template,
+            Some(root.join("src/pages/about/index.html"))
+        );
+        assert_eq!(pages[1].entry, root.join("src/pages/home/index.ts"));
+        assert_eq!(pages[1].template, None);
+
+        assert!(collect_pages(&root, "src/pages/home".as_ref()).is_err());
+    }
+}
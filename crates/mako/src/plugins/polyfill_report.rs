@@ -0,0 +1,185 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use anyhow::Result;
+use swc_core::common::Mark;
+use swc_core::ecma::ast::{Module, ModuleDecl, ModuleItem};
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
+use swc_core::ecma::preset_env::{self as swc_preset_env};
+use swc_core::ecma::transforms::base::feature::FeatureFlag;
+use swc_core::ecma::transforms::base::Assumptions;
+use swc_core::ecma::visit::{Fold, FoldWith};
+
+use crate::ast::comments::MakoComments;
+use crate::build::targets::swc_preset_env_targets_from_map;
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+use crate::stats::human_readable_size;
+
+/**
+ * report what each browser target costs, i.e. the downleveled code and the polyfills which
+ * are only required by the target, e.g.
+ *
+ * dropping ios 12 would remove 34.20 kB of downleveled code and 12 polyfills
+ *
+ * every module is downleveled again with each target left out by preset_env, so that the
+ * report follows the same feature mapping as the build, sizes are of the code without
+ * whitespaces before minification, polyfills are only counted with `import "core-js"`
+ */
+pub struct PolyfillReportPlugin {}
+
+impl Plugin for PolyfillReportPlugin {
+    fn name(&self) -> &str {
+        "polyfill_report"
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<Option<()>> {
+        if !enabled(context) {
+            return Ok(None);
+        }
+        let savings = context.stats_info.get_target_savings();
+        let mut targets = context.config.targets.iter().collect::<Vec<_>>();
+        targets.sort_by(|a, b| a.0.cmp(b.0));
+        println!("Polyfill report by targets:");
+        for (target, version) in targets {
+            let saving = savings.get(target).cloned().unwrap_or_default();
+            println!(
+                "  dropping {} {} would remove {} of downleveled code and {} polyfills",
+                target,
+                version,
+                human_readable_size(saving.size),
+                saving.polyfills.len()
+            );
+        }
+        Ok(None)
+    }
+}
+
+// it takes one more preset_env pass of every module for each target, so only for builds
+pub(crate) fn enabled(context: &Arc<Context>) -> bool {
+    context.config.polyfill_report && !context.args.watch && context.config.targets.len() > 1
+}
+
+// leaves the module as is, should go right before preset_env
+pub(crate) struct PolyfillReport {
+    pub unresolved_mark: Mark,
+    pub comments: MakoComments,
+    pub assumptions: Assumptions,
+    pub context: Arc<Context>,
+}
+
+impl PolyfillReport {
+    fn downlevel(
+        &self,
+        module: &Module,
+        targets: HashMap<String, f32>,
+    ) -> (usize, BTreeSet<String>) {
+        let mut folder = swc_preset_env::preset_env(
+            self.unresolved_mark,
+            Some(self.comments.clone()),
+            swc_preset_env::Config {
+                mode: Some(swc_preset_env::Mode::Entry),
+                targets: Some(swc_preset_env_targets_from_map(targets)),
+                ..Default::default()
+            },
+            self.assumptions,
+            &mut FeatureFlag::default(),
+        );
+        let module = module.clone().fold_with(&mut folder);
+        (emitted_size(&module, &self.context), polyfills(&module))
+    }
+}
+
+impl Fold for PolyfillReport {
+    fn fold_module(&mut self, module: Module) -> Module {
+        let targets = &self.context.config.targets;
+        let (size, all_polyfills) = self.downlevel(&module, targets.clone());
+        for target in targets.keys() {
+            let mut rest = targets.clone();
+            rest.remove(target);
+            let (rest_size, rest_polyfills) = self.downlevel(&module, rest);
+            self.context.stats_info.add_target_saving(
+                target,
+                size.saturating_sub(rest_size) as u64,
+                all_polyfills.difference(&rest_polyfills).cloned(),
+            );
+        }
+        module
+    }
+}
+
+fn emitted_size(module: &Module, context: &Arc<Context>) -> usize {
+    let cm = context.meta.script.cm.clone();
+    let mut buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: JsCodegenConfig::default().with_minify(true),
+            cm: cm.clone(),
+            comments: None,
+            wr: Box::new(JsWriter::new(cm, "\n", &mut buf, None)),
+        };
+        // the size is only for the report, it's fine to be 0 if the module can't be emitted
+        let _ = emitter.emit_module(module);
+    }
+    buf.len()
+}
+
+fn polyfills(module: &Module) -> BTreeSet<String> {
+    module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                Some(import.src.value.to_string())
+            }
+            _ => None,
+        })
+        .filter(|src| src.starts_with("core-js/") || src.starts_with("regenerator-runtime"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::FoldWith;
+
+    use super::PolyfillReport;
+    use crate::ast::js_ast::JsAst;
+    use crate::compiler::Context;
+
+    #[test]
+    fn test_target_savings() {
+        let mut context = Context::default();
+        context.config.targets = [("chrome".to_string(), 80.0), ("ie".to_string(), 11.0)]
+            .into_iter()
+            .collect();
+        let context = Arc::new(context);
+        let ast = JsAst::build(
+            "test.js",
+            "const f = (a, ...b) => `${a}${b}`;",
+            context.clone(),
+        )
+        .unwrap();
+        let mut report = PolyfillReport {
+            unresolved_mark: ast.unresolved_mark,
+            comments: Default::default(),
+            assumptions: Default::default(),
+            context: context.clone(),
+        };
+        let module = GLOBALS.set(&context.meta.script.globals, || {
+            ast.ast.clone().fold_with(&mut report)
+        });
+        assert_eq!(module, ast.ast);
+
+        let savings = context.stats_info.get_target_savings();
+        assert!(savings["ie"].size > 0);
+        assert_eq!(savings["chrome"].size, 0);
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::compiler::Args;
+use crate::config::{Config, ReactConfig};
+use crate::plugin::Plugin;
+
+// aliases are matched in order, so the more specific ones go first
+const PREACT_ALIAS: [(&str, &str); 4] = [
+    ("react-dom/test-utils", "preact/test-utils"),
+    ("react/jsx-runtime", "preact/jsx-runtime"),
+    ("react-dom", "preact/compat"),
+    ("react", "preact/compat"),
+];
+
+/**
+ * alias react and react-dom to preact/compat and compile jsx with preact,
+ * react-refresh is disabled since it doesn't work with preact,
+ * components are updated by hmr or a full reload instead
+ */
+pub struct PreactPlugin {}
+
+impl Plugin for PreactPlugin {
+    fn name(&self) -> &str {
+        "preact"
+    }
+
+    fn modify_config(&self, config: &mut Config, _root: &Path, _args: &Args) -> Result<()> {
+        if config.preact {
+            apply_preact_preset(config);
+        }
+        Ok(())
+    }
+}
+
+fn apply_preact_preset(config: &mut Config) {
+    // aliases configured by users take precedence
+    PREACT_ALIAS.iter().for_each(|(from, to)| {
+        if !config.resolve.alias.iter().any(|(key, _)| key == from) {
+            config
+                .resolve
+                .alias
+                .push((from.to_string(), to.to_string()));
+        }
+    });
+    config.react = ReactConfig {
+        pragma: "h".into(),
+        import_source: "preact".into(),
+        pragma_frag: "Fragment".into(),
+        runtime: config.react.runtime.clone(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_preact_preset;
+    use crate::config::Config;
+
+    #[test]
+    fn test_apply_preact_preset() {
+        let mut config = Config::new(
+            &std::env::current_dir().unwrap(),
+            None,
+            Some(r#"{ "preact": true, "resolve": { "alias": [["react", "./my-react"]] } }"#),
+        )
+        .unwrap();
+        apply_preact_preset(&mut config);
+        assert_eq!(
+            config.resolve.alias,
+            vec![
+                ("react".to_string(), "./my-react".to_string()),
+                (
+                    "react-dom/test-utils".to_string(),
+                    "preact/test-utils".to_string()
+                ),
+                (
+                    "react/jsx-runtime".to_string(),
+                    "preact/jsx-runtime".to_string()
+                ),
+                ("react-dom".to_string(), "preact/compat".to_string()),
+            ]
+        );
+        assert_eq!(config.react.import_source, "preact");
+        assert_eq!(config.react.pragma, "h");
+    }
+}
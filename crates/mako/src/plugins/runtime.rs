@@ -3,7 +3,8 @@ use std::sync::Arc;
 use anyhow::{anyhow, Result};
 
 use crate::compiler::Context;
-use crate::generate::swc_helpers::SwcHelpers;
+use crate::generate::runtime::runtime_global;
+use crate::generate::swc_helpers::{SwcHelpers, GENERATE_HELPERS};
 use crate::module::ModuleId;
 use crate::plugin::Plugin;
 
@@ -15,10 +16,11 @@ impl Plugin for MakoRuntime {
     }
 
     fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
-        let plugins = vec![
-            self.public_path(context),
-            self.helper_runtime(context).unwrap(),
-        ];
+        let mut plugins = vec![self.public_path(context)];
+        // helpers are inlined into modules otherwise
+        if context.config.swc_helpers.external() {
+            plugins.push(self.helper_runtime(context)?);
+        }
         Ok(plugins)
     }
 }
@@ -32,26 +34,42 @@ impl MakoRuntime {
             format!("\"{}\"", public_path)
         };
 
+        // __mako_public_path__ is read on every access, so that it can be set at any time
+        // before chunks, css, assets and workers are loaded,
+        // it's read from the namespace when output.globalNamespace is set
         format!(
             r#"
   /* mako/runtime/publicPath */
   !function () {{
-    requireModule.publicPath= {};
+    var publicPath = {};
+    Object.defineProperty(requireModule, "publicPath", {{
+      get: function () {{
+        var global = {};
+        return typeof global.__mako_public_path__ === 'string' ? global.__mako_public_path__ : publicPath;
+      }},
+      set: function (value) {{
+        publicPath = value;
+      }},
+      enumerable: true,
+      configurable: true,
+    }});
   }}();"#,
-            public_path
+            public_path,
+            runtime_global(context)
         )
     }
 
     fn helper_runtime(&self, context: &Arc<Context>) -> Result<String> {
-        let helpers = SwcHelpers::full_helpers()
-            .into_iter()
-            .map(|source| {
-                let code = Self::get_swc_helper_code(&source).unwrap();
+        let helpers = GENERATE_HELPERS
+            .iter()
+            .zip(SwcHelpers::full_helpers(&context.config.swc_helpers.source))
+            .map(|(name, source)| {
+                let code = Self::get_swc_helper_code(name)?;
                 let module_id: ModuleId = source.into();
                 let module_id = module_id.generate(context);
-                format!("\"{}\": {}", module_id, code)
+                Ok(format!("\"{}\": {}", module_id, code))
             })
-            .collect::<Vec<_>>()
+            .collect::<Result<Vec<_>>>()?
             .join(",\n");
 
         Ok(format!(
@@ -65,9 +83,9 @@ impl MakoRuntime {
         ))
     }
 
-    fn get_swc_helper_code(path: &str) -> Result<String> {
-        let code = match path {
-            "@swc/helpers/_/_interop_require_default" => r#"
+    fn get_swc_helper_code(name: &str) -> Result<String> {
+        let code = match name {
+            "_interop_require_default" => r#"
 function(module, exports, __mako_require__) {
     __mako_require__.d(exports, "__esModule", {
         value: true
@@ -93,7 +111,7 @@ function(module, exports, __mako_require__) {
     }
 }
             "#.trim(),
-            "@swc/helpers/_/_interop_require_wildcard" => r#"
+            "_interop_require_wildcard" => r#"
 function(module, exports, __mako_require__) {
     __mako_require__.d(exports, "__esModule", {
         value: true
@@ -140,7 +158,7 @@ function(module, exports, __mako_require__) {
     }
 }
             "#.trim(),
-            "@swc/helpers/_/_export_star" => r#"
+            "_export_star" => r#"
 function(module, exports, __mako_require__) {
     __mako_require__.d(exports, "__esModule", {
         value: true
@@ -172,7 +190,7 @@ function(module, exports, __mako_require__) {
     }
 }
             "#.trim(),
-            _ => return Err(anyhow!("swc helper not found: {}", path)),
+            _ => return Err(anyhow!("swc helper not found: {}", name)),
         };
         Ok(code.to_string())
     }
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::ast::file::{Content, JsContent};
+use crate::build::load::FileSystem;
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginLoadParam};
+
+const SPLIT_KEY_PARAM: &str = "splitKey";
+
+/**
+ * split a large json (e.g. translation catalog) into per-key async modules
+ * the json module itself becomes an accessor, e.g.
+ *
+ * import loadLocale, { keys } from './locales.json';
+ * loadLocale('fr').then((fr) => { ... });
+ */
+pub struct SplitByKeyPlugin {
+    pub patterns: Vec<Regex>,
+}
+
+impl Plugin for SplitByKeyPlugin {
+    fn name(&self) -> &str {
+        "split_by_key"
+    }
+
+    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+        let file = param.file;
+        if file.extname != "json" {
+            return Ok(None);
+        }
+        let path = file.pathname.to_string_lossy().to_string();
+        if !self.patterns.iter().any(|p| p.is_match(&path)) {
+            return Ok(None);
+        }
+
+        let content = FileSystem::read_file(&file.pathname)?;
+        let map = match serde_json::from_str::<Value>(&content)? {
+            Value::Object(map) => map,
+            _ => {
+                return Err(anyhow!(
+                    "splitByKey only supports json with object at top level: {}",
+                    path
+                ))
+            }
+        };
+
+        // per-key module
+        if let Some(key) = file.param(SPLIT_KEY_PARAM) {
+            let value = map
+                .get(&key)
+                .ok_or_else(|| anyhow!("key {} not found in {}", key, path))?;
+            return Ok(Some(Content::Js(JsContent {
+                content: format!("module.exports = {}", serde_json::to_string(value)?),
+                ..Default::default()
+            })));
+        }
+
+        // accessor module
+        let loaders = map
+            .keys()
+            .map(|key| {
+                let source = format!(
+                    "{}?{}={}",
+                    path,
+                    SPLIT_KEY_PARAM,
+                    utf8_percent_encode(key, NON_ALPHANUMERIC)
+                );
+                Ok(format!(
+                    "{}: () => import({})",
+                    serde_json::to_string(key)?,
+                    serde_json::to_string(&source)?
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let content = format!(
+            r#"
+const map = {{
+    {}
+}};
+
+export const keys = Object.keys(map);
+
+export default function load(key) {{
+    if (map[key]) return map[key]().then((m) => m.default);
+    return Promise.reject(new Error("Cannot find key '" + key + "' in " + {}));
+}}
+"#,
+            loaders.join(",\n    "),
+            serde_json::to_string(&file.relative_path.to_string_lossy())?
+        );
+        Ok(Some(Content::Js(JsContent {
+            content,
+            ..Default::default()
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use regex::Regex;
+
+    use super::SplitByKeyPlugin;
+    use crate::ast::file::{Content, File};
+    use crate::compiler::Context;
+    use crate::plugin::{Plugin, PluginLoadParam};
+    use crate::utils::test_helper::create_temp_dir;
+
+    fn load(plugin: &SplitByKeyPlugin, path: String, context: &Arc<Context>) -> Option<String> {
+        let file = File::new(path, context.clone());
+        match plugin
+            .load(&PluginLoadParam { file: &file }, context)
+            .unwrap()
+        {
+            Some(Content::Js(js)) => Some(js.content),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_split_by_key() {
+        let dir = create_temp_dir("split_by_key");
+        let root = dir.path();
+        let locales = root.join("locales.json");
+        fs::write(&locales, r#"{"en":{"hi":"Hello"},"fr":{"hi":"Bonjour"}}"#).unwrap();
+        fs::write(root.join("data.json"), r#"{"a":1}"#).unwrap();
+        let context = Arc::new(Context {
+            root: root.to_path_buf(),
+            ..Default::default()
+        });
+        let plugin = SplitByKeyPlugin {
+            patterns: vec![Regex::new(r"locales\.json$").unwrap()],
+        };
+        let locales = locales.to_string_lossy().to_string();
+
+        let accessor = load(&plugin, locales.clone(), &context).unwrap();
+        assert!(accessor.contains(&format!(r#""en": () => import("{}?splitKey=en")"#, locales)));
+        assert!(accessor.contains(&format!(r#""fr": () => import("{}?splitKey=fr")"#, locales)));
+        assert!(accessor.contains("export default function load(key)"));
+
+        assert_eq!(
+            load(&plugin, format!("{}?splitKey=fr", locales), &context).unwrap(),
+            r#"module.exports = {"hi":"Bonjour"}"#
+        );
+        assert!(load(
+            &plugin,
+            root.join("data.json").to_string_lossy().to_string(),
+            &context
+        )
+        .is_none());
+    }
+}
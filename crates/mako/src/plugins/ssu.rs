@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::ast::file::{Content, File, JsContent};
+use crate::build::load::Load;
 use crate::compiler::{Args, Compiler, Context};
 use crate::config::{
     CodeSplitting, CodeSplittingAdvancedOptions, CodeSplittingStrategy,
@@ -22,11 +23,15 @@ use crate::generate::chunk::ChunkType;
 use crate::generate::chunk_pot::util::{hash_hashmap, hash_vec};
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
 use crate::plugin::{NextBuildParam, Plugin, PluginLoadParam};
+use crate::plugins::lockfile::lockfile_hash;
 use crate::resolve::ResolverResource;
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 struct CacheState {
     config_hash: u64,
+    // dependencies are prebundled again when the lockfile is changed
+    #[serde(default)]
+    lockfile_hash: u64,
     reversed_required_files: HashSet<String>,
     cached_boundaries: HashMap<String, String>,
     js_patch_map: HashMap<String, String>,
@@ -43,6 +48,14 @@ impl CacheState {
             return false;
         }
 
+        if self.lockfile_hash != other.lockfile_hash {
+            debug!(
+                "lockfile_hash changed: {} -> {}",
+                self.lockfile_hash, other.lockfile_hash
+            );
+            return false;
+        }
+
         if self.cached_boundaries.len() != other.cached_boundaries.len() {
             debug!(
                 "different boundaries: {} -> {}",
@@ -174,6 +187,7 @@ impl Plugin for SUPlus {
                             min_module_size: None,
                             priority: 10,
                             test: Regex::new(r"[/\\]node_modules[/\\]").ok(),
+                            licenses: vec![],
                         },
                         OptimizeChunkGroup {
                             name: "common".to_string(),
@@ -222,8 +236,7 @@ impl Plugin for SUPlus {
             let port = context.config.dev_server.as_ref().unwrap().port.to_string();
             let host = &context.config.dev_server.as_ref().unwrap().host;
             let host = if host == "0.0.0.0" { "127.0.0.1" } else { host };
-            let hmr_runtime = include_str!("../runtime/runtime_hmr_entry.js")
-                .to_string()
+            let hmr_runtime = Load::hmr_entry_runtime(&context)
                 .replace("__PORT__", &port)
                 .replace("__HOST__", host);
 
@@ -359,6 +372,7 @@ module.export = Promise.all(
         }
 
         self.disable_cache();
+        debug!("prebundling dependencies");
 
         let files = self
             .dependence_node_module_files
@@ -459,7 +473,9 @@ module.export = Promise.all(
             *state = content;
         }
 
-        self.current_state.lock().unwrap().config_hash = Self::config_hash(&context.config);
+        let mut current_state = self.current_state.lock().unwrap();
+        current_state.config_hash = Self::config_hash(&context.config);
+        current_state.lockfile_hash = lockfile_hash(&context.root);
 
         Ok(None)
     }
@@ -517,3 +533,32 @@ requireModule._su_patch = function(){
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::CacheState;
+
+    #[test]
+    fn test_cache_state_valid_with() {
+        let state = |lockfile_hash: u64| CacheState {
+            config_hash: 1,
+            lockfile_hash,
+            cached_boundaries: HashMap::from([(
+                "/root/node_modules/foo/index.js".to_string(),
+                "1.0.0".to_string(),
+            )]),
+            ..Default::default()
+        };
+        assert!(state(1).valid_with(&state(1)));
+        assert!(!state(1).valid_with(&state(2)));
+
+        let mut upgraded = state(1);
+        upgraded.cached_boundaries.insert(
+            "/root/node_modules/foo/index.js".to_string(),
+            "1.1.0".to_string(),
+        );
+        assert!(!state(1).valid_with(&upgraded));
+    }
+}
@@ -8,11 +8,13 @@ use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 use crate::compiler::Context;
 use crate::module_graph::ModuleGraph;
 use crate::plugin::{Plugin, PluginTransformJsParam};
+use crate::visitors::no_side_effects::mark_imported_no_side_effects_calls;
 
 mod module;
-mod module_side_effects_flag;
+pub(crate) mod module_side_effects_flag;
 mod remove_useless_stmts;
 mod shake;
+pub mod side_effects_audit;
 mod statement_graph;
 
 pub struct FarmTreeShake {}
@@ -39,6 +41,7 @@ impl Plugin for FarmTreeShake {
         module_graph: &mut ModuleGraph,
         context: &Arc<Context>,
     ) -> Result<()> {
+        mark_imported_no_side_effects_calls(module_graph, context);
         shake::optimize_modules(module_graph, context)?;
         Ok(())
     }
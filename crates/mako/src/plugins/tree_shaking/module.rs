@@ -274,6 +274,10 @@ impl TreeShakeModule {
         false
     }
 
+    pub fn used_exports(&self) -> &UsedExports {
+        &self.used_exports
+    }
+
     pub fn not_used(&self) -> bool {
         self.used_exports.is_empty()
     }
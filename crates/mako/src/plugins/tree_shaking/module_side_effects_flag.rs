@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use glob::Pattern;
 use glob_match::glob_match;
 
+use crate::compiler::Context;
 use crate::module::{relative_to_root, ModuleInfo};
 use crate::resolve::{ResolvedResource, ResolverResource};
 
@@ -81,6 +82,23 @@ impl ModuleInfo {
     }
 }
 
+// sideEffects of the config, which take precedence over package.json since packages frequently
+// mis-declare it, e.g. { "src/polyfills/**": true, "node_modules/bad-pkg/**": false }
+pub(crate) fn configured_side_effect(path: &Path, context: &Context) -> Option<bool> {
+    if context.config.side_effects.is_empty() {
+        return None;
+    }
+    let relative_path = relative_to_root(&path.to_string_lossy().to_string(), &context.root);
+    // the longest matched glob wins as the most specific one, the config is not ordered
+    context
+        .config
+        .side_effects
+        .iter()
+        .filter(|(pattern, _)| match_glob_pattern(pattern, &relative_path))
+        .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .map(|(_, side_effects)| *side_effects)
+}
+
 fn match_glob_pattern(pattern: &str, path: &str) -> bool {
     let trimmed = path.trim_start_matches("./");
 
@@ -96,7 +114,12 @@ fn match_glob_pattern(pattern: &str, path: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::match_glob_pattern;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use super::{configured_side_effect, match_glob_pattern};
+    use crate::compiler::Context;
+    use crate::config::Config;
     use crate::utils::test_helper::{get_module, setup_compiler};
 
     #[test]
@@ -130,6 +153,38 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_configured_side_effect() {
+        let context = Context {
+            root: PathBuf::from("/project"),
+            config: Config {
+                side_effects: HashMap::from([
+                    ("src/polyfills/**".to_string(), true),
+                    ("node_modules/bad-pkg/**".to_string(), false),
+                    ("node_modules/bad-pkg/polyfill.js".to_string(), true),
+                    ("*.css".to_string(), true),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let side_effect = |path: &str| configured_side_effect(Path::new(path), &context);
+        assert_eq!(
+            side_effect("/project/src/polyfills/array/at.ts"),
+            Some(true)
+        );
+        assert_eq!(
+            side_effect("/project/node_modules/bad-pkg/esm/index.js"),
+            Some(false)
+        );
+        assert_eq!(
+            side_effect("/project/node_modules/bad-pkg/polyfill.js"),
+            Some(true)
+        );
+        assert_eq!(side_effect("/project/src/styles/index.css"), Some(true));
+        assert_eq!(side_effect("/project/src/index.ts"), None);
+    }
+
     #[test]
     fn test_side_effects_flag() {
         let compiler = setup_compiler("test/build/side-effects-flag", false);
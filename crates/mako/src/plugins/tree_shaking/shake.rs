@@ -8,6 +8,7 @@ use std::ops::DerefMut;
 use std::sync::Arc;
 
 use anyhow::Result;
+use rayon::prelude::*;
 use swc_core::common::comments::{Comment, CommentKind};
 use swc_core::common::{DUMMY_SP, GLOBALS};
 
@@ -16,11 +17,25 @@ use crate::compiler::Context;
 use crate::module::{ModuleAst, ModuleId, ModuleType, ResolveType};
 use crate::module_graph::ModuleGraph;
 use crate::plugins::tree_shaking::module::{AllExports, ModuleSystem, TreeShakeModule};
+use crate::plugins::tree_shaking::module_side_effects_flag::configured_side_effect;
 use crate::plugins::tree_shaking::shake::module_concatenate::optimize_module_graph;
+use crate::plugins::tree_shaking::side_effects_audit::audit_side_effects;
 use crate::plugins::tree_shaking::statement_graph::{ExportInfo, ExportSpecifierInfo, ImportInfo};
 use crate::plugins::tree_shaking::{module, remove_useless_stmts, statement_graph};
 
 pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>) -> Result<()> {
+    shake_modules(module_graph, context, mark_used_exports)
+}
+
+type MarkUsedExports =
+    fn(&[ModuleId], &HashMap<ModuleId, RefCell<TreeShakeModule>>, &ModuleGraph, &Arc<Context>);
+
+// marking of used exports is passed in, so that tests can compare it with the serial one
+fn shake_modules(
+    module_graph: &mut ModuleGraph,
+    context: &Arc<Context>,
+    mark_used_exports: MarkUsedExports,
+) -> Result<()> {
     let (topo_sorted_modules, _cyclic_modules) = {
         crate::mako_profile_scope!("tree shake topo-sort");
         module_graph.toposort()
@@ -44,10 +59,7 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
         }
     }
 
-    let mut tree_shake_modules_ids = vec![];
-    let mut tree_shake_modules_map = std::collections::HashMap::new();
-
-    let mut order: usize = 0;
+    let mut script_module_ids = vec![];
     for module_id in topo_sorted_modules.iter() {
         let module = module_graph.get_module(module_id).unwrap();
 
@@ -73,10 +85,35 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
             continue;
         };
 
-        let tree_shake_module = GLOBALS.set(&context.meta.script.globals, || {
-            TreeShakeModule::new(module, order, module_graph)
-        });
+        script_module_ids.push(module_id);
+    }
+
+    // statement graphs are built per module, so they can be built in parallel
+    let tree_shake_modules = {
+        crate::mako_profile_scope!("tree shake build statement graphs");
+        let module_graph = &*module_graph;
+        script_module_ids
+            .par_iter()
+            .enumerate()
+            .map(|(order, module_id)| {
+                let module = module_graph.get_module(module_id).unwrap();
+                let mut tree_shake_module = GLOBALS.set(&context.meta.script.globals, || {
+                    TreeShakeModule::new(module, order, module_graph)
+                });
+                if let Some(info) = &module.info
+                    && let Some(side_effects) = configured_side_effect(&info.file.path, context)
+                {
+                    tree_shake_module.described_side_effects = Some(side_effects);
+                }
+                tree_shake_module
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut tree_shake_modules_ids = vec![];
+    let mut tree_shake_modules_map = std::collections::HashMap::new();
 
+    for tree_shake_module in tree_shake_modules {
         if std::env::var("TS_DEBUG").is_ok() {
             let mut comments = context.meta.script.output_comments.write().unwrap();
 
@@ -94,7 +131,6 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
             }
         }
 
-        order += 1;
         tree_shake_modules_ids.push(tree_shake_module.module_id.clone());
         tree_shake_modules_map.insert(
             tree_shake_module.module_id.clone(),
@@ -156,151 +192,250 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
         );
     }
 
-    // traverse the tree_shake_modules
-    let mut current_index: usize = 0;
-    let len = tree_shake_modules_ids.len();
-    while current_index < len {
-        let mut next_index = current_index + 1;
-
-        let tree_shake_module_id = &tree_shake_modules_ids[current_index];
+    mark_used_exports(
+        &tree_shake_modules_ids,
+        &tree_shake_modules_map,
+        module_graph,
+        context,
+    );
 
-        let mut tree_shake_module = tree_shake_modules_map
-            .get(tree_shake_module_id)
-            .unwrap()
-            .borrow_mut();
+    if context
+        .config
+        .analyze
+        .as_ref()
+        .is_some_and(|analyze| analyze.side_effects)
+    {
+        context
+            .stats_info
+            .set_side_effects_audit(audit_side_effects(
+                &tree_shake_modules_map,
+                module_graph,
+                context,
+            ));
+    }
 
-        // if module is not esm, mark all imported modules as [UsedExports::All]
-        if !matches!(tree_shake_module.module_system, ModuleSystem::ESModule) {
-            drop(tree_shake_module);
-            for (dep_id, _) in module_graph.get_dependencies(tree_shake_module_id) {
-                if let Some(ref_cell) = tree_shake_modules_map.get(dep_id) {
-                    let mut dep_module = ref_cell.borrow_mut();
+    for (module_id, tsm) in &tree_shake_modules_map {
+        let tsm = tsm.borrow();
 
-                    if dep_module.use_all_exports() && dep_module.topo_order < next_index {
-                        next_index = dep_module.topo_order;
-                    }
-                }
-            }
-        } else {
-            if tree_shake_module.not_used() {
-                //if the module's used_exports is empty, means this module is not used and will be removed
-                current_index = next_index;
+        if tsm.not_used() {
+            module_graph.remove_module(module_id);
+        } else if let Some(swc_module) = &tsm.updated_ast {
+            module_graph
+                .get_module_mut(module_id)
+                .unwrap()
+                .info
+                .as_mut()
+                .unwrap()
+                .ast
+                .as_script_ast_mut()
+                .body = swc_module.body.clone();
+        }
+    }
 
-                continue;
-            }
+    if context
+        .config
+        .optimization
+        .as_ref()
+        .map_or(false, |o| o.concatenate_modules.unwrap_or(false))
+    {
+        optimize_module_graph(module_graph, &tree_shake_modules_map, context)?;
+    }
 
-            let side_effects = tree_shake_module.side_effects;
+    Ok(())
+}
 
-            let module = module_graph
-                .get_module_mut(&tree_shake_module.module_id)
-                .unwrap();
-            let ast = &mut module.info.as_mut().unwrap().ast;
+enum ShakeResult {
+    // not esm, all imported modules are used
+    NotESModule,
+    NotUsed,
+    Shaken(Vec<ImportInfo>, Vec<ExportInfo>),
+}
 
-            if let ModuleAst::Script(swc_module) = ast {
-                // remove useless statements and useless imports/exports identifiers, then all preserved import info and export info will be added to the used_exports.
+/**
+ * traverse the tree shake modules to mark used exports until nothing changes
+ *
+ * modules are partitioned into levels by their importers, modules in the same level don't import
+ * each other (except for cycles), so removing useless statements of them, which is the most time
+ * consuming part, runs in parallel, and the used exports are propagated to the dependencies
+ * sequentially in topo-sort order to keep the result stable
+ */
+fn mark_used_exports(
+    tree_shake_modules_ids: &[ModuleId],
+    tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) {
+    crate::mako_profile_function!();
+    let levels = partition_by_level(tree_shake_modules_ids, tree_shake_modules_map, module_graph);
 
-                let mut shadow = swc_module.ast.clone();
+    let mut dirty = vec![true; tree_shake_modules_ids.len()];
+    while dirty.iter().any(|d| *d) {
+        for level in &levels {
+            let wave = level
+                .iter()
+                .copied()
+                .filter(|order| dirty[*order])
+                .collect::<Vec<_>>();
+            if wave.is_empty() {
+                continue;
+            }
+            wave.iter().for_each(|order| dirty[*order] = false);
 
-                let (used_imports, used_exports_from) = remove_useless_stmts::remove_useless_stmts(
-                    tree_shake_module.deref_mut(),
-                    &mut shadow,
-                );
+            let results = {
+                crate::mako_profile_scope!("tree shake remove useless stmts");
+                let mut guards = wave
+                    .iter()
+                    .map(|order| {
+                        tree_shake_modules_map
+                            .get(&tree_shake_modules_ids[*order])
+                            .unwrap()
+                            .borrow_mut()
+                    })
+                    .collect::<Vec<_>>();
+                let mut tree_shake_modules = guards
+                    .iter_mut()
+                    .map(|guard| guard.deref_mut())
+                    .collect::<Vec<_>>();
+                tree_shake_modules
+                    .par_iter_mut()
+                    .map(|tree_shake_module| shake_module(tree_shake_module, module_graph, context))
+                    .collect::<Vec<_>>()
+            };
 
-                tree_shake_module.updated_ast = Some(shadow);
+            for (order, result) in wave.iter().zip(results) {
+                let tree_shake_module_id = &tree_shake_modules_ids[*order];
+                let mut mark_dirty = |order: Option<usize>| {
+                    if let Some(order) = order {
+                        dirty[order] = true;
+                    }
+                };
 
-                // 解决模块自己引用自己，导致 tree_shake_module 同时存在多个可变引用
-                drop(tree_shake_module);
+                match result {
+                    ShakeResult::NotESModule => {
+                        for (dep_id, _) in module_graph.get_dependencies(tree_shake_module_id) {
+                            if let Some(ref_cell) = tree_shake_modules_map.get(dep_id) {
+                                let mut dep_module = ref_cell.borrow_mut();
 
-                for import_info in used_imports {
-                    if let Some(order) = add_used_exports_by_import_info(
-                        &tree_shake_modules_map,
-                        &*module_graph,
-                        tree_shake_module_id,
-                        &import_info,
-                    ) {
-                        if next_index > order {
-                            next_index = order;
+                                if dep_module.use_all_exports() {
+                                    mark_dirty(Some(dep_module.topo_order));
+                                }
+                            }
                         }
                     }
-                }
+                    // if the module's used_exports is empty, means this module is not used and will be removed
+                    ShakeResult::NotUsed => continue,
+                    ShakeResult::Shaken(used_imports, used_exports_from) => {
+                        let side_effects = tree_shake_modules_map
+                            .get(tree_shake_module_id)
+                            .unwrap()
+                            .borrow()
+                            .side_effects;
+
+                        for import_info in used_imports {
+                            mark_dirty(add_used_exports_by_import_info(
+                                tree_shake_modules_map,
+                                module_graph,
+                                tree_shake_module_id,
+                                &import_info,
+                            ));
+                        }
 
-                for export_info in used_exports_from {
-                    if let Some(order) = add_used_exports_by_export_info(
-                        &tree_shake_modules_map,
-                        &*module_graph,
-                        tree_shake_module_id,
-                        side_effects,
-                        &export_info,
-                    ) {
-                        if next_index > order {
-                            next_index = order;
+                        for export_info in used_exports_from {
+                            mark_dirty(add_used_exports_by_export_info(
+                                tree_shake_modules_map,
+                                module_graph,
+                                tree_shake_module_id,
+                                side_effects,
+                                &export_info,
+                            ));
                         }
                     }
                 }
-            }
-        }
 
-        // add all dynamic imported dependencies as [UsedExports::All]
-        for (dep, edge) in module_graph.get_dependencies(tree_shake_module_id) {
-            match edge.resolve_type {
-                ResolveType::DynamicImport | ResolveType::Worker => {
-                    if let Some(ref_cell) = tree_shake_modules_map.get(dep) {
-                        let mut tree_shake_module = ref_cell.borrow_mut();
-                        if tree_shake_module.use_all_exports()
-                            && tree_shake_module.topo_order < next_index
-                        {
-                            next_index = tree_shake_module.topo_order;
-                        }
+                // add all dynamic imported dependencies as [UsedExports::All]
+                for (dep, edge) in module_graph.get_dependencies(tree_shake_module_id) {
+                    match edge.resolve_type {
+                        ResolveType::DynamicImport | ResolveType::Worker => {
+                            if let Some(ref_cell) = tree_shake_modules_map.get(dep) {
+                                let mut tree_shake_module = ref_cell.borrow_mut();
+                                if tree_shake_module.use_all_exports() {
+                                    mark_dirty(Some(tree_shake_module.topo_order));
+                                }
 
-                        tree_shake_module.side_effects = true;
-                    }
-                }
-                ResolveType::Require => {
-                    if let Some(ref_cell) = tree_shake_modules_map.get(dep) {
-                        let mut tree_shake_module = ref_cell.borrow_mut();
+                                tree_shake_module.side_effects = true;
+                            }
+                        }
+                        ResolveType::Require => {
+                            if let Some(ref_cell) = tree_shake_modules_map.get(dep) {
+                                let mut tree_shake_module = ref_cell.borrow_mut();
 
-                        if tree_shake_module.use_all_exports()
-                            && tree_shake_module.topo_order < next_index
-                        {
-                            next_index = tree_shake_module.topo_order;
+                                if tree_shake_module.use_all_exports() {
+                                    mark_dirty(Some(tree_shake_module.topo_order));
+                                }
+                            }
                         }
+                        _ => {}
                     }
                 }
-                _ => {}
             }
         }
+    }
+}
 
-        current_index = next_index;
+fn shake_module(
+    tree_shake_module: &mut TreeShakeModule,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> ShakeResult {
+    if !matches!(tree_shake_module.module_system, ModuleSystem::ESModule) {
+        return ShakeResult::NotESModule;
+    }
+    if tree_shake_module.not_used() {
+        return ShakeResult::NotUsed;
     }
 
-    for (module_id, tsm) in &tree_shake_modules_map {
-        let tsm = tsm.borrow();
+    // remove useless statements and useless imports/exports identifiers, then all preserved import info and export info will be added to the used_exports.
+    let mut shadow = module_graph
+        .get_module(&tree_shake_module.module_id)
+        .unwrap()
+        .as_script()
+        .unwrap()
+        .ast
+        .clone();
 
-        if tsm.not_used() {
-            module_graph.remove_module(module_id);
-        } else if let Some(swc_module) = &tsm.updated_ast {
-            module_graph
-                .get_module_mut(module_id)
-                .unwrap()
-                .info
-                .as_mut()
-                .unwrap()
-                .ast
-                .as_script_ast_mut()
-                .body = swc_module.body.clone();
+    let (used_imports, used_exports_from) = GLOBALS.set(&context.meta.script.globals, || {
+        remove_useless_stmts::remove_useless_stmts(tree_shake_module, &mut shadow)
+    });
+
+    tree_shake_module.updated_ast = Some(shadow);
+
+    ShakeResult::Shaken(used_imports, used_exports_from)
+}
+
+// level of a module is greater than all of its importers', back edges of cycles are ignored
+fn partition_by_level(
+    tree_shake_modules_ids: &[ModuleId],
+    tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+    module_graph: &ModuleGraph,
+) -> Vec<Vec<usize>> {
+    let mut module_levels = vec![0; tree_shake_modules_ids.len()];
+    for (order, module_id) in tree_shake_modules_ids.iter().enumerate() {
+        for (dependent_id, _) in module_graph.get_dependents(module_id) {
+            if let Some(tsm) = tree_shake_modules_map.get(dependent_id) {
+                let dependent_order = tsm.borrow().topo_order;
+                if dependent_order < order {
+                    module_levels[order] =
+                        module_levels[order].max(module_levels[dependent_order] + 1);
+                }
+            }
         }
     }
 
-    if context
-        .config
-        .optimization
-        .as_ref()
-        .map_or(false, |o| o.concatenate_modules.unwrap_or(false))
-    {
-        optimize_module_graph(module_graph, &tree_shake_modules_map, context)?;
+    let mut levels = vec![vec![]; module_levels.iter().max().map_or(0, |max| max + 1)];
+    for (order, level) in module_levels.into_iter().enumerate() {
+        levels[level].push(order);
     }
-
-    Ok(())
+    levels
 }
 
 // Add all imported to used_exports
@@ -615,3 +750,153 @@ fn greater_equal_than(a: usize, b: i64) -> bool {
         (a as i64) >= b
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::{
+        add_used_exports_by_export_info, add_used_exports_by_import_info, mark_used_exports,
+        shake_module, shake_modules, MarkUsedExports, ShakeResult,
+    };
+    use crate::ast::file::File;
+    use crate::compiler::Context;
+    use crate::module::{relative_to_root, ModuleId, ResolveType};
+    use crate::module_graph::ModuleGraph;
+    use crate::plugins::tree_shaking::module::TreeShakeModule;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_mark_used_exports_in_parallel() {
+        let shaken = shaken_modules(mark_used_exports);
+        assert_eq!(shaken, shaken_modules(mark_used_exports_serially));
+        // unused exports and modules are removed
+        let code = |path: &str| {
+            shaken
+                .iter()
+                .find(|(id, _)| id == path)
+                .map(|(_, code)| code.clone())
+                .unwrap_or_default()
+        };
+        assert!(!code("./shared.ts").contains("neverUsed"));
+        assert!(!code("./c.ts").contains("unusedC"));
+        assert!(code("./b.ts").contains("b2"));
+        assert!(shaken.iter().all(|(id, _)| id != "./unused.ts"));
+    }
+
+    fn shaken_modules(mark_used_exports: MarkUsedExports) -> Vec<(String, String)> {
+        let compiler = setup_compiler("test/build/tree-shaking-levels", false);
+        let context = compiler.context.clone();
+        let files = context
+            .config
+            .entry
+            .values()
+            .map(|entry| File::new_entry(entry.to_string_lossy().to_string(), context.clone()))
+            .collect();
+        compiler.build(files).unwrap();
+
+        let mut module_graph = context.module_graph.write().unwrap();
+        shake_modules(&mut module_graph, &context, mark_used_exports).unwrap();
+        let mut modules = module_graph
+            .modules()
+            .iter()
+            .map(|module| {
+                let code = module.as_script().map_or(String::new(), |ast| {
+                    ast.generate(context.clone()).unwrap().code
+                });
+                (relative_to_root(&module.id.id, &context.root), code)
+            })
+            .collect::<Vec<_>>();
+        modules.sort();
+        modules
+    }
+
+    // traverses the modules one by one in topo-sort order, which is how they were marked before
+    // marking in parallel, and goes back to the dependencies whose used exports are changed
+    fn mark_used_exports_serially(
+        tree_shake_modules_ids: &[ModuleId],
+        tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+        module_graph: &ModuleGraph,
+        context: &Arc<Context>,
+    ) {
+        let mut current_index: usize = 0;
+        while current_index < tree_shake_modules_ids.len() {
+            let next_index = current_index + 1;
+            let tree_shake_module_id = &tree_shake_modules_ids[current_index];
+
+            let result = shake_module(
+                &mut tree_shake_modules_map
+                    .get(tree_shake_module_id)
+                    .unwrap()
+                    .borrow_mut(),
+                module_graph,
+                context,
+            );
+            let mut orders = vec![];
+            match result {
+                ShakeResult::NotESModule => {
+                    for (dep_id, _) in module_graph.get_dependencies(tree_shake_module_id) {
+                        if let Some(ref_cell) = tree_shake_modules_map.get(dep_id) {
+                            let mut dep_module = ref_cell.borrow_mut();
+                            if dep_module.use_all_exports() {
+                                orders.push(dep_module.topo_order);
+                            }
+                        }
+                    }
+                }
+                ShakeResult::NotUsed => {
+                    current_index = next_index;
+                    continue;
+                }
+                ShakeResult::Shaken(used_imports, used_exports_from) => {
+                    let side_effects = tree_shake_modules_map
+                        .get(tree_shake_module_id)
+                        .unwrap()
+                        .borrow()
+                        .side_effects;
+                    for import_info in used_imports {
+                        orders.extend(add_used_exports_by_import_info(
+                            tree_shake_modules_map,
+                            module_graph,
+                            tree_shake_module_id,
+                            &import_info,
+                        ));
+                    }
+                    for export_info in used_exports_from {
+                        orders.extend(add_used_exports_by_export_info(
+                            tree_shake_modules_map,
+                            module_graph,
+                            tree_shake_module_id,
+                            side_effects,
+                            &export_info,
+                        ));
+                    }
+                }
+            }
+
+            for (dep, edge) in module_graph.get_dependencies(tree_shake_module_id) {
+                if let Some(ref_cell) = tree_shake_modules_map.get(dep) {
+                    let mut tree_shake_module = ref_cell.borrow_mut();
+                    match edge.resolve_type {
+                        ResolveType::DynamicImport | ResolveType::Worker => {
+                            if tree_shake_module.use_all_exports() {
+                                orders.push(tree_shake_module.topo_order);
+                            }
+                            tree_shake_module.side_effects = true;
+                        }
+                        ResolveType::Require => {
+                            if tree_shake_module.use_all_exports() {
+                                orders.push(tree_shake_module.topo_order);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            current_index = orders.into_iter().fold(next_index, usize::min);
+        }
+    }
+}
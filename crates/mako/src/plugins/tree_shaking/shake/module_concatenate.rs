@@ -293,6 +293,7 @@ pub fn optimize_module_graph(
                             Dependency {
                                 source: require_src,
                                 resolve_as: None,
+                                specifiers: vec![],
                                 resolve_type: ResolveType::Require,
                                 order: 0,
                                 span: None,
@@ -401,6 +402,7 @@ pub fn optimize_module_graph(
                         top_level_mark,
                         path: config.root.id.clone(),
                         contains_top_level_await: false,
+                        type_only_deps: vec![],
                     };
 
                     let code = a.generate(context.clone()).unwrap().code;
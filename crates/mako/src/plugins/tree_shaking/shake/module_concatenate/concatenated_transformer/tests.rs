@@ -673,6 +673,8 @@ fn inner_trans_code(code: &str, concatenate_context: &mut ConcatenateContext) ->
             optimization: Some(OptimizationConfig {
                 concatenate_modules: Some(true),
                 skip_modules: Some(true),
+                polyfill_chunk: None,
+                isolate_licenses: None,
             }),
             mode: Mode::Production,
             minify: false,
@@ -12,7 +12,9 @@ use swc_core::ecma::utils::{quote_ident, quote_str};
 use swc_core::quote;
 
 use crate::compiler::Context;
-use crate::module::{Dependency, ImportType, ModuleId, NamedExportType, ResolveType};
+use crate::module::{
+    Dependency, ImportType, ImportedSymbol, ModuleId, NamedExportType, ResolveType,
+};
 use crate::module_graph::ModuleGraph;
 use crate::plugins::tree_shaking::module::{is_ident_sym_equal, TreeShakeModule};
 use crate::plugins::tree_shaking::shake::strip_context;
@@ -68,6 +70,7 @@ impl ReExportReplace {
             order: 0,
             resolve_as: None,
             resolve_type: ResolveType::Import(import_type),
+            specifiers: self.imported_symbols(),
         }
     }
 
@@ -82,11 +85,20 @@ impl ReExportReplace {
             source: self.from_module_id.id.clone(),
             resolve_as: None,
             resolve_type,
+            specifiers: self.imported_symbols(),
             order: 0,
             span: Some(span),
         }
     }
 
+    fn imported_symbols(&self) -> Vec<ImportedSymbol> {
+        vec![match &self.re_export_source.re_export_type {
+            ReExportType::Namespace => ImportedSymbol::Namespace,
+            ReExportType::Default => ImportedSymbol::Default,
+            ReExportType::Named(local) => ImportedSymbol::Named(local.clone()),
+        }]
+    }
+
     pub(crate) fn to_import_module_item(&self, ident: Ident) -> ModuleItem {
         match &self.re_export_source.re_export_type {
             ReExportType::Default => {
@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::compiler::Context;
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+use crate::plugins::tree_shaking::module::{ModuleSystem, TreeShakeModule, UsedExports, UsedIdent};
+use crate::plugins::tree_shaking::statement_graph::{ExportSpecifierInfo, StatementId};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SideEffectsAuditItem {
+    pub module: String,
+    pub symbol: String,
+    pub reason: String,
+}
+
+/**
+ * find out the exports which are not used by the entry but still bundled, because their
+ * declarations or the statements referencing them may have side effects, so that SDK authors
+ * can add pure annotations (e.g. `#__PURE__`) where it's safe
+ *
+ * it only makes sense for libraries with a single entry, whose public API is the entry exports
+ */
+pub fn audit_side_effects(
+    tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Vec<SideEffectsAuditItem> {
+    if context.config.entry.len() != 1 {
+        context.stats_info.add_warning(
+            "analyze.sideEffects only works with a single entry, it's skipped".to_string(),
+        );
+        return vec![];
+    }
+
+    let mut items = vec![];
+    for (module_id, tsm) in tree_shake_modules_map {
+        let tsm = tsm.borrow();
+        let under_node_modules = module_graph
+            .get_module(module_id)
+            .and_then(|module| module.info.as_ref())
+            .map_or(true, |info| info.file.is_under_node_modules);
+        if tsm.module_system != ModuleSystem::ESModule || under_node_modules || tsm.not_used() {
+            continue;
+        }
+        let module = Path::new(&module_id.id)
+            .strip_prefix(&context.root)
+            .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string());
+        for (symbol, reason) in audit_module(&tsm, context) {
+            items.push(SideEffectsAuditItem {
+                module: module.clone(),
+                symbol,
+                reason,
+            });
+        }
+    }
+    items.sort_by(|a, b| (&a.module, &a.symbol).cmp(&(&b.module, &b.symbol)));
+    items
+}
+
+// (export name, reason) of the unused exports which are retained
+fn audit_module(tsm: &TreeShakeModule, context: &Arc<Context>) -> Vec<(String, String)> {
+    let used_exports = match tsm.used_exports() {
+        UsedExports::All => return vec![],
+        UsedExports::Partial(used) | UsedExports::ReferredPartial(used) => used,
+    };
+
+    let retained = tsm.used_statements();
+    // statements which are retained because of the used exports only
+    let mut used_exports_idents: BTreeMap<StatementId, HashSet<UsedIdent>> = BTreeMap::new();
+    for (used_ident, stmt_id) in tsm.used_exports_idents() {
+        used_exports_idents
+            .entry(stmt_id)
+            .or_default()
+            .insert(used_ident);
+    }
+    let required = tsm
+        .stmt_graph
+        .analyze_used_statements_and_idents(used_exports_idents);
+
+    let mut result = vec![];
+    for export_info in tsm.exports() {
+        // re-exports are audited in the modules which declare them
+        if export_info.source.is_some() {
+            continue;
+        }
+        for specifier in &export_info.specifiers {
+            let decl_stmt_id = match specifier {
+                ExportSpecifierInfo::Named { local, .. } => tsm
+                    .stmt_graph
+                    .stmts()
+                    .into_iter()
+                    .find(|stmt| stmt.defined_idents.contains(local))
+                    .map_or(export_info.stmt_id, |stmt| stmt.id),
+                ExportSpecifierInfo::Default(_) => export_info.stmt_id,
+                _ => continue,
+            };
+            for symbol in specifier.to_idents() {
+                if used_exports.contains(&symbol)
+                    || !retained.contains_key(&decl_stmt_id)
+                    || required.contains_key(&decl_stmt_id)
+                {
+                    continue;
+                }
+                result.push((symbol, retained_reason(tsm, decl_stmt_id, context)));
+            }
+        }
+    }
+    result
+}
+
+fn retained_reason(tsm: &TreeShakeModule, stmt_id: StatementId, context: &Arc<Context>) -> String {
+    let line = |stmt_id: &StatementId| {
+        context
+            .meta
+            .script
+            .cm
+            .lookup_char_pos(tsm.stmt_graph.stmt(stmt_id).span.lo)
+            .line
+    };
+
+    if tsm.stmt_graph.stmt(&stmt_id).is_self_executed {
+        return format!(
+            "its declaration at line {} may have side effects",
+            line(&stmt_id)
+        );
+    }
+
+    // find the statement with side effects which references it directly or indirectly
+    for stmt in tsm.stmt_graph.stmts() {
+        if !stmt.is_self_executed {
+            continue;
+        }
+        let mut queue = VecDeque::from([stmt.id]);
+        let mut visited = HashSet::from([stmt.id]);
+        while let Some(current) = queue.pop_front() {
+            if current == stmt_id {
+                return format!(
+                    "it's referenced by the statement at line {} which may have side effects",
+                    line(&stmt.id)
+                );
+            }
+            for (dep, _) in tsm.stmt_graph.dependencies(&current) {
+                if visited.insert(dep.id) {
+                    queue.push_back(dep.id);
+                }
+            }
+        }
+    }
+    "it's referenced by statements which may have side effects".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SideEffectsAuditItem;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_side_effects_audit() {
+        let compiler = setup_compiler("test/build/side-effects-audit", false);
+        compiler.compile().unwrap();
+        assert_eq!(
+            compiler.context.stats_info.get_side_effects_audit(),
+            vec![
+                SideEffectsAuditItem {
+                    module: "api.ts".to_string(),
+                    symbol: "plugin".to_string(),
+                    reason:
+                        "it's referenced by the statement at line 11 which may have side effects"
+                            .to_string(),
+                },
+                SideEffectsAuditItem {
+                    module: "api.ts".to_string(),
+                    symbol: "registered".to_string(),
+                    reason: "its declaration at line 9 may have side effects".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::vec;
 
@@ -70,7 +70,97 @@ pub fn resolve(
 
     let source = dep.resolve_as.as_ref().unwrap_or(&dep.source);
 
-    do_resolve(path, source, resolver, Some(&context.config.externals))
+    // specifiers mapped by the import map are left to the browser
+    if let Some(import_map) = &context.config.import_map
+        && let Some(url) = import_map.resolve(source)
+    {
+        return Ok(ResolverResource::External(ExternalResource {
+            source: source.to_string(),
+            external: format!("__mako_require__.importModule(\"{}\")", source),
+            script: None,
+            import_map: Some(url),
+        }));
+    }
+
+    // resolve dedupe packages from root, so that all importers share the same copy
+    let dedupe_root;
+    let path = if is_dedupe_package(source, &context.config.dedupe_packages) {
+        dedupe_root = context
+            .root
+            .join(".dedupe.root")
+            .to_string_lossy()
+            .to_string();
+        dedupe_root.as_str()
+    } else {
+        path
+    };
+
+    let resource = do_resolve(path, source, resolver, Some(&context.config.externals))?;
+    if context.config.mocks.is_empty() {
+        return Ok(resource);
+    }
+    mock_resource(path, resource, resolver, context)
+}
+
+// modules listed in mocks are replaced graph-wide, e.g. { "./src/api": "./src/api.mock" },
+// keys and values are resolved from root, the mock itself still gets the origin module so
+// that it can re-export the parts which are not mocked
+fn mock_resource(
+    path: &str,
+    resource: ResolverResource,
+    resolver: &Resolver,
+    context: &Arc<Context>,
+) -> Result<ResolverResource> {
+    let ResolverResource::Resolved(resolved) = &resource else {
+        return Ok(resource);
+    };
+    let resolved_path = resolved.0.full_path();
+    let root = context
+        .root
+        .join(".mocks.root")
+        .to_string_lossy()
+        .to_string();
+    for (origin, mock) in &context.config.mocks {
+        let Ok(ResolverResource::Resolved(origin)) = do_resolve(&root, origin, resolver, None)
+        else {
+            continue;
+        };
+        if origin.0.full_path() != resolved_path {
+            continue;
+        }
+        let mock = do_resolve(&root, mock, resolver, None).map_err(|_| {
+            anyhow!(
+                "mock {} of {} is not found",
+                mock,
+                origin.0.path().display()
+            )
+        })?;
+        if Path::new(path) == Path::new(&mock.get_resolved_path()) {
+            return Ok(resource);
+        }
+        context.stats_info.add_mocked_module(
+            relative_to_root(&resolved_path, &context.root),
+            relative_to_root(Path::new(&mock.get_resolved_path()), &context.root),
+        );
+        return Ok(mock);
+    }
+    Ok(resource)
+}
+
+fn relative_to_root(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .map_or(path, |p| p)
+        .to_string_lossy()
+        .to_string()
+}
+
+// e.g. with dedupePackages ["react"], `react` and `react/jsx-runtime` are matched
+fn is_dedupe_package(source: &str, dedupe_packages: &[String]) -> bool {
+    dedupe_packages.iter().any(|package| {
+        source
+            .strip_prefix(package.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
 }
 
 #[cached(key = "String", convert = r#"{ re.to_string() }"#)]
@@ -236,6 +326,7 @@ fn do_resolve(
             source: source.to_string(),
             external,
             script,
+            import_map: None,
         }))
     } else {
         let path = PathBuf::from(path);
@@ -306,11 +397,27 @@ pub fn get_module_extensions() -> Vec<String> {
     ]
 }
 
+// platform-specific extensions take precedence in order
+// e.g. [".ios.js", ".ios.jsx", ..., ".native.js", ..., ".js", ...] for ["ios", "native"]
+fn get_platform_extensions(platforms: &[String]) -> Vec<String> {
+    let extensions = get_module_extensions();
+    platforms
+        .iter()
+        .flat_map(|platform| {
+            extensions
+                .iter()
+                .map(move |ext| format!(".{}{}", platform, ext))
+        })
+        .chain(extensions.iter().cloned())
+        .collect()
+}
+
 fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
     let alias = parse_alias(config.resolve.alias.clone());
     let is_browser = config.platform == Platform::Browser;
-    let extensions = get_module_extensions();
-    let options = match (resolver_type, is_browser) {
+    let extensions = get_platform_extensions(&config.resolve.platforms);
+    let is_script = matches!(resolver_type, ResolverType::Cjs | ResolverType::Esm);
+    let mut options = match (resolver_type, is_browser) {
         (ResolverType::Cjs, true) => ResolveOptions {
             alias,
             extensions,
@@ -396,6 +503,14 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         },
     };
 
+    // the "react-native" field of packages takes precedence, like metro
+    if config.experimental.react_native.is_some() && is_script {
+        options.main_fields.insert(0, "react-native".to_string());
+        options
+            .condition_names
+            .insert(0, "react-native".to_string());
+    }
+
     Resolver::new(options)
 }
 
@@ -416,14 +531,28 @@ pub fn clear_resolver_cache(resolvers: &Resolvers) {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::Arc;
 
+    use crate::compiler::Context;
     use crate::config::{
         Config, ExternalAdvanced, ExternalAdvancedSubpath, ExternalAdvancedSubpathConverter,
         ExternalAdvancedSubpathRule, ExternalAdvancedSubpathTarget, ExternalConfig,
+        ReactNativeConfig, ReactNativePlatform,
     };
+    use crate::module::{Dependency, ResolveType};
     use crate::resolve::ResolverType;
 
+    #[test]
+    fn test_is_dedupe_package() {
+        let packages = vec!["react".to_string(), "@scope/foo".to_string()];
+        assert!(super::is_dedupe_package("react", &packages));
+        assert!(super::is_dedupe_package("react/jsx-runtime", &packages));
+        assert!(super::is_dedupe_package("@scope/foo/bar", &packages));
+        assert!(!super::is_dedupe_package("react-dom", &packages));
+        assert!(!super::is_dedupe_package("./react", &packages));
+    }
+
     #[test]
     fn test_resolve() {
         let x = resolve("test/resolve/normal", None, None, "index.ts", "./source");
@@ -459,6 +588,49 @@ mod tests {
         assert_eq!(x, "node_modules/foo/esm-browser.js".to_string());
     }
 
+    #[test]
+    fn test_resolve_platforms() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/platforms");
+        let mut config: Config = Default::default();
+        config.resolve.platforms = vec!["ios".to_string(), "native".to_string()];
+        config.experimental.react_native = Some(ReactNativeConfig {
+            platform: ReactNativePlatform::Ios,
+            inline_requires: false,
+        });
+        let resolver = super::get_resolver(&config, ResolverType::Cjs);
+        let resolve = |source: &str| {
+            super::do_resolve(
+                &fixture.join("index.ts").to_string_lossy(),
+                source,
+                &resolver,
+                None,
+            )
+            .unwrap()
+            .get_resolved_path()
+            .replace(format!("{}/", fixture.to_str().unwrap()).as_str(), "")
+        };
+        assert_eq!(resolve("./button"), "button.ios.ts");
+        assert_eq!(resolve("./list"), "list.native.ts");
+        assert_eq!(resolve("./source"), "source.ts");
+        assert_eq!(resolve("foo"), "node_modules/foo/native.js");
+
+        // e.g. react native web
+        config.resolve.platforms = vec!["web".to_string()];
+        config.experimental.react_native = None;
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        let path = super::do_resolve(
+            &fixture.join("index.ts").to_string_lossy(),
+            "./button",
+            &resolver,
+            None,
+        )
+        .unwrap()
+        .get_resolved_path();
+        assert!(path.ends_with("button.web.ts"));
+    }
+
     #[test]
     fn test_resolve_alias() {
         let alias = vec![("bar".to_string(), "foo".to_string())];
@@ -738,6 +910,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_mocks() {
+        let context = Arc::new(Context {
+            root: std::env::current_dir().unwrap().join("test/resolve/mocks"),
+            config: Config {
+                mocks: HashMap::from([("./api".to_string(), "./api.mock".to_string())]),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let resolvers = super::get_resolvers(&context.config);
+        let mock_resolve = |path: &str| {
+            super::resolve(
+                &context.root.join(path).to_string_lossy(),
+                &Dependency {
+                    source: "./api".to_string(),
+                    resolve_as: None,
+                    specifiers: vec![],
+                    resolve_type: ResolveType::DynamicImport,
+                    order: 0,
+                    span: None,
+                },
+                &resolvers,
+                &context,
+            )
+            .unwrap()
+            .get_resolved_path()
+        };
+        assert!(mock_resolve("index.ts").ends_with("api.mock.ts"));
+        // the mock can still import the origin module
+        assert!(mock_resolve("api.mock.ts").ends_with("api.ts"));
+        assert_eq!(
+            context.stats_info.get_mocked_modules(),
+            BTreeMap::from([("api.ts".to_string(), "api.mock.ts".to_string())])
+        );
+    }
+
     fn resolve(
         base: &str,
         alias: Option<Vec<(String, String)>>,
@@ -7,6 +7,8 @@ pub struct ExternalResource {
     pub source: String,
     pub external: String,
     pub script: Option<String>,
+    // the url of the import map entry, the module is imported natively by the browser
+    pub import_map: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +41,14 @@ impl ResolverResource {
             ResolverResource::Virtual(_) => None,
         }
     }
+    pub fn get_import_map(&self) -> Option<String> {
+        match self {
+            ResolverResource::External(ExternalResource { import_map, .. }) => import_map.clone(),
+            ResolverResource::Resolved(_) => None,
+            ResolverResource::Ignored(_) => None,
+            ResolverResource::Virtual(_) => None,
+        }
+    }
     pub fn get_script(&self) -> Option<String> {
         match self {
             ResolverResource::External(ExternalResource { script, .. }) => script.clone(),
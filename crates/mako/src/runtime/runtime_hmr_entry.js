@@ -1,9 +1,3 @@
-// swc will hoist
-const RefreshRuntime = require('react-refresh');
-RefreshRuntime.injectIntoGlobalHook(self);
-self.$RefreshReg$ = () => {};
-self.$RefreshSig$ = () => (type) => type;
-
 (function () {
   let hadRuntimeError = false;
 
@@ -78,6 +72,11 @@ self.$RefreshSig$ = () => (type) => type;
 
   socket.addEventListener('message', (rawMessage) => {
     const msg = JSON.parse(rawMessage.data);
+    // the changes can't be applied with hmr, e.g. the entry scripts in html are changed
+    if (msg.reload) {
+      location.reload();
+      return;
+    }
     latestHash = msg.hash;
 
     if (!updating) {
@@ -0,0 +1,5 @@
+// swc will hoist
+const RefreshRuntime = require('react-refresh');
+RefreshRuntime.injectIntoGlobalHook(self);
+self.$RefreshReg$ = () => {};
+self.$RefreshSig$ = () => (type) => type;
@@ -1,22 +1,27 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use colored::*;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use pathdiff::diff_paths;
 use serde::Serialize;
+use serde_json::Value;
 use swc_core::common::source_map::Pos;
 
 use crate::compiler::{Compiler, Context};
 use crate::features::rsc::{RscClientInfo, RscCssModules};
+use crate::generate::analyze::SymbolSizeItem;
 use crate::generate::chunk::ChunkType;
+use crate::generate::chunk_group::ChunkGroup;
+use crate::generate::generate_chunks::ChunkFile;
+use crate::plugins::tree_shaking::side_effects_audit::SideEffectsAuditItem;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 // name 记录实际 filename , 用在 stats.json 中, hashname 用在产物描述和 manifest 中
@@ -46,6 +51,28 @@ pub struct ModuleInfo {
     pub id: String,
     pub dependencies: Vec<String>,
     pub dependents: Vec<String>,
+    #[serde(rename = "elidedDependencies", skip_serializing_if = "Vec::is_empty")]
+    pub elided_dependencies: Vec<String>,
+    // namespace -> metadata attached by plugins
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ModuleTimingItem {
+    pub id: String,
+    // ms
+    pub time: f64,
+}
+
+// groups of mutually dependent modules which are at least this large are listed in stats
+const LARGE_SCC_GROUP_SIZE: usize = 10;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SccGroupItem {
+    pub size: usize,
+    // relative to root
+    pub modules: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -54,6 +81,39 @@ pub struct StatsInfo {
     pub rsc_client_components: Mutex<Vec<RscClientInfo>>,
     pub rsc_css_modules: Mutex<Vec<RscCssModules>>,
     pub modules: Mutex<HashMap<String, ModuleInfo>>,
+    // stage -> module id -> duration
+    pub module_timings: Mutex<HashMap<String, HashMap<String, Duration>>>,
+    // module path -> namespace -> metadata
+    pub module_metadata: Mutex<HashMap<String, BTreeMap<String, Value>>>,
+    pub symbol_sizes: Mutex<Vec<SymbolSizeItem>>,
+    pub side_effects_audit: Mutex<Vec<SideEffectsAuditItem>>,
+    // chunk file name -> content hash of the last emission, for incremental stats
+    pub chunk_hashes: Mutex<HashMap<String, u64>>,
+    pub chunk_changes: Mutex<ChunkChanges>,
+    pub warnings: Mutex<Vec<String>>,
+    // how many warnings are already reported in stats
+    pub reported_warnings: Mutex<usize>,
+    // message -> occurrences (e.g. module locations) of warnings fired by many modules
+    pub grouped_warnings: Mutex<IndexMap<String, IndexSet<String>>>,
+    // (message, occurrence) of grouped warnings which are recomputed on every rebuild
+    pub reported_occurrences: Mutex<HashSet<(String, String)>>,
+    // origin module -> mock module, substitutions made by mocks
+    pub mocked_modules: Mutex<BTreeMap<String, String>>,
+    // browser target -> what is only required by it, see polyfillReport
+    pub target_savings: Mutex<BTreeMap<String, TargetSaving>>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct TargetSaving {
+    // bytes of downleveled code
+    pub size: u64,
+    pub polyfills: BTreeSet<String>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ChunkChanges {
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
 }
 
 impl StatsInfo {
@@ -63,6 +123,18 @@ impl StatsInfo {
             rsc_client_components: Mutex::new(vec![]),
             rsc_css_modules: Mutex::new(vec![]),
             modules: Mutex::new(HashMap::new()),
+            module_timings: Mutex::new(HashMap::new()),
+            module_metadata: Mutex::new(HashMap::new()),
+            symbol_sizes: Mutex::new(vec![]),
+            side_effects_audit: Mutex::new(vec![]),
+            chunk_hashes: Mutex::new(HashMap::new()),
+            chunk_changes: Mutex::new(ChunkChanges::default()),
+            warnings: Mutex::new(vec![]),
+            reported_warnings: Mutex::new(0),
+            grouped_warnings: Mutex::new(IndexMap::new()),
+            reported_occurrences: Mutex::new(HashSet::new()),
+            mocked_modules: Mutex::new(BTreeMap::new()),
+            target_savings: Mutex::new(BTreeMap::new()),
         }
     }
 
@@ -96,6 +168,7 @@ impl StatsInfo {
     pub fn parse_modules(&self, context: Arc<Context>) {
         let module_graph = context.module_graph.read().unwrap();
         let mut modules = self.modules.lock().unwrap();
+        let module_metadata = self.module_metadata.lock().unwrap();
         module_graph.modules().iter().for_each(|module| {
             let dependencies = module_graph
                 .get_dependencies(&module.id)
@@ -107,6 +180,15 @@ impl StatsInfo {
                 .iter()
                 .map(|(id, _dep)| id.generate(&context))
                 .collect::<Vec<_>>();
+            let elided_dependencies = module
+                .info
+                .as_ref()
+                .map(|info| info.deps.elided_deps.clone())
+                .unwrap_or_default();
+            let metadata = module_metadata
+                .get(&module.id.id)
+                .cloned()
+                .unwrap_or_default();
             let id = module.id.generate(&context);
             modules.insert(
                 id.clone(),
@@ -114,6 +196,8 @@ impl StatsInfo {
                     id,
                     dependencies,
                     dependents,
+                    elided_dependencies,
+                    metadata,
                 },
             );
         });
@@ -141,6 +225,261 @@ impl StatsInfo {
     pub fn add_rsc_css_module(&self, rsc_css_module: RscCssModules) {
         self.rsc_css_modules.lock().unwrap().push(rsc_css_module)
     }
+
+    pub fn add_module_timing(&self, stage: &str, module_id: &str, duration: Duration) {
+        let mut module_timings = self.module_timings.lock().unwrap();
+        // modules may be rebuilt in watch mode, only the latest timing is kept
+        module_timings
+            .entry(stage.to_string())
+            .or_default()
+            .insert(module_id.to_string(), duration);
+    }
+
+    // attach serializable metadata (e.g. detected routes, i18n keys) to the module of the path
+    // passed to plugin hooks like transform_js, it appears in `modules.<id>.metadata.<namespace>`
+    // of stats.json, namespace is usually the plugin name
+    // metadata of the same namespace is replaced when the module is rebuilt in watch mode
+    pub fn set_module_metadata<T: Serialize>(
+        &self,
+        path: &str,
+        namespace: &str,
+        metadata: &T,
+    ) -> Result<()> {
+        let metadata = serde_json::to_value(metadata)?;
+        self.module_metadata
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .insert(namespace.to_string(), metadata);
+        Ok(())
+    }
+
+    pub fn get_module_metadata(&self, path: &str, namespace: &str) -> Option<Value> {
+        self.module_metadata
+            .lock()
+            .unwrap()
+            .get(path)
+            .and_then(|metadata| metadata.get(namespace).cloned())
+    }
+
+    pub fn set_symbol_sizes(&self, symbol_sizes: Vec<SymbolSizeItem>) {
+        *self.symbol_sizes.lock().unwrap() = symbol_sizes;
+    }
+
+    pub fn get_symbol_sizes(&self) -> Vec<SymbolSizeItem> {
+        self.symbol_sizes.lock().unwrap().clone()
+    }
+
+    pub fn set_side_effects_audit(&self, items: Vec<SideEffectsAuditItem>) {
+        *self.side_effects_audit.lock().unwrap() = items;
+    }
+
+    pub fn get_side_effects_audit(&self) -> Vec<SideEffectsAuditItem> {
+        self.side_effects_audit.lock().unwrap().clone()
+    }
+
+    pub fn add_mocked_module(&self, origin: String, mock: String) {
+        self.mocked_modules.lock().unwrap().insert(origin, mock);
+    }
+
+    pub fn get_mocked_modules(&self) -> BTreeMap<String, String> {
+        self.mocked_modules.lock().unwrap().clone()
+    }
+
+    pub fn add_target_saving(
+        &self,
+        target: &str,
+        size: u64,
+        polyfills: impl Iterator<Item = String>,
+    ) {
+        let mut target_savings = self.target_savings.lock().unwrap();
+        let saving = target_savings.entry(target.to_string()).or_default();
+        saving.size += size;
+        saving.polyfills.extend(polyfills);
+    }
+
+    pub fn get_target_savings(&self) -> BTreeMap<String, TargetSaving> {
+        self.target_savings.lock().unwrap().clone()
+    }
+
+    // compare with the last emission to find out which chunk files are changed
+    pub fn update_chunk_hashes(&self, chunk_files: &[ChunkFile]) {
+        let current = chunk_files
+            .iter()
+            .map(|file| (file.disk_name(), file.raw_hash))
+            .collect::<HashMap<_, _>>();
+        let mut chunk_hashes = self.chunk_hashes.lock().unwrap();
+        let mut changed = current
+            .iter()
+            .filter(|(name, hash)| chunk_hashes.get(*name) != Some(*hash))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let mut removed = chunk_hashes
+            .keys()
+            .filter(|name| !current.contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+        changed.sort();
+        removed.sort();
+        *chunk_hashes = current;
+        *self.chunk_changes.lock().unwrap() = ChunkChanges { changed, removed };
+    }
+
+    pub fn get_chunk_changes(&self) -> ChunkChanges {
+        self.chunk_changes.lock().unwrap().clone()
+    }
+
+    pub fn add_warning(&self, message: String) {
+        self.warnings.lock().unwrap().push(message);
+    }
+
+    // warnings with the same message are aggregated into one by flush_grouped_warnings,
+    // so that a diagnostic fired by hundreds of modules doesn't flood the terminal
+    pub fn add_grouped_warning(&self, message: String, occurrence: String) {
+        self.grouped_warnings
+            .lock()
+            .unwrap()
+            .entry(message)
+            .or_default()
+            .insert(occurrence);
+    }
+
+    // for diagnostics of the whole graph, which are fired again by every rebuild, occurrences
+    // reported by earlier builds are skipped
+    pub fn add_grouped_warning_once(&self, message: String, occurrence: String) {
+        let key = (message.clone(), occurrence.clone());
+        if self.reported_occurrences.lock().unwrap().insert(key) {
+            self.add_grouped_warning(message, occurrence);
+        }
+    }
+
+    // print and record the grouped warnings added since the last call, occurrences are
+    // only printed with verbose, but always recorded in stats
+    pub fn flush_grouped_warnings(&self, verbose: bool) {
+        let grouped_warnings = std::mem::take(&mut *self.grouped_warnings.lock().unwrap());
+        for (message, occurrences) in grouped_warnings {
+            if occurrences.len() == 1 {
+                let warning = format!("{} ({})", message, occurrences[0]);
+                println!("{}: {}", "warning".to_string().yellow(), warning);
+                self.add_warning(warning);
+                continue;
+            }
+            let summary = format!("{} ({} occurrences)", message, occurrences.len());
+            let list = occurrences
+                .iter()
+                .map(|occurrence| format!("\n  {}", occurrence))
+                .collect::<String>();
+            if verbose {
+                println!("{}: {}{}", "warning".to_string().yellow(), summary, list);
+            } else {
+                println!(
+                    "{}: {}, use --verbose to list them",
+                    "warning".to_string().yellow(),
+                    summary
+                );
+            }
+            self.add_warning(format!("{}{}", summary, list));
+        }
+    }
+
+    pub fn get_warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    // warnings added since the last call
+    pub fn take_new_warnings(&self) -> Vec<String> {
+        let warnings = self.warnings.lock().unwrap();
+        let mut reported = self.reported_warnings.lock().unwrap();
+        let new_warnings = warnings[*reported..].to_vec();
+        *reported = warnings.len();
+        new_warnings
+    }
+
+    pub fn get_slowest_modules(&self, n: usize) -> BTreeMap<String, Vec<ModuleTimingItem>> {
+        let module_timings = self.module_timings.lock().unwrap();
+        module_timings
+            .iter()
+            .map(|(stage, timings)| {
+                let mut timings = timings.iter().collect::<Vec<_>>();
+                timings.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let items = timings
+                    .into_iter()
+                    .take(n)
+                    .map(|(id, duration)| ModuleTimingItem {
+                        id: id.clone(),
+                        time: duration.as_secs_f64() * 1000.0,
+                    })
+                    .collect();
+                (stage.clone(), items)
+            })
+            .collect()
+    }
+}
+
+fn module_timing_enabled(context: &Context) -> bool {
+    context
+        .config
+        .stats
+        .as_ref()
+        .is_some_and(|stats| stats.slowest_modules > 0)
+}
+
+// run f and record its duration as the `stage` timing of the module
+// when stats.slowestModules is enabled
+pub fn time_module_stage<T>(
+    context: &Context,
+    stage: &str,
+    module_id: &str,
+    f: impl FnOnce() -> T,
+) -> T {
+    if !module_timing_enabled(context) {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    context
+        .stats_info
+        .add_module_timing(stage, module_id, start.elapsed());
+    result
+}
+
+// sums the durations of several runs into one stage timing of the module, e.g. all visitors
+// of the transform pipeline are reported as `transform:visitors`
+pub struct ModuleStageTimer<'a> {
+    context: &'a Context,
+    module_id: &'a str,
+    enabled: bool,
+    durations: BTreeMap<&'static str, Duration>,
+}
+
+impl<'a> ModuleStageTimer<'a> {
+    pub fn new(context: &'a Context, module_id: &'a str) -> Self {
+        Self {
+            context,
+            module_id,
+            enabled: module_timing_enabled(context),
+            durations: BTreeMap::new(),
+        }
+    }
+
+    pub fn time<T>(&mut self, stage: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        *self.durations.entry(stage).or_default() += start.elapsed();
+        result
+    }
+
+    pub fn finish(self) {
+        for (stage, duration) in self.durations {
+            self.context
+                .stats_info
+                .add_module_timing(stage, self.module_id, duration);
+        }
+    }
 }
 
 impl Default for StatsInfo {
@@ -224,6 +563,46 @@ pub struct StatsJsonMap {
     rsc_client_components: Vec<RscClientInfo>,
     #[serde(rename = "rscCSSModules")]
     rsc_css_modules: Vec<RscCssModules>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    slowest_modules: BTreeMap<String, Vec<ModuleTimingItem>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    symbol_sizes: Vec<SymbolSizeItem>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    side_effects_audit: Vec<SideEffectsAuditItem>,
+    // large groups of mutually dependent modules, which are worth refactoring since they can't
+    // be split or hoisted apart
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    large_scc_groups: Vec<SccGroupItem>,
+    chunk_groups: Vec<ChunkGroup>,
+    // entry name -> reason, entries skipped by entryConditions
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    skipped_entries: BTreeMap<String, String>,
+    // variables loaded by envFile
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env_file_vars: BTreeMap<String, String>,
+    // origin module -> mock module, substitutions made by mocks
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    mocked_modules: BTreeMap<String, String>,
+    // chunk name -> modules relative to root, chunks isolated by optimization.isolateLicenses
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    license_chunks: BTreeMap<String, Vec<String>>,
+    // browser target -> downleveled code and polyfills only required by it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    polyfill_report: BTreeMap<String, TargetSaving>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+// what's changed since the last stats, emitted on rebuilds when stats.incremental is enabled
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsJsonDelta {
+    hash: u64,
+    built_at: u128,
+    changed_files: Vec<String>,
+    removed_files: Vec<String>,
+    changed_assets: Vec<StatsJsonAssetsItem>,
+    warnings: Vec<String>,
 }
 
 impl StatsJsonMap {
@@ -241,6 +620,17 @@ impl StatsJsonMap {
             entrypoints: HashMap::new(),
             rsc_client_components: vec![],
             rsc_css_modules: vec![],
+            slowest_modules: BTreeMap::new(),
+            symbol_sizes: vec![],
+            side_effects_audit: vec![],
+            large_scc_groups: vec![],
+            chunk_groups: vec![],
+            skipped_entries: BTreeMap::new(),
+            env_file_vars: BTreeMap::new(),
+            mocked_modules: BTreeMap::new(),
+            license_chunks: BTreeMap::new(),
+            polyfill_report: BTreeMap::new(),
+            warnings: vec![],
         }
     }
 }
@@ -298,6 +688,8 @@ pub fn create_stats_info(compile_time: u128, compiler: &Compiler) -> StatsJsonMa
         })
         .collect();
 
+    stats_map.chunk_groups = compiler.chunk_groups();
+
     let chunk_graph = compiler.context.chunk_graph.read().unwrap();
     let module_graph = compiler.context.module_graph.read().unwrap();
     let chunks = chunk_graph.get_chunks();
@@ -331,8 +723,11 @@ pub fn create_stats_info(compile_time: u128, compiler: &Compiler) -> StatsJsonMa
                         module_type: StatsJsonType::Module("module".to_string()),
                         size,
                         id,
-                        // TODO: 现在是从每个 chunk 中找到包含的 module, 所以 chunk_id 是单个, 但是一个 module 有可能存在于多个 chunk 中
-                        chunks: vec![chunk.id.id.clone()],
+                        // a module may be in multiple chunks
+                        chunks: match module_graph.chunk_ids(module) {
+                            [] => vec![chunk.id.id.clone()],
+                            chunk_ids => chunk_ids.to_vec(),
+                        },
                     };
                     modules_vec.borrow_mut().push(module.clone());
                     module
@@ -409,14 +804,12 @@ pub fn create_stats_info(compile_time: u128, compiler: &Compiler) -> StatsJsonMa
         .iter()
         .filter_map(|chunk| match &chunk.chunk_type {
             ChunkType::Entry(_, name, _) => {
-                let mut chunks = chunk_graph
-                    .entry_dependencies_chunk(&chunk.id)
+                let chunks = chunk_graph
+                    .entry_chunk_group(&chunk.id)
                     .into_iter()
                     .map(|id| id.id)
                     .collect::<Vec<_>>();
 
-                chunks.push(chunk.id.id.clone());
-
                 Some((
                     name.clone(),
                     StatsJsonEntryItem {
@@ -435,6 +828,52 @@ pub fn create_stats_info(compile_time: u128, compiler: &Compiler) -> StatsJsonMa
     stats_map.modules = stats_info.get_modules();
     stats_map.rsc_client_components = stats_info.get_rsc_client_components();
     stats_map.rsc_css_modules = stats_info.get_rsc_css_modules();
+    if let Some(stats) = &context.config.stats
+        && stats.slowest_modules > 0
+    {
+        stats_map.slowest_modules = stats_info.get_slowest_modules(stats.slowest_modules);
+    }
+    stats_map.symbol_sizes = stats_info.get_symbol_sizes();
+    stats_map.side_effects_audit = stats_info.get_side_effects_audit();
+    stats_map.mocked_modules = stats_info.get_mocked_modules();
+    stats_map.polyfill_report = stats_info.get_target_savings();
+    if let Some(optimize_infos) = context.optimize_infos.lock().unwrap().as_ref() {
+        stats_map.license_chunks = optimize_infos
+            .iter()
+            .filter(|info| {
+                !info.group_options.licenses.is_empty() && !info.module_to_chunks.is_empty()
+            })
+            .map(|info| {
+                let modules = info
+                    .module_to_chunks
+                    .keys()
+                    .map(|module_id| {
+                        diff_paths(&module_id.id, &context.root)
+                            .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string())
+                    })
+                    .collect::<Vec<_>>();
+                (info.group_options.name.clone(), modules)
+            })
+            .collect();
+    }
+    stats_map.large_scc_groups = module_graph
+        .scc_groups()
+        .into_iter()
+        .take_while(|group| group.len() >= LARGE_SCC_GROUP_SIZE)
+        .map(|group| SccGroupItem {
+            size: group.len(),
+            modules: group
+                .iter()
+                .map(|module_id| {
+                    diff_paths(&module_id.id, &context.root)
+                        .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string())
+                })
+                .collect(),
+        })
+        .collect();
+    stats_map.skipped_entries = context.config.skipped_entries.clone();
+    stats_map.env_file_vars = context.config.env_file_vars.clone();
+    stats_map.warnings = stats_info.get_warnings();
 
     stats_map
 }
@@ -443,6 +882,49 @@ pub fn write_stats(stats: &StatsJsonMap, compiler: &Compiler) {
     let path = &compiler.context.config.output.path.join("stats.json");
     let stats_json = serde_json::to_string_pretty(stats).unwrap();
     fs::write(path, stats_json).unwrap();
+    // all warnings are in the full stats
+    compiler.context.stats_info.take_new_warnings();
+}
+
+pub fn create_stats_delta(hash: u64, compiler: &Compiler) -> StatsJsonDelta {
+    let stats_info = &compiler.context.stats_info;
+    let ChunkChanges { changed, removed } = stats_info.get_chunk_changes();
+    let changed_names = changed.iter().collect::<HashSet<_>>();
+    // source maps are changed with their chunks
+    let changed_assets = stats_info
+        .get_assets()
+        .into_iter()
+        .filter(|asset| {
+            changed_names.contains(&asset.hashname)
+                || asset
+                    .hashname
+                    .strip_suffix(".map")
+                    .is_some_and(|name| changed_names.contains(&name.to_string()))
+        })
+        .map(|asset| StatsJsonAssetsItem {
+            assets_type: StatsJsonType::Asset(asset.assets_type),
+            size: asset.size,
+            name: asset.hashname,
+            path: asset.path,
+        })
+        .collect();
+    StatsJsonDelta {
+        hash,
+        built_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis(),
+        changed_files: changed,
+        removed_files: removed,
+        changed_assets,
+        warnings: stats_info.take_new_warnings(),
+    }
+}
+
+pub fn write_stats_delta(delta: &StatsJsonDelta, compiler: &Compiler) {
+    let path = &compiler.context.config.output.path.join("stats.delta.json");
+    let stats_json = serde_json::to_string_pretty(delta).unwrap();
+    fs::write(path, stats_json).unwrap();
 }
 
 // 文件大小转换
@@ -568,9 +1050,152 @@ pub fn print_stats(compiler: &Compiler) {
     }
 
     println!("{}", s.trim_end_matches('\n'));
+
+    if let Some(stats) = &compiler.context.config.stats
+        && stats.slowest_modules > 0
+    {
+        print_slowest_modules(
+            &compiler
+                .context
+                .stats_info
+                .get_slowest_modules(stats.slowest_modules),
+            abs_path,
+        );
+    }
+
+    let symbol_sizes = compiler.context.stats_info.get_symbol_sizes();
+    if !symbol_sizes.is_empty() {
+        print_symbol_sizes(&symbol_sizes);
+    }
+
+    let side_effects_audit = compiler.context.stats_info.get_side_effects_audit();
+    if !side_effects_audit.is_empty() {
+        print_side_effects_audit(&side_effects_audit);
+    }
+}
+
+// the largest n symbols are printed, the full list is in stats.json and report.html
+const PRINT_SYMBOL_SIZES_LIMIT: usize = 20;
+
+fn print_symbol_sizes(symbol_sizes: &[SymbolSizeItem]) {
+    let mut s = format!("\n{}\n", "Largest exported symbols:".bold());
+    for item in symbol_sizes.iter().take(PRINT_SYMBOL_SIZES_LIMIT) {
+        let symbol = if item.module.is_empty() {
+            item.symbol.clone()
+        } else {
+            format!("{}#{}", item.module, item.symbol)
+        };
+        s.push_str(
+            format!(
+                "{} {}\n",
+                format!("{:>12}", human_readable_size(item.size)).yellow(),
+                symbol.truecolor(128, 128, 128)
+            )
+            .as_str(),
+        );
+    }
+    println!("{}", s.trim_end_matches('\n'));
+}
+
+fn print_side_effects_audit(items: &[SideEffectsAuditItem]) {
+    let mut s = format!(
+        "\n{}\n",
+        "Unused exports retained for possible side effects:".bold()
+    );
+    for item in items {
+        s.push_str(
+            format!(
+                "{} {}\n",
+                format!("{}#{}", item.module, item.symbol).yellow(),
+                item.reason.truecolor(128, 128, 128)
+            )
+            .as_str(),
+        );
+    }
+    println!("{}", s.trim_end_matches('\n'));
+}
+
+fn print_slowest_modules(slowest_modules: &BTreeMap<String, Vec<ModuleTimingItem>>, root: &Path) {
+    let mut s = String::new();
+    for (stage, items) in slowest_modules {
+        s.push_str(format!("\n{}\n", format!("Slowest modules in {}:", stage).bold()).as_str());
+        for item in items {
+            let id = diff_paths(&item.id, root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| item.id.clone());
+            s.push_str(
+                format!(
+                    "{} {}\n",
+                    format!("{:>12}", format!("{:.2}ms", item.time)).yellow(),
+                    id.truecolor(128, 128, 128)
+                )
+                .as_str(),
+            );
+        }
+    }
+    println!("{}", s.trim_end_matches('\n'));
 }
 
 fn file_size(path: &str) -> Result<u64> {
     let metadata = fs::metadata(path)?;
     Ok(metadata.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::StatsInfo;
+    use crate::compiler::Context;
+    use crate::config::ModuleIdStrategy;
+    use crate::module::ResolveType;
+    use crate::utils::test_helper::create_module_graph;
+
+    #[test]
+    fn test_add_grouped_warning_once() {
+        let stats_info = StatsInfo::new();
+        stats_info.add_grouped_warning_once("a".to_string(), "x".to_string());
+        stats_info.flush_grouped_warnings(false);
+        // rebuilds fire the same occurrence again
+        stats_info.add_grouped_warning_once("a".to_string(), "x".to_string());
+        stats_info.add_grouped_warning_once("a".to_string(), "y".to_string());
+        stats_info.flush_grouped_warnings(false);
+        assert_eq!(stats_info.get_warnings(), vec!["a (x)", "a (y)"]);
+    }
+
+    #[test]
+    fn test_set_module_metadata() {
+        let mut context = Context {
+            root: PathBuf::from("/root"),
+            module_graph: create_module_graph(
+                "/root/src/index.ts",
+                &[("/root/src/index.ts", "/root/src/a.ts", ResolveType::Import)],
+            )
+            .into(),
+            ..Default::default()
+        };
+        context.config.module_id_strategy = ModuleIdStrategy::Named;
+        let context = Arc::new(context);
+        // what a plugin does in transform_js with the path of the module
+        context
+            .stats_info
+            .set_module_metadata("/root/src/a.ts", "routes", &json!({ "path": "/a" }))
+            .unwrap();
+        // rebuilds replace the metadata of the same namespace
+        context
+            .stats_info
+            .set_module_metadata("/root/src/a.ts", "routes", &json!({ "path": "/b" }))
+            .unwrap();
+        context.stats_info.parse_modules(context.clone());
+
+        let modules = serde_json::to_value(context.stats_info.get_modules()).unwrap();
+        assert_eq!(
+            modules["src/a.ts"]["metadata"],
+            json!({ "routes": { "path": "/b" } })
+        );
+        assert!(modules["src/index.ts"].get("metadata").is_none());
+    }
+}
@@ -0,0 +1,61 @@
+//! Utilities to unit test visitors and plugins, the same way mako's own visitors are tested.
+//! Enabled by the `test-utils` feature, e.g. in `dev-dependencies`:
+//!
+//! ```toml
+//! mako = { version = "*", features = ["test-utils"] }
+//! ```
+//!
+//! ```rust,ignore
+//! use mako::testing::TestUtils;
+//!
+//! let mut test_utils = TestUtils::gen_js_ast("foo.bar()");
+//! let unresolved_mark = test_utils.ast.js().unresolved_mark;
+//! let code = test_utils
+//!     .transform_js(&mut MyVisitor::new(unresolved_mark))
+//!     .js_ast_to_code();
+//! assert_eq!(code, "foo.baz();");
+//! ```
+//!
+//! Projects can be compiled and compared with the expected output by `FixtureRunner`, see
+//! `fixture` for the directory conventions.
+//!
+//! Items re-exported here follow semver of the crate, changes of them are breaking changes,
+//! while the modules they are defined in are internal.
+
+pub mod fixture;
+
+pub use fixture::FixtureRunner;
+
+pub use crate::ast::css_ast::CssAst;
+pub use crate::ast::js_ast::JsAst;
+pub use crate::ast::tests::{TestAst, TestUtils, TestUtilsOpts};
+pub use crate::module::{Module, ModuleId};
+// build the project in `base` (relative to cwd) in production mode, and get modules of it
+pub use crate::utils::test_helper::{get_module, setup_compiler};
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::ast::Ident;
+    use swc_core::ecma::visit::VisitMut;
+
+    use super::TestUtils;
+
+    struct Rename;
+
+    impl VisitMut for Rename {
+        fn visit_mut_ident(&mut self, ident: &mut Ident) {
+            if &*ident.sym == "foo" {
+                ident.sym = "bar".into();
+            }
+        }
+    }
+
+    #[test]
+    fn test_transform_js() {
+        let mut test_utils = TestUtils::gen_js_ast("foo()");
+        assert_eq!(
+            test_utils.transform_js(&mut Rename).js_ast_to_code(),
+            "bar();"
+        );
+    }
+}
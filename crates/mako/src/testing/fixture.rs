@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+
+use crate::compiler::{Args, Compiler};
+use crate::config::Config;
+
+const EXPECTED_DIR: &str = "expected";
+const UPDATE_ENV: &str = "UPDATE_SNAPSHOT";
+
+/**
+ * run fixtures of projects and compare the emitted files with the expected ones
+ *
+ * fixtures/
+ *   basic/
+ *     mako.config.json    optional, config of the fixture
+ *     src/index.ts
+ *     expected/           files expected in the output directory
+ *       index.js
+ *
+ * with `UPDATE_SNAPSHOT=1`, `expected/` is rewritten with the emitted files instead,
+ * otherwise fixtures without `expected/` fail, so that missing snapshots can't pass silently
+ */
+pub struct FixtureRunner {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl FixtureRunner {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            update: std::env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0"),
+        }
+    }
+
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    // run all fixtures in the directory, errors of all failed fixtures are reported together
+    pub fn run(&self) -> Result<()> {
+        let mut names = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        let errors = names
+            .iter()
+            .filter_map(|name| {
+                self.run_fixture(name)
+                    .err()
+                    .map(|e| format!("fixture {} failed:\n{}", name, e))
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(errors.join("\n\n")))
+        }
+    }
+
+    pub fn run_fixture(&self, name: &str) -> Result<()> {
+        let root = self.dir.join(name).canonicalize()?;
+        let config =
+            Config::new(&root, None, None).map_err(|e| anyhow!("Load config failed: {}", e))?;
+        let compiler = Compiler::new(
+            config,
+            root.clone(),
+            Args {
+                watch: false,
+                verbose: false,
+            },
+            None,
+        )?;
+        compiler.compile()?;
+
+        let dist = compiler.context.config.output.path.clone();
+        let expected = root.join(EXPECTED_DIR);
+        if self.update {
+            if expected.exists() {
+                fs::remove_dir_all(&expected)?;
+            }
+            for file in list_files(&dist)? {
+                let to = expected.join(&file);
+                fs::create_dir_all(to.parent().unwrap())?;
+                fs::copy(dist.join(&file), to)?;
+            }
+            return Ok(());
+        }
+        if !expected.exists() {
+            return Err(anyhow!(
+                "{} doesn't exist, run with {}=1 to create the snapshots",
+                expected.display(),
+                UPDATE_ENV
+            ));
+        }
+        compare_dirs(&expected, &dist)
+    }
+}
+
+fn compare_dirs(expected: &Path, actual: &Path) -> Result<()> {
+    let expected_files = list_files(expected)?;
+    let actual_files = list_files(actual)?;
+    let mut errors = vec![];
+    for file in &expected_files {
+        if !actual_files.contains(file) {
+            errors.push(format!("missing {}", file.display()));
+            continue;
+        }
+        let expected_content = fs::read(expected.join(file))?;
+        let actual_content = fs::read(actual.join(file))?;
+        if expected_content != actual_content {
+            errors.push(format!(
+                "{} is changed\n{}",
+                file.display(),
+                diff(
+                    &String::from_utf8_lossy(&expected_content),
+                    &String::from_utf8_lossy(&actual_content)
+                )
+            ));
+        }
+    }
+    for file in &actual_files {
+        if !expected_files.contains(file) {
+            errors.push(format!("unexpected {}", file.display()));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        errors.push(format!("run with {}=1 to update the snapshots", UPDATE_ENV));
+        Err(anyhow!(errors.join("\n")))
+    }
+}
+
+// the first different line, which is enough to locate the change
+fn diff(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                return format!(
+                    "  line {}\n  - {}\n  + {}",
+                    line,
+                    e.unwrap_or("<eof>"),
+                    a.unwrap_or("<eof>")
+                );
+            }
+        }
+    }
+}
+
+// paths of files relative to the directory, sorted
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    fn walk(dir: &Path, base: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(&path, base, files)?;
+            } else {
+                files.push(path.strip_prefix(base)?.to_path_buf());
+            }
+        }
+        Ok(())
+    }
+    let mut files = vec![];
+    if dir.exists() {
+        walk(dir, dir, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{diff, FixtureRunner};
+    use crate::utils::test_helper::create_temp_dir;
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("a\nb\nc", "a\nd\nc"), "  line 2\n  - b\n  + d");
+        assert_eq!(diff("a", "a\nb"), "  line 2\n  - <eof>\n  + b");
+    }
+
+    #[test]
+    fn test_fixture_runner() {
+        let temp_dir = create_temp_dir("fixture_runner");
+        let dir = temp_dir.path();
+        let fixture = dir.join("basic");
+        fs::create_dir_all(fixture.join("src")).unwrap();
+        fs::write(fixture.join("src/index.js"), "console.log(1);").unwrap();
+        fs::write(
+            fixture.join("mako.config.json"),
+            r#"{ "entry": { "index": "./src/index.js" }, "devtool": false }"#,
+        )
+        .unwrap();
+
+        // missing snapshots fail unless they are updated
+        let err = FixtureRunner::new(dir).update(false).run().unwrap_err();
+        assert!(err.to_string().contains("UPDATE_SNAPSHOT=1"));
+        assert!(!fixture.join("expected").exists());
+        FixtureRunner::new(dir).update(true).run().unwrap();
+        assert!(fixture.join("expected/index.js").exists());
+        FixtureRunner::new(dir).update(false).run().unwrap();
+
+        fs::write(fixture.join("src/index.js"), "console.log(2);").unwrap();
+        let err = FixtureRunner::new(dir).update(false).run().unwrap_err();
+        assert!(err.to_string().contains("index.js is changed"));
+
+        FixtureRunner::new(dir).update(true).run().unwrap();
+        FixtureRunner::new(dir).update(false).run().unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 pub mod logger;
 #[cfg(feature = "profile")]
 pub mod profile_gui;
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub(crate) mod test_helper;
 pub(crate) mod thread_pool;
 pub mod tokio_runtime;
@@ -19,6 +19,22 @@ pub fn base64_decode(bytes: &[u8]) -> Vec<u8> {
     general_purpose::STANDARD.decode(bytes).unwrap()
 }
 
+// the salt is prepended with a `\0` separator, so that different salts and contents can't
+// concatenate to the same input, hashes are unchanged with an empty salt
+pub fn consume_salt(context: &mut md5::Context, salt: &str) {
+    if !salt.is_empty() {
+        context.consume(salt);
+        context.consume([0u8]);
+    }
+}
+
+pub fn salted_md5<T: AsRef<[u8]>>(content: T, salt: &str) -> md5::Digest {
+    let mut context = md5::Context::new();
+    consume_salt(&mut context, salt);
+    context.consume(content);
+    context.compute()
+}
+
 pub trait ParseRegex {
     fn parse_into_regex(&self) -> Result<Option<Regex>>;
 }
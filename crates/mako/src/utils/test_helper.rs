@@ -4,7 +4,67 @@ use tracing_subscriber::{fmt, EnvFilter};
 
 use crate::compiler::{self, Compiler};
 use crate::config::{Config, Mode};
+#[cfg(test)]
+use crate::module::{Dependency, ResolveType};
 use crate::module::{Module, ModuleId};
+#[cfg(test)]
+use crate::module_graph::ModuleGraph;
+
+// scratch dirs of tests, which are unique across test threads and removed when dropped, even
+// if assertions fail
+#[cfg(test)]
+pub fn create_temp_dir(name: &str) -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix(&format!("mako_{}_", name))
+        .tempdir()
+        .unwrap()
+}
+
+#[cfg(test)]
+pub fn module_id(id: &str) -> ModuleId {
+    ModuleId::from(id)
+}
+
+// dependency of module graph fixtures, without specifiers and span
+#[cfg(test)]
+pub fn dependency(source: &str, resolve_type: ResolveType, order: usize) -> Dependency {
+    Dependency {
+        source: source.to_string(),
+        resolve_as: None,
+        specifiers: vec![],
+        resolve_type,
+        order,
+        span: None,
+    }
+}
+
+/**
+ * module graph fixture of edges `(from, to, resolve_type)`, `entry` is added as the entry and
+ * other modules are added the first time they appear, dependencies are sourced by the id of
+ * `to` and ordered by the edges from the same module
+ */
+#[cfg(test)]
+pub fn create_module_graph(entry: &str, edges: &[(&str, &str, ResolveType)]) -> ModuleGraph {
+    let mut module_graph = ModuleGraph::new();
+    module_graph.add_module(Module::new(ModuleId::from(entry), true, None));
+    add_module_edges(&mut module_graph, edges);
+    module_graph
+}
+
+// add edges to a module graph fixture, see create_module_graph
+#[cfg(test)]
+pub fn add_module_edges(module_graph: &mut ModuleGraph, edges: &[(&str, &str, ResolveType)]) {
+    for (from, to, resolve_type) in edges {
+        let (from, to) = (ModuleId::from(*from), ModuleId::from(*to));
+        for id in [&from, &to] {
+            if !module_graph.has_module(id) {
+                module_graph.add_module(Module::new(id.clone(), false, None));
+            }
+        }
+        let order = module_graph.get_dependencies(&from).len();
+        module_graph.add_dependency(&from, &to, dependency(&to.id, *resolve_type, order));
+    }
+}
 
 #[macro_export]
 macro_rules! assert_display_snapshot {
@@ -8,6 +8,8 @@ static TOKIO_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
 fn build_tokio_runtime() -> tokio::runtime::Runtime {
     tokio::runtime::Builder::new_multi_thread()
         .enable_io()
+        // the connection pool of hyper clients needs timers
+        .enable_time()
         .worker_threads(2)
         .thread_name("tokio-worker")
         .build()
@@ -28,3 +30,14 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
         .get_or_init(build_tokio_runtime)
         .block_on(future)
 }
+
+// block on the future with the shared runtime, in or out of it, e.g. the builds of the cli
+// run inside block_on, where nested runtimes panic
+pub fn block_in_place<F: Future>(future: F) -> F::Output {
+    let runtime = TOKIO_RUNTIME.get_or_init(build_tokio_runtime);
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::task::block_in_place(|| runtime.block_on(future))
+    } else {
+        runtime.block_on(future)
+    }
+}
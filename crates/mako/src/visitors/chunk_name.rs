@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use swc_core::common::comments::Comments as CommentsTrait;
+use swc_core::ecma::ast::{CallExpr, Expr, ExprOrSpread, Lit};
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use crate::ast::comments::MakoComments;
+use crate::ast::utils::is_dynamic_import;
+
+/**
+ * Collect chunk names of dynamic imports from the leading magic comments of their sources.
+ *
+ * e.g.
+ * import(/* makoChunkName: "home" */ './pages/home');
+ * ->
+ * { "./pages/home": "home" }
+ *
+ * `webpackChunkName` is also supported for compatibility.
+ */
+pub struct ChunkNameCollector<'a> {
+    comments: &'a MakoComments,
+    // source -> chunk name
+    pub chunk_names: HashMap<String, String>,
+    regex: Regex,
+}
+
+impl<'a> ChunkNameCollector<'a> {
+    pub fn new(comments: &'a MakoComments) -> Self {
+        Self {
+            comments,
+            chunk_names: HashMap::new(),
+            regex: Regex::new(r#"(?:mako|webpack)ChunkName\s*:\s*["']([^"']+)["']"#).unwrap(),
+        }
+    }
+}
+
+impl Visit for ChunkNameCollector<'_> {
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        if is_dynamic_import(call_expr)
+            && let Some(ExprOrSpread {
+                expr: box Expr::Lit(Lit::Str(source)),
+                ..
+            }) = call_expr.args.first()
+            && let Some(comments) = self.comments.get_leading(source.span.lo)
+        {
+            let name = comments
+                .iter()
+                .find_map(|c| self.regex.captures(&c.text).map(|caps| caps[1].to_string()));
+            if let Some(name) = name.and_then(|name| sanitize_chunk_name(&name)) {
+                self.chunk_names.insert(source.value.to_string(), name);
+            }
+        }
+        call_expr.visit_children_with(self);
+    }
+}
+
+// chunk names are used in file names, keep `/` for sub directories, but never escape the
+// output dir with absolute paths or empty segments, names which are empty then are ignored
+pub fn sanitize_chunk_name(name: &str) -> Option<String> {
+    let name = name
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitWith;
+
+    use super::{sanitize_chunk_name, ChunkNameCollector};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_magic_comments() {
+        let chunk_names = run(r#"
+import(/* makoChunkName: "home" */ './home');
+import(/* webpackChunkName: 'about page' */ './about');
+import(/* other comment */ './other');
+import('./none');
+        "#);
+        assert_eq!(
+            chunk_names,
+            HashMap::from([
+                ("./home".to_string(), "home".to_string()),
+                ("./about".to_string(), "about_page".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sanitize_chunk_name() {
+        assert_eq!(
+            sanitize_chunk_name("pages/home"),
+            Some("pages/home".to_string())
+        );
+        assert_eq!(
+            sanitize_chunk_name("/pages//a b/"),
+            Some("pages/a_b".to_string())
+        );
+        assert_eq!(sanitize_chunk_name("../a"), Some("__/a".to_string()));
+        assert_eq!(sanitize_chunk_name("//"), None);
+    }
+
+    fn run(js_code: &str) -> HashMap<String, String> {
+        let test_utils = TestUtils::gen_js_ast(js_code);
+        let comments = test_utils
+            .context
+            .meta
+            .script
+            .origin_comments
+            .read()
+            .unwrap();
+        let mut collector = ChunkNameCollector::new(comments.get_swc_comments());
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            test_utils.ast.js().ast.visit_with(&mut collector);
+        });
+        collector.chunk_names
+    }
+}
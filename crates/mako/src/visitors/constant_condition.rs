@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+
+use swc_core::common::util::take::Take;
+use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::ecma::ast::{
+    BinExpr, BinaryOp, BlockStmt, CondExpr, Expr, Id, Ident, IfStmt, ImportDecl,
+    ImportDefaultSpecifier, ImportNamedSpecifier, ImportSpecifier, ImportStarAsSpecifier, Lit,
+    Module, ModuleDecl, ModuleItem, ParenExpr, Stmt, UnaryExpr, UnaryOp, VarDecl, VarDeclKind,
+    VarDeclarator,
+};
+use swc_core::ecma::atoms::{js_word, JsWord};
+use swc_core::ecma::utils::extract_var_ids;
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+/**
+ * Strip the dead branches of conditions that became literals after EnvReplacer, and the
+ * imports only used by them, so that dev-only code never reaches the bundle.
+ *
+ * e.g.
+ * import devtools from 'devtools';
+ * if ("production" === "development") { devtools(); }
+ * ->
+ * (empty)
+ *
+ * `if`, `?:`, `&&` and `||` are handled, conditions are only evaluated when they consist of
+ * literals, so that no side effects are dropped. `var` declarations in removed branches are
+ * kept since they are hoisted.
+ */
+pub struct ConstantCondition {
+    unresolved_mark: Mark,
+    // identifiers referenced in removed branches
+    removed_refs: HashSet<Id>,
+}
+
+impl ConstantCondition {
+    pub fn new(unresolved_mark: Mark) -> Self {
+        Self {
+            unresolved_mark,
+            removed_refs: HashSet::new(),
+        }
+    }
+
+    fn remove<T: VisitWith<IdentCollector>>(&mut self, node: &T) {
+        let mut collector = IdentCollector::default();
+        node.visit_with(&mut collector);
+        self.removed_refs.extend(collector.ids);
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<Literal> {
+        match expr {
+            Expr::Lit(Lit::Str(s)) => Some(Literal::Str(s.value.clone())),
+            Expr::Lit(Lit::Num(n)) => Some(Literal::Num(n.value)),
+            Expr::Lit(Lit::Bool(b)) => Some(Literal::Bool(b.value)),
+            Expr::Lit(Lit::Null(_)) => Some(Literal::Null),
+            Expr::Ident(Ident {
+                sym: js_word!("undefined"),
+                span,
+                ..
+            }) if span.ctxt.outer() == self.unresolved_mark => Some(Literal::Undefined),
+            Expr::Paren(ParenExpr { expr, .. }) => self.eval(expr),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Bang,
+                arg,
+                ..
+            }) => Some(Literal::Bool(!self.eval(arg)?.truthy())),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Void,
+                arg,
+                ..
+            }) => self.eval(arg).map(|_| Literal::Undefined),
+            Expr::Bin(BinExpr {
+                op, left, right, ..
+            }) => match op {
+                BinaryOp::LogicalAnd => {
+                    let left = self.eval(left)?;
+                    if left.truthy() {
+                        self.eval(right)
+                    } else {
+                        Some(left)
+                    }
+                }
+                BinaryOp::LogicalOr => {
+                    let left = self.eval(left)?;
+                    if left.truthy() {
+                        Some(left)
+                    } else {
+                        self.eval(right)
+                    }
+                }
+                BinaryOp::EqEqEq => Some(Literal::Bool(self.eval(left)? == self.eval(right)?)),
+                BinaryOp::NotEqEq => Some(Literal::Bool(self.eval(left)? != self.eval(right)?)),
+                BinaryOp::EqEq => self
+                    .eval(left)?
+                    .loose_eq(&self.eval(right)?)
+                    .map(Literal::Bool),
+                BinaryOp::NotEq => self
+                    .eval(left)?
+                    .loose_eq(&self.eval(right)?)
+                    .map(|eq| Literal::Bool(!eq)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for ConstantCondition {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        module.visit_mut_children_with(self);
+        if self.removed_refs.is_empty() {
+            return;
+        }
+
+        // references outside of import declarations
+        let mut collector = IdentCollector::default();
+        module.visit_with(&mut collector);
+        let used = collector.ids;
+
+        module.body.retain_mut(|item| {
+            let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+                return true;
+            };
+            if import.specifiers.is_empty() {
+                return true;
+            }
+            import.specifiers.retain(|specifier| {
+                let id = match specifier {
+                    ImportSpecifier::Named(ImportNamedSpecifier { local, .. })
+                    | ImportSpecifier::Default(ImportDefaultSpecifier { local, .. })
+                    | ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => {
+                        local.to_id()
+                    }
+                };
+                used.contains(&id) || !self.removed_refs.contains(&id)
+            });
+            !import.specifiers.is_empty()
+        });
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        // drop the statements emptied by removed branches
+        items.retain(|item| !matches!(item, ModuleItem::Stmt(Stmt::Empty(_))));
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+        stmts.retain(|stmt| !matches!(stmt, Stmt::Empty(_)));
+    }
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+
+        if let Stmt::If(IfStmt {
+            test, cons, alt, ..
+        }) = stmt
+            && let Some(value) = self.eval(test)
+        {
+            let (kept, removed) = if value.truthy() {
+                (Some(cons.take()), alt.take())
+            } else {
+                (alt.take(), Some(cons.take()))
+            };
+            let mut stmts = vec![];
+            if let Some(removed) = removed {
+                self.remove(&*removed);
+                let var_ids: Vec<Ident> = extract_var_ids(&*removed);
+                if !var_ids.is_empty() {
+                    stmts.push(
+                        VarDecl {
+                            span: DUMMY_SP,
+                            kind: VarDeclKind::Var,
+                            declare: false,
+                            decls: var_ids
+                                .into_iter()
+                                .map(|id| VarDeclarator {
+                                    span: DUMMY_SP,
+                                    name: id.into(),
+                                    init: None,
+                                    definite: false,
+                                })
+                                .collect(),
+                        }
+                        .into(),
+                    );
+                }
+            }
+            if let Some(kept) = kept {
+                stmts.push(*kept);
+            }
+            *stmt = match stmts.len() {
+                0 => Stmt::dummy(),
+                1 => stmts.pop().unwrap(),
+                _ => Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts,
+                }),
+            };
+        }
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        match expr {
+            Expr::Cond(CondExpr {
+                test, cons, alt, ..
+            }) => {
+                if let Some(value) = self.eval(test) {
+                    let (kept, removed) = if value.truthy() {
+                        (cons.take(), alt)
+                    } else {
+                        (alt.take(), cons)
+                    };
+                    self.remove(&**removed);
+                    *expr = *kept;
+                }
+            }
+            Expr::Bin(BinExpr {
+                op: op @ (BinaryOp::LogicalAnd | BinaryOp::LogicalOr),
+                left,
+                right,
+                ..
+            }) => {
+                if let Some(value) = self.eval(left) {
+                    // `true && x` -> x, `false && x` -> false
+                    // `true || x` -> true, `false || x` -> x
+                    let keep_left = value.truthy() == (*op == BinaryOp::LogicalOr);
+                    if keep_left {
+                        self.remove(&**right);
+                        *expr = *left.take();
+                    } else {
+                        *expr = *right.take();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Literal {
+    Str(JsWord),
+    Num(f64),
+    Bool(bool),
+    Null,
+    Undefined,
+}
+
+impl Literal {
+    fn truthy(&self) -> bool {
+        match self {
+            Literal::Str(s) => !s.is_empty(),
+            Literal::Num(n) => *n != 0.0 && !n.is_nan(),
+            Literal::Bool(b) => *b,
+            Literal::Null | Literal::Undefined => false,
+        }
+    }
+
+    // only the cases without type coercion are evaluated
+    fn loose_eq(&self, other: &Literal) -> Option<bool> {
+        match (self, other) {
+            (Literal::Null | Literal::Undefined, Literal::Null | Literal::Undefined) => Some(true),
+            (Literal::Null | Literal::Undefined, _) | (_, Literal::Null | Literal::Undefined) => {
+                Some(false)
+            }
+            (Literal::Str(_), Literal::Str(_))
+            | (Literal::Num(_), Literal::Num(_))
+            | (Literal::Bool(_), Literal::Bool(_)) => Some(self == other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct IdentCollector {
+    ids: HashSet<Id>,
+}
+
+impl Visit for IdentCollector {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.ids.insert(ident.to_id());
+    }
+
+    fn visit_import_decl(&mut self, _: &ImportDecl) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::ConstantCondition;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_if() {
+        assert_eq!(
+            run(r#"
+if ("production" === "development") { a(); } else { b(); }
+if ("production" !== "development") { c(); }
+if (!true) { d(); }
+            "#),
+            r#"
+{
+    b();
+}
+{
+    c();
+}
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_cond_and_logical() {
+        assert_eq!(
+            run(r#"log(false ? a : b, true && c, false && d, true || e, null || f, x && true);"#),
+            r#"log(b, c, false, true, f, x && true);"#
+        );
+    }
+
+    #[test]
+    fn test_keep_non_literal_conditions() {
+        assert_eq!(
+            run(r#"if (a === "development") { b(); }"#),
+            r#"if (a === "development") {
+    b();
+}"#
+        );
+    }
+
+    #[test]
+    fn test_keep_hoisted_var() {
+        assert_eq!(
+            run(r#"if (false) { var a = 1; } log(a);"#),
+            r#"var a;
+log(a);"#
+        );
+    }
+
+    #[test]
+    fn test_remove_unused_imports() {
+        assert_eq!(
+            run(r#"
+import devtools, { inspect } from 'devtools';
+import { keep } from 'keep';
+import 'side-effect';
+import unused from 'unused';
+if ("production" === "development") { devtools(inspect, keep); }
+keep();
+            "#),
+            r#"
+import { keep } from 'keep';
+import 'side-effect';
+import unused from 'unused';
+keep();
+            "#
+            .trim()
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = ConstantCondition::new(ast.unresolved_mark);
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
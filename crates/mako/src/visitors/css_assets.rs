@@ -35,6 +35,7 @@ impl VisitMut for CSSAssets {
         let dep = Dependency {
             source: url,
             resolve_as: None,
+            specifiers: vec![],
             resolve_type: ResolveType::Css,
             order: 0,
             span: None,
@@ -27,6 +27,7 @@ impl CSSDepAnalyzer {
         self.dependencies.push(Dependency {
             source: url,
             resolve_as: None,
+            specifiers: vec![],
             order: self.order,
             resolve_type: ResolveType::Css,
             span: None,
@@ -1,14 +1,18 @@
 use swc_core::common::{Mark, Span};
-use swc_core::ecma::ast::{CallExpr, Expr, Lit, ModuleDecl, NewExpr, Str};
+use swc_core::ecma::ast::{
+    CallExpr, ExportSpecifier, Expr, ImportDecl, ImportSpecifier, Lit, Module, ModuleDecl,
+    ModuleItem, NamedExport, NewExpr, Str,
+};
 use swc_core::ecma::visit::{Visit, VisitWith};
 
 use crate::ast::utils;
-use crate::module::{Dependency, ResolveType};
+use crate::module::{Dependency, ImportedSymbol, ResolveType};
 
 pub struct DepAnalyzer {
     pub dependencies: Vec<Dependency>,
     order: usize,
     unresolved_mark: Mark,
+    require_resolve: bool,
 }
 
 impl DepAnalyzer {
@@ -17,14 +21,32 @@ impl DepAnalyzer {
             dependencies: vec![],
             order: 1,
             unresolved_mark,
+            require_resolve: false,
         }
     }
+
+    pub fn with_require_resolve(mut self, require_resolve: bool) -> Self {
+        self.require_resolve = require_resolve;
+        self
+    }
+
     fn add_dependency(&mut self, source: String, resolve_type: ResolveType, span: Option<Span>) {
+        self.add_esm_dependency(source, resolve_type, vec![], span);
+    }
+
+    fn add_esm_dependency(
+        &mut self,
+        source: String,
+        resolve_type: ResolveType,
+        specifiers: Vec<ImportedSymbol>,
+        span: Option<Span>,
+    ) {
         self.dependencies.push(Dependency {
             source,
             resolve_as: None,
             order: self.order,
             resolve_type,
+            specifiers,
             span,
         });
         self.order += 1;
@@ -37,23 +59,28 @@ impl Visit for DepAnalyzer {
             // e.g.
             // import { a, b, c } from './module';
             ModuleDecl::Import(import) => {
-                if import.type_only {
+                if is_type_only_import(import) {
                     return;
                 }
                 let src = import.src.value.to_string();
-                self.add_dependency(
+                self.add_esm_dependency(
                     src,
                     ResolveType::Import(import.into()),
+                    ImportedSymbol::from_import(import),
                     Some(import.src.span),
                 );
             }
             // e.g.
             // export { a, b, c } from './module';
             ModuleDecl::ExportNamed(export) => {
+                if is_type_only_export(export) {
+                    return;
+                }
                 if let Some(src) = &export.src {
-                    self.add_dependency(
+                    self.add_esm_dependency(
                         src.value.to_string(),
                         ResolveType::ExportNamed(export.into()),
+                        ImportedSymbol::from_named_export(export),
                         Some(src.span),
                     );
                 }
@@ -82,6 +109,16 @@ impl Visit for DepAnalyzer {
             }
         }
         // e.g.
+        // require.resolve('a')
+        else if self.require_resolve
+            && utils::is_commonjs_require_resolve(expr, &self.unresolved_mark)
+        {
+            if let Some(src) = utils::get_first_str_arg(expr) {
+                self.add_dependency(src, ResolveType::Require, Some(expr.span));
+                return;
+            }
+        }
+        // e.g.
         // import('a')
         else if utils::is_dynamic_import(expr) {
             if let Some(src) = utils::get_first_str_arg(expr) {
@@ -103,6 +140,52 @@ impl Visit for DepAnalyzer {
     }
 }
 
+// type-only imports never create edges in module graph
+// e.g.
+// import type { A } from './a';
+// import { type A, type B } from './a';
+pub fn is_type_only_import(import: &ImportDecl) -> bool {
+    import.type_only
+        || (!import.specifiers.is_empty()
+            && import.specifiers.iter().all(
+                |specifier| matches!(specifier, ImportSpecifier::Named(named) if named.is_type_only),
+            ))
+}
+
+// e.g.
+// export type { A } from './a';
+// export { type A } from './a';
+pub fn is_type_only_export(export: &NamedExport) -> bool {
+    export.type_only
+        || (!export.specifiers.is_empty()
+            && export.specifiers.iter().all(
+                |specifier| matches!(specifier, ExportSpecifier::Named(named) if named.is_type_only),
+            ))
+}
+
+// sources of type-only imports and exports
+// they should be collected before typescript strip since they are removed by it
+pub fn collect_type_only_deps(module: &Module) -> Vec<String> {
+    let mut sources = module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) if is_type_only_import(import) => {
+                Some(import.src.value.to_string())
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export))
+                if is_type_only_export(export) =>
+            {
+                export.src.as_ref().map(|src| src.value.to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
 // get the value of url when the following conditions are met
 // notice: only add dependency when the second argument is import.meta.url
 // e.g.
@@ -153,7 +236,8 @@ mod tests {
     use swc_core::common::GLOBALS;
     use swc_core::ecma::visit::VisitWith;
 
-    use crate::ast::tests::TestUtils;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::module::ImportedSymbol;
 
     #[test]
     fn test_normal() {
@@ -172,6 +256,66 @@ mod tests {
         assert_eq!(run(r#"export * from "a";"#), vec!["a"]);
     }
 
+    #[test]
+    fn test_type_only() {
+        assert!(run_ts(r#"import type { A } from 'a';"#).is_empty());
+        assert!(run_ts(r#"import { type A, type B } from 'a';"#).is_empty());
+        assert!(run_ts(r#"export type { A } from 'a';"#).is_empty());
+        assert!(run_ts(r#"export { type A } from 'a';"#).is_empty());
+        assert_eq!(run_ts(r#"import { type A, B } from 'a';"#), vec!["a"]);
+        assert_eq!(run_ts(r#"export { type A, B } from 'a';"#), vec!["a"]);
+    }
+
+    #[test]
+    fn test_specifiers() {
+        assert_eq!(
+            run_specifiers(
+                r#"
+import a, { b as c, default as d, type E } from 'a';
+import * as f from 'f';
+import 'g';
+export { h as i, default, type J } from 'h';
+export * as k from 'k';
+export * from 'l';
+require('m');
+"#
+            ),
+            vec![
+                vec![
+                    ImportedSymbol::Default,
+                    ImportedSymbol::Named("b".to_string()),
+                    ImportedSymbol::Default,
+                ],
+                vec![ImportedSymbol::Namespace],
+                vec![],
+                vec![
+                    ImportedSymbol::Named("h".to_string()),
+                    ImportedSymbol::Default,
+                ],
+                vec![ImportedSymbol::Namespace],
+                vec![],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_type_only_deps() {
+        let test_utils = gen_ts_ast(
+            r#"
+import type { A } from 'b';
+import { type B } from 'a';
+import { type C } from 'a';
+import { D } from 'c';
+export type { E } from 'd';
+            "#,
+        );
+        assert_eq!(
+            super::collect_type_only_deps(&test_utils.ast.js().ast),
+            vec!["a", "b", "d"]
+        );
+    }
+
     #[test]
     fn test_dynamic_import() {
         assert_eq!(run(r#"import('a');"#), vec!["a"]);
@@ -184,6 +328,13 @@ mod tests {
         assert!(run(r#"require(a);"#).is_empty());
     }
 
+    #[test]
+    fn test_require_resolve() {
+        assert_eq!(run(r#"require.resolve('a');"#), vec!["a"]);
+        assert!(run(r#"const require = {}; require.resolve('a');"#).is_empty());
+        assert!(run(r#"require.resolve(a);"#).is_empty());
+    }
+
     #[test]
     fn test_worker() {
         assert_eq!(
@@ -209,10 +360,45 @@ mod tests {
         assert_eq!(run(r#"require(require("b"))"#), vec!["b"]);
     }
 
+    fn gen_ts_ast(ts_code: &str) -> TestUtils {
+        TestUtils::new(TestUtilsOpts {
+            file: Some("test.ts".to_string()),
+            content: Some(ts_code.to_string()),
+        })
+    }
+
+    fn run_ts(ts_code: &str) -> Vec<String> {
+        let test_utils = gen_ts_ast(ts_code);
+        let ast = test_utils.ast.js();
+        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark);
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast.visit_with(&mut analyzer);
+        });
+        analyzer
+            .dependencies
+            .iter()
+            .map(|dep| dep.source.clone())
+            .collect()
+    }
+
+    fn run_specifiers(ts_code: &str) -> Vec<Vec<ImportedSymbol>> {
+        let test_utils = gen_ts_ast(ts_code);
+        let ast = test_utils.ast.js();
+        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark);
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast.visit_with(&mut analyzer);
+        });
+        analyzer
+            .dependencies
+            .into_iter()
+            .map(|dep| dep.specifiers)
+            .collect()
+    }
+
     fn run(js_code: &str) -> Vec<String> {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
-        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark);
+        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark).with_require_resolve(true);
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
             ast.ast.visit_with(&mut analyzer);
         });
@@ -3,15 +3,20 @@ use std::sync::Arc;
 
 use swc_core::common::{Mark, DUMMY_SP};
 use swc_core::ecma::ast::{
-    AssignOp, BlockStmt, Expr, ExprOrSpread, FnExpr, Function, Ident, ImportDecl, Lit, NamedExport,
-    NewExpr, Stmt, Str, ThrowStmt, VarDeclKind,
+    AssignOp, BinExpr, BinaryOp, BlockStmt, Expr, ExprOrSpread, FnExpr, Function, Ident,
+    ImportDecl, Lit, MemberExpr, MemberProp, NamedExport, NewExpr, Stmt, Str, ThrowStmt,
+    VarDeclKind,
 };
 use swc_core::ecma::utils::{member_expr, quote_ident, quote_str, ExprFactory};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::file::parse_path;
-use crate::ast::utils::{is_commonjs_require, is_dynamic_import, is_remote_or_data};
+use crate::ast::utils::{
+    get_first_str_arg, is_commonjs_require, is_commonjs_require_resolve, is_dynamic_import,
+    is_remote_or_data,
+};
 use crate::compiler::Context;
+use crate::config::OutputMode;
 use crate::module::{Dependency, ModuleId};
 use crate::visitors::virtual_css_modules::is_css_path;
 
@@ -86,6 +91,28 @@ pub fn miss_throw_stmt<T: AsRef<str>>(source: T) -> Expr {
 
 impl VisitMut for DepReplacer<'_> {
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        // require.resolve('a')
+        if let Expr::Call(call_expr) = expr
+            && self.context.config.require_resolve()
+            && is_commonjs_require_resolve(call_expr, &self.unresolved_mark)
+            && let Some(source) = get_first_str_arg(call_expr)
+        {
+            if self.to_replace.missing.contains_key(&source) {
+                *expr = miss_throw_stmt(&source);
+                return;
+            }
+            if let Some((module_id, raw_id)) = self.to_replace.resolved.get(&source) {
+                if self.context.config.output.mode == OutputMode::Bundless {
+                    // keep the call and only replace the source like other deps
+                    if let box Expr::Lit(Lit::Str(ref mut str)) = call_expr.args[0].expr {
+                        self.replace_source(str);
+                    }
+                } else {
+                    *expr = self.require_resolve_expr(module_id, raw_id);
+                }
+                return;
+            }
+        }
         if let Expr::Call(call_expr) = expr {
             let is_commonjs_require_flag = is_commonjs_require(call_expr, &self.unresolved_mark);
             if is_commonjs_require_flag || is_dynamic_import(call_expr) {
@@ -164,8 +191,23 @@ impl VisitMut for DepReplacer<'_> {
     }
 
     fn visit_mut_new_expr(&mut self, new_expr: &mut NewExpr) {
-        if let Some(str) = resolve_web_worker_mut(new_expr, self.unresolved_mark) {
+        if let Some(url) = resolve_web_worker_mut(new_expr, self.unresolved_mark)
+            && let Expr::Lit(Lit::Str(str)) = &mut **url
+        {
+            let is_resolved = self
+                .to_replace
+                .resolved
+                .contains_key(&str.value.to_string());
             self.replace_source(str);
+            // worker chunks are loaded from the runtime publicPath if it's in use, they are
+            // resolved relative to the base url of `new URL()` as written otherwise
+            if is_resolved
+                && self.context.config.public_path == "runtime"
+                && self.context.config.output.mode != OutputMode::Bundless
+            {
+                let filename = Expr::Lit(Lit::Str(str.clone()));
+                *url = Box::new(self.public_path_expr(filename));
+            }
         }
         new_expr.visit_mut_children_with(self);
     }
@@ -182,6 +224,36 @@ impl VisitMut for DepReplacer<'_> {
 }
 
 impl DepReplacer<'_> {
+    // module id for js modules
+    // require.publicPath + "emitted filename" for emitted assets
+    fn require_resolve_expr(&self, module_id: &str, raw_id: &str) -> Expr {
+        let path = parse_path(raw_id)
+            .map(|(path, ..)| path)
+            .unwrap_or_else(|_| raw_id.to_string());
+        let assets_info = self.context.assets_info.lock().unwrap();
+        match assets_info.get(&path) {
+            Some(filename) => self.public_path_expr(quote_str!(filename.clone()).into()),
+            None => quote_str!(module_id).into(),
+        }
+    }
+
+    // require.publicPath + filename
+    fn public_path_expr(&self, filename: Expr) -> Expr {
+        Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::Add,
+            left: Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(quote_ident!(
+                    DUMMY_SP.apply_mark(self.unresolved_mark),
+                    "require"
+                ))),
+                prop: MemberProp::Ident(quote_ident!("publicPath")),
+            })),
+            right: Box::new(filename),
+        })
+    }
+
     fn replace_source(&mut self, source: &mut Str) {
         if let Some(replacement) = self.to_replace.resolved.get(&source.value.to_string()) {
             let module_id = replacement.0.clone();
@@ -193,7 +265,11 @@ impl DepReplacer<'_> {
 }
 
 // TODO: duplicated code with dep_analyzer.rs
-pub fn resolve_web_worker_mut(new_expr: &mut NewExpr, unresolved_mark: Mark) -> Option<&mut Str> {
+// returns the first argument of new URL() so that it can be replaced as a whole
+pub fn resolve_web_worker_mut(
+    new_expr: &mut NewExpr,
+    unresolved_mark: Mark,
+) -> Option<&mut Box<Expr>> {
     if !new_expr.args.as_ref().is_some_and(|args| !args.is_empty()) || !new_expr.callee.is_ident() {
         return None;
     }
@@ -215,10 +291,10 @@ pub fn resolve_web_worker_mut(new_expr: &mut NewExpr, unresolved_mark: Mark) ->
                     if sym == "URL" && (span.ctxt.outer() == unresolved_mark) {
                         // new URL('');
                         let args = new_expr.args.as_mut().unwrap();
-                        if let box Expr::Lit(Lit::Str(ref mut str)) = &mut args[0].expr {
-                            if !is_remote_or_data(&str.value) {
-                                return Some(str);
-                            }
+                        if let box Expr::Lit(Lit::Str(str)) = &args[0].expr
+                            && !is_remote_or_data(&str.value)
+                        {
+                            return Some(&mut args[0].expr);
                         }
                     }
                 }
@@ -232,13 +308,16 @@ pub fn resolve_web_worker_mut(new_expr: &mut NewExpr, unresolved_mark: Mark) ->
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     use maplit::hashmap;
     use swc_core::common::GLOBALS;
+    use swc_core::ecma::transforms::base::resolver;
     use swc_core::ecma::visit::VisitMutWith;
 
     use super::{DepReplacer, DependenciesToReplace, ResolvedModuleId, ResolvedModulePath};
-    use crate::ast::tests::TestUtils;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::compiler::Context;
     use crate::module::{Dependency, ImportType, ModuleId, ResolveType};
 
     #[test]
@@ -301,6 +380,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_worker_with_runtime_public_path() {
+        let mut context = Context::default();
+        context.config.devtool = None;
+        context.config.public_path = "runtime".to_string();
+        let mut test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: None,
+                content: Some(r#"new Worker(new URL('x', import.meta.url))"#.to_string()),
+            },
+            Arc::new(context),
+        );
+        let ast = test_utils.ast.js();
+        let (unresolved_mark, top_level_mark) = (ast.unresolved_mark, ast.top_level_mark);
+        test_utils.transform_js(&mut resolver(unresolved_mark, top_level_mark, false));
+        assert_eq!(
+            replace(
+                test_utils,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"new Worker(new URL(require.publicPath + "/x/index.js", import.meta.url));"#,
+        );
+    }
+
+    #[test]
+    fn test_require_resolve() {
+        assert_eq!(
+            run(
+                r#"const x = require.resolve("x");"#,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"const x = "/x/index.js";"#,
+        );
+    }
+
     #[test]
     fn test_missing_dep() {
         assert_eq!(
@@ -361,6 +477,7 @@ try {
                 resolve_type: ResolveType::Import(ImportType::Default),
                 source: key.to_string(),
                 resolve_as: None,
+                specifiers: vec![],
                 span: None,
                 order: 0,
             }
@@ -372,7 +489,14 @@ try {
         resolved: HashMap<String, (ResolvedModuleId, ResolvedModulePath)>,
         missing: HashMap<String, Dependency>,
     ) -> String {
-        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        replace(TestUtils::gen_js_ast(js_code), resolved, missing)
+    }
+
+    fn replace(
+        mut test_utils: TestUtils,
+        resolved: HashMap<String, (ResolvedModuleId, ResolvedModulePath)>,
+        missing: HashMap<String, Dependency>,
+    ) -> String {
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
             let mut visitor = DepReplacer {
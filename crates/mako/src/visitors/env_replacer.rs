@@ -1,18 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use serde_json::Value;
 use swc_core::common::collections::AHashMap;
+use swc_core::common::errors::HANDLER;
 use swc_core::common::sync::Lrc;
-use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::common::{Mark, Span, DUMMY_SP};
 use swc_core::ecma::ast::{
     ArrayLit, Bool, ComputedPropName, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, MemberExpr,
-    MemberProp, MetaPropExpr, MetaPropKind, ModuleItem, Null, Number, ObjectLit, Prop, PropName,
-    PropOrSpread, Stmt, Str,
+    MemberProp, MetaPropExpr, MetaPropKind, ModuleItem, Null, Number, ObjectLit, ParenExpr, Prop,
+    PropName, PropOrSpread, Stmt, Str, UnaryExpr, UnaryOp,
 };
 use swc_core::ecma::atoms::{js_word, JsWord};
-use swc_core::ecma::utils::{quote_ident, ExprExt};
+use swc_core::ecma::utils::{member_expr, quote_ident, quote_str, ExprExt};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::js_ast::JsAst;
@@ -24,11 +25,16 @@ enum EnvsType {
     Browser(Lrc<AHashMap<String, Expr>>),
 }
 
-#[derive(Debug)]
 pub struct EnvReplacer {
     unresolved_mark: Mark,
     envs: Lrc<AHashMap<JsWord, Expr>>,
     meta_envs: Lrc<AHashMap<String, Expr>>,
+    // `process` exists at runtime, e.g. node platform or provided by node polyfill
+    typeof_process: bool,
+    // keys of `process.env` and `import.meta.env` looked up at runtime instead of inlined
+    runtime_env: HashSet<String>,
+    // report unknown env keys of the module at path, as warnings or errors with strictEnv
+    diagnostics: Option<(Arc<Context>, String)>,
 }
 
 impl EnvReplacer {
@@ -37,6 +43,10 @@ impl EnvReplacer {
 
         // generate meta_envs from envs
         for (k, v) in envs.iter() {
+            // dotted keys like `__APP__.version` are member expression defines
+            if k.contains('.') {
+                continue;
+            }
             // convert NODE_ENV to MODE
             let key: String = if k.eq(&js_word!("NODE_ENV")) {
                 "MODE".into()
@@ -51,7 +61,72 @@ impl EnvReplacer {
             unresolved_mark,
             envs,
             meta_envs: Lrc::new(meta_env_map),
+            typeof_process: false,
+            runtime_env: HashSet::new(),
+            diagnostics: None,
+        }
+    }
+
+    pub fn with_typeof_process(mut self, typeof_process: bool) -> Self {
+        self.typeof_process = typeof_process;
+        self
+    }
+
+    pub fn with_runtime_env(mut self, runtime_env: &[String]) -> Self {
+        self.runtime_env = runtime_env.iter().cloned().collect();
+        self
+    }
+
+    pub fn with_diagnostics(mut self, context: Arc<Context>, path: String) -> Self {
+        self.diagnostics = Some((context, path));
+        self
+    }
+
+    fn report_unknown_env(&self, name: String, span: Span) {
+        let Some((context, path)) = &self.diagnostics else {
+            return;
+        };
+        let message = format!("{} is not defined, it's replaced with undefined", name);
+        if context.config.strict_env {
+            // errors of the handler fail the transform with code frame
+            HANDLER.with(|handler| handler.struct_span_err(span, &message).emit());
+            return;
         }
+        // accesses of the same key are aggregated into one warning
+        let loc = context.meta.script.cm.lookup_char_pos(span.lo);
+        context.stats_info.add_grouped_warning(
+            message,
+            format!("{}:{}:{}", path, loc.line, loc.col_display + 1),
+        );
+    }
+
+    // `window.__RUNTIME_ENV__.XX` for keys configured in runtimeEnv
+    fn get_runtime_env(&self, sym: &JsWord) -> Option<Expr> {
+        if !self.runtime_env.contains(sym.as_ref()) {
+            return None;
+        }
+        Some(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: member_expr!(DUMMY_SP, window.__RUNTIME_ENV__),
+            prop: MemberProp::Ident(quote_ident!(sym.clone())),
+        }))
+    }
+
+    // the result of `typeof` over a defined identifier or member expression, e.g. "boolean"
+    // for `typeof __DEV__` with `__DEV__: true`, and "object" for `typeof __APP__` with
+    // `__APP__.version` defined
+    fn get_typeof(&self, arg: &Expr) -> Option<&'static str> {
+        let path = self.get_member_path(arg)?;
+        if let Some(env) = self.envs.get(&JsWord::from(path.as_str())) {
+            return typeof_value(env);
+        }
+        let prefix = format!("{}.", path);
+        if self.envs.keys().any(|k| k.starts_with(&prefix))
+            || (self.typeof_process && path == "process")
+        {
+            return Some("object");
+        }
+        None
     }
 
     fn get_env(envs: &EnvsType, sym: &JsWord) -> Option<Expr> {
@@ -60,9 +135,49 @@ impl EnvReplacer {
             EnvsType::Browser(envs) => envs.get(&sym.to_string()).cloned(),
         }
     }
+
+    // get the dotted path of a member expression chain like `a.b["c"]`,
+    // only when the root object is an unresolved identifier
+    fn get_member_path(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Ident(Ident { sym, span, .. }) => {
+                if span.ctxt.outer() == self.unresolved_mark {
+                    Some(sym.to_string())
+                } else {
+                    None
+                }
+            }
+            Expr::Member(MemberExpr { obj, prop, .. }) => {
+                let obj_path = self.get_member_path(obj)?;
+                let prop_name = match prop {
+                    MemberProp::Ident(Ident { sym, .. }) => sym.to_string(),
+                    MemberProp::Computed(ComputedPropName { expr: c, .. }) => match &**c {
+                        Expr::Lit(Lit::Str(Str { value, .. })) => value.to_string(),
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                Some(format!("{}.{}", obj_path, prop_name))
+            }
+            _ => None,
+        }
+    }
 }
 impl VisitMut for EnvReplacer {
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Unary(UnaryExpr {
+            op: UnaryOp::TypeOf,
+            arg,
+            ..
+        }) = expr
+            && let Some(type_name) = self.get_typeof(arg)
+        {
+            // fold `typeof` before the argument is replaced, so that dead branches like
+            // `typeof __DEV__ === 'boolean'` can be eliminated
+            *expr = Expr::Lit(Lit::Str(quote_str!(type_name)));
+            return;
+        }
+
         if let Expr::Ident(Ident { ref sym, span, .. }) = expr {
             let envs = EnvsType::Node(self.envs.clone());
 
@@ -79,7 +194,20 @@ impl VisitMut for EnvReplacer {
             }
         }
 
-        if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
+        if let Expr::Member(_) = expr
+            && let Some(path) = self.get_member_path(expr)
+            && path.contains('.')
+            && let Some(env) = self.envs.get(&JsWord::from(path))
+        {
+            // handle member expression defines like `__APP__.version`
+            *expr = env.clone();
+            return;
+        }
+
+        if let Expr::Member(MemberExpr {
+            obj, prop, span, ..
+        }) = expr
+        {
             if let Expr::Member(MemberExpr {
                 obj: first_obj,
                 prop:
@@ -92,6 +220,7 @@ impl VisitMut for EnvReplacer {
             {
                 // handle `env.XX`
                 let mut envs = EnvsType::Node(self.envs.clone());
+                let mut env_name = "process.env";
 
                 if match &**first_obj {
                     Expr::Ident(Ident {
@@ -103,40 +232,35 @@ impl VisitMut for EnvReplacer {
                         ..
                     }) => {
                         envs = EnvsType::Browser(self.meta_envs.clone());
+                        env_name = "import.meta.env";
                         true
                     }
                     _ => false,
                 } {
                     // handle `process.env.XX` and `import.meta.env.XX`
-                    match prop {
-                        MemberProp::Computed(ComputedPropName { expr: c, .. }) => {
-                            if let Expr::Lit(Lit::Str(Str { value: sym, .. })) = &**c {
-                                if let Some(env) = EnvReplacer::get_env(&envs, sym) {
-                                    // replace with real value if env found
-                                    *expr = env;
-                                } else {
-                                    // replace with `undefined` if env not found
-                                    *expr = *Box::new(Expr::Ident(Ident::new(
-                                        js_word!("undefined"),
-                                        DUMMY_SP,
-                                    )));
-                                }
-                            }
-                        }
-
-                        MemberProp::Ident(Ident { sym, .. }) => {
-                            if let Some(env) = EnvReplacer::get_env(&envs, sym) {
-                                // replace with real value if env found
-                                *expr = env;
-                            } else {
-                                // replace with `undefined` if env not found
-                                *expr = *Box::new(Expr::Ident(Ident::new(
-                                    js_word!("undefined"),
-                                    DUMMY_SP,
-                                )));
-                            }
+                    let sym = match prop {
+                        MemberProp::Computed(ComputedPropName { expr: c, .. }) => match &**c {
+                            Expr::Lit(Lit::Str(Str { value, .. })) => Some(value.clone()),
+                            _ => None,
+                        },
+                        MemberProp::Ident(Ident { sym, .. }) => Some(sym.clone()),
+                        _ => None,
+                    };
+                    if let Some(sym) = sym {
+                        if let Some(env) = self.get_runtime_env(&sym) {
+                            // defer to the runtime global, so that the value can be changed
+                            // after build
+                            *expr = env;
+                        } else if let Some(env) = EnvReplacer::get_env(&envs, &sym) {
+                            // replace with real value if env found
+                            *expr = env;
+                        } else {
+                            // replace with `undefined` if env not found, keys of runtimeEnv
+                            // are looked up at runtime above
+                            self.report_unknown_env(format!("{}.{}", env_name, sym), *span);
+                            *expr =
+                                *Box::new(Expr::Ident(Ident::new(js_word!("undefined"), DUMMY_SP)));
                         }
-                        _ => {}
                     }
                 }
             } else if let Expr::Member(MemberExpr {
@@ -175,6 +299,25 @@ impl VisitMut for EnvReplacer {
     }
 }
 
+fn typeof_value(expr: &Expr) -> Option<&'static str> {
+    match expr {
+        Expr::Lit(Lit::Str(_)) | Expr::Tpl(_) => Some("string"),
+        Expr::Lit(Lit::Bool(_)) => Some("boolean"),
+        Expr::Lit(Lit::Num(_)) => Some("number"),
+        Expr::Lit(Lit::BigInt(_)) => Some("bigint"),
+        Expr::Lit(Lit::Null(_)) | Expr::Lit(Lit::Regex(_)) | Expr::Object(_) | Expr::Array(_) => {
+            Some("object")
+        }
+        Expr::Fn(_) | Expr::Arrow(_) | Expr::Class(_) => Some("function"),
+        Expr::Ident(Ident {
+            sym: js_word!("undefined"),
+            ..
+        }) => Some("undefined"),
+        Expr::Paren(ParenExpr { expr, .. }) => typeof_value(expr),
+        _ => None,
+    }
+}
+
 pub fn build_env_map(
     env_map: HashMap<String, Value>,
     context: &Arc<Context>,
@@ -361,12 +504,151 @@ log([
         );
     }
 
+    #[test]
+    fn test_member_expr_define() {
+        assert_eq!(
+            run(
+                r#"log(__APP__.version, __APP__["version"], __APP__.name)"#,
+                hashmap! {
+                    "__APP__.version".to_string() => json!("\"1.2.3\"")
+                }
+            ),
+            r#"log("1.2.3", "1.2.3", __APP__.name);"#
+        );
+    }
+
+    #[test]
+    fn test_nested_member_expr_define() {
+        assert_eq!(
+            run(
+                r#"log(a.b.c, a.b)"#,
+                hashmap! {
+                    "a.b.c".to_string() => json!(1)
+                }
+            ),
+            r#"log(1, a.b);"#
+        );
+    }
+
+    #[test]
+    fn test_member_expr_define_with_local_binding() {
+        assert_eq!(
+            run(
+                r#"const __APP__ = {}; log(__APP__.version)"#,
+                hashmap! {
+                    "__APP__.version".to_string() => json!("\"1.2.3\"")
+                }
+            ),
+            r#"const __APP__ = {};
+log(__APP__.version);"#
+        );
+    }
+
+    #[test]
+    fn test_typeof_define() {
+        assert_eq!(
+            run(
+                r#"log(typeof __DEV__ === "boolean", typeof A, typeof B, typeof C)"#,
+                hashmap! {
+                    "__DEV__".to_string() => json!(true),
+                    "A".to_string() => json!("\"foo\""),
+                    "B".to_string() => json!({"x": 1}),
+                }
+            ),
+            r#"log("boolean" === "boolean", "string", "object", typeof C);"#
+        );
+    }
+
+    #[test]
+    fn test_typeof_member_expr_define() {
+        assert_eq!(
+            run(
+                r#"log(typeof __APP__, typeof __APP__.version, typeof __APP__.name)"#,
+                hashmap! {
+                    "__APP__.version".to_string() => json!("\"1.2.3\"")
+                }
+            ),
+            r#"log("object", "string", typeof __APP__.name);"#
+        );
+    }
+
+    #[test]
+    fn test_typeof_process() {
+        assert_eq!(
+            run(r#"log(typeof process)"#, Default::default()),
+            r#"log(typeof process);"#
+        );
+        assert_eq!(
+            run_with_typeof_process(r#"log(typeof process !== "undefined")"#, Default::default()),
+            r#"log("object" !== "undefined");"#
+        );
+    }
+
+    #[test]
+    fn test_runtime_env() {
+        assert_eq!(
+            run_with_runtime_env(
+                r#"log(process.env.API_URL, process.env["API_URL"], import.meta.env.API_URL, process.env.A)"#,
+                hashmap! {
+                    "API_URL".to_string() => json!("\"https://example.com\""),
+                    "A".to_string() => json!("1")
+                },
+                &["API_URL"]
+            ),
+            r#"log(window.__RUNTIME_ENV__.API_URL, window.__RUNTIME_ENV__.API_URL, window.__RUNTIME_ENV__.API_URL, 1);"#
+        );
+    }
+
+    #[test]
+    fn test_unknown_env_warning() {
+        let mut test_utils =
+            TestUtils::gen_js_ast(r#"log(process.env.A, process.env.A, import.meta.env.B);"#);
+        let context = test_utils.context.clone();
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&context.meta.script.globals, || {
+            let mut visitor = EnvReplacer::new(Default::default(), ast.unresolved_mark)
+                .with_diagnostics(context.clone(), "index.js".to_string());
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        context.stats_info.flush_grouped_warnings(false);
+        assert_eq!(
+            context.stats_info.get_warnings(),
+            vec![
+                "process.env.A is not defined, it's replaced with undefined (2 occurrences)\n  index.js:1:5\n  index.js:1:20",
+                "import.meta.env.B is not defined, it's replaced with undefined (index.js:1:35)",
+            ]
+        );
+    }
+
     fn run(js_code: &str, envs: HashMap<String, Value>) -> String {
+        run_inner(js_code, envs, |visitor| visitor)
+    }
+
+    fn run_with_typeof_process(js_code: &str, envs: HashMap<String, Value>) -> String {
+        run_inner(js_code, envs, |visitor| visitor.with_typeof_process(true))
+    }
+
+    fn run_with_runtime_env(
+        js_code: &str,
+        envs: HashMap<String, Value>,
+        runtime_env: &[&str],
+    ) -> String {
+        let runtime_env: Vec<String> = runtime_env.iter().map(|k| k.to_string()).collect();
+        run_inner(js_code, envs, |visitor| {
+            visitor.with_runtime_env(&runtime_env)
+        })
+    }
+
+    fn run_inner(
+        js_code: &str,
+        envs: HashMap<String, Value>,
+        configure: impl FnOnce(EnvReplacer) -> EnvReplacer,
+    ) -> String {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let envs = build_env_map(envs, &test_utils.context).unwrap();
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
-            let mut visitor = EnvReplacer::new(Lrc::new(envs), ast.unresolved_mark);
+            let mut visitor = configure(EnvReplacer::new(Lrc::new(envs), ast.unresolved_mark));
             ast.ast.visit_mut_with(&mut visitor);
         });
         test_utils.js_ast_to_code()
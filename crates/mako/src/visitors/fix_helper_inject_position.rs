@@ -161,12 +161,15 @@ export { foo };
             ast.ast.visit_mut_with(&mut v);
 
             // preset_env
-            let mut folders: Vec<Box<dyn Fold>> = vec![];
-            folders.push(Box::new(decorators(decorators::Config {
-                legacy: true,
-                emit_metadata: false,
-                ..Default::default()
-            })));
+            let mut folders: Vec<(&str, Box<dyn Fold>)> = vec![];
+            folders.push((
+                "decorators",
+                Box::new(decorators(decorators::Config {
+                    legacy: true,
+                    emit_metadata: false,
+                    ..Default::default()
+                })),
+            ));
             let origin_comments = test_utils
                 .context
                 .meta
@@ -177,18 +180,21 @@ export { foo };
             let comments = origin_comments.get_swc_comments().clone();
             let mut targets = HashMap::new();
             targets.insert("chrome".to_string(), 50.0);
-            folders.push(Box::new(swc_preset_env::preset_env(
-                unresolved_mark,
-                Some(comments),
-                swc_preset_env::Config {
-                    mode: Some(swc_preset_env::Mode::Entry),
-                    targets: Some(swc_preset_env_targets_from_map(targets)),
-                    ..Default::default()
-                },
-                Assumptions::default(),
-                &mut FeatureFlag::default(),
-            )));
-            let mut visitors: Vec<Box<dyn VisitMut>> = vec![];
+            folders.push((
+                "preset_env",
+                Box::new(swc_preset_env::preset_env(
+                    unresolved_mark,
+                    Some(comments),
+                    swc_preset_env::Config {
+                        mode: Some(swc_preset_env::Mode::Entry),
+                        targets: Some(swc_preset_env_targets_from_map(targets)),
+                        ..Default::default()
+                    },
+                    Assumptions::default(),
+                    &mut FeatureFlag::default(),
+                )),
+            ));
+            let mut visitors: Vec<(&str, Box<dyn VisitMut>)> = vec![];
             let context = test_utils.context.clone();
             let file = File::new("test.ts".to_string(), context.clone());
             ast.transform(&mut visitors, &mut folders, &file, false, context)
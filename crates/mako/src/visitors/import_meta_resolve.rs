@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use swc_core::ecma::ast::{
+    Callee, Expr, Ident, Lit, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind,
+};
+use swc_core::ecma::utils::quote_str;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::get_first_str_arg;
+use crate::compiler::Context;
+use crate::module::{Dependency, ResolveType};
+use crate::resolve::{self, ResolverResource};
+
+/**
+ * Replace `import.meta.resolve("x")` with the url resolved at build time, since `import.meta`
+ * is not available in chunks.
+ *
+ * e.g.
+ * import.meta.resolve("./worker.js")
+ * ->
+ * "file:///path/to/src/worker.js"
+ *
+ * externals are replaced with their sources, calls which fail to resolve are kept with
+ * warnings.
+ */
+pub struct ImportMetaResolve {
+    pub path: String,
+    pub context: Arc<Context>,
+}
+
+impl ImportMetaResolve {
+    fn resolve(&self, source: &str) -> Option<String> {
+        let dep = Dependency {
+            source: source.to_string(),
+            resolve_as: None,
+            specifiers: vec![],
+            resolve_type: ResolveType::DynamicImport,
+            order: 0,
+            span: None,
+        };
+        match resolve::resolve(&self.path, &dep, &self.context.resolvers, &self.context) {
+            Ok(ResolverResource::Resolved(resolved)) => Some(format!(
+                "file://{}",
+                resolved.0.full_path().to_string_lossy()
+            )),
+            Ok(ResolverResource::External(_)) => Some(source.to_string()),
+            Ok(ResolverResource::Ignored(_) | ResolverResource::Virtual(_)) | Err(_) => None,
+        }
+    }
+}
+
+impl VisitMut for ImportMetaResolve {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Call(call_expr) = expr
+            && let Callee::Expr(box Expr::Member(MemberExpr {
+                obj:
+                    box Expr::MetaProp(MetaPropExpr {
+                        kind: MetaPropKind::ImportMeta,
+                        ..
+                    }),
+                prop: MemberProp::Ident(Ident { sym, .. }),
+                ..
+            })) = &call_expr.callee
+            && sym == "resolve"
+            && let Some(source) = get_first_str_arg(call_expr)
+        {
+            if let Some(url) = self.resolve(&source) {
+                *expr = Expr::Lit(Lit::Str(quote_str!(url)));
+                return;
+            }
+            let relative_path = Path::new(&self.path)
+                .strip_prefix(&self.context.root)
+                .map_or(self.path.clone(), |p| p.to_string_lossy().to_string());
+            self.context.stats_info.add_grouped_warning(
+                format!("import.meta.resolve(\"{}\") can't be resolved", source),
+                relative_path,
+            );
+        }
+
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::ImportMetaResolve;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_import_meta_resolve() {
+        let mut test_utils = TestUtils::gen_js_ast(
+            r#"log(import.meta.resolve("./not-exists"), import.meta.resolve(name));"#,
+        );
+        let context = test_utils.context.clone();
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&context.meta.script.globals, || {
+            let mut visitor = ImportMetaResolve {
+                path: "/root/index.js".to_string(),
+                context: context.clone(),
+            };
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        assert_eq!(
+            test_utils.js_ast_to_code(),
+            r#"log(import.meta.resolve("./not-exists"), import.meta.resolve(name));"#
+        );
+        context.stats_info.flush_grouped_warnings(false);
+        assert_eq!(
+            context.stats_info.get_warnings(),
+            vec![r#"import.meta.resolve("./not-exists") can't be resolved (/root/index.js)"#]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::common::Mark;
+use swc_core::ecma::ast::{
+    AssignExpr, BindingIdent, CallExpr, Callee, Decl, Expr, Id, KeyValueProp, Module, ModuleItem,
+    Pat, PatOrExpr, Prop, Stmt, UpdateExpr, VarDecl,
+};
+use swc_core::ecma::utils::find_pat_ids;
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::ast::utils::{get_first_str_arg, is_commonjs_require};
+
+/**
+ * Move the top-level requires to where they are used, like the inlineRequires of Metro, so that
+ * the dependencies are evaluated lazily, which speeds up the startup of large React Native apps.
+ *
+ * e.g.
+ * var _foo = _interop_require_default(require("./foo"));
+ * function bar() { return _foo.default; }
+ * ->
+ * function bar() { return _interop_require_default(require("./foo")).default; }
+ *
+ * the order of side effects of the dependencies is changed, bindings which are reassigned are
+ * kept as is.
+ */
+pub struct InlineRequires {
+    pub unresolved_mark: Mark,
+}
+
+impl InlineRequires {
+    // require("x") or helper(require("x")), e.g. _interop_require_default(require("x"))
+    fn is_inlinable(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Call(call_expr) if is_commonjs_require(call_expr, &self.unresolved_mark) => {
+                call_expr.args.len() == 1 && get_first_str_arg(call_expr).is_some()
+            }
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(box Expr::Ident(helper)),
+                args,
+                ..
+            }) if helper.sym.starts_with("_interop_require") && args.len() == 1 => {
+                args[0].spread.is_none() && self.is_inlinable(&args[0].expr)
+            }
+            _ => false,
+        }
+    }
+
+    fn inlinable_decl(&self, item: &ModuleItem) -> Option<(Id, Expr)> {
+        if let ModuleItem::Stmt(Stmt::Decl(Decl::Var(box VarDecl { decls, .. }))) = item
+            && decls.len() == 1
+            && let Pat::Ident(BindingIdent { id, .. }) = &decls[0].name
+            && let Some(init) = &decls[0].init
+            && self.is_inlinable(init)
+        {
+            return Some((id.to_id(), *init.clone()));
+        }
+        None
+    }
+}
+
+impl VisitMut for InlineRequires {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let mut inits = module
+            .body
+            .iter()
+            .filter_map(|item| self.inlinable_decl(item))
+            .collect::<HashMap<_, _>>();
+
+        let mut assigned = AssignedIdents::default();
+        module.visit_with(&mut assigned);
+        inits.retain(|id, _| !assigned.ids.contains(id));
+        if inits.is_empty() {
+            return;
+        }
+
+        module.body.retain(|item| {
+            self.inlinable_decl(item)
+                .map_or(true, |(id, _)| !inits.contains_key(&id))
+        });
+        module.visit_mut_children_with(&mut RequireInliner { inits });
+    }
+}
+
+#[derive(Default)]
+struct AssignedIdents {
+    ids: HashSet<Id>,
+}
+
+impl Visit for AssignedIdents {
+    fn visit_assign_expr(&mut self, assign_expr: &AssignExpr) {
+        match &assign_expr.left {
+            PatOrExpr::Pat(pat) => self.ids.extend(find_pat_ids::<_, Id>(&**pat)),
+            PatOrExpr::Expr(box Expr::Ident(ident)) => {
+                self.ids.insert(ident.to_id());
+            }
+            PatOrExpr::Expr(_) => {}
+        }
+        assign_expr.visit_children_with(self);
+    }
+
+    fn visit_update_expr(&mut self, update_expr: &UpdateExpr) {
+        if let Expr::Ident(ident) = &*update_expr.arg {
+            self.ids.insert(ident.to_id());
+        }
+        update_expr.visit_children_with(self);
+    }
+}
+
+struct RequireInliner {
+    inits: HashMap<Id, Expr>,
+}
+
+impl VisitMut for RequireInliner {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Ident(ident) = expr
+            && let Some(init) = self.inits.get(&ident.to_id())
+        {
+            *expr = init.clone();
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        // { _foo } -> { _foo: require("foo") }
+        if let Prop::Shorthand(ident) = prop
+            && let Some(init) = self.inits.get(&ident.to_id())
+        {
+            *prop = Prop::KeyValue(KeyValueProp {
+                key: ident.clone().into(),
+                value: Box::new(init.clone()),
+            });
+            return;
+        }
+        prop.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::InlineRequires;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_inline_requires() {
+        assert_eq!(
+            run(r#"
+var _foo = _interop_require_default(require("./foo"));
+const bar = require("./bar");
+function baz() {
+    return _foo.default + bar.name;
+}
+var o = { bar };
+            "#),
+            r#"
+function baz() {
+    return _interop_require_default(require("./foo")).default + require("./bar").name;
+}
+var o = {
+    bar: require("./bar")
+};
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_inline_requires_reassigned() {
+        assert_eq!(
+            run(r#"
+var foo = require("./foo");
+var bar = require(name);
+foo = 1;
+log(foo, bar);
+            "#),
+            r#"
+var foo = require("./foo");
+var bar = require(name);
+foo = 1;
+log(foo, bar);
+            "#
+            .trim()
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        let unresolved_mark = ast.unresolved_mark;
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast
+                .visit_mut_with(&mut InlineRequires { unresolved_mark });
+        });
+        test_utils.js_ast_to_code()
+    }
+}
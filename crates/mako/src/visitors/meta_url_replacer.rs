@@ -1,22 +1,83 @@
 use swc_core::common::DUMMY_SP;
-use swc_core::ecma::ast::{CondExpr, Expr};
-use swc_core::ecma::utils::member_expr;
+use swc_core::ecma::ast::{
+    BinExpr, BinaryOp, CondExpr, Expr, Ident, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind,
+};
+use swc_core::ecma::atoms::js_word;
+use swc_core::ecma::utils::{member_expr, quote_ident, quote_str};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
-use crate::ast::utils::is_import_meta_url;
+use crate::config::Platform;
 
-pub struct MetaUrlReplacer {}
+/**
+ * Rewrite `import.meta.url`, `import.meta.filename` and `import.meta.dirname`, which are
+ * syntax errors in the non-module scripts of chunks.
+ *
+ * browser:
+ * import.meta.url -> url of the current script, or the base url of the document or worker
+ * import.meta.filename / import.meta.dirname -> undefined
+ *
+ * node:
+ * import.meta.url -> "file://" + __filename
+ * import.meta.filename -> __filename
+ * import.meta.dirname -> __dirname
+ */
+pub struct MetaUrlReplacer {
+    pub platform: Platform,
+}
 
-impl VisitMut for MetaUrlReplacer {
-    fn visit_mut_expr(&mut self, expr: &mut Expr) {
-        if is_import_meta_url(expr) {
-            // Compatible with workers: self.document ? self.document.baseURI : self.location.href
-            *expr = Expr::Cond(CondExpr {
+impl MetaUrlReplacer {
+    fn replace(&self, prop: &str) -> Option<Expr> {
+        match (&self.platform, prop) {
+            // Compatible with workers:
+            // self.document ? self.document.currentScript && self.document.currentScript.src
+            //   || self.document.baseURI : self.location.href
+            (Platform::Browser, "url") => Some(Expr::Cond(CondExpr {
                 span: DUMMY_SP,
                 test: member_expr!(DUMMY_SP, self.document),
-                cons: member_expr!(DUMMY_SP, self.document.baseURI),
+                cons: Box::new(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::LogicalOr,
+                    left: Box::new(Expr::Bin(BinExpr {
+                        span: DUMMY_SP,
+                        op: BinaryOp::LogicalAnd,
+                        left: member_expr!(DUMMY_SP, self.document.currentScript),
+                        right: member_expr!(DUMMY_SP, self.document.currentScript.src),
+                    })),
+                    right: member_expr!(DUMMY_SP, self.document.baseURI),
+                })),
                 alt: member_expr!(DUMMY_SP, self.location.href),
-            });
+            })),
+            (Platform::Browser, "filename" | "dirname") => {
+                Some(Expr::Ident(Ident::new(js_word!("undefined"), DUMMY_SP)))
+            }
+            (Platform::Node, "url") => Some(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::Add,
+                left: quote_str!("file://").into(),
+                right: Box::new(Expr::Ident(quote_ident!("__filename"))),
+            })),
+            (Platform::Node, "filename") => Some(Expr::Ident(quote_ident!("__filename"))),
+            (Platform::Node, "dirname") => Some(Expr::Ident(quote_ident!("__dirname"))),
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for MetaUrlReplacer {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Member(MemberExpr {
+            obj:
+                box Expr::MetaProp(MetaPropExpr {
+                    kind: MetaPropKind::ImportMeta,
+                    ..
+                }),
+            prop: MemberProp::Ident(Ident { sym, .. }),
+            ..
+        }) = expr
+            && let Some(replaced) = self.replace(sym)
+        {
+            *expr = replaced;
+            return;
         }
 
         expr.visit_mut_children_with(self);
@@ -30,20 +91,43 @@ mod tests {
 
     use super::MetaUrlReplacer;
     use crate::ast::tests::TestUtils;
+    use crate::config::Platform;
 
     #[test]
     fn test_normal() {
         assert_eq!(
-            run("import.meta.url"),
-            "self.document ? self.document.baseURI : self.location.href;"
+            run("import.meta.url", Platform::Browser),
+            "self.document ? self.document.currentScript && self.document.currentScript.src || self.document.baseURI : self.location.href;"
+        )
+    }
+
+    #[test]
+    fn test_browser_dirname() {
+        assert_eq!(
+            run(
+                "log(import.meta.dirname, import.meta.filename, import.meta.env)",
+                Platform::Browser
+            ),
+            "log(undefined, undefined, import.meta.env);"
+        )
+    }
+
+    #[test]
+    fn test_node() {
+        assert_eq!(
+            run(
+                "log(import.meta.url, import.meta.filename, import.meta.dirname)",
+                Platform::Node
+            ),
+            r#"log("file://" + __filename, __filename, __dirname);"#
         )
     }
 
-    fn run(js_code: &str) -> String {
+    fn run(js_code: &str, platform: Platform) -> String {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
-            let mut visitor = MetaUrlReplacer {};
+            let mut visitor = MetaUrlReplacer { platform };
             ast.ast.visit_mut_with(&mut visitor);
         });
         test_utils.js_ast_to_code()
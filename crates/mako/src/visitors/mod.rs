@@ -1,4 +1,6 @@
 pub(crate) mod async_module;
+pub(crate) mod chunk_name;
+pub(crate) mod constant_condition;
 pub(crate) mod css_assets;
 pub(crate) mod css_dep_analyzer;
 pub(crate) mod css_flexbugs;
@@ -12,12 +14,17 @@ pub(crate) mod dynamic_import_to_require;
 pub(crate) mod env_replacer;
 pub(crate) mod fix_helper_inject_position;
 pub(crate) mod fix_symbol_conflict;
+pub(crate) mod import_meta_resolve;
+pub(crate) mod inline_requires;
 pub(crate) mod mako_require;
 pub(crate) mod meta_url_replacer;
 pub(crate) mod new_url_assets;
+pub(crate) mod no_side_effects;
 pub(crate) mod optimize_define_utils;
 pub(crate) mod provide;
+pub(crate) mod public_assets;
 pub(crate) mod react;
+pub(crate) mod swc_helpers_source;
 pub(crate) mod try_resolve;
 pub(crate) mod ts_strip;
 pub(crate) mod tsx_strip;
@@ -26,6 +26,7 @@ impl NewUrlAssets {
         let dep = Dependency {
             source: url,
             resolve_as: None,
+            specifiers: vec![],
             resolve_type: ResolveType::Css,
             order: 0,
             span: None,
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use swc_core::common::comments::Comments as CommentsTrait;
+use swc_core::common::Span;
+use swc_core::ecma::ast::{
+    CallExpr, Callee, Decl, DefaultDecl, ExportSpecifier, Expr, FnDecl, Id, ImportSpecifier,
+    Module, ModuleDecl, ModuleExportName, ModuleItem, NewExpr, Stmt, VarDecl,
+};
+use swc_core::ecma::visit::{Visit, VisitMut, VisitWith};
+
+use crate::ast::comments::MakoComments;
+use crate::compiler::Context;
+use crate::module::ModuleAst;
+use crate::module_graph::ModuleGraph;
+
+/**
+ * Mark call sites of functions annotated with `/*#__NO_SIDE_EFFECTS__*/` as pure,
+ * so that they are tree-shakable like calls annotated with `/*#__PURE__*/`.
+ *
+ * e.g.
+ * /*#__NO_SIDE_EFFECTS__*/ function foo() {}
+ * const bar = /*#__NO_SIDE_EFFECTS__*/ () => {};
+ * foo(); bar();
+ * ->
+ * /*#__PURE__*/ foo(); /*#__PURE__*/ bar();
+ */
+pub struct NoSideEffects {
+    comments: MakoComments,
+}
+
+impl NoSideEffects {
+    pub fn new(comments: MakoComments) -> Self {
+        Self { comments }
+    }
+}
+
+impl VisitMut for NoSideEffects {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let mut collector = NoSideEffectsCollector::new(&self.comments);
+        module.visit_with(&mut collector);
+        if !collector.fns.is_empty() {
+            module.visit_with(&mut PureCallMarker {
+                comments: &self.comments,
+                fns: &collector.fns,
+            });
+        }
+    }
+}
+
+// annotate calls of `#__NO_SIDE_EFFECTS__` functions imported from other modules,
+// only direct imports are handled, re-exports are not followed
+pub fn mark_imported_no_side_effects_calls(module_graph: &ModuleGraph, context: &Arc<Context>) {
+    crate::mako_profile_function!();
+    let comments = context.meta.script.origin_comments.read().unwrap();
+    let comments = comments.get_swc_comments();
+
+    let mut exports_cache: HashMap<_, HashSet<String>> = HashMap::new();
+    for module in module_graph.modules() {
+        let Some(ModuleAst::Script(ast)) = module.info.as_ref().map(|info| &info.ast) else {
+            continue;
+        };
+
+        let mut fns = HashSet::new();
+        for item in &ast.ast.body {
+            let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+                continue;
+            };
+            if import.type_only {
+                continue;
+            }
+            let Some((dep_id, _)) = module_graph
+                .get_dependencies(&module.id)
+                .into_iter()
+                .find(|(_, dep)| dep.source == import.src.value.as_ref())
+            else {
+                continue;
+            };
+            let exports =
+                exports_cache.entry(dep_id.clone()).or_insert_with(|| {
+                    match module_graph
+                        .get_module(dep_id)
+                        .and_then(|m| m.info.as_ref())
+                        .map(|info| &info.ast)
+                    {
+                        Some(ModuleAst::Script(dep_ast)) => {
+                            let mut collector = NoSideEffectsCollector::new(comments);
+                            dep_ast.ast.visit_with(&mut collector);
+                            collector.exports
+                        }
+                        _ => HashSet::new(),
+                    }
+                });
+            if exports.is_empty() {
+                continue;
+            }
+            for specifier in &import.specifiers {
+                let (local, imported) = match specifier {
+                    ImportSpecifier::Named(named) => (
+                        &named.local,
+                        named
+                            .imported
+                            .as_ref()
+                            .map_or(named.local.sym.to_string(), export_name),
+                    ),
+                    ImportSpecifier::Default(default) => (&default.local, "default".to_string()),
+                    ImportSpecifier::Namespace(_) => continue,
+                };
+                if exports.contains(&imported) {
+                    fns.insert(local.to_id());
+                }
+            }
+        }
+
+        if !fns.is_empty() {
+            ast.ast.visit_with(&mut PureCallMarker {
+                comments,
+                fns: &fns,
+            });
+        }
+    }
+}
+
+struct NoSideEffectsCollector<'a> {
+    comments: &'a MakoComments,
+    // annotated functions
+    fns: HashSet<Id>,
+    // exported names of annotated functions
+    exports: HashSet<String>,
+}
+
+impl<'a> NoSideEffectsCollector<'a> {
+    fn new(comments: &'a MakoComments) -> Self {
+        Self {
+            comments,
+            fns: HashSet::new(),
+            exports: HashSet::new(),
+        }
+    }
+
+    fn is_annotated(&self, spans: &[Span]) -> bool {
+        spans
+            .iter()
+            .any(|span| !span.is_dummy() && self.comments.has_no_side_effects(*span))
+    }
+
+    fn collect_fn_decl(&mut self, fn_decl: &FnDecl, export_span: Option<Span>) {
+        let mut spans = vec![fn_decl.function.span];
+        spans.extend(export_span);
+        if self.is_annotated(&spans) {
+            self.fns.insert(fn_decl.ident.to_id());
+            if export_span.is_some() {
+                self.exports.insert(fn_decl.ident.sym.to_string());
+            }
+        }
+    }
+
+    fn collect_var_decl(&mut self, var_decl: &VarDecl, export_span: Option<Span>) {
+        for decl in &var_decl.decls {
+            let (Some(ident), Some(init)) = (decl.name.as_ident(), &decl.init) else {
+                continue;
+            };
+            if !matches!(&**init, Expr::Fn(_) | Expr::Arrow(_)) {
+                continue;
+            }
+            let mut spans = vec![var_decl.span, decl.span, init_span(init)];
+            spans.extend(export_span);
+            if self.is_annotated(&spans) {
+                self.fns.insert(ident.to_id());
+                if export_span.is_some() {
+                    self.exports.insert(ident.sym.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl Visit for NoSideEffectsCollector<'_> {
+    fn visit_module(&mut self, module: &Module) {
+        module.visit_children_with(self);
+
+        // export { foo, foo as bar }
+        let mut exported_locals = vec![];
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) = item
+                && export.src.is_none()
+            {
+                for specifier in &export.specifiers {
+                    if let ExportSpecifier::Named(named) = specifier
+                        && let ModuleExportName::Ident(orig) = &named.orig
+                    {
+                        let exported = named.exported.as_ref().unwrap_or(&named.orig);
+                        exported_locals.push((orig.to_id(), export_name(exported)));
+                    }
+                }
+            }
+        }
+        for (id, exported) in exported_locals {
+            if self.fns.contains(&id) {
+                self.exports.insert(exported);
+            }
+        }
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        match decl {
+            ModuleDecl::ExportDecl(export) => match &export.decl {
+                Decl::Fn(fn_decl) => {
+                    self.collect_fn_decl(fn_decl, Some(export.span));
+                    fn_decl.visit_children_with(self);
+                }
+                Decl::Var(var_decl) => {
+                    self.collect_var_decl(var_decl, Some(export.span));
+                    var_decl.visit_children_with(self);
+                }
+                _ => decl.visit_children_with(self),
+            },
+            ModuleDecl::ExportDefaultDecl(export) => {
+                if let DefaultDecl::Fn(fn_expr) = &export.decl
+                    && self.is_annotated(&[export.span, fn_expr.function.span])
+                {
+                    if let Some(ident) = &fn_expr.ident {
+                        self.fns.insert(ident.to_id());
+                    }
+                    self.exports.insert("default".to_string());
+                }
+                decl.visit_children_with(self);
+            }
+            _ => decl.visit_children_with(self),
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Decl(Decl::Fn(fn_decl)) => self.collect_fn_decl(fn_decl, None),
+            Stmt::Decl(Decl::Var(var_decl)) => self.collect_var_decl(var_decl, None),
+            _ => {}
+        }
+        stmt.visit_children_with(self);
+    }
+}
+
+struct PureCallMarker<'a> {
+    comments: &'a MakoComments,
+    fns: &'a HashSet<Id>,
+}
+
+impl PureCallMarker<'_> {
+    fn mark(&self, callee: &Expr, span: Span) {
+        if let Expr::Ident(ident) = callee
+            && self.fns.contains(&ident.to_id())
+            && !span.is_dummy()
+            && !self.comments.has_pure(span)
+        {
+            self.comments.add_pure_comment(span.lo);
+        }
+    }
+}
+
+impl Visit for PureCallMarker<'_> {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            self.mark(callee, call.span);
+        }
+        call.visit_children_with(self);
+    }
+
+    fn visit_new_expr(&mut self, new_expr: &NewExpr) {
+        self.mark(&new_expr.callee, new_expr.span);
+        new_expr.visit_children_with(self);
+    }
+}
+
+fn init_span(init: &Expr) -> Span {
+    match init {
+        Expr::Fn(fn_expr) => fn_expr.function.span,
+        Expr::Arrow(arrow) => arrow.span,
+        _ => unreachable!(),
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::NoSideEffects;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_fn_decl() {
+        assert_eq!(
+            run(r#"/*#__NO_SIDE_EFFECTS__*/ function foo() {} foo(); new foo();"#),
+            "/*#__NO_SIDE_EFFECTS__*/ function foo() {}\n/*#__PURE__*/ foo();\n/*#__PURE__*/ new foo();"
+        );
+    }
+
+    #[test]
+    fn test_var_decl() {
+        assert_eq!(
+            run(r#"const foo = /*@__NO_SIDE_EFFECTS__*/ () => {}; foo();"#),
+            "const foo = /*@__NO_SIDE_EFFECTS__*/ ()=>{};\n/*#__PURE__*/ foo();"
+        );
+    }
+
+    #[test]
+    fn test_export_decl() {
+        assert_eq!(
+            run(r#"/*#__NO_SIDE_EFFECTS__*/ export function foo() {} foo();"#),
+            "/*#__NO_SIDE_EFFECTS__*/ export function foo() {}\n/*#__PURE__*/ foo();"
+        );
+    }
+
+    #[test]
+    fn test_not_annotated() {
+        assert_eq!(
+            run(r#"function foo() {} foo();"#),
+            "function foo() {}\nfoo();"
+        );
+    }
+
+    #[test]
+    fn test_shadowed() {
+        assert_eq!(
+            run(r#"/*#__NO_SIDE_EFFECTS__*/ function foo() {} function bar(foo) { foo(); }"#),
+            "/*#__NO_SIDE_EFFECTS__*/ function foo() {}\nfunction bar(foo) {\n    foo();\n}"
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let comments = test_utils
+            .context
+            .meta
+            .script
+            .origin_comments
+            .read()
+            .unwrap()
+            .get_swc_comments()
+            .clone();
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = NoSideEffects::new(comments);
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
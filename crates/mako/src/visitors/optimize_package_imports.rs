@@ -17,6 +17,7 @@ use crate::compiler::Context;
 use crate::module::{
     Dependency, ExportInfo, ExportSpecifierInfo, ImportSpecifierInfo, ResolveType,
 };
+use crate::plugins::tree_shaking::module_side_effects_flag::configured_side_effect;
 use crate::resolve::{resolve, ResolverResource};
 use crate::task::{Task, TaskType};
 
@@ -86,6 +87,7 @@ impl Fold for OptimizePackageImports {
                 let dep = Dependency {
                     source: raw_src.clone(),
                     resolve_as: None,
+                    specifiers: vec![],
                     resolve_type: ResolveType::Import(import_decl.into()),
                     order: 0,
                     span: None,
@@ -225,11 +227,14 @@ fn parse_barrel_file(
     }
     let resolved = resolved.unwrap();
     // handle side effects
-    let side_effects = if let Some(package_json) = &resolved.0.package_json() {
-        has_side_effects(package_json)
-    } else {
-        true
-    };
+    let side_effects =
+        if let Some(side_effects) = configured_side_effect(resolved.0.path(), context) {
+            side_effects
+        } else if let Some(package_json) = &resolved.0.package_json() {
+            has_side_effects(package_json)
+        } else {
+            true
+        };
     debug!("    side_effects: {:?}", side_effects);
     if side_effects {
         debug!("    has side effects");
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::ecma::ast::{BinExpr, BinaryOp, Callee, Expr, Lit};
+use swc_core::ecma::utils::{member_expr, quote_str};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::file::File;
+use crate::ast::utils;
+use crate::build::load::Load;
+use crate::compiler::Context;
+use crate::config::{Platform, PublicAssetsConfig};
+
+pub(crate) fn default_public_dir() -> PathBuf {
+    PathBuf::from("public")
+}
+
+pub(crate) fn default_public_asset_helper() -> String {
+    "publicAsset".to_string()
+}
+
+/**
+ * fingerprint the files of the public dir which are referenced by the helper, e.g.
+ *
+ * publicAsset("robots.txt")
+ * -> __mako_require__.publicPath + "robots.8e6c05c3.txt"
+ *
+ * the helper is a global which is not defined, files which can't be found are referenced
+ * as they are, since the public dir is copied to output by default
+ */
+pub struct PublicAssets {
+    pub context: Arc<Context>,
+    pub unresolved_mark: Mark,
+}
+
+impl PublicAssets {
+    fn config(&self) -> &PublicAssetsConfig {
+        self.context.config.public_assets.as_ref().unwrap()
+    }
+
+    fn handle_asset(&self, path: &str) -> Result<String> {
+        let public_dir = self.context.root.join(&self.config().dir);
+        let file_path = public_dir.join(path.trim_start_matches('/'));
+        let file_path = file_path
+            .canonicalize()
+            .map_err(|e| anyhow!("{}: {}", file_path.display(), e))?;
+        if !file_path.starts_with(public_dir.canonicalize()?) {
+            return Err(anyhow!("{} is not in the public dir", path));
+        }
+        Load::handle_asset(
+            &File::new(
+                file_path.to_string_lossy().to_string(),
+                self.context.clone(),
+            ),
+            false,
+            false,
+            self.context.clone(),
+        )
+    }
+}
+
+impl VisitMut for PublicAssets {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Call(call_expr) = expr
+            && let Callee::Expr(box Expr::Ident(ident)) = &call_expr.callee
+            && utils::is_ident_undefined(ident, &self.config().helper, &self.unresolved_mark)
+            && let Some(path) = utils::get_first_str_arg(call_expr)
+            && !utils::is_remote_or_data(&path)
+        {
+            let url = self.handle_asset(&path).unwrap_or_else(|e| {
+                self.context
+                    .stats_info
+                    .add_warning(format!("Failed to handle public asset: {}, {}", path, e));
+                path.trim_start_matches('/').to_string()
+            });
+            *expr = if matches!(self.context.config.platform, Platform::Browser) {
+                Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::Add,
+                    left: member_expr!(DUMMY_SP, __mako_require__.publicPath),
+                    right: Lit::Str(quote_str!(url)).into(),
+                })
+            } else {
+                Lit::Str(quote_str!(url)).into()
+            };
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::PublicAssets;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::compiler::Context;
+    use crate::config::PublicAssetsConfig;
+
+    #[test]
+    fn test_public_assets() {
+        assert_eq!(
+            run(r#"publicAsset("robots.txt")"#),
+            r#"__mako_require__.publicPath + "robots.5e0bd1c2.txt";"#
+        );
+        assert_eq!(
+            run(r#"publicAsset("missing.txt")"#),
+            r#"__mako_require__.publicPath + "missing.txt";"#
+        );
+        assert_eq!(
+            run(r#"const publicAsset = (a) => a; publicAsset("robots.txt")"#),
+            r#"const publicAsset = (a)=>a;
+publicAsset("robots.txt");"#
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut context = Context {
+            root: std::env::current_dir()
+                .unwrap()
+                .join("src/visitors/fixtures/public_assets"),
+            ..Default::default()
+        };
+        context.config.devtool = None;
+        context.config.public_assets = Some(PublicAssetsConfig {
+            dir: super::default_public_dir(),
+            helper: super::default_public_asset_helper(),
+        });
+        let mut test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: None,
+                content: Some(js_code.to_string()),
+            },
+            Arc::new(context),
+        );
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = PublicAssets {
+                context: test_utils.context.clone(),
+                unresolved_mark: ast.unresolved_mark,
+            };
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
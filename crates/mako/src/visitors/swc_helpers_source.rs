@@ -0,0 +1,89 @@
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, ImportDecl, Lit, Str};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::is_ident;
+
+pub const SWC_HELPERS_SOURCE: &str = "@swc/helpers";
+
+// rewrite the injected `@swc/helpers/_/*` sources to a configured module
+// e.g. `@swc/helpers/_/_class_call_check` => `my-helpers/_/_class_call_check`
+pub struct SwcHelpersSource {
+    source: String,
+}
+
+impl SwcHelpersSource {
+    pub fn new(source: String) -> Self {
+        Self { source }
+    }
+
+    fn replace(&self, str: &mut Str) {
+        let value = str.value.to_string();
+        if let Some(rest) = value.strip_prefix(SWC_HELPERS_SOURCE)
+            && rest.starts_with('/')
+        {
+            str.value = format!("{}{}", self.source, rest).into();
+            str.raw = None;
+        }
+    }
+}
+
+impl VisitMut for SwcHelpersSource {
+    fn visit_mut_import_decl(&mut self, import_decl: &mut ImportDecl) {
+        self.replace(import_decl.src.as_mut());
+    }
+
+    fn visit_mut_call_expr(&mut self, call_expr: &mut CallExpr) {
+        if let Callee::Expr(box Expr::Ident(ident)) = &call_expr.callee
+            && is_ident(ident, "require")
+            && call_expr.args.len() == 1
+            && let box Expr::Lit(Lit::Str(str)) = &mut call_expr.args[0].expr
+        {
+            self.replace(str);
+        }
+        call_expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::SwcHelpersSource;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_esm_helpers() {
+        assert_eq!(
+            run(r#"import { _ } from "@swc/helpers/_/_class_call_check";"#),
+            r#"import { _ } from "my-helpers/_/_class_call_check";"#
+        );
+    }
+
+    #[test]
+    fn test_cjs_helpers() {
+        assert_eq!(
+            run(r#"var _class_call_check = require("@swc/helpers/_/_class_call_check");"#),
+            r#"var _class_call_check = require("my-helpers/_/_class_call_check");"#
+        );
+    }
+
+    #[test]
+    fn test_ignore_other_sources() {
+        assert_eq!(
+            run(r#"import a from "@swc/helpers-foo"; require("foo");"#),
+            r#"import a from "@swc/helpers-foo";
+require("foo");"#
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = SwcHelpersSource::new("my-helpers".to_string());
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
@@ -27,6 +27,7 @@ impl TryResolve {
                 let mut deps = vec![Dependency {
                     source: source.clone(),
                     resolve_as: None,
+                    specifiers: vec![],
                     resolve_type: ResolveType::Require,
                     order: 0,
                     span: None,
@@ -43,6 +44,7 @@ impl TryResolve {
                     &Dependency {
                         source: source.clone(),
                         resolve_as: None,
+                        specifiers: vec![],
                         resolve_type: ResolveType::Require,
                         order: 0,
                         span: None,
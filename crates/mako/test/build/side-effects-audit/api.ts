@@ -0,0 +1,13 @@
+const registry = [];
+function register(fn) {
+  return fn;
+}
+
+export function used() { return 1; }
+export function unused() { return 2; }
+
+const registered = register(() => 3);
+export function plugin() { return 4; }
+registry.push(plugin);
+
+export { registered };
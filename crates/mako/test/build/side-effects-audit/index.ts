@@ -0,0 +1 @@
+export { used } from './api';
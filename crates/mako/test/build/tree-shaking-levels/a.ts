@@ -0,0 +1,6 @@
+import { shared } from './shared';
+import { cycle } from './cycle';
+
+export const a = shared + cycle;
+export const unusedA = 'unused';
+export { neverCalled } from './unused';
@@ -0,0 +1,4 @@
+import { shared, unusedShared } from './shared';
+
+export const b1 = shared;
+export const b2 = unusedShared;
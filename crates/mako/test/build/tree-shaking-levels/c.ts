@@ -0,0 +1,4 @@
+import { shared } from './shared';
+
+export const c = shared * 2;
+export const unusedC = 1;
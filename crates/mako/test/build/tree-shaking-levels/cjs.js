@@ -0,0 +1,3 @@
+const { d } = require('./d');
+
+module.exports = d;
@@ -0,0 +1,6 @@
+import { a } from './a';
+
+export const cycle = 1;
+export function useA() {
+  return a;
+}
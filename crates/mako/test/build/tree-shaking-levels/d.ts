@@ -0,0 +1,2 @@
+export const d = 'd';
+export const unusedD = 'unused';
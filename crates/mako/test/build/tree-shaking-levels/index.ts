@@ -0,0 +1,8 @@
+import { a, unusedA } from './a';
+import * as b from './b';
+import { c } from './reexport';
+import './side-effect';
+const cjs = require('./cjs');
+
+import('./lazy').then((m) => console.log(m.lazy));
+console.log(a, b.b1, c, cjs);
@@ -0,0 +1,4 @@
+import { shared } from './shared';
+
+export const lazy = shared;
+export const unusedLazy = 1;
@@ -0,0 +1,2 @@
+export { c } from './c';
+export * from './d';
@@ -0,0 +1,2 @@
+console.log('side effect');
+export const unusedSideEffect = 1;
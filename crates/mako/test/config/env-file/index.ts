@@ -0,0 +1 @@
+console.log(process.env.MAKO_APP_API);
@@ -0,0 +1,2 @@
+export * from "./api";
+export const fetchUser = () => Promise.resolve({ name: "mock" });
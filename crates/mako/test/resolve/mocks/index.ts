@@ -0,0 +1,2 @@
+import { fetchUser } from "./api";
+fetchUser();
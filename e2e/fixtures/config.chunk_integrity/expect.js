@@ -0,0 +1,32 @@
+const assert = require("assert");
+const { parseBuildResult, testWithBrowser } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const test = async () => {
+  const asyncChunk = Object.keys(files).find(
+    (file) => file.endsWith(".js") && file !== "index.js"
+  );
+  assert(
+    files[asyncChunk].includes("//# sourceMappingURL="),
+    "async chunk should have source map link"
+  );
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        await page.waitForFunction(
+          () => document.getElementById("root").innerHTML !== ""
+        );
+        const elm = await page.locator("#root");
+        const content = await elm.evaluate((el) => el.innerHTML);
+        assert.equal(content, "a", "async chunk should be verified and loaded");
+        const corruptChunks = await page.evaluate(() => window.corruptChunks);
+        assert.equal(corruptChunks, undefined, "async chunk should not be corrupt");
+      },
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
@@ -0,0 +1,3 @@
+import('./a').then(({ default: a }) => {
+  document.getElementById('root')!.innerHTML = a;
+});
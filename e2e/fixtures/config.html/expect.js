@@ -0,0 +1,24 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const html = files['index.html'];
+
+assert(html, 'index.html should be generated');
+assert(html.includes('<div id="index"></div>'), 'entry.name should be replaced');
+assert(
+  html.includes('<link rel="preload" href="/index.js" as="script" />'),
+  'preloadLinks should be replaced',
+);
+assert(
+  html.includes('<link rel="stylesheet" href="/index.css" />'),
+  'entry.styles should be replaced',
+);
+assert(
+  html.includes('<script src="/index.js"></script>'),
+  'entry.scripts should be replaced',
+);
+assert(
+  html.includes('window.files = ["/index.js"];'),
+  'entry.js should be replaced',
+);
@@ -0,0 +1,2 @@
+console.log('hello');
+import './index.css';
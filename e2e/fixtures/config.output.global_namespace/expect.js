@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+const lazy = Object.entries(files).find(([fileName]) => fileName.startsWith("src_lazy_ts"))[1];
+
+assert(
+  content.includes(`root = root['myApp'] = root['myApp'] || {};`),
+  "runtime state should be attached to the namespace"
+);
+assert.match(
+  lazy,
+  /\[['"]myApp['"]\] \|\| \{\}\)\[['"]makoChunk_/,
+  "chunks should be pushed to the chunk loading global under the namespace"
+);
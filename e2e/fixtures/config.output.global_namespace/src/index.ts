@@ -0,0 +1 @@
+import('./lazy').then(({ foo }) => console.log(foo));
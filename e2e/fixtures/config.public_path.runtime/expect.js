@@ -6,6 +6,11 @@ const content = files["index.js"];
 
 assert.match(
   content,
-  /requireModule.publicPath = \(typeof globalThis !== 'undefined' \? globalThis : self\).publicPath \|\| '\/';/,
+  /var publicPath = \(typeof globalThis !== 'undefined' \? globalThis : self\).publicPath \|\| '\/';/,
   "requireModule.publicPath not correct"
 );
+
+assert(
+  content.includes("global.__mako_public_path__"),
+  "requireModule.publicPath should be overridable with __mako_public_path__"
+);
@@ -0,0 +1,16 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.match(
+  content,
+  /\/\*#__PURE__\*\/ \(0, \w+\.createStore\)\(\)/,
+  'calls of imported no side effects function should be pure',
+);
+assert.match(
+  content,
+  /\/\*#__PURE__\*\/ createLocal\(\)/,
+  'calls of local no side effects function should be pure',
+);
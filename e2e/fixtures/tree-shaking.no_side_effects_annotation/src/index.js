@@ -0,0 +1,9 @@
+import { createStore } from './lib';
+
+const createLocal = /*#__NO_SIDE_EFFECTS__*/ () => {
+  console.log('create local');
+};
+
+const store = createStore();
+const local = createLocal();
+console.log('index');
@@ -11,7 +11,9 @@ export interface JsHooks {
   name?: string;
   load?: (
     filePath: string,
-  ) => Promise<{ content: string; type: 'css' | 'js' } | void> | void;
+  ) => Promise<
+    { content: string; type: 'css' | 'js'; watchFiles?: string[] } | void
+  > | void;
   generateEnd?: (data: {
     isFirstCompile: boolean;
     time: number;
@@ -22,6 +24,10 @@ export interface JsHooks {
   }) => void;
   onGenerateFile?: (path: string, content: Buffer) => Promise<void>;
   buildStart?: () => Promise<void>;
+  transformOutput?: (
+    filename: string,
+    content: Buffer,
+  ) => Promise<Buffer | void> | Buffer | void;
 }
 export interface BuildParams {
   root: string;
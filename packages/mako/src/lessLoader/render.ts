@@ -5,7 +5,7 @@ import { LessLoaderOpts } from '.';
 module.exports = async function render(param: {
   filename: string;
   opts: LessLoaderOpts;
-}): Promise<{ content: string; type: 'css' }> {
+}): Promise<{ content: string; type: 'css'; watchFiles: string[] }> {
   const { modifyVars, math, sourceMap, plugins } = param.opts;
   const input = fs.readFileSync(param.filename, 'utf-8');
 
@@ -33,5 +33,6 @@ module.exports = async function render(param: {
       throw new Error(err.toString());
     });
 
-  return { content: result.css, type: 'css' };
+  // imported less files are watched, so that edits to them rebuild the importer
+  return { content: result.css, type: 'css', watchFiles: result.imports };
 };
@@ -6,7 +6,11 @@ import 'zx/globals';
   const baseline = argv.baseline || 'master';
   const casePath =
     argv.case ||
-    (argv.multiChunks ? './tmp/three10x/multiChunks' : './tmp/three10x');
+    (argv.multiChunks
+      ? './tmp/three10x/multiChunks'
+      : argv.treeShaking
+        ? './tmp/three10x/treeShaking'
+        : './tmp/three10x');
 
   const originBranch = (await $`git rev-parse --abbrev-ref HEAD`).stdout.trim();
   const isGitClean = (await $`git status --porcelain`).stdout.trim() === '';